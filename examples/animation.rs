@@ -15,7 +15,7 @@ use bevy_entitiles::{
             TileRenderSize, TilemapRotation, TilemapSlotSize, TilemapStorage, TilemapTexture,
             TilemapTextureDescriptor, TilemapType,
         },
-        tile::{RawTileAnimation, TileBuilder},
+        tile::{RawTileAnimation, TileAnimationMode, TileBuilder},
     },
     EntiTilesPlugin,
 };
@@ -55,14 +55,14 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         ..Default::default()
     };
 
-    let anim_a = tilemap.animations.register(RawTileAnimation {
-        fps: 2,
-        sequence: vec![0, 1, 2, 3],
-    });
-    let anim_b = tilemap.animations.register(RawTileAnimation {
-        fps: 3,
-        sequence: vec![0, 1, 2],
-    });
+    let anim_a = tilemap
+        .animations
+        .register(RawTileAnimation::new(vec![0, 1, 2, 3], 2));
+    // Frames with their own durations, played back and forth.
+    let anim_b = tilemap.animations.register(
+        RawTileAnimation::with_durations(vec![0, 1, 2], vec![600, 200, 200])
+            .with_mode(TileAnimationMode::PingPong),
+    );
 
     tilemap.storage.fill_rect(
         &mut commands,
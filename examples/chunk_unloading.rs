@@ -31,8 +31,8 @@ use bevy_entitiles::{
         bundles::StandardTilemapBundle,
         chunking::camera::{CameraChunkUpdater, CameraChunkUpdation},
         map::{
-            TileRenderSize, TilemapName, TilemapRotation, TilemapSlotSize, TilemapStorage,
-            TilemapTexture, TilemapTextureDescriptor, TilemapType,
+            TileRenderSize, TilemapChunkFadeIn, TilemapName, TilemapRotation, TilemapSlotSize,
+            TilemapStorage, TilemapTexture, TilemapTextureDescriptor, TilemapType,
         },
         physics::{PhysicsTile, PhysicsTilemap},
         tile::{TileBuilder, TileLayer},
@@ -150,7 +150,10 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     // );
     commands.entity(entity).insert(physics_tilemap);
 
-    commands.entity(entity).insert(tilemap);
+    // Make the loaded chunks fade in smoothly.
+    commands
+        .entity(entity)
+        .insert((tilemap, TilemapChunkFadeIn(0.3)));
 }
 
 fn on_update(
@@ -8,7 +8,7 @@
 
 use bevy::{
     app::{App, PluginGroup, Startup, Update},
-    asset::{AssetServer, Assets},
+    asset::AssetServer,
     core_pipeline::core_2d::Camera2dBundle,
     ecs::{
         component::Component,
@@ -20,8 +20,7 @@ use bevy::{
     input::{keyboard::KeyCode, ButtonInput},
     math::Vec2,
     reflect::Reflect,
-    render::{mesh::Mesh, render_resource::FilterMode, texture::ImagePlugin, view::Msaa},
-    sprite::TextureAtlasLayout,
+    render::{render_resource::FilterMode, texture::ImagePlugin, view::Msaa},
     utils::HashMap,
     DefaultPlugins,
 };
@@ -29,11 +28,11 @@ use bevy_entitiles::tilemap::tile::RawTileAnimation;
 use bevy_entitiles::{
     ldtk::{
         app_ext::LdtkApp,
+        components::LdtkAtmosphereReceiver,
         events::LdtkEvent,
         json::{field::FieldInstance, level::EntityInstance, EntityRef},
         layer::physics::LdtkPhysicsLayer,
         resources::{LdtkAdditionalLayers, LdtkAssets, LdtkLevelManager, LdtkLoadConfig},
-        sprite::LdtkEntityMaterial,
     },
     tilemap::physics::PhysicsTile,
     EntiTilesPlugin,
@@ -69,17 +68,11 @@ fn main() {
             // replace the filename with grid_vania.ldtk before running
             // this file uses finalbossblues-icons_full_16 and it only exists
             // in my local disk.
-            file_path: "assets/ldtk/ignore grid_vania.ldtk".to_string(),
+            file_path: "ldtk/ignore grid_vania.ldtk".to_string(),
             asset_path_prefix: "ldtk/".to_string(),
             filter_mode: FilterMode::Nearest,
             ignore_unregistered_entities: true,
-            animation_mapper: HashMap::from([(
-                470,
-                RawTileAnimation {
-                    sequence: vec![469, 446, 447],
-                    fps: 3,
-                },
-            )]),
+            animation_mapper: HashMap::from([(470, RawTileAnimation::new(vec![469, 446, 447], 3))]),
             ..Default::default()
         })
         .insert_resource(LdtkAdditionalLayers {
@@ -115,11 +108,17 @@ fn main() {
         .register_ldtk_entity_tag::<Actor>("actor")
         .register_ldtk_entity_tag::<Loot>("loot")
         .register_ldtk_entity_tag::<Region>("region")
+        // spawn hooks can read resources when the entity is spawned
+        .add_ldtk_spawn_hook("Player", |ctx| {
+            let gravity = ctx.resource::<Gravity>().0;
+            println!("Player {:?} spawned with gravity {}", ctx.entity, gravity);
+        })
         .run();
 }
 
 fn setup(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
+    // levels with `ambient_color` or `bloom` fields will change the mood of this camera
+    commands.spawn((Camera2dBundle::default(), LdtkAtmosphereReceiver));
 }
 
 macro_rules! level_control {
@@ -154,28 +153,26 @@ fn load(
     if input.just_pressed(KeyCode::Digit8) {
         manager.load(&mut commands, "Entrance".to_string(), None);
     }
+
+    if input.just_pressed(KeyCode::Digit9) {
+        let offset = manager.get_instances("Entrance").len() as f32 + 1.;
+        manager.load_instance(
+            &mut commands,
+            "Entrance".to_string(),
+            Vec2::new(offset * 400., 0.),
+        );
+    }
 }
 
 fn hot_reload(
     input: Res<ButtonInput<KeyCode>>,
     mut manager: ResMut<LdtkLevelManager>,
     config: Res<LdtkLoadConfig>,
-    mut assets: ResMut<LdtkAssets>,
     asset_server: Res<AssetServer>,
-    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    mut entity_material_assets: ResMut<Assets<LdtkEntityMaterial>>,
-    mut mesh_assets: ResMut<Assets<Mesh>>,
 ) {
     if input.just_pressed(KeyCode::Enter) {
-        manager.reload_json(&config);
-        assets.initialize(
-            &config,
-            &manager,
-            &asset_server,
-            &mut atlas_layouts,
-            &mut entity_material_assets,
-            &mut mesh_assets,
-        );
+        // the assets will be initialized again when the next level is loaded
+        manager.reload_json(&config, &asset_server);
         println!("Hot reloaded!")
     }
 }
@@ -186,9 +183,23 @@ fn events(mut ldtk_events: EventReader<LdtkEvent>) {
             LdtkEvent::LevelLoaded(level) => {
                 println!("Level loaded: {}", level.identifier);
             }
+            LdtkEvent::LevelSpawned(spawned) => {
+                println!(
+                    "Level spawned: {} with {} layers and {} entities",
+                    spawned.level.identifier,
+                    spawned.layers.len(),
+                    spawned.entities.len()
+                );
+            }
             LdtkEvent::LevelUnloaded(level) => {
                 println!("Level unloaded: {}", level.identifier);
             }
+            LdtkEvent::GlobalEntityPreserved(global) => {
+                println!(
+                    "Global entity {:?} preserved from: {}",
+                    global.entity, global.level.identifier
+                );
+            }
         }
     }
 }
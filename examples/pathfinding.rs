@@ -79,7 +79,14 @@ fn setup(mut commands: Commands, assets_server: Res<AssetServer>) {
         },
     );
 
-    let queue = (0..100).into_iter().map(|_| {
+    // Flying units don't care about the terrain.
+    let mut air_path_tilemap = PathTilemap::new();
+    air_path_tilemap.fill_path_rect(
+        TileArea::new(IVec2::ZERO, UVec2 { x: 1000, y: 1000 }),
+        PathTile { cost: 1 },
+    );
+
+    let queue = (0..100).into_iter().map(|i| {
         (
             commands.spawn_empty().id(),
             PathFinder {
@@ -87,11 +94,14 @@ fn setup(mut commands: Commands, assets_server: Res<AssetServer>) {
                 dest: IVec2::splat(499),
                 allow_diagonal: false,
                 max_steps: None,
+                // Ground units use layer 0 and flying units use layer 1.
+                nav_layer: i % 2,
             },
         )
     });
 
-    let pathfinding_queue = PathFindingQueue::new_with_schedules(path_tilemap.clone(), queue);
+    let mut pathfinding_queue = PathFindingQueue::new_with_schedules(path_tilemap.clone(), queue);
+    pathfinding_queue.insert_layer(1, air_path_tilemap);
 
     commands
         .entity(entity)
@@ -7,6 +7,7 @@ use bevy::{
         system::{Commands, Res, ResMut},
     },
     input::{keyboard::KeyCode, ButtonInput},
+    math::Vec2,
     reflect::Reflect,
     render::{color::Color, texture::ImagePlugin},
     DefaultPlugins,
@@ -37,12 +38,13 @@ fn main() {
         .add_systems(Update, switching)
         .insert_resource(TiledLoadConfig {
             map_path: vec![
-                "assets/tiled/tilemaps/hexagonal.tmx".to_string(),
-                "assets/tiled/tilemaps/infinite.tmx".to_string(),
-                "assets/tiled/tilemaps/orthogonal.tmx".to_string(),
-                "assets/tiled/tilemaps/isometric.tmx".to_string(),
+                "tiled/tilemaps/hexagonal.tmx".to_string(),
+                "tiled/tilemaps/infinite.tmx".to_string(),
+                "tiled/tilemaps/orthogonal.tmx".to_string(),
+                "tiled/tilemaps/isometric.tmx".to_string(),
             ],
             ignore_unregisterd_objects: true,
+            ..Default::default()
         })
         .register_tiled_object::<BlockBundle>("Block")
         .register_tiled_object::<PlainBlockBundle>("PlainBlock")
@@ -73,6 +75,15 @@ fn switching(
     map_switching!(Digit2, "infinite", input, manager, commands);
     map_switching!(Digit3, "orthogonal", input, manager, commands);
     map_switching!(Digit4, "isometric", input, manager, commands);
+
+    if input.just_pressed(KeyCode::Digit5) {
+        let offset = manager.get_instances("orthogonal").len() as f32 + 1.;
+        manager.load_instance(
+            &mut commands,
+            "orthogonal".to_string(),
+            Vec2::new(offset * 600., 0.),
+        );
+    }
 }
 
 /*
@@ -59,10 +59,15 @@ pub fn expand_ldtk_enum_derive(input: syn::DeriveInput) -> proc_macro::TokenStre
     quote::quote!(
         impl bevy_entitiles::ldtk::traits::LdtkEnum for #ty {
             fn get_identifier(ident: &str) -> Self {
-                match ident {
+                Self::try_get_identifier(ident)
+                    .unwrap_or_else(|| panic!("Unknown enum variant: {}", ident))
+            }
+
+            fn try_get_identifier(ident: &str) -> Option<Self> {
+                Some(match ident {
                     #(#variants_cton)*
-                    _ => panic!("Unknown enum variant: {}", ident),
-                }
+                    _ => return None,
+                })
             }
         }
 
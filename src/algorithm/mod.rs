@@ -1,7 +1,7 @@
-use bevy::prelude::{Plugin, Update};
+use bevy::prelude::{IntoSystemConfigs, Plugin, Update};
 
 use self::{
-    pathfinding::Path,
+    pathfinding::{Path, PathFinder, PathFollower, PathFollowerEvent},
     wfc::{WfcData, WfcElement, WfcHistory, WfcSource},
 };
 
@@ -12,7 +12,11 @@ pub struct EntiTilesAlgorithmPlugin;
 
 impl Plugin for EntiTilesAlgorithmPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.register_type::<Path>();
+        app.register_type::<Path>()
+            .register_type::<PathFinder>()
+            .register_type::<PathFollower>();
+
+        app.add_event::<PathFollowerEvent>();
 
         app.register_type::<WfcElement>()
             .register_type::<WfcHistory>()
@@ -22,8 +26,13 @@ impl Plugin for EntiTilesAlgorithmPlugin {
         app.add_systems(
             Update,
             (
-                pathfinding::pathfinding_scheduler,
-                pathfinding::path_assigner,
+                (
+                    pathfinding::path_follower_scheduler,
+                    pathfinding::pathfinding_scheduler,
+                    pathfinding::path_assigner,
+                    pathfinding::path_follower_updater,
+                )
+                    .chain(),
                 wfc::wave_function_collapse,
                 wfc::wfc_data_assigner,
                 wfc::wfc_applier,
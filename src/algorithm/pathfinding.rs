@@ -2,9 +2,12 @@ use std::{cmp::Ordering, collections::BinaryHeap, sync::Arc};
 
 use bevy::{
     ecs::{
+        change_detection::{DetectChanges, Ref},
         entity::EntityHashMap,
+        event::{Event, EventWriter},
         system::{Commands, Query},
     },
+    log::error,
     math::IVec2,
     prelude::{Component, Entity},
     reflect::Reflect,
@@ -17,27 +20,34 @@ use crate::{
     tilemap::{algorithm::path::PathTilemap, map::TilemapType},
 };
 
-#[derive(Component, Reflect)]
+/// The navigation layer used when you create the queue using `new` or `new_with_schedules`.
+pub const DEFAULT_NAV_LAYER: u32 = 0;
+
+#[derive(Component, Debug, Clone, Reflect)]
 pub struct PathFinder {
     pub origin: IVec2,
     pub dest: IVec2,
     pub allow_diagonal: bool,
     pub max_steps: Option<u32>,
+    /// Which path tilemap in the queue to use.
+    /// For example, `0` for ground units and `1` for flying units.
+    pub nav_layer: u32,
 }
 
 #[derive(Component)]
 pub struct PathFindingQueue {
     pub(crate) finders: EntityHashMap<PathFinder>,
-    pub(crate) tasks: EntityHashMap<Task<Path>>,
-    pub(crate) cache: Arc<PathTilemap>,
+    pub(crate) tasks: EntityHashMap<Task<Option<Path>>>,
+    pub(crate) caches: HashMap<u32, Arc<PathTilemap>>,
 }
 
 impl PathFindingQueue {
+    /// Create a queue with a single path tilemap on `DEFAULT_NAV_LAYER`.
     pub fn new(cache: PathTilemap) -> Self {
         PathFindingQueue {
             finders: EntityHashMap::default(),
             tasks: EntityHashMap::default(),
-            cache: Arc::new(cache),
+            caches: HashMap::from([(DEFAULT_NAV_LAYER, Arc::new(cache))]),
         }
     }
 
@@ -48,10 +58,52 @@ impl PathFindingQueue {
         PathFindingQueue {
             finders: schedules.collect(),
             tasks: EntityHashMap::default(),
-            cache: Arc::new(cache),
+            caches: HashMap::from([(DEFAULT_NAV_LAYER, Arc::new(cache))]),
+        }
+    }
+
+    /// Create a queue with multiple path tilemaps, each keyed by a navigation layer id.
+    ///
+    /// An empty path tilemap is inserted on `DEFAULT_NAV_LAYER` if it's not given.
+    pub fn new_with_layers(layers: impl IntoIterator<Item = (u32, PathTilemap)>) -> Self {
+        let mut caches = layers
+            .into_iter()
+            .map(|(layer, cache)| (layer, Arc::new(cache)))
+            .collect::<HashMap<_, _>>();
+        caches
+            .entry(DEFAULT_NAV_LAYER)
+            .or_insert_with(|| Arc::new(PathTilemap::new()));
+
+        PathFindingQueue {
+            finders: EntityHashMap::default(),
+            tasks: EntityHashMap::default(),
+            caches,
         }
     }
 
+    /// Add a path tilemap for the navigation layer. Overwrites the previous one.
+    #[inline]
+    pub fn insert_layer(&mut self, nav_layer: u32, cache: PathTilemap) {
+        self.caches.insert(nav_layer, Arc::new(cache));
+    }
+
+    /// Remove the path tilemap of the navigation layer.
+    ///
+    /// **Notice**: `DEFAULT_NAV_LAYER` can't be removed, this returns `None` for it.
+    /// Use `insert_layer` to replace it instead.
+    #[inline]
+    pub fn remove_layer(&mut self, nav_layer: u32) -> Option<Arc<PathTilemap>> {
+        if nav_layer == DEFAULT_NAV_LAYER {
+            return None;
+        }
+        self.caches.remove(&nav_layer)
+    }
+
+    #[inline]
+    pub fn has_layer(&self, nav_layer: u32) -> bool {
+        self.caches.contains_key(&nav_layer)
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.tasks.is_empty()
@@ -62,14 +114,27 @@ impl PathFindingQueue {
         self.finders.insert(requester, pathfinder);
     }
 
+    /// Get the path tilemap of `DEFAULT_NAV_LAYER`.
     #[inline]
     pub fn get_cache(&self) -> Arc<PathTilemap> {
-        self.cache.clone()
+        self.get_layer_cache(DEFAULT_NAV_LAYER).unwrap()
     }
 
+    /// Get the path tilemap of `DEFAULT_NAV_LAYER`.
     #[inline]
     pub fn get_cache_mut(&mut self) -> &mut PathTilemap {
-        Arc::get_mut(&mut self.cache).unwrap()
+        self.get_layer_cache_mut(DEFAULT_NAV_LAYER).unwrap()
+    }
+
+    #[inline]
+    pub fn get_layer_cache(&self, nav_layer: u32) -> Option<Arc<PathTilemap>> {
+        self.caches.get(&nav_layer).cloned()
+    }
+
+    /// **Notice**: This will return `None` if there are pathfinding tasks using this layer.
+    #[inline]
+    pub fn get_layer_cache_mut(&mut self, nav_layer: u32) -> Option<&mut PathTilemap> {
+        self.caches.get_mut(&nav_layer).and_then(Arc::get_mut)
     }
 }
 
@@ -108,6 +173,160 @@ impl Path {
     }
 }
 
+/// Sent by `PathFollower`s.
+#[derive(Event, Debug, Clone, Copy, Reflect)]
+pub enum PathFollowerEvent {
+    /// The follower reached the destination.
+    Arrived(Entity, IVec2),
+    /// The next waypoint of the follower is no longer walkable.
+    Blocked(Entity, IVec2),
+    /// No path to the destination was found.
+    Unreachable(Entity, IVec2),
+}
+
+/// A component that tracks the progress of walking along a path.
+///
+/// Insert this to the requester and it will schedule the pathfinding task on the
+/// `PathFindingQueue` of the tilemap by itself. Once the `Path` is found, the waypoints
+/// will be loaded into this component. Then call `step` every time you reach the current waypoint.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct PathFollower {
+    pub(crate) tilemap: Entity,
+    pub(crate) finder: PathFinder,
+    /// The waypoints from the origin (excluded) to the destination.
+    pub(crate) waypoints: Vec<IVec2>,
+    pub(crate) current: usize,
+    pub(crate) position: IVec2,
+    pub(crate) repath_requested: bool,
+    pub(crate) waiting: bool,
+    pub(crate) blocked: bool,
+    pub(crate) unreachable: bool,
+    pub(crate) arrival_sent: bool,
+    /// Find a new path automatically when the next waypoint is blocked.
+    pub repath_on_blocked: bool,
+}
+
+impl PathFollower {
+    pub fn new(tilemap: Entity, finder: PathFinder) -> Self {
+        Self {
+            tilemap,
+            position: finder.origin,
+            finder,
+            waypoints: Vec::new(),
+            current: 0,
+            repath_requested: true,
+            waiting: false,
+            blocked: false,
+            unreachable: false,
+            arrival_sent: false,
+            repath_on_blocked: false,
+        }
+    }
+
+    pub fn with_repath_on_blocked(mut self) -> Self {
+        self.repath_on_blocked = true;
+        self
+    }
+
+    /// Mark the current waypoint as reached. Or do nothing if already arrived.
+    pub fn step(&mut self) {
+        if let Some(waypoint) = self.current_waypoint() {
+            self.position = waypoint;
+            self.current += 1;
+        }
+    }
+
+    /// Find a new path from the last reached waypoint to the destination.
+    #[inline]
+    pub fn request_repath(&mut self) {
+        self.repath_requested = true;
+    }
+
+    /// Change the destination and find a new path.
+    pub fn retarget(&mut self, dest: IVec2) {
+        self.finder.dest = dest;
+        self.request_repath();
+    }
+
+    #[inline]
+    pub fn current_waypoint(&self) -> Option<IVec2> {
+        self.waypoints.get(self.current).cloned()
+    }
+
+    /// The waypoints that are not reached yet.
+    #[inline]
+    pub fn remaining_waypoints(&self) -> &[IVec2] {
+        &self.waypoints[self.current.min(self.waypoints.len())..]
+    }
+
+    /// The length of the rest of the path, in tiles.
+    pub fn remaining_distance(&self) -> f32 {
+        self.remaining_waypoints()
+            .iter()
+            .fold((self.position, 0.), |(last, dist), waypoint| {
+                (*waypoint, dist + (*waypoint - last).as_vec2().length())
+            })
+            .1
+    }
+
+    /// The last reached waypoint.
+    #[inline]
+    pub fn position(&self) -> IVec2 {
+        self.position
+    }
+
+    #[inline]
+    pub fn dest(&self) -> IVec2 {
+        self.finder.dest
+    }
+
+    #[inline]
+    pub fn tilemap(&self) -> Entity {
+        self.tilemap
+    }
+
+    /// Return true if the path is still being calculated.
+    #[inline]
+    pub fn is_waiting(&self) -> bool {
+        self.waiting || self.repath_requested
+    }
+
+    #[inline]
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+
+    /// Return true if no path to the destination was found.
+    /// Use `request_repath` or `retarget` to try again.
+    #[inline]
+    pub fn is_unreachable(&self) -> bool {
+        self.unreachable
+    }
+
+    #[inline]
+    pub fn is_arrived(&self) -> bool {
+        !self.is_waiting() && !self.unreachable && self.current >= self.waypoints.len()
+    }
+
+    pub(crate) fn load_path(&mut self, path: &Path) {
+        // The path is collected from the destination to the origin.
+        self.waypoints = path.path.iter().rev().cloned().collect();
+        self.current = 0;
+        self.waiting = false;
+        self.blocked = false;
+        self.unreachable = false;
+        self.arrival_sent = false;
+    }
+
+    pub(crate) fn mark_unreachable(&mut self) {
+        self.waypoints.clear();
+        self.current = 0;
+        self.waiting = false;
+        self.blocked = false;
+        self.unreachable = true;
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PathNode {
     pub index: IVec2,
@@ -248,33 +467,47 @@ impl PathGrid {
         }
     }
 
-    pub fn collect_path(&self) -> Path {
+    /// Collect the path from the destination to the origin.
+    /// Returns `None` if the destination is not reached.
+    pub fn collect_path(&self) -> Option<Path> {
         let mut path = Path {
             path: vec![],
             current_step: 0,
             tilemap: self.tilemap,
         };
-        let mut current = self.all_nodes.get(&self.dest).unwrap();
+        let mut current = self.all_nodes.get(&self.dest)?;
         while current.index != self.origin {
             path.path.push(current.index);
-            current = self.all_nodes.get(&current.parent.unwrap()).unwrap();
+            current = self.all_nodes.get(&current.parent?)?;
         }
-        path
+        Some(path)
     }
 }
 
 pub fn pathfinding_scheduler(
     mut queues_query: Query<(Entity, &TilemapType, &mut PathFindingQueue)>,
+    mut followers_query: Query<&mut PathFollower>,
+    mut event: EventWriter<PathFollowerEvent>,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
     queues_query
         .iter_mut()
         .for_each(|(tilemap, ty, mut queue)| {
             let mut tasks = Vec::new();
-            let path_tilemap = queue.cache.clone();
+            let caches = queue.caches.clone();
             queue.finders.drain().for_each(|(requester, finder)| {
                 let ty = *ty;
-                let path_tilemap = path_tilemap.clone();
+                let Some(path_tilemap) = caches.get(&finder.nav_layer).cloned() else {
+                    error!(
+                        "Failed to find path for {:?}! The navigation layer {} does not exist in tilemap {:?}.",
+                        requester, finder.nav_layer, tilemap
+                    );
+                    if let Ok(mut follower) = followers_query.get_mut(requester) {
+                        follower.mark_unreachable();
+                        event.send(PathFollowerEvent::Unreachable(requester, follower.dest()));
+                    }
+                    return;
+                };
                 let task = thread_pool.spawn(async move {
                     let mut grid = PathGrid::new(finder, requester, tilemap, path_tilemap.clone());
                     grid.find_path(ty);
@@ -286,13 +519,101 @@ pub fn pathfinding_scheduler(
         });
 }
 
-pub fn path_assigner(mut commands: Commands, mut queues_query: Query<&mut PathFindingQueue>) {
+pub fn path_follower_scheduler(
+    mut followers_query: Query<(Entity, &mut PathFollower)>,
+    mut queues_query: Query<&mut PathFindingQueue>,
+    mut event: EventWriter<PathFollowerEvent>,
+) {
+    followers_query
+        .iter_mut()
+        .filter(|(_, follower)| follower.repath_requested)
+        .for_each(|(entity, mut follower)| {
+            let Ok(mut queue) = queues_query.get_mut(follower.tilemap) else {
+                error!(
+                    "Failed to schedule path for {:?}! The tilemap {:?} does not have a PathFindingQueue.",
+                    entity, follower.tilemap
+                );
+                follower.repath_requested = false;
+                follower.mark_unreachable();
+                event.send(PathFollowerEvent::Unreachable(entity, follower.dest()));
+                return;
+            };
+
+            let mut finder = follower.finder.clone();
+            finder.origin = follower.position;
+            queue.schedule(entity, finder);
+            follower.repath_requested = false;
+            follower.waiting = true;
+        });
+}
+
+pub fn path_follower_updater(
+    mut followers_query: Query<(Entity, &mut PathFollower, Option<Ref<Path>>)>,
+    queues_query: Query<&PathFindingQueue>,
+    mut event: EventWriter<PathFollowerEvent>,
+) {
+    followers_query
+        .iter_mut()
+        .for_each(|(entity, mut follower, path)| {
+            if let Some(path) = path {
+                if follower.waiting && path.is_changed() {
+                    follower.load_path(&path);
+                }
+            }
+
+            if follower.is_waiting() || follower.is_unreachable() {
+                return;
+            }
+
+            if follower.is_arrived() {
+                if !follower.arrival_sent {
+                    event.send(PathFollowerEvent::Arrived(entity, follower.finder.dest));
+                    follower.arrival_sent = true;
+                }
+                return;
+            }
+
+            let Some(next) = follower.current_waypoint() else {
+                return;
+            };
+            let Some(cache) = queues_query
+                .get(follower.tilemap)
+                .ok()
+                .and_then(|q| q.caches.get(&follower.finder.nav_layer))
+            else {
+                return;
+            };
+
+            let blocked = cache.get(next).is_none();
+            if blocked && !follower.blocked {
+                event.send(PathFollowerEvent::Blocked(entity, next));
+                if follower.repath_on_blocked {
+                    follower.request_repath();
+                }
+            }
+            follower.blocked = blocked;
+        });
+}
+
+pub fn path_assigner(
+    mut commands: Commands,
+    mut queues_query: Query<&mut PathFindingQueue>,
+    mut followers_query: Query<&mut PathFollower>,
+    mut event: EventWriter<PathFollowerEvent>,
+) {
     queues_query.iter_mut().for_each(|mut queue| {
         let mut completed = Vec::new();
         queue.tasks.iter_mut().for_each(|(requester, task)| {
-            if let Some(path) = bevy::tasks::block_on(futures_lite::future::poll_once(task)) {
+            let Some(path) = bevy::tasks::block_on(futures_lite::future::poll_once(task)) else {
+                return;
+            };
+            completed.push(*requester);
+
+            if let Some(path) = path {
                 commands.entity(*requester).insert(path);
-                completed.push(*requester);
+            } else if let Ok(mut follower) = followers_query.get_mut(*requester) {
+                follower.mark_unreachable();
+                event.send(PathFollowerEvent::Unreachable(*requester, follower.dest()));
             }
         });
         completed.iter().for_each(|requester| {
@@ -303,6 +624,15 @@ pub fn path_assigner(mut commands: Commands, mut queues_query: Query<&mut PathFi
 
 #[cfg(test)]
 mod test {
+    use bevy::{
+        ecs::{
+            event::Events,
+            schedule::{IntoSystemConfigs, Schedule},
+            world::World,
+        },
+        tasks::TaskPool,
+    };
+
     use super::*;
     use crate::tilemap::algorithm::path::PathTile;
 
@@ -335,7 +665,132 @@ mod test {
         };
 
         grid.find_path(TilemapType::Square);
-        let path = grid.collect_path();
+        let path = grid.collect_path().unwrap();
         dbg!(path.path);
     }
+
+    #[test]
+    fn test_default_nav_layer() {
+        let mut queue = PathFindingQueue::new_with_layers([(1, PathTilemap::new())]);
+        assert!(queue.has_layer(DEFAULT_NAV_LAYER));
+        assert!(queue.remove_layer(DEFAULT_NAV_LAYER).is_none());
+        assert!(queue.remove_layer(1).is_some());
+
+        queue.get_cache_mut().set(IVec2::ZERO, PathTile { cost: 1 });
+        assert!(queue.get_cache().get(IVec2::ZERO).is_some());
+    }
+
+    fn run_follower(dest: IVec2) -> (World, Entity) {
+        AsyncComputeTaskPool::get_or_init(TaskPool::default);
+
+        let mut path_tilemap = PathTilemap::new();
+        for x in 0..=3 {
+            path_tilemap.set(IVec2 { x, y: 0 }, PathTile { cost: 1 });
+        }
+
+        let mut world = World::new();
+        let tilemap = world
+            .spawn((TilemapType::Square, PathFindingQueue::new(path_tilemap)))
+            .id();
+        let follower = spawn_follower(&mut world, tilemap, dest, DEFAULT_NAV_LAYER);
+        run_schedule(&mut world, follower);
+
+        (world, follower)
+    }
+
+    fn spawn_follower(world: &mut World, tilemap: Entity, dest: IVec2, nav_layer: u32) -> Entity {
+        world.init_resource::<Events<PathFollowerEvent>>();
+        world
+            .spawn(PathFollower::new(
+                tilemap,
+                PathFinder {
+                    origin: IVec2::ZERO,
+                    dest,
+                    allow_diagonal: false,
+                    max_steps: None,
+                    nav_layer,
+                },
+            ))
+            .id()
+    }
+
+    fn run_schedule(world: &mut World, follower: Entity) {
+        let mut schedule = Schedule::default();
+        schedule.add_systems(
+            (
+                path_follower_scheduler,
+                pathfinding_scheduler,
+                path_assigner,
+                path_follower_updater,
+            )
+                .chain(),
+        );
+        for _ in 0..1000 {
+            schedule.run(world);
+            if !world.get::<PathFollower>(follower).unwrap().is_waiting() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    fn assert_unreachable(world: &World, follower_entity: Entity, dest: IVec2) {
+        let follower = world.get::<PathFollower>(follower_entity).unwrap();
+        assert!(!follower.is_waiting());
+        assert!(follower.is_unreachable());
+        assert!(!follower.is_arrived());
+
+        let events = world.resource::<Events<PathFollowerEvent>>();
+        assert!(events.iter_current_update_events().any(|e| matches!(
+            e,
+            PathFollowerEvent::Unreachable(entity, d) if *entity == follower_entity && *d == dest
+        )));
+        assert!(!events
+            .iter_current_update_events()
+            .any(|e| matches!(e, PathFollowerEvent::Arrived(..))));
+    }
+
+    #[test]
+    fn test_follower_reachable() {
+        let (world, follower) = run_follower(IVec2::new(3, 0));
+        let follower = world.get::<PathFollower>(follower).unwrap();
+
+        assert!(!follower.is_waiting());
+        assert!(!follower.is_unreachable());
+        assert_eq!(
+            follower.remaining_waypoints(),
+            &[IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(3, 0)]
+        );
+    }
+
+    #[test]
+    fn test_follower_unreachable() {
+        let (world, follower) = run_follower(IVec2::new(3, 3));
+        assert_unreachable(&world, follower, IVec2::new(3, 3));
+    }
+
+    #[test]
+    fn test_follower_without_queue() {
+        let mut world = World::new();
+        let tilemap = world.spawn(TilemapType::Square).id();
+        let follower = spawn_follower(&mut world, tilemap, IVec2::new(3, 0), DEFAULT_NAV_LAYER);
+        run_schedule(&mut world, follower);
+        assert_unreachable(&world, follower, IVec2::new(3, 0));
+    }
+
+    #[test]
+    fn test_follower_missing_nav_layer() {
+        AsyncComputeTaskPool::get_or_init(TaskPool::default);
+
+        let mut world = World::new();
+        let tilemap = world
+            .spawn((
+                TilemapType::Square,
+                PathFindingQueue::new(PathTilemap::new()),
+            ))
+            .id();
+        let follower = spawn_follower(&mut world, tilemap, IVec2::new(3, 0), 1);
+        run_schedule(&mut world, follower);
+        assert_unreachable(&world, follower, IVec2::new(3, 0));
+    }
 }
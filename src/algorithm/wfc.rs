@@ -53,7 +53,11 @@ impl WfcRules {
     pub fn from_file(rule_path: &str, ty: TilemapType) -> Self {
         let rule_vec: Vec<Vec<Vec<u8>>> =
             ron::from_str(std::fs::read_to_string(rule_path).unwrap().as_str()).unwrap();
+        Self::from_raw(&rule_vec, ty)
+    }
 
+    /// Create rules from the allowed neighbours of each element in each direction.
+    pub fn from_raw(rule_vec: &[Vec<Vec<u8>>], ty: TilemapType) -> Self {
         assert!(
             rule_vec.len() <= 128,
             "We only support 128 elements for now"
@@ -96,6 +100,19 @@ impl WfcRules {
         res
     }
 
+    /// Convert the rules back to the allowed neighbours of each element in each direction.
+    /// This is the format used in rule files.
+    pub fn to_raw(&self) -> Vec<Vec<Vec<u8>>> {
+        self.0
+            .iter()
+            .map(|elem| {
+                elem.iter()
+                    .map(|rule| (0..128).filter(|i| rule & (1 << i) != 0).collect())
+                    .collect()
+            })
+            .collect()
+    }
+
     /// Check if there are conflicts in the rules.
     pub fn check_rules(&self, ty: TilemapType) {
         let (total_dirs, dir_names) = match ty {
@@ -126,6 +143,49 @@ impl WfcRules {
     }
 }
 
+/// Rules and weights that can be saved to and loaded from a file,
+/// so you don't need to re-derive them every time.
+#[derive(Debug, Clone, Reflect, serde::Serialize, serde::Deserialize)]
+pub struct WfcRuleSet {
+    pub ty: TilemapType,
+    /// The allowed neighbours of each element in each direction.
+    pub rules: Vec<Vec<Vec<u8>>>,
+    pub weights: Option<Vec<u8>>,
+}
+
+impl WfcRuleSet {
+    pub fn new(rules: &WfcRules, ty: TilemapType, weights: Option<Vec<u8>>) -> Self {
+        if let Some(weights) = &weights {
+            assert_eq!(
+                weights.len(),
+                rules.0.len(),
+                "weights length not match! weights: {}, rules: {}",
+                weights.len(),
+                rules.0.len()
+            );
+        }
+
+        Self {
+            ty,
+            rules: rules.to_raw(),
+            weights,
+        }
+    }
+
+    pub fn save(&self, path: &Path, file_name: &str) {
+        crate::serializing::save_object(path, file_name, self);
+    }
+
+    pub fn load(path: &Path, file_name: &str) -> Result<Self, ron::error::SpannedError> {
+        crate::serializing::load_object(path, file_name)
+    }
+
+    /// Get the rules. This will also check if there are conflicts in the rules.
+    pub fn get_rules(&self) -> WfcRules {
+        WfcRules::from_raw(&self.rules, self.ty)
+    }
+}
+
 #[derive(Default, Clone, PartialEq, Eq, Debug, Reflect)]
 pub enum WfcMode {
     #[default]
@@ -255,16 +315,42 @@ impl WfcRunner {
         }
     }
 
+    /// Create a runner using the rules and weights in the rule set.
+    pub fn from_rule_set(rule_set: &WfcRuleSet, area: TileArea, seed: Option<u64>) -> Self {
+        let runner = Self::new(rule_set.ty, rule_set.get_rules(), area, seed);
+        match &rule_set.weights {
+            Some(weights) => runner.with_weights_vec(weights.clone()),
+            None => runner,
+        }
+    }
+
+    /// Export the rules and weights of this runner.
+    pub fn to_rule_set(&self) -> WfcRuleSet {
+        WfcRuleSet {
+            ty: self.ty,
+            rules: WfcRules(self.conn_rules.clone()).to_raw(),
+            weights: match &self.mode {
+                WfcMode::Weighted(weights) => Some(weights.clone()),
+                _ => None,
+            },
+        }
+    }
+
     /// Set the weights of the tiles.
     /// The length of the weights should be the same as the length of the rule.
-    pub fn with_weights(mut self, weights_path: String) -> Self {
+    pub fn with_weights(self, weights_path: String) -> Self {
+        let weights_vec: Vec<u8> =
+            ron::from_str(std::fs::read_to_string(weights_path).unwrap().as_str()).unwrap();
+        self.with_weights_vec(weights_vec)
+    }
+
+    /// Similar to `with_weights`, but use the weights directly.
+    pub fn with_weights_vec(mut self, weights_vec: Vec<u8>) -> Self {
         assert_eq!(
             self.mode,
             WfcMode::NonWeighted,
             "You can only use one sampler or one weights vector"
         );
-        let weights_vec: Vec<u8> =
-            ron::from_str(std::fs::read_to_string(weights_path).unwrap().as_str()).unwrap();
         assert_eq!(
             weights_vec.len(),
             self.conn_rules.len(),
@@ -381,6 +467,124 @@ impl WfcData {
             }
         }
     }
+
+    /// Get the element at the given index, where `area.origin` is the first element.
+    pub fn get_global(&self, index: IVec2) -> Option<u8> {
+        self.to_local(index).and_then(|local| self.get(local))
+    }
+
+    fn to_local(&self, index: IVec2) -> Option<UVec2> {
+        let local = index - self.area.origin;
+        if local.cmpge(IVec2::ZERO).all() && local.as_uvec2().cmplt(self.area.extent).all() {
+            Some(local.as_uvec2())
+        } else {
+            None
+        }
+    }
+
+    /// Find the elements of this data that break the rules with the adjacent elements
+    /// in `neighbour`. This is useful when the chunks of a streamed world are generated
+    /// independently, as their seams can contradict each other.
+    ///
+    /// Returns the indices in the same space as `get_global`.
+    pub fn seam_violations(&self, neighbour: &WfcData, rule_set: &WfcRuleSet) -> Vec<IVec2> {
+        let rules = rule_set.get_rules();
+        (0..self.data.len())
+            .map(|i| {
+                self.area.origin
+                    + IVec2::new(
+                        (i as u32 % self.area.extent.x) as i32,
+                        (i as u32 / self.area.extent.x) as i32,
+                    )
+            })
+            .filter(|index| {
+                let elem = self.get_global(*index).unwrap() as usize;
+                index
+                    .neighbours(rule_set.ty, false)
+                    .into_iter()
+                    .enumerate()
+                    .any(|(dir, nei)| {
+                        let Some(nei) = nei.filter(|n| self.to_local(*n).is_none()) else {
+                            return false;
+                        };
+                        neighbour
+                            .get_global(nei)
+                            .is_some_and(|n| rules.0[elem][dir] & (1 << n) == 0)
+                    })
+            })
+            .collect()
+    }
+
+    /// Repair the seams between this data and the `neighbours`.
+    ///
+    /// The elements within `radius` of the violations are solved again,
+    /// while the rest of this data and all the `neighbours` stay untouched.
+    ///
+    /// Returns false and keeps the data unchanged if the seams can't be repaired.
+    /// Try again with a larger `radius` in this case.
+    pub fn stitch(
+        &mut self,
+        neighbours: &[&WfcData],
+        rule_set: &WfcRuleSet,
+        radius: u32,
+        seed: Option<u64>,
+    ) -> bool {
+        let violations = neighbours
+            .iter()
+            .flat_map(|n| self.seam_violations(n, rule_set))
+            .collect::<Vec<_>>();
+        if violations.is_empty() {
+            return true;
+        }
+
+        let r = radius as i32;
+        let region = violations
+            .into_iter()
+            .flat_map(|v| (-r..=r).flat_map(move |y| (-r..=r).map(move |x| v + IVec2 { x, y })))
+            .filter_map(|index| self.to_local(index))
+            .collect::<HashSet<_>>();
+
+        let rules = rule_set.get_rules();
+        let total_dirs = rules.0[0].len();
+        let mut constraints = HashMap::with_capacity(region.len());
+        for local in region.iter() {
+            let index = local.as_ivec2() + self.area.origin;
+            let mut psbs = (!0) >> (128 - rules.0.len());
+            for (dir, nei) in index.neighbours(rule_set.ty, false).into_iter().enumerate() {
+                let Some(nei) = nei else {
+                    continue;
+                };
+                let fixed = match self.to_local(nei) {
+                    Some(nei_local) if region.contains(&nei_local) => None,
+                    Some(nei_local) => self.get(nei_local),
+                    None => neighbours.iter().find_map(|n| n.get_global(nei)),
+                };
+                if let Some(fixed) = fixed {
+                    psbs &= rules.0[fixed as usize][total_dirs - dir - 1];
+                }
+            }
+
+            if psbs == 0 {
+                return false;
+            }
+            constraints.insert(*local, psbs);
+        }
+
+        let mut runner = WfcRunner::from_rule_set(rule_set, self.area, seed);
+        let mut grid = WfcGrid::from_runner(&mut runner);
+        grid.restrict_to(constraints);
+        while grid.remaining > 0 && grid.retraced_time < grid.max_retrace_time {
+            grid.collapse();
+        }
+        if grid.retraced_time >= grid.max_retrace_time {
+            return false;
+        }
+
+        grid.elements.drain().for_each(|(i, e)| {
+            self.set(i, e.element_index.unwrap());
+        });
+        true
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
@@ -561,6 +765,35 @@ impl WfcGrid {
         self.retrace_strength = 1;
     }
 
+    /// Only solve the elements in `constraints`, with their possibilities limited.
+    fn restrict_to(&mut self, constraints: HashMap<UVec2, u128>) {
+        self.remaining = constraints.len();
+        self.uncollapsed = constraints
+            .iter()
+            .map(|(index, psbs)| (psbs.count_ones() as u8, *index))
+            .collect();
+        self.elements = constraints
+            .iter()
+            .map(|(index, psbs)| {
+                (
+                    *index,
+                    WfcElement {
+                        index: *index,
+                        collapsed: false,
+                        element_index: None,
+                        psbs: *psbs,
+                    },
+                )
+            })
+            .collect();
+
+        constraints.keys().for_each(|index| {
+            if self.retraced_time < self.max_retrace_time {
+                self.constrain(*index);
+            }
+        });
+    }
+
     pub fn update_entropy(&mut self, old: u8, new: u8, target: UVec2) {
         self.uncollapsed.remove(&(old, target));
         self.uncollapsed.insert((new, target));
@@ -846,8 +1079,10 @@ pub fn ldtk_wfc_helper(
                 let Some(patterns) = &ldtk_patterns else {
                     return;
                 };
-                if !patterns.is_ready() && ldtk_manager.is_initialized() {
-                    ldtk_manager.load_all_patterns(&mut commands);
+                if !patterns.is_ready() {
+                    if ldtk_manager.is_initialized() {
+                        ldtk_manager.load_all_patterns(&mut commands);
+                    }
                     return;
                 }
 
@@ -856,12 +1091,12 @@ pub fn ldtk_wfc_helper(
                         data.data.iter().enumerate().for_each(|(i, e)| {
                             let mut bg = patterns.backgrounds[*e as usize].clone().unwrap();
                             let ptn_idx = data.elem_idx_to_grid(i);
-                            let ptn_render_size = bg.sprite.custom_size.unwrap();
-                            let z = bg.transform.translation.z;
-                            bg.transform.translation = ((ptn_render_size / 2.)
+                            let ptn_render_size = bg.size();
+                            let z = bg.color.transform.translation.z;
+                            bg.color.transform.translation = ((ptn_render_size / 2.)
                                 + ptn_idx.as_vec2() * ptn_render_size)
                                 .extend(z);
-                            commands.spawn(bg);
+                            bg.spawn(&mut commands);
                         });
 
                         commands
@@ -881,3 +1116,37 @@ pub fn ldtk_wfc_helper(
             _ => {}
         });
 }
+
+#[cfg(test)]
+mod test {
+    use bevy::math::{IVec2, UVec2};
+
+    use crate::{math::TileArea, tilemap::map::TilemapType};
+
+    use super::{WfcData, WfcRuleSet, WfcRules};
+
+    #[test]
+    fn test_stitch() {
+        // 0 and 1 can't be adjacent, 2 can be next to anything.
+        let rules = WfcRules::from_raw(
+            &[
+                vec![vec![0, 2]; 4],
+                vec![vec![1, 2]; 4],
+                vec![vec![0, 1, 2]; 4],
+            ],
+            TilemapType::Square,
+        );
+        let rule_set = WfcRuleSet::new(&rules, TilemapType::Square, None);
+
+        let mut left = WfcData::new(TileArea::new(IVec2::ZERO, UVec2::splat(3)));
+        let mut right = WfcData::new(TileArea::new(IVec2::new(3, 0), UVec2::splat(3)));
+        left.data.fill(0);
+        right.data.fill(1);
+
+        assert_eq!(right.seam_violations(&left, &rule_set).len(), 3);
+        assert!(right.stitch(&[&left], &rule_set, 0, Some(0)));
+        assert!(right.seam_violations(&left, &rule_set).is_empty());
+        assert_eq!(right.get_global(IVec2::new(3, 1)), Some(2));
+        assert_eq!(right.get_global(IVec2::new(4, 1)), Some(1));
+    }
+}
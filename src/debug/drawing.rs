@@ -9,7 +9,9 @@ use crate::{
 };
 
 #[cfg(feature = "algorithm")]
-use crate::algorithm::pathfinding::Path;
+use crate::{
+    algorithm::pathfinding::Path, debug::PathCostOverlay, tilemap::algorithm::path::PathTilemap,
+};
 
 pub fn draw_chunk_aabb(
     mut gizmos: Gizmos,
@@ -84,6 +86,70 @@ pub fn draw_path(
     }
 }
 
+#[cfg(feature = "algorithm")]
+pub fn draw_path_costs(
+    mut gizmos: Gizmos,
+    overlay: bevy::ecs::system::Res<PathCostOverlay>,
+    cameras_query: Query<&CameraAabb2d>,
+    tilemaps_query: Query<(
+        &PathTilemap,
+        &TilemapType,
+        &TilePivot,
+        &TilemapAxisFlip,
+        &TilemapSlotSize,
+        &TilemapTransform,
+    )>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+
+    tilemaps_query.iter().for_each(
+        |(path_tilemap, ty, tile_pivot, axis_flip, slot_size, transform)| {
+            let storage = &path_tilemap.storage;
+            let range = overlay.cost_range.unwrap_or_else(|| {
+                storage
+                    .iter_some()
+                    .fold((u32::MAX, u32::MIN), |(min, max), tile| {
+                        (min.min(tile.cost), max.max(tile.cost))
+                    })
+            });
+            let rect_size = slot_size.0 * overlay.scale;
+
+            storage
+                .chunks
+                .iter()
+                .filter(|(chunk_index, _)| {
+                    let aabb = Aabb2d::from_tilemap(
+                        **chunk_index,
+                        storage.chunk_size,
+                        *ty,
+                        tile_pivot.0,
+                        *axis_flip,
+                        slot_size.0,
+                        *transform,
+                    );
+                    cameras_query
+                        .iter()
+                        .any(|camera| camera.0.is_intersected(aabb))
+                })
+                .for_each(|(chunk_index, chunk)| {
+                    chunk.iter_some().for_each(|(in_chunk_index, tile)| {
+                        let index = storage.inverse_transform_index(*chunk_index, in_chunk_index);
+                        let center = crate::tilemap::coordinates::index_to_world(
+                            index,
+                            *ty,
+                            transform,
+                            tile_pivot.0,
+                            slot_size.0,
+                        ) + slot_size.0 / 2.;
+                        gizmos.rect_2d(center, 0., rect_size, overlay.color(tile.cost, range));
+                    });
+                });
+        },
+    );
+}
+
 pub fn draw_axis(mut gizmos: Gizmos) {
     gizmos.line_2d(Vec2::NEG_X * 1e10, Vec2::X * 1e10, Color::RED);
     gizmos.line_2d(Vec2::NEG_Y * 1e10, Vec2::Y * 1e10, Color::GREEN);
@@ -4,6 +4,9 @@ use bevy::{
     math::Vec2,
 };
 
+#[cfg(feature = "algorithm")]
+use bevy::{math::Vec4, reflect::Reflect, render::color::Color};
+
 pub mod drawing;
 
 pub struct EntiTilesDebugPlugin;
@@ -21,11 +24,17 @@ impl Plugin for EntiTilesDebugPlugin {
                 // drawing::draw_path,
                 #[cfg(feature = "serializing")]
                 drawing::draw_updater_aabbs,
+                #[cfg(feature = "algorithm")]
+                drawing::draw_path_costs,
             ),
         );
 
         #[cfg(feature = "debug")]
         app.init_resource::<CameraAabbScale>();
+
+        #[cfg(feature = "algorithm")]
+        app.init_resource::<PathCostOverlay>()
+            .register_type::<PathCostOverlay>();
     }
 }
 
@@ -37,3 +46,63 @@ impl Default for CameraAabbScale {
         Self(Vec2::splat(1.))
     }
 }
+
+/// Draws the costs of the `PathTilemap`s as a heatmap of rects over the slots,
+/// to see why the agents choose certain routes.
+///
+/// Only the chunks visible to the cameras are drawn.
+#[cfg(feature = "algorithm")]
+#[derive(Resource, Debug, Clone, Reflect)]
+pub struct PathCostOverlay {
+    /// Disabled by default. Toggle this at runtime to show or hide the overlay.
+    pub enabled: bool,
+    /// The colors from the lowest to the highest cost, evenly spaced.
+    pub ramp: Vec<Color>,
+    /// The costs mapped to the ends of the ramp. Costs out of it are clamped.
+    ///
+    /// Leave it `None` to use the lowest and highest cost of each tilemap.
+    pub cost_range: Option<(u32, u32)>,
+    /// The size of the rects relative to the slots.
+    pub scale: f32,
+}
+
+#[cfg(feature = "algorithm")]
+impl Default for PathCostOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ramp: vec![Color::GREEN, Color::YELLOW, Color::RED],
+            cost_range: None,
+            scale: 0.8,
+        }
+    }
+}
+
+#[cfg(feature = "algorithm")]
+impl PathCostOverlay {
+    #[inline]
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    /// Sample the ramp at `cost`, where `range` is mapped to the ends of it.
+    pub fn color(&self, cost: u32, range: (u32, u32)) -> Color {
+        let Some(last) = self.ramp.len().checked_sub(1) else {
+            return Color::WHITE;
+        };
+
+        let t = if range.1 > range.0 {
+            (cost.clamp(range.0, range.1) - range.0) as f32 / (range.1 - range.0) as f32
+                * last as f32
+        } else {
+            0.
+        };
+        let lower = (t.floor() as usize).min(last);
+        let upper = (lower + 1).min(last);
+
+        Color::rgba_from_array(
+            Vec4::from(self.ramp[lower].as_rgba_f32())
+                .lerp(Vec4::from(self.ramp[upper].as_rgba_f32()), t - lower as f32),
+        )
+    }
+}
@@ -4,13 +4,35 @@ use bevy::{
 };
 
 use super::traits::{
-    LdtkEntity, LdtkEntityRegistry, LdtkEntityTag, LdtkEntityTagRegistry, PhantomLdtkEntity,
-    PhantomLdtkEntityTag,
+    LdtkEntity, LdtkEntityRegistry, LdtkEntityTag, LdtkEntityTagRegistry, LdtkEnum,
+    LdtkEnumRegistry, LdtkGlobalEntitySnapshots, LdtkSpawnContext, LdtkSpawnHooks,
+    PhantomLdtkEntity, PhantomLdtkEntityTag,
 };
 
 pub trait LdtkApp {
     fn register_ldtk_entity<T: LdtkEntity + Bundle>(&mut self, ident: &str) -> &mut App;
     fn register_ldtk_entity_tag<T: LdtkEntityTag + Component>(&mut self, tag: &str) -> &mut App;
+    /// Add a hook that runs after the entity with the identifier is spawned.
+    /// Hooks can read resources, which is not possible in `LdtkEntity::initialize`.
+    fn add_ldtk_spawn_hook(
+        &mut self,
+        ident: &str,
+        hook: impl Fn(&mut LdtkSpawnContext) + Send + Sync + 'static,
+    ) -> &mut App;
+    /// Map the LDtk enum with the identifier to `T`. All the values of the LDtk enum
+    /// are checked against `T` when the LDtk file is loaded.
+    fn register_ldtk_enum<T: LdtkEnum>(&mut self, ident: &str) -> &mut App;
+    /// Keep the component of the `GlobalEntity` entities when they are initialized again.
+    /// See `LdtkLoadConfig::refresh_global_entities`.
+    fn preserve_ldtk_global_component<C: Component + Clone>(&mut self) -> &mut App;
+    /// Generate a rectangle collider for the entities with the identifier,
+    /// using their size and pivot in LDtk.
+    #[cfg(feature = "physics")]
+    fn register_ldtk_entity_collider(
+        &mut self,
+        ident: &str,
+        collider: super::layer::physics::LdtkEntityCollider,
+    ) -> &mut App;
 }
 
 impl LdtkApp for App {
@@ -46,4 +68,41 @@ impl LdtkApp for App {
 
         self
     }
+
+    fn add_ldtk_spawn_hook(
+        &mut self,
+        ident: &str,
+        hook: impl Fn(&mut LdtkSpawnContext) + Send + Sync + 'static,
+    ) -> &mut App {
+        self.world
+            .get_resource_or_insert_with(LdtkSpawnHooks::default)
+            .add(ident, Box::new(hook));
+        self
+    }
+
+    fn register_ldtk_enum<T: LdtkEnum>(&mut self, ident: &str) -> &mut App {
+        self.world
+            .get_resource_or_insert_with(LdtkEnumRegistry::default)
+            .register::<T>(ident);
+        self
+    }
+
+    fn preserve_ldtk_global_component<C: Component + Clone>(&mut self) -> &mut App {
+        self.world
+            .get_resource_or_insert_with(LdtkGlobalEntitySnapshots::default)
+            .register::<C>();
+        self
+    }
+
+    #[cfg(feature = "physics")]
+    fn register_ldtk_entity_collider(
+        &mut self,
+        ident: &str,
+        collider: super::layer::physics::LdtkEntityCollider,
+    ) -> &mut App {
+        self.world
+            .get_resource_or_insert_with(super::layer::physics::LdtkEntityColliderRegistry::default)
+            .insert(ident, collider);
+        self
+    }
 }
@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    log::warn,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+use super::json::{
+    level::Level,
+    version::{self, LdtkJsonVersion},
+    LdtkJson,
+};
+
+#[derive(Debug)]
+pub enum LdtkJsonLoaderError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// The `jsonVersion` of the project is missing or can't be parsed.
+    UnknownVersion(Option<String>),
+    /// The project is saved by a version of LDtk that can't be loaded.
+    UnsupportedVersion {
+        version: LdtkJsonVersion,
+        error: Option<serde_json::Error>,
+    },
+}
+
+impl Display for LdtkJsonLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LdtkJsonLoaderError::Io(e) => write!(f, "Could not read the LDtk file: {}", e),
+            LdtkJsonLoaderError::Json(e) => write!(f, "Could not parse the LDtk file: {}", e),
+            LdtkJsonLoaderError::UnknownVersion(version) => write!(
+                f,
+                "Could not detect the version of the LDtk file, jsonVersion is {:?}",
+                version
+            ),
+            LdtkJsonLoaderError::UnsupportedVersion { version, error } => {
+                write!(
+                    f,
+                    "LDtk {} is not supported, only {} to {}.x are",
+                    version,
+                    LdtkJsonVersion::MIN_SUPPORTED,
+                    LdtkJsonVersion::MAX_SUPPORTED.major,
+                )?;
+                if let Some(e) = error {
+                    write!(f, ": {}", e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LdtkJsonLoaderError {}
+
+async fn read_json<T: DeserializeOwned>(reader: &mut Reader<'_>) -> Result<T, LdtkJsonLoaderError> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(LdtkJsonLoaderError::Io)?;
+    serde_json::from_slice(&bytes).map_err(LdtkJsonLoaderError::Json)
+}
+
+/// Read the project file, checking its version and migrating it from older versions.
+async fn read_project(reader: &mut Reader<'_>) -> Result<LdtkJson, LdtkJsonLoaderError> {
+    let mut json = read_json::<serde_json::Value>(reader).await?;
+    let raw_version = version::detect_version(&json).map(|v| v.to_string());
+    let Some(version) = raw_version.as_deref().and_then(LdtkJsonVersion::parse) else {
+        return Err(LdtkJsonLoaderError::UnknownVersion(raw_version));
+    };
+    if version.is_incompatible() {
+        return Err(LdtkJsonLoaderError::UnsupportedVersion {
+            version,
+            error: None,
+        });
+    }
+
+    version::migrate(&mut json, version);
+    match serde_json::from_value(json) {
+        Ok(ldtk_json) => {
+            if version.is_newer() {
+                warn!(
+                    "LDtk {} is newer than {}, some data may be missing!",
+                    version,
+                    LdtkJsonVersion::MAX_SUPPORTED
+                );
+            }
+            Ok(ldtk_json)
+        }
+        Err(error) if version.is_newer() => Err(LdtkJsonLoaderError::UnsupportedVersion {
+            version,
+            error: Some(error),
+        }),
+        Err(error) => Err(LdtkJsonLoaderError::Json(error)),
+    }
+}
+
+/// Loads `.ldtk` files as `LdtkJson` assets.
+///
+/// Files from LDtk 1.3 onwards are supported, see `LdtkJsonVersion`.
+#[derive(Default)]
+pub struct LdtkJsonLoader;
+
+impl AssetLoader for LdtkJsonLoader {
+    type Asset = LdtkJson;
+    type Settings = ();
+    type Error = LdtkJsonLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(read_project(reader))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// Loads external level files (`.ldtkl`) as `Level` assets.
+///
+/// These files are created when "Save levels separately" is enabled in LDtk.
+#[derive(Default)]
+pub struct LdtkLevelLoader;
+
+impl AssetLoader for LdtkLevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LdtkJsonLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(read_json(reader))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtkl"]
+    }
+}
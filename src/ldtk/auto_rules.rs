@@ -0,0 +1,534 @@
+use bevy::{
+    ecs::{
+        reflect::ReflectResource,
+        system::{Commands, Resource},
+    },
+    math::{IVec2, UVec2, Vec4},
+    reflect::Reflect,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    math::aabb::IAabb2d,
+    tilemap::{
+        map::TilemapStorage,
+        tile::{TileBuilder, TileLayer},
+    },
+};
+
+use super::{
+    components::LdtkIntGridLayer,
+    json::{
+        definitions::{
+            AutoLayerRuleCheckerMode, AutoLayerRuleDef, AutoLayerRuleTileMode, LayerDef,
+        },
+        level::LayerInstance,
+        LdtkJson,
+    },
+};
+
+/// The pattern value that matches any non-empty cell, or empty cells if negative.
+const AUTO_LAYER_ANYTHING: i32 = 1000001;
+
+/// A tile generated by the auto-layer rules.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct LdtkAutoTile {
+    pub texture_index: u32,
+    /// Bit 0 = X flip, bit 1 = Y flip. The same as `TileInstance::flip`.
+    pub flip: u32,
+    pub alpha: f32,
+}
+
+/// The auto-layer rules of a layer, which can be evaluated at runtime
+/// to recompute the auto tiles after the IntGrid values are changed.
+///
+/// Use `LdtkLevelManager::get_auto_rules` to get the rules of a loaded level,
+/// modify the values in `LdtkIntGrid`, and then call `apply` with the changed area.
+///
+/// **Notice**: The random choices (`chance` and random tiles) are deterministic
+/// but they don't use the same random generator as the editor. The perlin filter and
+/// the tile offsets are not supported, so the rules using them are applied
+/// everywhere they match, without any offset.
+#[derive(Debug, Clone, Reflect)]
+pub struct LdtkAutoRules {
+    /// The active rules, from the top priority to the lowest.
+    pub rules: Vec<AutoLayerRuleDef>,
+    /// IntGrid value to its group uid in the source layer.
+    pub groups: HashMap<i32, i32>,
+    pub seed: i32,
+    /// The count of tile columns in the tileset, used to lay out the tile rects.
+    pub tileset_columns: i32,
+}
+
+impl LdtkAutoRules {
+    /// Collect the rules of a layer instance.
+    /// Returns `None` if the layer doesn't have any active rule.
+    pub fn from_layer(ldtk_data: &LdtkJson, layer: &LayerInstance) -> Option<Self> {
+        let layer_def = ldtk_data
+            .defs
+            .layers
+            .iter()
+            .find(|d| d.uid == layer.layer_def_uid)?;
+        Self::from_layer_def(
+            ldtk_data,
+            layer_def,
+            layer.override_tileset_uid.or(layer.tileset_def_uid),
+            layer.seed,
+            &layer.optional_rules,
+        )
+    }
+
+    /// Collect the rules of a layer definition, which is not bound to any level.
+    ///
+    /// `tileset_uid` overrides the tileset of the definition, and only the optional
+    /// rule groups in `optional_rules` are enabled.
+    /// Returns `None` if the layer doesn't have any active rule.
+    pub fn from_layer_def(
+        ldtk_data: &LdtkJson,
+        layer_def: &LayerDef,
+        tileset_uid: Option<i32>,
+        seed: i32,
+        optional_rules: &[i32],
+    ) -> Option<Self> {
+        let defs = &ldtk_data.defs;
+        let source_def = match layer_def.auto_source_layer_def_uid {
+            Some(uid) => defs.layers.iter().find(|d| d.uid == uid)?,
+            None => layer_def,
+        };
+        let tileset_uid = tileset_uid.or(layer_def.tileset_def_uid)?;
+        let tileset = defs.tilesets.iter().find(|t| t.uid == tileset_uid)?;
+
+        let rules = layer_def
+            .auto_rule_groups
+            .iter()
+            .filter(|group| {
+                group.active && (!group.is_optional || optional_rules.contains(&group.uid))
+            })
+            .flat_map(|group| group.rules.iter())
+            .filter(|rule| rule.active && !rule.tile_rects_ids.is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if rules.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            rules,
+            groups: source_def
+                .int_grid_values
+                .iter()
+                .map(|v| (v.value, v.group_uid))
+                .collect(),
+            seed,
+            tileset_columns: tileset.c_wid,
+        })
+    }
+
+    /// The count of cells around a cell that the rules check.
+    pub fn radius(&self) -> i32 {
+        self.rules.iter().map(|r| r.size / 2).max().unwrap_or(0)
+    }
+
+    /// Evaluate the rules for the cells in `area`, using `source` as the IntGrid layer.
+    ///
+    /// `area` is in tile indices, and the returned tiles are sorted from the bottom to the top.
+    /// Tiles that stamps put out of `area` are left out.
+    pub fn evaluate(
+        &self,
+        source: &LdtkIntGridLayer,
+        area: IAabb2d,
+    ) -> HashMap<IVec2, Vec<LdtkAutoTile>> {
+        let area = area.justified();
+        let mut result = HashMap::<IVec2, Vec<LdtkAutoTile>>::new();
+        let mut broken = HashSet::new();
+
+        for rule in &self.rules {
+            for y in area.min.y..=area.max.y {
+                for x in area.min.x..=area.max.x {
+                    let index = IVec2 { x, y };
+                    if broken.contains(&index) {
+                        continue;
+                    }
+
+                    let cell = IVec2 { x, y: -y - 1 };
+                    if !is_in_bounds(source.size, cell) || !self.is_relevant_at(rule, cell) {
+                        continue;
+                    }
+
+                    let Some(flip) = self.find_match(rule, source, cell) else {
+                        continue;
+                    };
+
+                    self.place_tiles(rule, cell, flip)
+                        .filter(|(index, _)| area.contains(*index))
+                        .for_each(|(index, tile)| result.entry(index).or_default().push(tile));
+
+                    if rule.break_on_match {
+                        broken.insert(index);
+                    }
+                }
+            }
+        }
+
+        // The tiles from the rules with higher priority are on top.
+        result.values_mut().for_each(|tiles| tiles.reverse());
+        result
+    }
+
+    /// Recompute the auto tiles around `area`, which is in tile indices,
+    /// and replace the tiles of the tilemap there.
+    pub fn apply(
+        &self,
+        commands: &mut Commands,
+        storage: &mut TilemapStorage,
+        source: &LdtkIntGridLayer,
+        area: IAabb2d,
+    ) {
+        let radius = IVec2::splat(self.radius());
+        let area = area.justified();
+        let area = IAabb2d {
+            min: area.min - radius,
+            max: area.max + radius,
+        };
+
+        storage.despawn_region(commands, area);
+        self.evaluate(source, area)
+            .into_iter()
+            .for_each(|(index, tiles)| {
+                let builder = tiles.iter().enumerate().fold(
+                    TileBuilder::new().with_color(Vec4::new(1., 1., 1., tiles[0].alpha)),
+                    |builder, (i, tile)| {
+                        builder.with_layer(
+                            i,
+                            TileLayer::new()
+                                .with_texture_index(tile.texture_index)
+                                .with_flip_raw(tile.flip),
+                        )
+                    },
+                );
+                storage.set(commands, index, builder);
+            });
+    }
+
+    /// Set the value of a cell in `source` and recompute the auto tiles around it.
+    /// `0` clears the cell.
+    pub fn paint(
+        &self,
+        commands: &mut Commands,
+        storage: &mut TilemapStorage,
+        source: &mut LdtkIntGridLayer,
+        index: IVec2,
+        value: i32,
+    ) {
+        source.set(index, value);
+        self.apply(commands, storage, source, IAabb2d::splat(index));
+    }
+
+    fn is_relevant_at(&self, rule: &AutoLayerRuleDef, cell: IVec2) -> bool {
+        let x_modulo = rule.x_modulo.max(1);
+        let y_modulo = rule.y_modulo.max(1);
+
+        let y = match rule.checker {
+            AutoLayerRuleCheckerMode::Vertical => cell.y + (cell.x / x_modulo) % 2 * rule.y_offset,
+            _ => cell.y - rule.y_offset,
+        };
+        let x = match rule.checker {
+            AutoLayerRuleCheckerMode::Horizontal => {
+                cell.x + (cell.y / y_modulo) % 2 * rule.x_offset
+            }
+            _ => cell.x - rule.x_offset,
+        };
+
+        x.rem_euclid(x_modulo) == 0 && y.rem_euclid(y_modulo) == 0
+    }
+
+    /// Returns the flip bits of the first matching orientation.
+    fn find_match(
+        &self,
+        rule: &AutoLayerRuleDef,
+        source: &LdtkIntGridLayer,
+        cell: IVec2,
+    ) -> Option<u32> {
+        if rule.chance <= 0.
+            || rule.chance < 1.
+                && rand_coords(self.seed.wrapping_add(rule.uid), cell, 100) as f32
+                    >= rule.chance * 100.
+        {
+            return None;
+        }
+
+        [
+            (IVec2::new(1, 1), true),
+            (IVec2::new(-1, 1), rule.flip_x),
+            (IVec2::new(1, -1), rule.flip_y),
+            (IVec2::new(-1, -1), rule.flip_x && rule.flip_y),
+        ]
+        .into_iter()
+        .find(|(dir, enabled)| *enabled && self.matches(rule, source, cell, *dir))
+        .map(|(dir, _)| (dir.x < 0) as u32 | ((dir.y < 0) as u32) << 1)
+    }
+
+    fn matches(
+        &self,
+        rule: &AutoLayerRuleDef,
+        source: &LdtkIntGridLayer,
+        cell: IVec2,
+        dir: IVec2,
+    ) -> bool {
+        let radius = rule.size / 2;
+
+        for py in 0..rule.size {
+            for px in 0..rule.size {
+                let expected = rule.pattern[(px + py * rule.size) as usize];
+                if expected == 0 {
+                    continue;
+                }
+
+                let target = cell + dir * IVec2::new(px - radius, py - radius);
+                let value = if is_in_bounds(source.size, target) {
+                    source
+                        .get(IVec2 {
+                            x: target.x,
+                            y: -target.y - 1,
+                        })
+                        .unwrap_or_default()
+                } else if let Some(value) = rule.out_of_bounds_value {
+                    value
+                } else {
+                    return false;
+                };
+
+                let is_match = if expected.abs() == AUTO_LAYER_ANYTHING {
+                    value != 0
+                } else if expected.abs() > 999 {
+                    self.groups.get(&value) == Some(&(expected.abs() / 1000 - 1))
+                } else {
+                    value == expected.abs()
+                };
+
+                if is_match != (expected > 0) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Pick a tile rect for the cell and lay it out. Returns the tile indices and the tiles.
+    fn place_tiles<'a>(
+        &'a self,
+        rule: &'a AutoLayerRuleDef,
+        cell: IVec2,
+        flip: u32,
+    ) -> impl Iterator<Item = (IVec2, LdtkAutoTile)> + 'a {
+        let rect = &rule.tile_rects_ids[rand_coords(
+            self.seed.wrapping_add(rule.uid),
+            cell,
+            rule.tile_rects_ids.len() as u32,
+        ) as usize];
+
+        let columns = self.tileset_columns.max(1);
+        let coords = move |id: i32| IVec2::new(id % columns, id / columns);
+        let (min, max) = rect.iter().fold(
+            (IVec2::splat(i32::MAX), IVec2::splat(i32::MIN)),
+            |(min, max), id| (min.min(coords(*id)), max.max(coords(*id))),
+        );
+        let extent = max - min;
+        let pivot = match rule.tile_mode {
+            AutoLayerRuleTileMode::Single => IVec2::ZERO,
+            AutoLayerRuleTileMode::Stamp => IVec2::new(
+                (extent.x as f32 * rule.pivot_x).round() as i32,
+                (extent.y as f32 * rule.pivot_y).round() as i32,
+            ),
+        };
+
+        rect.iter().map(move |id| {
+            let mut offset = coords(*id) - min;
+            if flip & 1 != 0 {
+                offset.x = extent.x - offset.x;
+            }
+            if flip & 2 != 0 {
+                offset.y = extent.y - offset.y;
+            }
+            let target = cell + offset - pivot;
+
+            (
+                IVec2 {
+                    x: target.x,
+                    y: -target.y - 1,
+                },
+                LdtkAutoTile {
+                    texture_index: *id as u32,
+                    flip,
+                    alpha: rule.alpha,
+                },
+            )
+        })
+    }
+}
+
+/// The auto-layer rules of all the layer definitions in the LDtk file, by layer identifier.
+///
+/// The rules are not bound to any level, so they can be used with any tilemap:
+/// paint the IntGrid values into a `LdtkIntGridLayer` and use `LdtkAutoRules::paint`
+/// or `LdtkAutoRules::apply` to resolve them into tiles, the same way as LDtk.
+///
+/// This is refreshed when the LDtk file is loaded. Optional rule groups are disabled
+/// and the seed is 0, use `LdtkAutoRules::from_layer_def` to build the rules with
+/// other settings and `insert` them.
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct AutoRuleSet {
+    pub layers: HashMap<String, LdtkAutoRules>,
+}
+
+impl AutoRuleSet {
+    pub fn from_ldtk(ldtk_data: &LdtkJson) -> Self {
+        Self {
+            layers: ldtk_data
+                .defs
+                .layers
+                .iter()
+                .filter_map(|layer_def| {
+                    LdtkAutoRules::from_layer_def(ldtk_data, layer_def, None, 0, &[])
+                        .map(|rules| (layer_def.identifier.clone(), rules))
+                })
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, layer: &str) -> Option<&LdtkAutoRules> {
+        self.layers.get(layer)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, layer: String, rules: LdtkAutoRules) {
+        self.layers.insert(layer, rules);
+    }
+
+    /// The identifiers of the layers that have rules.
+    #[inline]
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys().map(|layer| layer.as_str())
+    }
+}
+
+/// `cell` is in LDtk grid coordinates, with y pointing down.
+#[inline]
+fn is_in_bounds(size: UVec2, cell: IVec2) -> bool {
+    cell.x >= 0 && cell.y >= 0 && cell.x < size.x as i32 && cell.y < size.y as i32
+}
+
+/// A deterministic random number in `0..max` for the cell.
+fn rand_coords(seed: i32, cell: IVec2, max: u32) -> u32 {
+    let mut h = (seed as u32).wrapping_mul(0x9E3779B1)
+        ^ (cell.x as u32).wrapping_mul(0x85EBCA77)
+        ^ (cell.y as u32).wrapping_mul(0xC2B2AE3D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B3C6D);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297A2D39);
+    h ^= h >> 15;
+    h % max.max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        math::{IVec2, UVec2},
+        utils::HashMap,
+    };
+
+    use crate::{
+        ldtk::{
+            components::LdtkIntGridLayer,
+            json::definitions::{
+                AutoLayerRuleCheckerMode, AutoLayerRuleDef, AutoLayerRuleTileMode,
+            },
+        },
+        math::aabb::IAabb2d,
+    };
+
+    use super::{LdtkAutoRules, LdtkAutoTile};
+
+    fn rule(uid: i32, pattern: Vec<i32>, tile: i32) -> AutoLayerRuleDef {
+        AutoLayerRuleDef {
+            active: true,
+            alpha: 1.,
+            break_on_match: true,
+            chance: 1.,
+            checker: AutoLayerRuleCheckerMode::None,
+            flip_x: false,
+            flip_y: false,
+            out_of_bounds_value: None,
+            pattern,
+            perlin_active: false,
+            pivot_x: 0.,
+            pivot_y: 0.,
+            size: 3,
+            tile_mode: AutoLayerRuleTileMode::Single,
+            tile_rects_ids: vec![vec![tile]],
+            uid,
+            x_modulo: 1,
+            x_offset: 0,
+            y_modulo: 1,
+            y_offset: 0,
+        }
+    }
+
+    fn tile(texture_index: u32, flip: u32) -> LdtkAutoTile {
+        LdtkAutoTile {
+            texture_index,
+            flip,
+            alpha: 1.,
+        }
+    }
+
+    #[test]
+    fn test_auto_rules() {
+        // A 3x3 layer with a wall in the middle row:
+        // . . .
+        // # # #
+        // . . .
+        let mut source = LdtkIntGridLayer {
+            identifier: "IntGrid".to_string(),
+            grid_size: 16,
+            size: UVec2::splat(3),
+            values: (0..3).map(|x| (IVec2::new(x, -2), 1)).collect(),
+        };
+
+        // Walls with air above, and the cells above walls.
+        let mut floor = rule(1, vec![0, -1, 0, 0, 1, 0, 0, 0, 0], 10);
+        floor.out_of_bounds_value = Some(0);
+        let mut shadow = rule(2, vec![0, 0, 0, 0, -1, 0, 0, 1, 0], 20);
+        shadow.flip_y = true;
+        shadow.break_on_match = false;
+        let fill = rule(3, vec![0, 0, 0, 0, 1, 0, 0, 0, 0], 30);
+
+        let rules = LdtkAutoRules {
+            rules: vec![floor, shadow, fill],
+            groups: HashMap::default(),
+            seed: 0,
+            tileset_columns: 8,
+        };
+
+        let tiles = rules.evaluate(&source, IAabb2d::new(0, -3, 2, -1));
+        assert_eq!(tiles.len(), 9);
+        // The floor breaks so the fill is never applied.
+        assert_eq!(tiles[&IVec2::new(1, -2)], vec![tile(10, 0)]);
+        assert_eq!(tiles[&IVec2::new(1, -1)], vec![tile(20, 0)]);
+        // The shadow under the walls is matched with the flipped pattern.
+        assert_eq!(tiles[&IVec2::new(1, -3)], vec![tile(20, 2)]);
+
+        let tiles = rules.evaluate(&source, IAabb2d::new(0, -1, 2, -1));
+        assert_eq!(tiles.len(), 3);
+
+        // Painting air into the wall leaves a gap without any tile.
+        source.set(IVec2::new(1, -2), 0);
+        let tiles = rules.evaluate(&source, IAabb2d::new(0, -3, 2, -1));
+        assert!(!tiles.contains_key(&IVec2::new(1, -2)));
+    }
+}
@@ -1,11 +1,33 @@
 use bevy::{
-    ecs::{component::Component, entity::Entity, system::Commands},
-    math::Vec2,
+    asset::Handle,
+    ecs::{component::Component, entity::Entity, system::Commands, world::World},
+    hierarchy::{BuildChildren, BuildWorldChildren, DespawnRecursiveExt, Parent},
+    math::{IVec2, UVec2, Vec2, Vec4},
     reflect::Reflect,
+    render::{color::Color, view::Visibility},
+    sprite::{ImageScaleMode, SpriteBundle},
+    transform::components::GlobalTransform,
     utils::HashMap,
 };
 
-use super::resources::LdtkGlobalEntityRegistry;
+#[cfg(feature = "physics")]
+use crate::tilemap::physics::{self, PhysicsTilemap};
+use crate::tilemap::{
+    bundles::StandardPureColorTilemapBundle,
+    chunking::{suggest_chunk_size, DEFAULT_EXPECTED_VIEW_SIZE},
+    map::{TileRenderSize, TilemapName, TilemapSlotSize, TilemapStorage, TilemapType},
+    tile::{RawTileAnimation, TileAnimationMode, TileBuilder},
+};
+
+use super::{
+    json::{
+        definitions::LayerType,
+        field::{FieldInstance, FieldValue},
+        level::LayerInstance,
+        LdtkJson,
+    },
+    resources::{LdtkAtmosphereFields, LdtkGlobalEntityRegistry},
+};
 
 #[derive(Reflect, Default, Clone, Copy, PartialEq, Eq)]
 pub enum LdtkLoaderMode {
@@ -14,11 +36,15 @@ pub enum LdtkLoaderMode {
     MapPattern,
 }
 
-#[derive(Component, Reflect, Default)]
+#[derive(Component, Reflect, Default, Clone)]
 pub struct LdtkLoader {
+    /// The level won't be loaded until this asset is loaded.
+    pub(crate) json: Handle<LdtkJson>,
     pub(crate) level: String,
     pub(crate) mode: LdtkLoaderMode,
     pub(crate) trans_ovrd: Option<Vec2>,
+    /// The level root is spawned as a child of this entity.
+    pub(crate) parent: Option<Entity>,
 }
 
 #[derive(Component, Reflect, Default)]
@@ -27,12 +53,22 @@ pub struct LdtkUnloader;
 #[derive(Component)]
 pub struct LdtkUnloadLayer;
 
+/// The root entity of a loaded level.
+///
+/// The tilemap layers, the background and the entities are spawned as its children,
+/// so moving or hiding this entity moves or hides the whole level.
+/// Use `LdtkLevelManager::load_with_parent` to attach it to another entity.
+/// `GlobalEntity` entities are kept at the root as they outlive the level.
 #[derive(Component, Reflect)]
 pub struct LdtkLoadedLevel {
     pub identifier: String,
     pub layers: HashMap<LayerIid, Entity>,
     pub entities: HashMap<EntityIid, Entity>,
     pub background: Entity,
+    /// The `worldDepth` of the level in LDtk.
+    pub world_depth: i32,
+    /// The base z index of the layers, see `LdtkLoadConfig::level_z_index`.
+    pub z_index: i32,
 }
 
 impl LdtkLoadedLevel {
@@ -44,15 +80,119 @@ impl LdtkLoadedLevel {
             .iter()
             .filter(|(iid, _)| !global_entities.contains(iid))
             .for_each(|(_, e)| {
-                commands.entity(*e).despawn();
+                LdtkPersistent::despawn_or_detach(commands, *e);
+            });
+        commands.entity(self.background).despawn_recursive();
+    }
+
+    /// Show or hide the layers whose identifier is `layer`, along with their colliders.
+    ///
+    /// Hidden layers are set to `Visibility::Hidden`, which makes them fully transparent.
+    /// This is applied when the commands are applied, and is reset when the level is reloaded.
+    pub fn set_layer_visible(&self, commands: &mut Commands, layer: &str, visible: bool) {
+        let layers = self.layers.values().copied().collect::<Vec<_>>();
+        let layer = layer.to_string();
+        commands.add(move |world: &mut World| {
+            for entity in layers {
+                let Some(mut entity) = world.get_entity_mut(entity) else {
+                    continue;
+                };
+                if entity.get::<TilemapName>().map(|name| &name.0) != Some(&layer) {
+                    continue;
+                }
+
+                entity.insert(if visible {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                });
+
+                #[cfg(feature = "physics")]
+                if let Some(colliders) = entity
+                    .get_mut::<PhysicsTilemap>()
+                    .and_then(|mut physics| physics.switch_enabled(visible))
+                {
+                    colliders.into_iter().for_each(|collider| {
+                        physics::set_collider_enabled(world, collider, visible);
+                    });
+                }
+            }
+        });
+    }
+}
+
+/// Entities with this survive when their level is unloaded, like the player or
+/// dropped items, without being a `GlobalEntity`.
+///
+/// They are detached from the level instead: `LdtkLevelRoot` is removed and they are
+/// moved to the world root, keeping their global transform. Insert this at runtime, or
+/// use `LdtkLoadConfig::persistent_entity_tags` to insert it when the entities are spawned.
+///
+/// **Notice**: Unlike `GlobalEntity`, these are spawned again when the level is loaded again.
+#[derive(Component, Debug, Default, Reflect, Clone, Copy)]
+pub struct LdtkPersistent;
+
+impl LdtkPersistent {
+    pub(crate) fn despawn_or_detach(commands: &mut Commands, entity: Entity) {
+        commands.add(move |world: &mut World| {
+            let Some(mut entity) = world.get_entity_mut(entity) else {
+                return;
+            };
+            if !entity.contains::<LdtkPersistent>() {
+                entity.despawn();
+                return;
+            }
+
+            entity.remove::<LdtkLevelRoot>();
+            if entity.contains::<Parent>() {
+                if let Some(global) = entity.get::<GlobalTransform>().copied() {
+                    entity.insert(global.compute_transform());
+                }
+                entity.remove_parent();
+            }
+        });
+    }
+}
+
+/// The background of a level.
+///
+/// The background color fills the whole level, and the background image
+/// is spawned as a child of it, positioned the same way as in LDtk.
+#[derive(Default, Clone)]
+pub struct LdtkBackground {
+    pub color: SpriteBundle,
+    pub image: Option<(SpriteBundle, Option<ImageScaleMode>)>,
+}
+
+impl LdtkBackground {
+    /// The size of the level in pixels.
+    #[inline]
+    pub fn size(&self) -> Vec2 {
+        self.color.sprite.custom_size.unwrap_or_default()
+    }
+
+    pub fn spawn(&self, commands: &mut Commands) -> Entity {
+        let mut background = commands.spawn(self.color.clone());
+        if let Some((image, scale_mode)) = &self.image {
+            background.with_children(|builder| {
+                let mut image = builder.spawn(image.clone());
+                if let Some(scale_mode) = scale_mode {
+                    image.insert(scale_mode.clone());
+                }
             });
-        commands.entity(self.background).despawn();
+        }
+        background.id()
     }
 }
 
+/// The position of an LDtk entity, applied to its `Transform` after it's spawned.
+/// Entities without a `Transform` get a `TransformBundle`.
 #[derive(Component, Debug, Clone)]
 pub struct LdtkTempTransform {
     pub level_translation: Vec2,
+    /// The pivot of the entity in the level, including the offset of its layer,
+    /// with the y axis pointing up.
+    pub local_translation: Vec2,
     pub z_index: f32,
 }
 
@@ -62,6 +202,93 @@ pub struct GlobalEntity;
 #[derive(Component, Debug, Reflect, Hash, Eq, PartialEq, Clone)]
 pub struct EntityIid(pub String);
 
+/// The `EntityRef` fields of an LDtk entity, resolved to the spawned entities.
+///
+/// This is inserted on the entities that have at least one `EntityRef` field.
+/// A reference stays unresolved until the referred entity is spawned, which may happen
+/// later if it's in another level. It becomes unresolved again if that entity is despawned.
+#[derive(Component, Debug, Default, Reflect, Clone)]
+pub struct LdtkEntityRefs {
+    /// field identifier to the iids of the referred entities
+    pub(crate) iids: HashMap<String, Vec<String>>,
+    /// field identifier to the referred entities, in the same order as the iids
+    pub(crate) entities: HashMap<String, Vec<Option<Entity>>>,
+}
+
+impl LdtkEntityRefs {
+    /// Collect the `EntityRef` fields. Returns `None` if there isn't any.
+    pub fn from_fields<'a>(fields: impl Iterator<Item = &'a FieldInstance>) -> Option<Self> {
+        let iids = fields
+            .filter_map(|field| {
+                let iids = if let Some(r) = field.as_entity_ref() {
+                    vec![r.entity_iid.clone()]
+                } else {
+                    field
+                        .as_entity_refs()?
+                        .iter()
+                        .map(|r| r.entity_iid.clone())
+                        .collect()
+                };
+                Some((field.identifier.clone(), iids))
+            })
+            .collect::<HashMap<_, _>>();
+
+        if iids.is_empty() {
+            None
+        } else {
+            Some(Self {
+                entities: iids
+                    .iter()
+                    .map(|(f, i)| (f.clone(), vec![None; i.len()]))
+                    .collect(),
+                iids,
+            })
+        }
+    }
+
+    /// Get the entity the field refers to. For array fields, this is the first one.
+    #[inline]
+    pub fn get(&self, field: &str) -> Option<Entity> {
+        self.entities
+            .get(field)
+            .and_then(|e| e.first().copied().flatten())
+    }
+
+    /// Get all the entities the field refers to. `None` means the entity is not spawned yet.
+    #[inline]
+    pub fn get_all(&self, field: &str) -> Option<&[Option<Entity>]> {
+        self.entities.get(field).map(|e| e.as_slice())
+    }
+
+    /// Returns true if all the references are resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.entities.values().flatten().all(|e| e.is_some())
+    }
+
+    /// Returns true if any of the references changed.
+    pub(crate) fn resolve(&mut self, lookup: impl Fn(&str) -> Option<Entity>) -> bool {
+        let mut changed = false;
+        for (field, iids) in &self.iids {
+            let Some(entities) = self.entities.get_mut(field) else {
+                continue;
+            };
+            iids.iter().zip(entities.iter_mut()).for_each(|(iid, e)| {
+                let resolved = lookup(iid);
+                changed |= *e != resolved;
+                *e = resolved;
+            });
+        }
+        changed
+    }
+}
+
+/// The root entity of the level, or the level instance, that the LDtk entity is spawned in.
+///
+/// `EntityRef` fields are resolved to the entities with the same root first,
+/// so the instances of a level don't refer to each other.
+#[derive(Component, Debug, Reflect, Hash, Eq, PartialEq, Clone, Copy)]
+pub struct LdtkLevelRoot(pub Entity);
+
 #[derive(Component, Debug, Reflect, Hash, Eq, PartialEq, Clone)]
 pub struct LayerIid(pub String);
 
@@ -70,3 +297,426 @@ pub struct LevelIid(pub String);
 
 #[derive(Component, Debug, Reflect, Hash, Eq, PartialEq, Clone)]
 pub struct WorldIid(pub String);
+
+/// The uid of the tileset of an LDtk layer, inserted on the layer tilemaps with a tileset.
+#[derive(Component, Debug, Reflect, Hash, Eq, PartialEq, Clone, Copy)]
+pub struct TilesetUid(pub i32);
+
+/// The custom data and enum tags of a tile in an LDtk tileset.
+///
+/// This is inserted on the tiles if `LdtkLoadConfig::tile_data_components` is enabled.
+/// See `LdtkTileMetadata` for all of them.
+#[derive(Component, Debug, Default, Reflect, Clone, PartialEq, Eq)]
+pub struct LdtkTileData {
+    pub custom_data: Option<String>,
+    /// The enum values the tile is tagged with.
+    pub enum_tags: Vec<String>,
+}
+
+impl LdtkTileData {
+    #[inline]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.enum_tags.iter().any(|t| t == tag)
+    }
+
+    /// The animation in the custom data, see `LdtkLoadConfig::custom_data_animations`.
+    /// Returns `None` if there isn't one or it can't be parsed.
+    #[inline]
+    pub fn animation(&self) -> Option<RawTileAnimation> {
+        self.custom_data
+            .as_deref()
+            .and_then(|data| Self::parse_animation(data).ok().flatten())
+    }
+
+    /// Parse the `animation:` line of the custom data of a tile, like
+    /// `animation: 12 13 14 15 @ 8` or `animation: 12:100 13:100 14:250 ping_pong`.
+    ///
+    /// Returns `Ok(None)` if the custom data has no animation.
+    pub fn parse_animation(custom_data: &str) -> Result<Option<RawTileAnimation>, String> {
+        let Some(line) = custom_data
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("animation:"))
+        else {
+            return Ok(None);
+        };
+
+        let mut sequence = Vec::new();
+        let mut durations = Vec::new();
+        let mut fps = None;
+        let mut mode = TileAnimationMode::Loop;
+        let mut tokens = line.split(|c: char| c.is_whitespace() || c == ',');
+        while let Some(token) = tokens.next() {
+            let parse = |n: &str| {
+                n.parse::<u32>()
+                    .map_err(|_| format!("Invalid number {:?} in {:?}", n, line))
+            };
+            match token {
+                "" => {}
+                "loop" => mode = TileAnimationMode::Loop,
+                "ping_pong" => mode = TileAnimationMode::PingPong,
+                "once" => mode = TileAnimationMode::Once,
+                "@" => {
+                    let n = tokens.by_ref().find(|t| !t.is_empty()).unwrap_or("");
+                    fps = Some(parse(n)?);
+                }
+                _ => {
+                    if let Some(n) = token.strip_prefix('@') {
+                        fps = Some(parse(n)?);
+                    } else if let Some((frame, duration)) = token.split_once(':') {
+                        sequence.push(parse(frame)?);
+                        durations.push(parse(duration)?);
+                    } else {
+                        sequence.push(parse(token)?);
+                    }
+                }
+            }
+        }
+
+        if sequence.is_empty() {
+            return Err(format!("No frames in {:?}", line));
+        }
+        let animation = match fps {
+            Some(_) if !durations.is_empty() => {
+                return Err(format!("Both fps and durations are set in {:?}", line))
+            }
+            Some(fps) => RawTileAnimation::new(sequence, fps),
+            None if durations.len() == sequence.len() => {
+                RawTileAnimation::with_durations(sequence, durations)
+            }
+            None => return Err(format!("Missing fps or frame durations in {:?}", line)),
+        };
+        Ok(Some(animation.with_mode(mode)))
+    }
+}
+
+/// The tileset enum tags of the placed tiles in an LDtk layer, like `Ladder` or `Breakable`.
+///
+/// This is inserted on the layer tilemaps with a tileset and kept up to date
+/// when tiles are set. Removing a tile without replacing it doesn't clear its tags.
+#[derive(Component, Debug, Default, Reflect, Clone)]
+pub struct LdtkTileTags {
+    /// tile index to the enum values
+    pub(crate) tags: HashMap<IVec2, Vec<String>>,
+}
+
+impl LdtkTileTags {
+    /// Get the tags of the tile at `index`. Empty if there's no tile or it isn't tagged.
+    #[inline]
+    pub fn tags_at(&self, index: IVec2) -> &[String] {
+        self.tags
+            .get(&index)
+            .map(|t| t.as_slice())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn has_tag(&self, index: IVec2, tag: &str) -> bool {
+        self.tags_at(index).iter().any(|t| t == tag)
+    }
+
+    /// Iterate over the indices of the tiles with the tag.
+    pub fn tiles_with<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = IVec2> + 'a {
+        self.tags
+            .iter()
+            .filter(move |(_, tags)| tags.iter().any(|t| t == tag))
+            .map(|(index, _)| *index)
+    }
+
+    pub(crate) fn set(&mut self, index: IVec2, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.tags.remove(&index);
+        } else {
+            self.tags.insert(index, tags);
+        }
+    }
+}
+
+/// The `worldDepth` of a level, inserted on the level entity.
+///
+/// Levels with the same world position but different depths are usually
+/// the floors of a building. See `LdtkVisibleDepths`.
+#[derive(Component, Debug, Default, Reflect, Clone, Copy, PartialEq, Eq)]
+pub struct LdtkWorldDepth(pub i32);
+
+/// The opacities of a layer before its level was hidden by `LdtkVisibleDepths`.
+/// They are restored once the level is shown again.
+#[derive(Component, Debug, Reflect, Clone, Copy)]
+pub struct LdtkHiddenLayerOpacities(pub Vec4);
+
+/// The custom fields of a level, like the music track or the biome.
+///
+/// This is inserted on the level entity if the level has at least one field.
+#[derive(Component, Debug, Default, Reflect, Clone)]
+pub struct LdtkLevelFields {
+    /// field identifier to the field
+    pub fields: HashMap<String, FieldInstance>,
+}
+
+impl LdtkLevelFields {
+    /// Collect the fields. Returns `None` if there isn't any.
+    pub fn from_fields(fields: &[FieldInstance]) -> Option<Self> {
+        if fields.is_empty() {
+            None
+        } else {
+            Some(Self {
+                fields: fields
+                    .iter()
+                    .map(|f| (f.identifier.clone(), f.clone()))
+                    .collect(),
+            })
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, field: &str) -> Option<&FieldInstance> {
+        self.fields.get(field)
+    }
+}
+
+/// The progress of a level that is being loaded with `LdtkLoadConfig::async_loading`
+/// or `LdtkLoadConfig::incremental_spawning`.
+///
+/// This is removed once the level is fully spawned and `LdtkEvent::LevelLoaded` is sent.
+#[derive(Component, Debug, Default, Reflect, Clone, Copy)]
+pub struct LdtkLevelLoadProgress {
+    /// Whether the layers are built. Always true for levels not loaded asynchronously.
+    pub built: bool,
+    pub spawned_layers: usize,
+    pub total_layers: usize,
+}
+
+impl LdtkLevelLoadProgress {
+    pub fn new(total_layers: usize) -> Self {
+        Self {
+            built: false,
+            spawned_layers: 0,
+            total_layers,
+        }
+    }
+
+    /// The spawned fraction of the level, in `[0, 1]`.
+    pub fn fraction(&self) -> f32 {
+        if !self.built {
+            0.
+        } else if self.total_layers == 0 {
+            1.
+        } else {
+            self.spawned_layers as f32 / self.total_layers as f32
+        }
+    }
+}
+
+/// The mood settings of a level, read from the level fields.
+///
+/// Which fields are used is configured by `LdtkLoadConfig::atmosphere_fields`.
+/// This is inserted on the level entity only if at least one of the fields is set.
+#[derive(Component, Debug, Default, Reflect, Clone, Copy, PartialEq)]
+pub struct LdtkLevelAtmosphere {
+    pub ambient_color: Option<Color>,
+    pub bloom: Option<f32>,
+}
+
+impl LdtkLevelAtmosphere {
+    pub fn from_fields(fields: &[FieldInstance], names: &LdtkAtmosphereFields) -> Option<Self> {
+        let mut atmosphere = Self::default();
+
+        for field in fields {
+            match &field.value {
+                Some(FieldValue::Color(color)) if field.identifier == names.ambient_color => {
+                    atmosphere.ambient_color = Some((*color).into());
+                }
+                Some(FieldValue::Float(bloom)) if field.identifier == names.bloom => {
+                    atmosphere.bloom = Some(*bloom);
+                }
+                _ => {}
+            }
+        }
+
+        if atmosphere == Self::default() {
+            None
+        } else {
+            Some(atmosphere)
+        }
+    }
+}
+
+/// The cell values of an IntGrid layer. Empty cells (`0`) are left out.
+#[derive(Debug, Default, Reflect, Clone)]
+pub struct LdtkIntGridLayer {
+    pub identifier: String,
+    /// The size of a cell in pixels.
+    pub grid_size: i32,
+    /// The size of the layer in cells.
+    pub size: UVec2,
+    /// Cell index to the value. The indices are the same as the tile indices of the layer tilemaps.
+    pub values: HashMap<IVec2, i32>,
+}
+
+impl LdtkIntGridLayer {
+    pub fn from_layer(layer: &LayerInstance) -> Self {
+        let values = layer
+            .int_grid_csv
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0)
+            .map(|(i, value)| {
+                let index = IVec2 {
+                    x: i as i32 % layer.c_wid,
+                    y: -(i as i32 / layer.c_wid) - 1,
+                };
+                (index, *value)
+            })
+            .collect();
+
+        Self {
+            identifier: layer.identifier.clone(),
+            grid_size: layer.grid_size,
+            size: UVec2 {
+                x: layer.c_wid as u32,
+                y: layer.c_hei as u32,
+            },
+            values,
+        }
+    }
+
+    /// An empty layer to paint values into, for tilemaps not loaded from LDtk.
+    ///
+    /// The indices are from `(0, -1)` to `(size.x - 1, -size.y)` like LDtk layers,
+    /// so the top left corner of the layer is at the origin.
+    pub fn new(identifier: String, size: UVec2) -> Self {
+        Self {
+            identifier,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Get the value of the cell. Returns `None` if the cell is empty.
+    #[inline]
+    pub fn get(&self, index: IVec2) -> Option<i32> {
+        self.values.get(&index).copied()
+    }
+
+    /// Set the value of the cell. `0` clears the cell.
+    #[inline]
+    pub fn set(&mut self, index: IVec2, value: i32) {
+        if value == 0 {
+            self.values.remove(&index);
+        } else {
+            self.values.insert(index, value);
+        }
+    }
+
+    /// Spawn a pure color tilemap of this layer, which is handy for prototyping before
+    /// the art exists. Use `LdtkAssets::get_int_grid_colors` to get the colors in the editor.
+    ///
+    /// Cells whose values are not in `colors` are left out. The top left corner of the
+    /// tilemap is at the origin, insert a `TilemapTransform` to move it.
+    pub fn spawn_pure_color_tilemap(
+        &self,
+        commands: &mut Commands,
+        colors: &HashMap<i32, Color>,
+    ) -> Entity {
+        let entity = commands.spawn_empty().id();
+        let grid_size = Vec2::splat(self.grid_size as f32);
+        let mut storage = TilemapStorage::new(
+            suggest_chunk_size(self.size, grid_size, DEFAULT_EXPECTED_VIEW_SIZE),
+            entity,
+        );
+        self.values.iter().for_each(|(index, value)| {
+            if let Some(color) = colors.get(value) {
+                storage.set(
+                    commands,
+                    *index,
+                    TileBuilder::new().with_color(Vec4::from(color.as_rgba_f32())),
+                );
+            }
+        });
+
+        commands
+            .entity(entity)
+            .insert(StandardPureColorTilemapBundle {
+                name: TilemapName(self.identifier.clone()),
+                tile_render_size: TileRenderSize(grid_size),
+                slot_size: TilemapSlotSize(grid_size),
+                ty: TilemapType::Square,
+                storage,
+                ..Default::default()
+            });
+        entity
+    }
+}
+
+/// The IntGrid layers of a level, including the ones used as path or physics layers.
+///
+/// This is inserted on the level entity if the level has at least one IntGrid layer.
+#[derive(Component, Debug, Default, Reflect, Clone)]
+pub struct LdtkIntGrid {
+    /// layer identifier to the layer
+    pub layers: HashMap<String, LdtkIntGridLayer>,
+}
+
+impl LdtkIntGrid {
+    /// Collect the IntGrid layers. Returns `None` if there isn't any.
+    pub fn from_layers(layers: &[LayerInstance]) -> Option<Self> {
+        let layers = layers
+            .iter()
+            .filter(|layer| layer.ty == LayerType::IntGrid)
+            .map(|layer| {
+                (
+                    layer.identifier.clone(),
+                    LdtkIntGridLayer::from_layer(layer),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        if layers.is_empty() {
+            None
+        } else {
+            Some(Self { layers })
+        }
+    }
+
+    #[inline]
+    pub fn get_layer(&self, layer: &str) -> Option<&LdtkIntGridLayer> {
+        self.layers.get(layer)
+    }
+
+    /// Get the value of the cell in the layer. Returns `None` if the cell is empty.
+    #[inline]
+    pub fn get(&self, layer: &str, index: IVec2) -> Option<i32> {
+        self.layers.get(layer).and_then(|l| l.get(index))
+    }
+}
+
+/// Cameras with this component will follow the atmosphere of the latest loaded level.
+#[derive(Component, Debug, Default, Reflect, Clone, Copy)]
+pub struct LdtkAtmosphereReceiver;
+
+#[cfg(test)]
+mod test {
+    use crate::tilemap::tile::TileAnimationMode;
+
+    use super::LdtkTileData;
+
+    #[test]
+    fn test_parse_animation() {
+        let anim = LdtkTileData::parse_animation("solid\nanimation: 12 13, 14 @ 8")
+            .unwrap()
+            .unwrap();
+        assert_eq!(anim.sequence, vec![12, 13, 14]);
+        assert_eq!(anim.fps, 8);
+        assert_eq!(anim.mode, TileAnimationMode::Loop);
+
+        let anim = LdtkTileData::parse_animation("animation: 1:100 2:250 ping_pong")
+            .unwrap()
+            .unwrap();
+        assert_eq!(anim.sequence, vec![1, 2]);
+        assert_eq!(anim.durations, vec![100, 250]);
+        assert_eq!(anim.mode, TileAnimationMode::PingPong);
+
+        assert!(LdtkTileData::parse_animation("water").unwrap().is_none());
+        assert!(LdtkTileData::parse_animation("animation: 1 2").is_err());
+        assert!(LdtkTileData::parse_animation("animation: 1:100 2 @8").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    hierarchy::Parent,
+    transform::components::Transform,
+    utils::HashSet,
+};
+
+use crate::tilemap::diff::{self, EntityDiff, LevelDiff};
+
+use super::{
+    components::{LdtkBackground, LdtkLevelRoot, LdtkLoadedLevel, LdtkLoader, LdtkLoaderMode},
+    layer::LdtkLayers,
+    resources::{
+        LdtkAdditionalLayers, LdtkAssets, LdtkGlobalEntityRegistry, LdtkLevelManager,
+        LdtkLoadConfig, LdtkPatterns,
+    },
+};
+
+/// Compare a loaded level against the level in the LDtk file.
+///
+/// The level is built again from the cached json with the current `LdtkLoadConfig`,
+/// so layers and entities filtered out by the config are not compared.
+/// Entities spawned at runtime are counted as added if they have a `LdtkLevelRoot`
+/// pointing at the level.
+///
+/// Returns `None` if the level is not loaded yet.
+pub fn diff_ldtk_level(world: &mut World, level_entity: Entity) -> Option<LevelDiff> {
+    let runtime_entities = world
+        .query::<(Entity, &LdtkLevelRoot)>()
+        .iter(world)
+        .filter(|(_, root)| root.0 == level_entity)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    let world = &*world;
+
+    let loaded = world.get::<LdtkLoadedLevel>(level_entity)?;
+    let manager = world.resource::<LdtkLevelManager>();
+    let (_, level) = manager.get_level(&loaded.identifier)?;
+    let config = world.resource::<LdtkLoadConfig>();
+    let translation = world
+        .get::<Transform>(level_entity)
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or_default();
+
+    let mut source = LdtkLayers::new(
+        level_entity,
+        level.layer_instances.len(),
+        world.resource::<LdtkAssets>(),
+        translation,
+        config.level_z_index(level),
+        LdtkLoaderMode::Tilemap,
+        LdtkBackground::default(),
+    );
+    // Global entities are compared as well, so nothing is skipped as already spawned.
+    super::build_layers(
+        level,
+        &mut source,
+        translation,
+        config,
+        world.resource::<LdtkAdditionalLayers>(),
+        &LdtkGlobalEntityRegistry::default(),
+        world.resource::<LdtkPatterns>(),
+        &LdtkLoader {
+            level: loaded.identifier.clone(),
+            ..Default::default()
+        },
+    );
+
+    let mut level_diff = LevelDiff::default();
+
+    source
+        .layers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, layer)| layer.as_ref().map(|layer| (index, layer)))
+        .for_each(|(index, (pattern, _, iid, _))| {
+            let tilemap = loaded
+                .layers
+                .get(iid)
+                .copied()
+                .unwrap_or(Entity::PLACEHOLDER);
+            level_diff.insert_layer(
+                level.layer_instances[index].identifier.clone(),
+                diff::diff_tiles(world, tilemap, &pattern.tiles.tiles),
+            );
+        });
+
+    source.entities.iter().for_each(|packed| {
+        let Some(entity) = loaded
+            .entities
+            .get(&packed.iid)
+            .copied()
+            .filter(|entity| world.get_entity(*entity).is_some())
+        else {
+            level_diff.entities.push(EntityDiff::Removed {
+                id: packed.iid.0.clone(),
+            });
+            return;
+        };
+
+        let Some(transform) = world.get::<Transform>(entity) else {
+            return;
+        };
+        let mut runtime = transform.translation.truncate();
+        if world.get::<Parent>(entity).map(|parent| parent.get()) != Some(level_entity) {
+            runtime -= translation;
+        }
+        let source = packed.transform.local_translation;
+        if diff::is_moved(source, runtime) {
+            level_diff.entities.push(EntityDiff::Moved {
+                id: packed.iid.0.clone(),
+                entity,
+                source,
+                runtime,
+            });
+        }
+    });
+
+    let loaded_entities = loaded.entities.values().collect::<HashSet<_>>();
+    level_diff.entities.extend(
+        runtime_entities
+            .into_iter()
+            .filter(|entity| !loaded_entities.contains(entity))
+            .map(|entity| EntityDiff::Added { entity }),
+    );
+
+    Some(level_diff)
+}
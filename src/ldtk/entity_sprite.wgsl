@@ -16,6 +16,10 @@ var<uniform> atlas_rect: AtlasRect;
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(texture, texture_sampler,
-                         in.uv * (atlas_rect.max - atlas_rect.min) + atlas_rect.min);
+    // Keep half a texel away from the edges of the rect, so the repeated
+    // and sliced parts don't bleed into the neighboring tiles of the atlas.
+    let half_texel = 0.5 / vec2<f32>(textureDimensions(texture));
+    let uv = clamp(in.uv * (atlas_rect.max - atlas_rect.min) + atlas_rect.min,
+                   atlas_rect.min + half_texel, atlas_rect.max - half_texel);
+    return textureSample(texture, texture_sampler, uv);
 }
@@ -1,13 +1,42 @@
-use bevy::{ecs::event::Event, reflect::Reflect};
+use bevy::{
+    ecs::{entity::Entity, event::Event},
+    reflect::Reflect,
+    utils::HashMap,
+};
+
+use super::components::{EntityIid, LayerIid};
 
 #[derive(Event)]
 pub enum LdtkEvent {
     LevelLoaded(LevelEvent),
+    /// Sent right after `LevelLoaded` for the levels loaded in `LdtkLoaderMode::Tilemap`.
+    LevelSpawned(LevelSpawnedEvent),
     LevelUnloaded(LevelEvent),
+    /// Sent after `LevelUnloaded` for each `GlobalEntity` of the level that is kept.
+    GlobalEntityPreserved(GlobalEntityEvent),
 }
 
 #[derive(Reflect, Debug, Clone)]
 pub struct LevelEvent {
     pub identifier: String,
     pub iid: String,
+    /// The level entity.
+    pub entity: Entity,
+}
+
+/// The entities spawned for a level, so references can be wired up
+/// without waiting for `Added<LdtkLoadedLevel>`.
+#[derive(Reflect, Debug, Clone)]
+pub struct LevelSpawnedEvent {
+    pub level: LevelEvent,
+    pub layers: HashMap<LayerIid, Entity>,
+    pub entities: HashMap<EntityIid, Entity>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct GlobalEntityEvent {
+    /// The level the entity is spawned in.
+    pub level: LevelEvent,
+    pub iid: EntityIid,
+    pub entity: Entity,
 }
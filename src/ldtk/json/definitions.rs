@@ -34,6 +34,9 @@ pub struct LayerDef {
     #[serde(rename = "__type")]
     pub ty: LayerType,
 
+    /// Contains all the auto-layer rule definitions.
+    pub auto_rule_groups: Vec<AutoLayerRuleGroup>,
+
     pub auto_source_layer_def_uid: Option<i32>,
 
     /// Opacity of the layer (0 to 1.0)
@@ -52,6 +55,7 @@ pub struct LayerDef {
     pub int_grid_values: Vec<IntGridValue>,
 
     /// Group informations for IntGrid values
+    #[serde(default)]
     pub int_grid_values_groups: Vec<IntGroupValueGroup>,
 
     /// Parallax horizontal factor (from -1 to 1, defaults to 0) which affects
@@ -100,6 +104,7 @@ pub enum LayerType {
 #[serde(rename_all = "camelCase")]
 pub struct IntGridValue {
     pub color: String,
+    #[serde(default)]
     pub group_uid: i32,
     pub identifier: Option<String>,
     pub tile: Option<TilesetRect>,
@@ -119,6 +124,102 @@ pub struct IntGroupValueGroup {
     pub uid: i32,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoLayerRuleGroup {
+    pub active: bool,
+
+    /// Optional groups are only applied to the layer instances that enabled them.
+    pub is_optional: bool,
+
+    pub name: String,
+
+    pub rules: Vec<AutoLayerRuleDef>,
+
+    pub uid: i32,
+}
+
+/// This complex section isn't meant to be used by game devs at all,
+/// as these rules are completely resolved internally by the editor before any saving.
+/// But it's also what `LdtkAutoRules` uses to re-evaluate the auto tiles at runtime.
+#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoLayerRuleDef {
+    /// If FALSE, the rule effect isn't applied, and no tiles are generated.
+    pub active: bool,
+
+    #[serde(default = "super::version::default_alpha")]
+    pub alpha: f32,
+
+    /// When TRUE, the rule will prevent other rules to be applied in the same cell if it matches
+    /// (TRUE by default).
+    pub break_on_match: bool,
+
+    /// Chances for this rule to be applied (0 to 1)
+    pub chance: f32,
+
+    /// Checker mode
+    pub checker: AutoLayerRuleCheckerMode,
+
+    /// If TRUE, allow rule to be matched by flipping its pattern horizontally
+    pub flip_x: bool,
+
+    /// If TRUE, allow rule to be matched by flipping its pattern vertically
+    pub flip_y: bool,
+
+    /// Default IntGrid value when checking cells outside of level bounds
+    pub out_of_bounds_value: Option<i32>,
+
+    /// Rule pattern (size x size)
+    pub pattern: Vec<i32>,
+
+    /// If TRUE, enable Perlin filtering to only apply rule on specific random area
+    pub perlin_active: bool,
+
+    /// X pivot of a tile stamp (0-1)
+    pub pivot_x: f32,
+
+    /// Y pivot of a tile stamp (0-1)
+    pub pivot_y: f32,
+
+    /// Pattern width & height. Should only be 1,3,5 or 7.
+    pub size: i32,
+
+    /// Defines how tileIds array is used
+    pub tile_mode: AutoLayerRuleTileMode,
+
+    /// Array containing all the possible tile IDs rectangles (picked randomly).
+    pub tile_rects_ids: Vec<Vec<i32>>,
+
+    /// Unique Int identifier
+    pub uid: i32,
+
+    /// Cell coord modulo
+    pub x_modulo: i32,
+
+    /// Cell start offset
+    pub x_offset: i32,
+
+    /// Cell coord modulo
+    pub y_modulo: i32,
+
+    /// Cell start offset
+    pub y_offset: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum AutoLayerRuleCheckerMode {
+    None,
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum AutoLayerRuleTileMode {
+    Single,
+    Stamp,
+}
+
 /*
  * Entity Definition
  */
@@ -283,7 +384,7 @@ pub struct EnumTag {
     pub tile_ids: Vec<i32>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct TilesetRect {
     /// UID of the tileset
@@ -330,6 +431,7 @@ pub struct EnumDef {
     pub identifier: String,
 
     /// An array of user-defined tags to organize the Enums
+    #[serde(default)]
     pub tags: Vec<String>,
 
     /// Unique Int identifier
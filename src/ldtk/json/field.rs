@@ -4,11 +4,16 @@ use serde::{
     Deserialize, Deserializer, Serialize,
 };
 
-use crate::{ldtk::json::LdtkColor, match_field, match_field_enum, transfer_field, unwrap_field};
+use crate::{
+    ldtk::{json::LdtkColor, traits::LdtkEnum},
+    match_field, match_field_enum,
+    tilemap::region::RegionField,
+    transfer_field, unwrap_field,
+};
 
 use super::{definitions::TilesetRect, EntityRef, GridPoint};
 
-#[derive(Serialize, Debug, Clone, Reflect)]
+#[derive(Serialize, Debug, Clone, PartialEq, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct FieldInstance {
     /// Reference of the Field definition UID
@@ -30,6 +35,116 @@ pub struct FieldInstance {
     pub value: Option<FieldValue>,
 }
 
+/// Typed accessors of the value.
+///
+/// These return `None` if the field has no value, or the value is of another type.
+impl FieldInstance {
+    pub fn as_int(&self) -> Option<i32> {
+        match self.value {
+            Some(FieldValue::Integer(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self.value {
+            Some(FieldValue::Float(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.value {
+            Some(FieldValue::Bool(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Some(FieldValue::String(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<LdtkColor> {
+        match self.value {
+            Some(FieldValue::Color(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_point(&self) -> Option<IVec2> {
+        match &self.value {
+            Some(FieldValue::Point(p)) => Some(IVec2 { x: p.cx, y: p.cy }),
+            _ => None,
+        }
+    }
+
+    pub fn as_points(&self) -> Option<Vec<IVec2>> {
+        match &self.value {
+            Some(FieldValue::PointArray(p)) => {
+                Some(p.iter().map(|p| IVec2 { x: p.cx, y: p.cy }).collect())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_entity_ref(&self) -> Option<&EntityRef> {
+        match &self.value {
+            Some(FieldValue::EntityRef(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_entity_refs(&self) -> Option<&[EntityRef]> {
+        match &self.value {
+            Some(FieldValue::EntityRefArray(x)) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// The identifier of the enum and the selected value.
+    pub fn as_enum_raw(&self) -> Option<(&str, &str)> {
+        match &self.value {
+            Some(FieldValue::LocalEnum((e, v))) | Some(FieldValue::ExternEnum((e, v))) => {
+                Some((e, v))
+            }
+            _ => None,
+        }
+    }
+
+    /// Also returns `None` if the value doesn't exist in `T`.
+    pub fn as_enum<T: LdtkEnum>(&self) -> Option<T> {
+        self.as_enum_raw()
+            .and_then(|(_, v)| T::try_get_identifier(v))
+    }
+
+    /// Also returns `None` if any of the values doesn't exist in `T`.
+    pub fn as_enums<T: LdtkEnum>(&self) -> Option<Vec<T>> {
+        match &self.value {
+            Some(FieldValue::LocalEnumArray((_, v)))
+            | Some(FieldValue::ExternEnumArray((_, v))) => {
+                v.iter().map(|v| T::try_get_identifier(v)).collect()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `None` for the types that `RegionField` doesn't support.
+    pub fn as_region_field(&self) -> Option<RegionField> {
+        match &self.value {
+            Some(FieldValue::Integer(x)) => Some(RegionField::Int(*x)),
+            Some(FieldValue::Float(x)) => Some(RegionField::Float(*x)),
+            Some(FieldValue::Bool(x)) => Some(RegionField::Bool(*x)),
+            Some(FieldValue::String(x)) => Some(RegionField::String(x.clone())),
+            _ => self
+                .as_enum_raw()
+                .map(|(_, v)| RegionField::String(v.to_string())),
+        }
+    }
+}
+
 const FIELDS: &[&str] = &["defUid", "__identifier", "__tile", "__type", "__value"];
 
 impl<'de> Deserialize<'de> for FieldInstance {
@@ -184,7 +299,7 @@ impl<'de> Deserialize<'de> for FieldInstance {
 /// - For Point, the value is a GridPoint object.
 /// - For Tile, the value is a TilesetRect object.
 /// - For EntityRef, the value is an EntityReferenceInfos object.
-#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Reflect)]
 #[serde(untagged)]
 pub enum FieldValue {
     Integer(i32),
@@ -314,4 +429,54 @@ mod test {
 
         dbg!(field_instance);
     }
+
+    #[derive(Debug, PartialEq)]
+    enum Fruit {
+        A,
+        B,
+    }
+
+    impl LdtkEnum for Fruit {
+        fn get_identifier(ident: &str) -> Self {
+            Self::try_get_identifier(ident).unwrap()
+        }
+
+        fn try_get_identifier(ident: &str) -> Option<Self> {
+            match ident {
+                "A" => Some(Self::A),
+                "B" => Some(Self::B),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let field = |ty: &str, value: &str| -> FieldInstance {
+            serde_json::from_str(&format!(
+                r#"{{
+                    "defUid": 1,
+                    "__identifier": "test",
+                    "__tile": null,
+                    "__type": "{}",
+                    "__value": {}
+                }}"#,
+                ty, value
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(field("Int", "3").as_int(), Some(3));
+        assert_eq!(field("Int", "3").as_float(), None);
+        assert_eq!(field("LocalEnum.Fruit", r#""B""#).as_enum(), Some(Fruit::B));
+        assert_eq!(field("LocalEnum.Fruit", r#""C""#).as_enum::<Fruit>(), None);
+        assert_eq!(
+            field("Array<LocalEnum.Fruit>", r#"["A", "B"]"#).as_enums(),
+            Some(vec![Fruit::A, Fruit::B])
+        );
+        assert_eq!(
+            field("Array<Point>", r#"[{ "cx": 1, "cy": 2 }]"#).as_points(),
+            Some(vec![IVec2::new(1, 2)])
+        );
+    }
 }
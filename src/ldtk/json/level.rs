@@ -1,8 +1,11 @@
 use bevy::{
-    ecs::system::EntityCommands, reflect::Reflect, sprite::MaterialMesh2dBundle,
-    transform::components::Transform,
+    asset::Asset,
+    ecs::system::EntityCommands,
+    math::{URect, UVec2, Vec2},
+    reflect::Reflect,
+    sprite::MaterialMesh2dBundle,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::ldtk::resources::LdtkAssets;
 
@@ -16,7 +19,17 @@ use super::{
  * Level
  */
 
-#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+/// Deserializes `null` as the default value.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// A level. This is also the content of the external level files (`.ldtkl`).
+#[derive(Asset, Serialize, Deserialize, Debug, Clone, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct Level {
     /// Background color of the level (same as `bgColor`, except
@@ -28,6 +41,11 @@ pub struct Level {
     #[serde(rename = "__bgPos")]
     pub bg_pos: Option<ImagePosition>,
 
+    /// An enum defining the way the background image (if any) is positioned on the level.
+    /// See `__bgPos` for resulting position info.
+    #[serde(rename = "bgPos")]
+    pub bg_pos_mode: Option<BgPos>,
+
     /// An array listing all other levels touching this one on the world map.
     /// Since 1.4.0, this includes levels that overlap in the same world layer,
     /// or in nearby world layers.
@@ -62,6 +80,7 @@ pub struct Level {
     ///
     /// This array is **sorted in display order**: the 1st layer is
     /// the top-most and the last is behind.
+    #[serde(deserialize_with = "null_as_default")]
     pub layer_instances: Vec<LayerInstance>,
 
     /// Height of the level in pixels
@@ -114,6 +133,15 @@ pub struct ImagePosition {
     pub top_left_px: [i32; 2],
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum BgPos {
+    Unscaled,
+    Contain,
+    Cover,
+    CoverDirty,
+    Repeat,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct Neighbour {
@@ -236,6 +264,9 @@ pub struct LayerInstance {
     /// Reference to the UID of the level containing this layer instance
     pub level_id: i32,
 
+    /// An Array containing the UIDs of optional rules that were enabled in this specific layer instance.
+    pub optional_rules: Vec<i32>,
+
     /// This layer can use another tileset by overriding the tileset UID here.
     pub override_tileset_uid: Option<i32>,
 
@@ -253,15 +284,29 @@ pub struct LayerInstance {
     /// which contains the total offset value
     pub px_offset_y: i32,
 
+    /// Random seed used for Auto-Layers rendering
+    pub seed: i32,
+
     /// Layer instance visibility
     pub visible: bool,
 }
 
+impl LayerInstance {
+    /// The total offset of the layer in pixels, with y pointing up.
+    #[inline]
+    pub fn total_offset(&self) -> Vec2 {
+        Vec2::new(
+            self.px_total_offset_x as f32,
+            -self.px_total_offset_y as f32,
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct TileInstance {
     ///	Alpha/opacity of the tile (0-1, defaults to 1)
-    #[serde(rename = "a")]
+    #[serde(rename = "a", default = "super::version::default_alpha")]
     pub alpha: f32,
 
     /// "Flip bits", a 2-bits integer to represent the mirror transformations of the tile.
@@ -286,6 +331,15 @@ pub struct TileInstance {
     pub tile_id: i32,
 }
 
+impl TileInstance {
+    /// The rect of the tile in the tileset image, in pixels.
+    #[inline]
+    pub fn src_rect(&self, tile_size: UVec2) -> URect {
+        let min = UVec2::new(self.src[0] as u32, self.src[1] as u32);
+        URect::from_corners(min, min + tile_size)
+    }
+}
+
 /*
  * Entity Instance
  */
@@ -352,6 +406,13 @@ pub struct EntityInstance {
 }
 
 impl EntityInstance {
+    /// The position of the pivot in the layer, with the y axis pointing up.
+    /// This is where the entity is placed, and the origin of its sprite mesh.
+    #[inline]
+    pub fn pivot_translation(&self) -> Vec2 {
+        Vec2::new(self.local_pos[0] as f32, -self.local_pos[1] as f32)
+    }
+
     pub fn generate_sprite(&self, commands: &mut EntityCommands, assets: &LdtkAssets) {
         if self.tile.is_none() {
             return;
@@ -360,7 +421,6 @@ impl EntityInstance {
         commands.insert(MaterialMesh2dBundle {
             mesh: assets.clone_mesh_handle(&self.iid),
             material: assets.clone_material_handle(&self.iid),
-            transform: Transform::from_xyz(self.local_pos[0] as f32, -self.local_pos[1] as f32, 0.),
             ..Default::default()
         });
     }
@@ -1,4 +1,10 @@
-use bevy::{math::Vec4, reflect::Reflect, render::color::Color, utils::HashMap};
+use bevy::{
+    asset::Asset,
+    math::{Vec2, Vec4},
+    reflect::Reflect,
+    render::color::Color,
+    utils::HashMap,
+};
 use serde::{de::Visitor, Deserialize, Serialize};
 
 use self::{definitions::Definitions, level::Level};
@@ -7,8 +13,9 @@ pub mod definitions;
 pub mod field;
 pub mod level;
 pub mod macros;
+pub mod version;
 
-#[derive(Serialize, Debug, Clone, Copy, Reflect)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Reflect)]
 pub struct LdtkColor {
     pub r: f32,
     pub g: f32,
@@ -61,7 +68,7 @@ impl<'de> Deserialize<'de> for LdtkColor {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[derive(Asset, Serialize, Deserialize, Debug, Clone, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct LdtkJson {
     /// Project background color
@@ -88,6 +95,7 @@ pub struct LdtkJson {
 
     /// All instances of entities that have their `exportToToc`flag enabled
     /// are listed in this array.
+    #[serde(default)]
     pub toc: Vec<Toc>,
 
     /// ## WARNING:
@@ -125,6 +133,7 @@ pub struct LdtkJson {
     ///
     /// If you want to start supporting this future update easily,
     /// please refer to this documentation: https://github.com/deepnight/ldtk/issues/231
+    #[serde(default)]
     pub worlds: Vec<World>,
 }
 
@@ -158,6 +167,17 @@ pub struct TocInstance {
     pub untyped_fields: HashMap<String, TocField>,
 }
 
+impl TocInstance {
+    /// The position of the pivot of the entity in world space, the same space
+    /// as the levels that are loaded without a translation override.
+    ///
+    /// **Notice**: This is only meaningful for the `GridVania` and `Free` world layouts.
+    #[inline]
+    pub fn world_pos(&self) -> Vec2 {
+        Vec2::new(self.world_x as f32, -self.world_y as f32)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
 #[serde(untagged)]
 pub enum TocField {
@@ -229,7 +249,7 @@ pub struct EntityRef {
     pub world_iid: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Reflect)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct GridPoint {
     /// X grid-based coordinate
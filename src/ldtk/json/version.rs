@@ -0,0 +1,181 @@
+use std::fmt::Display;
+
+use serde_json::{Map, Value};
+
+/// The version of the LDtk json format, read from `jsonVersion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LdtkJsonVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl LdtkJsonVersion {
+    /// The oldest version that can be loaded. Older files need to be saved again in LDtk.
+    pub const MIN_SUPPORTED: Self = Self::new(1, 3, 0);
+    /// The newest version the json module is written against.
+    ///
+    /// Newer patch and minor versions are still loaded if they can be parsed.
+    pub const MAX_SUPPORTED: Self = Self::new(1, 5, 3);
+
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse versions like `1.5.3`. Missing minor or patch numbers are treated as 0.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut numbers = version.trim().split('.').map(|n| n.parse::<u32>());
+        let major = numbers.next()?.ok()?;
+        let minor = numbers.next().unwrap_or(Ok(0)).ok()?;
+        let patch = numbers.next().unwrap_or(Ok(0)).ok()?;
+        if numbers.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// Whether the version is too old, or has a different major version.
+    #[inline]
+    pub fn is_incompatible(&self) -> bool {
+        *self < Self::MIN_SUPPORTED || self.major != Self::MAX_SUPPORTED.major
+    }
+
+    /// Whether the version is newer than `MAX_SUPPORTED`, so loading it may fail.
+    #[inline]
+    pub fn is_newer(&self) -> bool {
+        *self > Self::MAX_SUPPORTED
+    }
+}
+
+impl Display for LdtkJsonVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Read the `jsonVersion` of a project file.
+pub fn detect_version(json: &Value) -> Option<&str> {
+    json.get("jsonVersion").and_then(Value::as_str)
+}
+
+/// Rewrite the fields that were renamed or restructured since `version`,
+/// so the project can be deserialized into `LdtkJson`.
+///
+/// Fields that were only added are handled by `#[serde(default)]` instead.
+pub fn migrate(json: &mut Value, version: LdtkJsonVersion) {
+    if version < LdtkJsonVersion::new(1, 4, 1) {
+        migrate_toc_instances(json);
+    }
+    if version < LdtkJsonVersion::new(1, 5, 0) {
+        migrate_rule_tile_ids(json);
+    }
+}
+
+/// `toc[].instances` became `toc[].instancesData`, which also contains the positions.
+/// The positions and fields are not known for older files.
+fn migrate_toc_instances(json: &mut Value) {
+    let Some(toc) = json.get_mut("toc").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    toc.iter_mut()
+        .filter_map(Value::as_object_mut)
+        .filter(|entry| !entry.contains_key("instancesData"))
+        .for_each(|entry| {
+            let instances = match entry.remove("instances") {
+                Some(Value::Array(instances)) => instances,
+                _ => Vec::new(),
+            };
+            let data = instances
+                .into_iter()
+                .map(|iids| {
+                    let mut instance = Map::new();
+                    instance.insert("iids".to_string(), iids);
+                    ["worldX", "worldY", "widPx", "heiPx"]
+                        .into_iter()
+                        .for_each(|key| {
+                            instance.insert(key.to_string(), Value::from(0));
+                        });
+                    instance.insert("fields".to_string(), Value::Object(Map::new()));
+                    Value::Object(instance)
+                })
+                .collect();
+            entry.insert("instancesData".to_string(), Value::Array(data));
+        });
+}
+
+/// `tileIds` of the auto layer rules became `tileRectsIds`, where each element
+/// is a rectangle of tiles instead of a single tile.
+fn migrate_rule_tile_ids(json: &mut Value) {
+    let Some(layers) = json
+        .pointer_mut("/defs/layers")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+
+    layers
+        .iter_mut()
+        .filter_map(|layer| layer.get_mut("autoRuleGroups"))
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(|group| group.get_mut("rules"))
+        .filter_map(Value::as_array_mut)
+        .flatten()
+        .filter_map(Value::as_object_mut)
+        .filter(|rule| !rule.contains_key("tileRectsIds"))
+        .for_each(|rule| {
+            let rects = match rule.remove("tileIds") {
+                Some(Value::Array(ids)) => {
+                    ids.into_iter().map(|id| Value::Array(vec![id])).collect()
+                }
+                _ => Vec::new(),
+            };
+            rule.insert("tileRectsIds".to_string(), Value::Array(rects));
+        });
+}
+
+/// The alpha of tiles and rules, which were always opaque before LDtk 1.4.
+pub(crate) fn default_alpha() -> f32 {
+    1.
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{migrate, LdtkJsonVersion};
+
+    #[test]
+    fn test_version() {
+        let version = LdtkJsonVersion::parse("1.4").unwrap();
+        assert_eq!(version, LdtkJsonVersion::new(1, 4, 0));
+        assert!(!version.is_incompatible());
+        assert!(LdtkJsonVersion::parse("1.1.3").unwrap().is_incompatible());
+        assert!(LdtkJsonVersion::parse("2.0.0").unwrap().is_incompatible());
+        assert!(LdtkJsonVersion::parse("1.6.0").unwrap().is_newer());
+        assert_eq!(LdtkJsonVersion::parse("1.x"), None);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut project = json!({
+            "toc": [{ "identifier": "Door", "instances": [{ "entityIid": "a" }] }],
+            "defs": { "layers": [{ "autoRuleGroups": [{ "rules": [{ "tileIds": [3, 4] }] }] }] },
+        });
+        migrate(&mut project, LdtkJsonVersion::new(1, 3, 0));
+
+        assert_eq!(
+            project["toc"][0]["instancesData"][0]["iids"]["entityIid"],
+            "a"
+        );
+        assert_eq!(
+            project["defs"]["layers"][0]["autoRuleGroups"][0]["rules"][0]["tileRectsIds"],
+            json!([[3], [4]])
+        );
+    }
+}
@@ -4,36 +4,47 @@ use bevy::{
         entity::Entity,
         system::{Commands, EntityCommands},
     },
-    math::{IVec2, Vec2, Vec4},
+    hierarchy::BuildChildren,
+    log::warn,
+    math::{IVec2, UVec2, Vec2, Vec4},
     prelude::SpatialBundle,
-    sprite::SpriteBundle,
     transform::components::Transform,
     utils::HashMap,
 };
 
 use crate::{
-    math::aabb::IAabb2d,
+    math::aabb::{Aabb2d, IAabb2d},
     serializing::pattern::TilemapPattern,
     tilemap::{
+        audio::TilemapAudioEmitters,
         buffers::TileBuffer,
-        bundles::StandardTilemapBundle,
+        bundles::{StandardPureColorTilemapBundle, StandardTilemapBundle},
+        chunking::{suggest_chunk_size, DEFAULT_EXPECTED_VIEW_SIZE},
         map::{
             TileRenderSize, TilemapLayerOpacities, TilemapName, TilemapSlotSize, TilemapStorage,
-            TilemapTexture, TilemapTransform, TilemapType,
+            TilemapTexture, TilemapTransform, TilemapTransformSync, TilemapType,
         },
-        tile::{TileBuilder, TileLayer, TileTexture},
+        region::Region,
+        tile::{RawTileAnimation, TileBuilder, TileLayer, TileTexture},
     },
-    DEFAULT_CHUNK_SIZE,
 };
 
 use super::{
-    components::{EntityIid, LayerIid, LdtkLoadedLevel, LdtkTempTransform, LevelIid},
+    components::{
+        EntityIid, LayerIid, LdtkBackground, LdtkEntityRefs, LdtkIntGrid, LdtkLevelAtmosphere,
+        LdtkLevelFields, LdtkLevelRoot, LdtkLoadedLevel, LdtkPersistent, LdtkTempTransform,
+        LdtkTileTags, LdtkUnloadLayer, LdtkWorldDepth, LevelIid, TilesetUid,
+    },
     json::{
         field::FieldInstance,
         level::{EntityInstance, LayerInstance, Level, TileInstance},
     },
-    resources::{LdtkAssets, LdtkLoadConfig, LdtkPatterns},
-    traits::{LdtkEntityRegistry, LdtkEntityTagRegistry},
+    resources::{
+        LdtkAssets, LdtkGlobalEntityRegistry, LdtkLayerMaterials, LdtkLoadConfig, LdtkPatterns,
+    },
+    traits::{
+        LdtkEntityRegistry, LdtkEntityTagRegistry, LdtkGlobalEntitySnapshots, LdtkSpawnHooks,
+    },
     LdtkLoaderMode,
 };
 
@@ -42,8 +53,6 @@ use crate::tilemap::{algorithm::path::PathTilemap, chunking::storage::ChunkedSto
 
 #[cfg(feature = "physics")]
 use crate::tilemap::physics::{DataPhysicsTilemap, SerializablePhysicsSource};
-#[cfg(feature = "physics")]
-use bevy::math::UVec2;
 
 #[cfg(feature = "algorithm")]
 pub mod path;
@@ -71,13 +80,19 @@ impl PackedLdtkEntity {
         let phantom_entity = {
             if let Some(e) = entity_registry.get(&self.instance.identifier) {
                 e
-            } else if !config.ignore_unregistered_entities {
+            } else if config.ignore_unregistered_entities {
+                return;
+            } else if config.strict_validation {
                 panic!(
                     "Could not find entity type with entity identifier: {}! \
                     You need to register it using App::register_ldtk_entity::<T>() first!",
                     self.instance.identifier
                 );
             } else {
+                warn!(
+                    "Skipping entity {} as its type is not registered!",
+                    self.instance.identifier
+                );
                 return;
             }
         };
@@ -85,13 +100,17 @@ impl PackedLdtkEntity {
         self.instance.tags.iter().for_each(|tag| {
             if let Some(entity_tag) = entity_tag_registry.get(tag) {
                 entity_tag.add_tag(commands);
-            } else if !config.ignore_unregistered_entity_tags {
+            } else if config.ignore_unregistered_entity_tags {
+                // Skipped silently.
+            } else if config.strict_validation {
                 panic!(
                     "Could not find entity tag with tag: {}! \
                     You need to register it using App::register_ldtk_entity_tag::<T>() first! \
                     Or call LdtkLevelManager::ignore_unregistered_entity_tags to ignore.",
                     tag
                 );
+            } else {
+                warn!("Skipping entity tag {} as it's not registered!", tag);
             }
         });
 
@@ -101,21 +120,58 @@ impl PackedLdtkEntity {
             &self.fields,
             asset_server,
             ldtk_assets,
-        )
+        );
+
+        if let Some(refs) = LdtkEntityRefs::from_fields(self.fields.values()) {
+            commands.insert(refs);
+        }
+        if self
+            .instance
+            .tags
+            .iter()
+            .any(|tag| config.persistent_entity_tags.contains(tag))
+        {
+            commands.insert(LdtkPersistent);
+        }
+        if config.refresh_global_entities {
+            LdtkGlobalEntityRegistry::record_fields_deferred(
+                commands,
+                self.iid.clone(),
+                self.fields.clone(),
+            );
+        }
+        #[cfg(feature = "physics")]
+        physics::LdtkEntityColliderRegistry::insert_deferred(commands, &self.instance);
+        LdtkSpawnHooks::run_deferred(commands, self.instance, self.fields);
     }
 }
 
 pub type LayerOpacity = f32;
 
-pub struct LdtkLayers<'a> {
+pub struct LdtkLayers {
     pub ty: LdtkLoaderMode,
     pub level_entity: Entity,
-    pub layers: Vec<Option<(TilemapPattern, TilemapTexture, LayerIid, LayerOpacity)>>,
+    /// The texture is `None` for the IntGrid layers rendered with `LdtkLoadConfig::int_grid_palette`.
+    pub layers: Vec<
+        Option<(
+            TilemapPattern,
+            Option<TilemapTexture>,
+            LayerIid,
+            LayerOpacity,
+        )>,
+    >,
     pub entities: Vec<PackedLdtkEntity>,
-    pub tilesets: &'a HashMap<i32, TilemapTexture>,
+    /// The `GlobalEntity` entities to initialize again, with their fields changed.
+    pub refreshed_entities: Vec<(Entity, PackedLdtkEntity)>,
+    pub tilesets: HashMap<i32, TilemapTexture>,
+    /// See `LdtkAssets::get_tile_animation`.
+    pub tile_animations: HashMap<(i32, u32), RawTileAnimation>,
     pub translation: Vec2,
     pub base_z_index: i32,
-    pub background: SpriteBundle,
+    pub background: LdtkBackground,
+    /// The layers and entities spawned so far.
+    pub(crate) spawned_layers: HashMap<LayerIid, Entity>,
+    pub(crate) spawned_entities: HashMap<EntityIid, Entity>,
     #[cfg(feature = "algorithm")]
     pub path_layer: Option<(
         path::LdtkPathLayer,
@@ -125,24 +181,28 @@ pub struct LdtkLayers<'a> {
     pub physics_layer: Option<(physics::LdtkPhysicsLayer, Vec<i32>, UVec2)>,
 }
 
-impl<'a> LdtkLayers<'a> {
+impl LdtkLayers {
     pub fn new(
         level_entity: Entity,
         total_layers: usize,
-        ldtk_assets: &'a LdtkAssets,
+        ldtk_assets: &LdtkAssets,
         translation: Vec2,
         base_z_index: i32,
         ty: LdtkLoaderMode,
-        background: SpriteBundle,
+        background: LdtkBackground,
     ) -> Self {
         Self {
             level_entity,
             layers: vec![None; total_layers],
             entities: vec![],
-            tilesets: &ldtk_assets.tilesets,
+            refreshed_entities: vec![],
+            tilesets: ldtk_assets.tilesets.clone(),
+            tile_animations: ldtk_assets.tile_animations.clone(),
             translation,
             base_z_index,
             background,
+            spawned_layers: HashMap::with_capacity(total_layers),
+            spawned_entities: HashMap::default(),
             ty,
             #[cfg(feature = "algorithm")]
             path_layer: None,
@@ -163,13 +223,20 @@ impl<'a> LdtkLayers<'a> {
         self.try_create_new_layer(layer_index, layer);
 
         let (pattern, texture, _, _) = self.layers[layer_index].as_mut().unwrap();
-        let tile_size = texture.desc.tile_size;
+        let Some(texture) = texture.as_ref() else {
+            return;
+        };
+        // Tiles larger than the grid are indexed by their bottom left cell,
+        // and overlap the cells above and to the right of it.
+        let tile_size = texture.desc.tile_size.as_ivec2();
+        let grid_size = layer.grid_size.max(1);
+        let cells_hei = ((tile_size.y + grid_size - 1) / grid_size).max(1);
         let tile_index = IVec2 {
-            x: tile.px[0] / tile_size.x as i32,
+            x: tile.px[0] / grid_size,
             y: match mode {
-                LdtkLoaderMode::Tilemap => -tile.px[1] / tile_size.y as i32 - 1,
+                LdtkLoaderMode::Tilemap => -tile.px[1] / grid_size - cells_hei,
                 LdtkLoaderMode::MapPattern => {
-                    patterns.pattern_size.y as i32 - tile.px[1] / tile_size.y as i32 - 1
+                    patterns.pattern_size.y as i32 - tile.px[1] / grid_size - cells_hei
                 }
             },
         };
@@ -183,9 +250,17 @@ impl<'a> LdtkLayers<'a> {
                 );
             };
             tile_layers.push(TileLayer::new().with_texture_index(texture_index));
+            if let Some(tag) = config.audio_emitter_mapper.get(&texture_index) {
+                ser_tile.audio_emitter = Some(tag.clone());
+            }
         } else {
             let mut builder = TileBuilder::new().with_color(Vec4::new(1., 1., 1., tile.alpha));
-            builder = if let Some(anim) = config.animation_mapper.get(&texture_index) {
+            let anim = config.animation_mapper.get(&texture_index).or_else(|| {
+                layer
+                    .tileset_def_uid
+                    .and_then(|uid| self.tile_animations.get(&(uid, texture_index)))
+            });
+            builder = if let Some(anim) = anim {
                 let animation = pattern.animations.register(anim.clone());
                 builder.with_animation(animation)
             } else {
@@ -196,21 +271,56 @@ impl<'a> LdtkLayers<'a> {
                         .with_flip_raw(tile.flip as u32),
                 )
             };
+            if let Some(tag) = config.audio_emitter_mapper.get(&texture_index) {
+                builder = builder.with_audio_emitter(tag.clone());
+            }
 
             pattern.tiles.tiles.insert(tile_index, builder);
         }
     }
 
+    /// Set a pure color tile for the IntGrid cell. `cell` is the index in `int_grid_csv`.
+    pub fn set_int_grid_cell(
+        &mut self,
+        layer_index: usize,
+        layer: &LayerInstance,
+        cell: usize,
+        color: Vec4,
+        patterns: &LdtkPatterns,
+        mode: &LdtkLoaderMode,
+    ) {
+        self.try_create_new_layer(layer_index, layer);
+
+        let (pattern, _, _, _) = self.layers[layer_index].as_mut().unwrap();
+        let row = cell as i32 / layer.c_wid;
+        let tile_index = IVec2 {
+            x: cell as i32 % layer.c_wid,
+            y: match mode {
+                LdtkLoaderMode::Tilemap => -row - 1,
+                LdtkLoaderMode::MapPattern => patterns.pattern_size.y as i32 - row - 1,
+            },
+        };
+
+        pattern
+            .tiles
+            .tiles
+            .insert(tile_index, TileBuilder::new().with_color(color));
+    }
+
     pub fn set_entity(&mut self, entity: PackedLdtkEntity) {
         self.entities.push(entity);
     }
 
+    pub fn refresh_entity(&mut self, global_entity: Entity, entity: PackedLdtkEntity) {
+        self.refreshed_entities.push((global_entity, entity));
+    }
+
     fn try_create_new_layer(&mut self, layer_index: usize, layer: &LayerInstance) {
-        let tileset = self
-            .tilesets
-            .get(&layer.tileset_def_uid.unwrap())
-            .cloned()
-            .unwrap();
+        // IntGrid layers without auto-layer tiles don't have tilesets.
+        // Missing tilesets are reported in `LdtkValidationReport`.
+        let tileset = layer
+            .tileset_def_uid
+            .and_then(|uid| self.tilesets.get(&uid).cloned());
 
         if self.layers[layer_index].is_some() {
             return;
@@ -259,98 +369,16 @@ impl<'a> LdtkLayers<'a> {
     ) {
         match self.ty {
             LdtkLoaderMode::Tilemap => {
-                let mut layers = HashMap::with_capacity(self.layers.len());
-                let mut entities = HashMap::with_capacity(self.entities.len());
-
-                self.entities.drain(..).for_each(|entity| {
-                    let mut ldtk_entity =
-                        commands.spawn((entity.transform.clone(), entity.iid.clone()));
-                    entities.insert(entity.iid.clone(), ldtk_entity.id());
-                    entity.instantiate(
-                        &mut ldtk_entity,
-                        entity_registry,
-                        entity_tag_registry,
-                        config,
-                        ldtk_assets,
-                        asset_server,
-                    );
-                });
-
-                self.layers
-                    .drain(..)
-                    .enumerate()
-                    .filter_map(|(i, e)| if let Some(e) = e { Some((i, e)) } else { None })
-                    .for_each(|(index, (pattern, texture, iid, opacity))| {
-                        let tilemap_entity = commands.spawn_empty().id();
-                        let mut tilemap = StandardTilemapBundle {
-                            name: TilemapName(pattern.label.clone().unwrap()),
-                            ty: TilemapType::Square,
-                            tile_render_size: TileRenderSize(texture.desc.tile_size.as_vec2()),
-                            slot_size: TilemapSlotSize(texture.desc.tile_size.as_vec2()),
-                            texture: texture.clone(),
-                            storage: TilemapStorage::new(DEFAULT_CHUNK_SIZE, tilemap_entity),
-                            transform: TilemapTransform {
-                                translation: self.translation,
-                                z_index: self.base_z_index - index as i32 - 1,
-                                ..Default::default()
-                            },
-                            layer_opacities: TilemapLayerOpacities([opacity; 4].into()),
-                            animations: pattern.animations.clone(),
-                            ..Default::default()
-                        };
-
-                        tilemap
-                            .storage
-                            .fill_with_buffer(commands, IVec2::ZERO, pattern.tiles);
-
-                        #[cfg(feature = "algorithm")]
-                        if let Some((path_layer, path_tilemap)) = &self.path_layer {
-                            if path_layer.parent == tilemap.name.0 {
-                                commands.entity(tilemap_entity).insert(PathTilemap {
-                                    storage: ChunkedStorage::from_mapper(
-                                        path_tilemap.clone(),
-                                        None,
-                                    ),
-                                });
-                            }
-                        }
-
-                        #[cfg(feature = "physics")]
-                        if let Some((physics_layer, physics_data, size)) = &self.physics_layer {
-                            if pattern.label.clone().unwrap() == physics_layer.parent {
-                                commands
-                                    .entity(tilemap_entity)
-                                    .insert(DataPhysicsTilemap::new(
-                                        IVec2::new(0, -(size.y as i32)),
-                                        physics_data.clone(),
-                                        *size,
-                                        physics_layer.air,
-                                        physics_layer.tiles.clone().unwrap_or_default(),
-                                    ));
-                            }
-                        }
-
-                        commands
-                            .entity(tilemap_entity)
-                            .insert((tilemap, iid.clone()));
-                        layers.insert(iid, tilemap_entity);
-                    });
-
-                let bg = commands.spawn(self.background.clone()).id();
-
-                commands.entity(self.level_entity).insert((
-                    LdtkLoadedLevel {
-                        identifier: level.identifier.clone(),
-                        layers,
-                        entities,
-                        background: bg,
-                    },
-                    SpatialBundle {
-                        transform: Transform::from_translation(self.translation.extend(0.)),
-                        ..Default::default()
-                    },
-                    LevelIid(level.iid.clone()),
-                ));
+                self.apply_entities(
+                    commands,
+                    entity_registry,
+                    entity_tag_registry,
+                    config,
+                    ldtk_assets,
+                    asset_server,
+                );
+                while self.apply_next_layer(commands, level).is_some() {}
+                self.finish(commands, level, config);
             }
             LdtkLoaderMode::MapPattern => {
                 self.layers
@@ -388,7 +416,7 @@ impl<'a> LdtkLayers<'a> {
                             layer_index,
                             &iid,
                             pattern,
-                            &Some(texture),
+                            &texture,
                             &level.identifier,
                         );
 
@@ -400,6 +428,256 @@ impl<'a> LdtkLayers<'a> {
         }
     }
 
+    /// Spawn all the entities of the level.
+    pub fn apply_entities(
+        &mut self,
+        commands: &mut Commands,
+        entity_registry: &LdtkEntityRegistry,
+        entity_tag_registry: &LdtkEntityTagRegistry,
+        config: &LdtkLoadConfig,
+        ldtk_assets: &LdtkAssets,
+        asset_server: &AssetServer,
+    ) {
+        self.entities.drain(..).for_each(|entity| {
+            let mut ldtk_entity = commands.spawn((
+                entity.transform.clone(),
+                entity.iid.clone(),
+                LdtkLevelRoot(self.level_entity),
+            ));
+            self.spawned_entities
+                .insert(entity.iid.clone(), ldtk_entity.id());
+            entity.instantiate(
+                &mut ldtk_entity,
+                entity_registry,
+                entity_tag_registry,
+                config,
+                ldtk_assets,
+                asset_server,
+            );
+        });
+
+        self.refreshed_entities
+            .drain(..)
+            .for_each(|(global_entity, entity)| {
+                let mut ldtk_entity = commands.entity(global_entity);
+                LdtkGlobalEntitySnapshots::snapshot_deferred(&mut ldtk_entity);
+                entity.instantiate(
+                    &mut ldtk_entity,
+                    entity_registry,
+                    entity_tag_registry,
+                    config,
+                    ldtk_assets,
+                    asset_server,
+                );
+                LdtkGlobalEntitySnapshots::restore_deferred(&mut ldtk_entity);
+            });
+    }
+
+    /// The count of tiles in the next layer to spawn.
+    /// Returns `None` if all the layers are spawned.
+    pub fn next_layer_tile_count(&self) -> Option<usize> {
+        self.layers
+            .iter()
+            .find_map(|l| l.as_ref().map(|(pattern, ..)| pattern.tiles.tiles.len()))
+    }
+
+    #[inline]
+    pub fn remaining_layers(&self) -> usize {
+        self.layers.iter().filter(|l| l.is_some()).count()
+    }
+
+    /// Spawn the next tilemap layer. Returns the count of tiles in it,
+    /// or `None` if all the layers are spawned.
+    pub fn apply_next_layer(&mut self, commands: &mut Commands, level: &Level) -> Option<usize> {
+        let (index, (pattern, texture, iid, opacity)) = self
+            .layers
+            .iter_mut()
+            .enumerate()
+            .find_map(|(i, l)| l.take().map(|l| (i, l)))?;
+        let tile_count = pattern.tiles.tiles.len();
+
+        let tilemap_entity = commands.spawn_empty().id();
+        let grid_size = Vec2::splat(level.layer_instances[index].grid_size as f32);
+        let tile_size = texture
+            .as_ref()
+            .map(|t| t.desc.tile_size.as_vec2())
+            .unwrap_or(grid_size);
+        let local_translation = level.layer_instances[index].total_offset();
+        let z_index = self.base_z_index - index as i32 - 1;
+        let mut tilemap = StandardTilemapBundle {
+            name: TilemapName(pattern.label.clone().unwrap()),
+            ty: TilemapType::Square,
+            tile_render_size: TileRenderSize(tile_size),
+            slot_size: TilemapSlotSize(grid_size),
+            texture: texture.clone().unwrap_or_default(),
+            storage: TilemapStorage::new(
+                suggest_chunk_size(
+                    UVec2::new(
+                        level.layer_instances[index].c_wid as u32,
+                        level.layer_instances[index].c_hei as u32,
+                    ),
+                    tile_size,
+                    DEFAULT_EXPECTED_VIEW_SIZE,
+                ),
+                tilemap_entity,
+            ),
+            transform: TilemapTransform {
+                translation: self.translation + local_translation,
+                z_index,
+                ..Default::default()
+            },
+            layer_opacities: TilemapLayerOpacities([opacity; 4].into()),
+            animations: pattern.animations.clone(),
+            ..Default::default()
+        };
+
+        if pattern
+            .tiles
+            .tiles
+            .values()
+            .any(|t| t.audio_emitter.is_some())
+        {
+            commands
+                .entity(tilemap_entity)
+                .insert(TilemapAudioEmitters::default());
+        }
+        tilemap
+            .storage
+            .fill_with_buffer(commands, IVec2::ZERO, pattern.tiles);
+
+        #[cfg(feature = "algorithm")]
+        if let Some((path_layer, path_tilemap)) = &self.path_layer {
+            if path_layer.parent == tilemap.name.0 {
+                commands.entity(tilemap_entity).insert(PathTilemap {
+                    storage: ChunkedStorage::from_mapper(path_tilemap.clone(), None),
+                });
+            }
+        }
+
+        #[cfg(feature = "physics")]
+        if let Some((physics_layer, physics_data, size)) = &self.physics_layer {
+            if pattern.label.clone().unwrap() == physics_layer.parent {
+                commands
+                    .entity(tilemap_entity)
+                    .insert(DataPhysicsTilemap::new(
+                        IVec2::new(0, -(size.y as i32)),
+                        physics_data.clone(),
+                        *size,
+                        physics_layer.air,
+                        physics_layer.tiles.clone().unwrap_or_default(),
+                    ));
+            }
+        }
+
+        if let Some(uid) = level.layer_instances[index].tileset_def_uid {
+            commands.entity(tilemap_entity).insert((
+                tilemap,
+                iid.clone(),
+                TilesetUid(uid),
+                LdtkTileTags::default(),
+            ));
+        } else {
+            commands.entity(tilemap_entity).insert((
+                Into::<StandardPureColorTilemapBundle>::into(tilemap),
+                iid.clone(),
+            ));
+        }
+        // The `TilemapTransform` follows the level entity from now on.
+        commands
+            .entity(tilemap_entity)
+            .insert((
+                SpatialBundle::from_transform(Transform::from_translation(
+                    local_translation.extend(z_index as f32),
+                )),
+                TilemapTransformSync,
+            ))
+            .set_parent(self.level_entity);
+        LdtkLayerMaterials::apply_deferred(
+            &mut commands.entity(tilemap_entity),
+            level.layer_instances[index].identifier.clone(),
+        );
+        self.spawned_layers.insert(iid, tilemap_entity);
+        Some(tile_count)
+    }
+
+    /// Spawn the background and insert the `LdtkLoadedLevel` to the level entity.
+    pub fn finish(&mut self, commands: &mut Commands, level: &Level, config: &LdtkLoadConfig) {
+        let bg = self.background.spawn(commands);
+        commands.entity(self.level_entity).add_child(bg);
+
+        commands.entity(self.level_entity).insert((
+            LdtkLoadedLevel {
+                identifier: level.identifier.clone(),
+                layers: self.spawned_layers.clone(),
+                entities: self.spawned_entities.clone(),
+                background: bg,
+                world_depth: level.world_depth,
+                z_index: config.level_z_index(level),
+            },
+            LevelIid(level.iid.clone()),
+            LdtkWorldDepth(level.world_depth),
+            self.region(level),
+        ));
+
+        if let Some(atmosphere) =
+            LdtkLevelAtmosphere::from_fields(&level.field_instances, &config.atmosphere_fields)
+        {
+            commands.entity(self.level_entity).insert(atmosphere);
+        }
+
+        if let Some(int_grid) = LdtkIntGrid::from_layers(&level.layer_instances) {
+            commands.entity(self.level_entity).insert(int_grid);
+        }
+
+        if let Some(fields) = LdtkLevelFields::from_fields(&level.field_instances) {
+            commands.entity(self.level_entity).insert(fields);
+        }
+    }
+
+    /// The level as a `Region`. The tiles are measured with the smallest grid size of the layers.
+    fn region(&self, level: &Level) -> Region {
+        let grid_size = level
+            .layer_instances
+            .iter()
+            .map(|layer| layer.grid_size)
+            .min()
+            .unwrap_or(1);
+        let c_wid = (level.px_wid + grid_size - 1) / grid_size;
+        let c_hei = (level.px_hei + grid_size - 1) / grid_size;
+
+        Region {
+            identifier: level.identifier.clone(),
+            tile_area: IAabb2d::new(0, -c_hei, c_wid - 1, -1),
+            world_aabb: Aabb2d {
+                min: self.translation - Vec2::new(0., level.px_hei as f32),
+                max: self.translation + Vec2::new(level.px_wid as f32, 0.),
+            },
+            fields: level
+                .field_instances
+                .iter()
+                .filter_map(|f| Some((f.identifier.clone(), f.as_region_field()?)))
+                .collect(),
+        }
+    }
+
+    /// Clear the layers and entities spawned so far, for levels that are
+    /// unloaded before being fully spawned.
+    pub fn unload_spawned(
+        &self,
+        commands: &mut Commands,
+        global_entities: &LdtkGlobalEntityRegistry,
+    ) {
+        self.spawned_layers.values().for_each(|e| {
+            commands.entity(*e).insert(LdtkUnloadLayer);
+        });
+        self.spawned_entities
+            .iter()
+            .filter(|(iid, _)| !global_entities.contains(iid))
+            .for_each(|(_, e)| {
+                LdtkPersistent::despawn_or_detach(commands, *e);
+            });
+    }
+
     #[cfg(feature = "algorithm")]
     pub fn assign_path_layer(
         &mut self,
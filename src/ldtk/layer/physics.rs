@@ -1,6 +1,19 @@
-use bevy::{ecs::system::Resource, reflect::Reflect, utils::HashMap};
+use bevy::{
+    ecs::{
+        entity::Entity,
+        system::{CommandQueue, Commands, EntityCommands, Resource},
+        world::World,
+    },
+    math::Vec2,
+    reflect::Reflect,
+    utils::HashMap,
+};
+use bevy_xpbd_2d::{
+    components::{Friction, RigidBody},
+    plugins::collision::{Collider, Sensor},
+};
 
-use crate::tilemap::physics::PhysicsTile;
+use crate::{ldtk::json::level::EntityInstance, tilemap::physics::PhysicsTile};
 
 #[derive(Debug, Resource, Clone, Reflect)]
 pub struct LdtkPhysicsLayer {
@@ -9,3 +22,101 @@ pub struct LdtkPhysicsLayer {
     pub air: i32,
     pub tiles: Option<HashMap<i32, PhysicsTile>>,
 }
+
+/// The collider generated for an LDtk entity from its size and pivot.
+#[derive(Debug, Clone, Reflect)]
+pub struct LdtkEntityCollider {
+    /// Insert a static `RigidBody`.
+    pub rigid_body: bool,
+    /// Insert `Sensor`, for triggers that only detect overlaps.
+    pub sensor: bool,
+    pub friction: Option<f32>,
+}
+
+impl Default for LdtkEntityCollider {
+    fn default() -> Self {
+        Self {
+            rigid_body: true,
+            sensor: false,
+            friction: None,
+        }
+    }
+}
+
+impl LdtkEntityCollider {
+    /// A sensor without a rigid body.
+    pub fn sensor() -> Self {
+        Self {
+            rigid_body: false,
+            sensor: true,
+            friction: None,
+        }
+    }
+
+    /// The corners of the entity rect, relative to the pivot which is the origin of the entity.
+    pub fn vertices(size: Vec2, pivot: Vec2) -> Vec<Vec2> {
+        let min = Vec2::new(-pivot.x * size.x, -(1. - pivot.y) * size.y);
+        let max = Vec2::new((1. - pivot.x) * size.x, pivot.y * size.y);
+        vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+
+    pub fn insert(&self, commands: &mut EntityCommands, entity_instance: &EntityInstance) {
+        let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32);
+        let Some(collider) =
+            Collider::convex_hull(Self::vertices(size, entity_instance.pivot.into()))
+        else {
+            return;
+        };
+
+        commands.insert(collider);
+        if self.rigid_body {
+            commands.insert(RigidBody::Static);
+        }
+        if self.sensor {
+            commands.insert(Sensor);
+        }
+        if let Some(friction) = self.friction {
+            commands.insert(Friction::new(friction));
+        }
+    }
+}
+
+/// The LDtk entities that get colliders, by identifier.
+///
+/// Use `App::register_ldtk_entity_collider` to register them.
+#[derive(Resource, Default, Reflect)]
+pub struct LdtkEntityColliderRegistry(pub(crate) HashMap<String, LdtkEntityCollider>);
+
+impl LdtkEntityColliderRegistry {
+    #[inline]
+    pub fn insert(&mut self, identifier: &str, collider: LdtkEntityCollider) {
+        self.0.insert(identifier.to_string(), collider);
+    }
+
+    #[inline]
+    pub fn get(&self, identifier: &str) -> Option<&LdtkEntityCollider> {
+        self.0.get(identifier)
+    }
+
+    /// Insert the collider if the entity is registered. This is deferred using commands
+    /// so the registry can be read from the world.
+    pub(crate) fn insert_deferred(commands: &mut EntityCommands, entity_instance: &EntityInstance) {
+        let entity_instance = entity_instance.clone();
+        commands.add(move |entity: Entity, world: &mut World| {
+            let Some(collider) = world
+                .get_resource::<LdtkEntityColliderRegistry>()
+                .and_then(|r| r.get(&entity_instance.identifier))
+                .cloned()
+            else {
+                return;
+            };
+
+            let mut queue = CommandQueue::default();
+            collider.insert(
+                &mut Commands::new(&mut queue, world).entity(entity),
+                &entity_instance,
+            );
+            queue.apply(world);
+        });
+    }
+}
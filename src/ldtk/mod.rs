@@ -2,62 +2,101 @@ use std::path::Path;
 
 use bevy::{
     app::{Plugin, Startup, Update},
-    asset::{load_internal_asset, AssetServer, Assets, Handle},
+    asset::{load_internal_asset, AssetApp, AssetEvent, AssetServer, Assets, Handle},
+    core_pipeline::bloom::BloomSettings,
     ecs::{
+        change_detection::{DetectChanges, DetectChangesMut},
+        component::Component,
         entity::Entity,
-        event::EventWriter,
-        query::{Added, With},
-        system::{Commands, NonSend, ParallelCommands, Query, Res, ResMut},
+        event::{EventReader, EventWriter},
+        query::{Added, Changed, Has, Or, With, Without},
+        removal_detection::RemovedComponents,
+        schedule::IntoSystemConfigs,
+        system::{Commands, Local, NonSend, ParallelCommands, Query, Res, ResMut, SystemParam},
+        world::Ref,
     },
-    math::{UVec2, Vec2},
-    render::{mesh::Mesh, render_resource::Shader},
-    sprite::{Material2dPlugin, Sprite, SpriteBundle, TextureAtlasLayout},
+    hierarchy::{BuildChildren, Parent},
+    math::{Rect, UVec2, Vec2, Vec4},
+    prelude::SpatialBundle,
+    render::{
+        camera::{Camera, ClearColorConfig},
+        mesh::Mesh,
+        render_resource::Shader,
+        view::{InheritedVisibility, Visibility},
+    },
+    sprite::{Anchor, ImageScaleMode, Material2dPlugin, Sprite, SpriteBundle, TextureAtlasLayout},
+    tasks::{AsyncComputeTaskPool, ComputeTaskPool, ParallelSlice, Task},
     transform::components::Transform,
+    utils::{FloatOrd, HashMap, HashSet},
 };
 
 use crate::{
     ldtk::{
-        components::{LayerIid, LdtkLoader, LdtkLoaderMode, LdtkUnloader, WorldIid},
+        auto_rules::AutoRuleSet,
+        components::{
+            LayerIid, LdtkLoader, LdtkLoaderMode, LdtkTileData, LdtkTileTags, LdtkUnloader,
+            TilesetUid, WorldIid,
+        },
         json::{
             field::FieldInstance,
             level::{EntityInstance, ImagePosition, Neighbour, TileInstance},
             EntityRef, GridPoint, LdtkColor, Toc, World,
         },
         resources::{
-            LdtkAdditionalLayers, LdtkAssets, LdtkGlobalEntityRegistry, LdtkPatterns, LdtkTocs,
+            LdtkAdditionalLayers, LdtkAssets, LdtkGlobalEntityRegistry, LdtkPatterns,
+            LdtkTileMetadata, LdtkTocs,
         },
         sprite::{AtlasRect, NineSliceBorders, SpriteMesh},
     },
-    tilemap::map::TilemapStorage,
+    math::CameraAabb2d,
+    tilemap::{
+        chunking::budget::StreamingBudget,
+        map::{TilemapLayerOpacities, TilemapStorage},
+        tile::{Tile, TileTexture},
+    },
 };
 
 use self::{
+    asset::{LdtkJsonLoader, LdtkLevelLoader},
     components::{
-        EntityIid, GlobalEntity, LdtkLoadedLevel, LdtkTempTransform, LdtkUnloadLayer, LevelIid,
+        EntityIid, GlobalEntity, LdtkAtmosphereReceiver, LdtkBackground, LdtkEntityRefs,
+        LdtkHiddenLayerOpacities, LdtkIntGrid, LdtkIntGridLayer, LdtkLevelAtmosphere,
+        LdtkLevelFields, LdtkLevelLoadProgress, LdtkLevelRoot, LdtkLoadedLevel, LdtkPersistent,
+        LdtkTempTransform, LdtkUnloadLayer, LdtkWorldDepth, LevelIid,
     },
-    events::{LdtkEvent, LevelEvent},
+    events::{GlobalEntityEvent, LdtkEvent, LevelEvent, LevelSpawnedEvent},
     json::{
         definitions::LayerType,
-        level::{LayerInstance, Level},
+        level::{BgPos, LayerInstance, Level},
         LdtkJson, WorldLayout,
     },
     layer::{LdtkLayers, PackedLdtkEntity},
-    resources::{LdtkLevelManager, LdtkLoadConfig},
+    resources::{LdtkLevelManager, LdtkLevelStreaming, LdtkLoadConfig, LdtkVisibleDepths},
     sprite::LdtkEntityMaterial,
-    traits::{LdtkEntityRegistry, LdtkEntityTagRegistry},
+    traits::{
+        LdtkEntityRegistry, LdtkEntityTagRegistry, LdtkEnumRegistry, LdtkGlobalEntitySnapshots,
+    },
+    validation::{LdtkIssue, LdtkValidationReport},
 };
 
 pub mod app_ext;
+pub mod asset;
+pub mod auto_rules;
 pub mod components;
+pub mod diff;
 pub mod events;
 pub mod json;
 pub mod layer;
 pub mod resources;
 pub mod sprite;
 pub mod traits;
+pub mod validation;
 
 pub const ENTITY_SPRITE_SHADER: Handle<Shader> = Handle::weak_from_u128(89874656485416351634163551);
 
+/// The count of LDtk entities packed in each parallel task when loading a layer.
+const ENTITY_BATCH_SIZE: usize = 64;
+
 pub struct EntiTilesLdtkPlugin;
 
 impl Plugin for EntiTilesLdtkPlugin {
@@ -71,15 +110,35 @@ impl Plugin for EntiTilesLdtkPlugin {
 
         app.add_plugins(Material2dPlugin::<LdtkEntityMaterial>::default());
 
+        app.init_asset::<LdtkJson>()
+            .init_asset_loader::<LdtkJsonLoader>()
+            .init_asset::<Level>()
+            .init_asset_loader::<LdtkLevelLoader>();
+
         app.add_systems(Startup, parse_ldtk_json);
         app.add_systems(
             Update,
             (
+                ldtk_json_applier.before(load_ldtk_json),
+                ldtk_hot_reloader
+                    .after(ldtk_json_applier)
+                    .before(load_ldtk_json),
+                ldtk_level_streamer
+                    .after(ldtk_json_applier)
+                    .before(load_ldtk_json),
                 load_ldtk_json,
+                ldtk_level_task_applier,
+                ldtk_level_spawner.after(ldtk_level_task_applier),
                 unload_ldtk_level,
                 unload_ldtk_layer,
                 global_entity_registerer,
                 ldtk_temp_tranform_applier,
+                ldtk_atmosphere_applier,
+                ldtk_depth_visibility_applier,
+                ldtk_layer_visibility_applier,
+                ldtk_entity_ref_resolver,
+                ldtk_tile_data_applier,
+                ldtk_tile_tags_collector,
             ),
         );
 
@@ -91,28 +150,49 @@ impl Plugin for EntiTilesLdtkPlugin {
             .init_resource::<LdtkAssets>()
             .init_resource::<LdtkPatterns>()
             .init_resource::<LdtkTocs>()
-            .init_resource::<LdtkGlobalEntityRegistry>();
+            .init_resource::<AutoRuleSet>()
+            .init_resource::<LdtkGlobalEntityRegistry>()
+            .init_resource::<LdtkGlobalEntitySnapshots>()
+            .init_resource::<LdtkTileMetadata>()
+            .init_resource::<LdtkValidationReport>();
 
         app.add_event::<LdtkEvent>();
 
         app.register_type::<LdtkLoadedLevel>()
             .register_type::<GlobalEntity>()
+            .register_type::<LdtkPersistent>()
             .register_type::<EntityIid>()
             .register_type::<LayerIid>()
             .register_type::<LevelIid>()
             .register_type::<WorldIid>()
             .register_type::<LevelEvent>()
+            .register_type::<GlobalEntityEvent>()
+            .register_type::<LevelSpawnedEvent>()
             .register_type::<LdtkLoader>()
             .register_type::<LdtkUnloader>()
             .register_type::<LdtkLoaderMode>()
             .register_type::<AtlasRect>()
             .register_type::<LdtkEntityMaterial>()
             .register_type::<NineSliceBorders>()
-            .register_type::<SpriteMesh>();
+            .register_type::<SpriteMesh>()
+            .register_type::<LdtkLevelAtmosphere>()
+            .register_type::<LdtkAtmosphereReceiver>()
+            .register_type::<LdtkWorldDepth>()
+            .register_type::<LdtkHiddenLayerOpacities>()
+            .register_type::<LdtkEntityRefs>()
+            .register_type::<LdtkLevelRoot>()
+            .register_type::<LdtkIntGrid>()
+            .register_type::<LdtkIntGridLayer>()
+            .register_type::<LdtkLevelFields>()
+            .register_type::<LdtkLevelLoadProgress>()
+            .register_type::<TilesetUid>()
+            .register_type::<LdtkTileData>()
+            .register_type::<LdtkTileTags>();
 
         app.register_type::<FieldInstance>()
             .register_type::<Level>()
             .register_type::<ImagePosition>()
+            .register_type::<BgPos>()
             .register_type::<Neighbour>()
             .register_type::<LayerInstance>()
             .register_type::<TileInstance>()
@@ -126,10 +206,16 @@ impl Plugin for EntiTilesLdtkPlugin {
 
         app.register_type::<LdtkLevelManager>()
             .register_type::<LdtkLoadConfig>()
+            .register_type::<LdtkValidationReport>()
+            .register_type::<LdtkIssue>()
             .register_type::<LdtkAdditionalLayers>()
             .register_type::<LdtkAssets>()
             .register_type::<LdtkPatterns>()
-            .register_type::<LdtkGlobalEntityRegistry>();
+            .register_type::<AutoRuleSet>()
+            .register_type::<LdtkGlobalEntityRegistry>()
+            .register_type::<LdtkTileMetadata>()
+            .register_type::<LdtkLevelStreaming>()
+            .register_type::<LdtkVisibleDepths>();
 
         #[cfg(feature = "algorithm")]
         {
@@ -140,13 +226,301 @@ impl Plugin for EntiTilesLdtkPlugin {
 
         #[cfg(feature = "physics")]
         {
-            app.register_type::<layer::physics::LdtkPhysicsLayer>();
+            app.init_resource::<layer::physics::LdtkEntityColliderRegistry>();
+
+            app.register_type::<layer::physics::LdtkPhysicsLayer>()
+                .register_type::<layer::physics::LdtkEntityCollider>()
+                .register_type::<layer::physics::LdtkEntityColliderRegistry>();
         }
     }
 }
 
-fn parse_ldtk_json(mut manager: ResMut<LdtkLevelManager>, config: Res<LdtkLoadConfig>) {
-    manager.reload_json(&config);
+fn parse_ldtk_json(
+    mut manager: ResMut<LdtkLevelManager>,
+    config: Res<LdtkLoadConfig>,
+    asset_server: Res<AssetServer>,
+) {
+    manager.reload_json(&config, &asset_server);
+}
+
+/// Refresh the cached data of the manager when the LDtk file
+/// or an external level file is loaded or modified.
+pub fn ldtk_json_applier(
+    mut manager: ResMut<LdtkLevelManager>,
+    mut tile_metadata: ResMut<LdtkTileMetadata>,
+    mut tocs: ResMut<LdtkTocs>,
+    mut auto_rule_set: ResMut<AutoRuleSet>,
+    mut asset_events: EventReader<AssetEvent<LdtkJson>>,
+    mut level_events: EventReader<AssetEvent<Level>>,
+    ldtk_jsons: Res<Assets<LdtkJson>>,
+    levels: Res<Assets<Level>>,
+    (config, mut validation, enum_registry, entity_registry, entity_tag_registry): (
+        Res<LdtkLoadConfig>,
+        ResMut<LdtkValidationReport>,
+        Option<Res<LdtkEnumRegistry>>,
+        Option<NonSend<LdtkEntityRegistry>>,
+        Option<NonSend<LdtkEntityTagRegistry>>,
+    ),
+) {
+    for event in asset_events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => id,
+            _ => continue,
+        };
+
+        if manager.json_handle.as_ref().is_some_and(|h| h.id() == *id) {
+            if let Some(ldtk_json) = ldtk_jsons.get(*id) {
+                *validation = LdtkValidationReport::new(
+                    ldtk_json,
+                    &config,
+                    entity_registry.as_deref(),
+                    entity_tag_registry.as_deref(),
+                    enum_registry.as_deref(),
+                );
+                validation.report(config.strict_validation);
+                *tile_metadata = LdtkTileMetadata::new(&ldtk_json.defs);
+                *tocs = LdtkTocs::new(ldtk_json);
+                *auto_rule_set = AutoRuleSet::from_ldtk(ldtk_json);
+                manager.ldtk_json = Some(ldtk_json.clone());
+            }
+        }
+    }
+
+    for event in level_events.read() {
+        let id = match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => id,
+            _ => continue,
+        };
+
+        let Some(identifier) = manager
+            .external_handles
+            .iter()
+            .find(|(_, h)| h.id() == *id)
+            .map(|(identifier, _)| identifier.clone())
+        else {
+            continue;
+        };
+        if let Some(level) = levels.get(*id) {
+            manager.external_levels.insert(identifier, level.clone());
+        }
+    }
+}
+
+/// Resolve the `LdtkEntityRefs` when LDtk entities are spawned or despawned.
+pub fn ldtk_entity_ref_resolver(
+    mut refs_query: Query<(&mut LdtkEntityRefs, Option<&LdtkLevelRoot>)>,
+    new_refs_query: Query<(), Added<LdtkEntityRefs>>,
+    new_iids_query: Query<(), Added<EntityIid>>,
+    iids_query: Query<(Entity, &EntityIid, Option<&LdtkLevelRoot>)>,
+    mut removed: RemovedComponents<EntityIid>,
+) {
+    let despawned = removed.read().count() > 0;
+    if !despawned && new_refs_query.is_empty() && new_iids_query.is_empty() {
+        return;
+    }
+
+    // The instances of a level share the iids.
+    let mut spawned = HashMap::<&str, Vec<(Entity, Option<LdtkLevelRoot>)>>::default();
+    iids_query.iter().for_each(|(entity, iid, root)| {
+        spawned
+            .entry(iid.0.as_str())
+            .or_default()
+            .push((entity, root.copied()));
+    });
+    refs_query.iter_mut().for_each(|(mut refs, root)| {
+        let lookup = |iid: &str| {
+            let candidates = spawned.get(iid)?;
+            candidates
+                .iter()
+                .find(|(_, r)| r.as_ref() == root)
+                .or(candidates.first())
+                .map(|(e, _)| *e)
+        };
+        if refs.bypass_change_detection().resolve(lookup) {
+            refs.set_changed();
+        }
+    });
+}
+
+/// Respawn the loaded levels in place when the LDtk file or their level files are modified,
+/// if `LdtkLoadConfig::hot_reload` is enabled.
+///
+/// The root entities of the levels and the `GlobalEntity` entities are kept.
+pub fn ldtk_hot_reloader(
+    mut commands: Commands,
+    manager: Res<LdtkLevelManager>,
+    config: Res<LdtkLoadConfig>,
+    (mut asset_events, mut level_events): (
+        EventReader<AssetEvent<LdtkJson>>,
+        EventReader<AssetEvent<Level>>,
+    ),
+    (levels_query, spawners_query): (
+        Query<(&LdtkLoadedLevel, &LevelIid)>,
+        Query<&LdtkLevelSpawner>,
+    ),
+    mut ldtk_events: EventWriter<LdtkEvent>,
+    global_entities: Res<LdtkGlobalEntityRegistry>,
+) {
+    if !config.hot_reload {
+        asset_events.clear();
+        level_events.clear();
+        return;
+    }
+
+    let json_modified = asset_events.read().any(|event| {
+        matches!(event, AssetEvent::Modified { id }
+            if manager.json_handle.as_ref().is_some_and(|h| h.id() == *id))
+    });
+    let modified_levels = level_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => manager
+                .external_handles
+                .iter()
+                .find(|(_, h)| h.id() == *id)
+                .map(|(identifier, _)| identifier.clone()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    if !json_modified && modified_levels.is_empty() {
+        return;
+    }
+
+    manager
+        .loaded_levels
+        .iter()
+        .map(|(level, entity)| (*entity, level))
+        .chain(
+            manager
+                .instances
+                .iter()
+                .map(|(entity, level)| (*entity, level)),
+        )
+        .filter(|(_, level)| json_modified || modified_levels.contains(*level))
+        .for_each(|(entity, level)| {
+            if let Ok((loaded, iid)) = levels_query.get(entity) {
+                send_level_unloaded(&mut ldtk_events, entity, loaded, iid, &global_entities);
+                loaded.unload(&mut commands, &global_entities);
+            }
+            if let Ok(spawner) = spawners_query.get(entity) {
+                spawner
+                    .layers
+                    .unload_spawned(&mut commands, &global_entities);
+            }
+
+            // Levels that are still being built are restarted as well.
+            commands
+                .entity(entity)
+                .remove::<(
+                    LdtkLoadedLevel,
+                    LdtkLevelAtmosphere,
+                    LdtkIntGrid,
+                    LdtkLevelFields,
+                    LdtkLevelTask,
+                    LdtkLevelSpawner,
+                    LdtkLevelLoadProgress,
+                )>()
+                .insert(LdtkLoader {
+                    json: manager.json_handle.clone().unwrap(),
+                    level: level.clone(),
+                    mode: LdtkLoaderMode::Tilemap,
+                    trans_ovrd: manager.trans_ovrds.get(&entity).copied(),
+                    parent: manager.parents.get(&entity).copied(),
+                });
+        });
+}
+
+/// Load the levels around the camera and unload the far away ones
+/// if `LdtkLevelStreaming` exists.
+pub fn ldtk_level_streamer(
+    mut commands: Commands,
+    streaming: Option<ResMut<LdtkLevelStreaming>>,
+    mut manager: ResMut<LdtkLevelManager>,
+    cameras_query: Query<&CameraAabb2d>,
+) {
+    let Some(mut streaming) = streaming else {
+        return;
+    };
+    if !manager.is_initialized() {
+        return;
+    }
+    let Some(focus) = cameras_query.iter().next().map(|aabb| aabb.0.center()) else {
+        return;
+    };
+
+    let ldtk_data = manager.get_cached_data();
+    if !matches!(
+        ldtk_data.world_layout,
+        Some(WorldLayout::GridVania | WorldLayout::Free)
+    ) {
+        return;
+    }
+
+    let current = streaming
+        .current
+        .as_ref()
+        .and_then(|c| ldtk_data.levels.iter().find(|l| &l.identifier == c))
+        .filter(|l| level_distance(l, focus) == 0.)
+        .or_else(|| {
+            ldtk_data
+                .levels
+                .iter()
+                .filter(|l| level_distance(l, focus) == 0.)
+                .max_by_key(|l| l.world_depth)
+        })
+        .or_else(|| {
+            // The camera is between the levels, keep the previous one.
+            streaming
+                .current
+                .as_ref()
+                .and_then(|c| ldtk_data.levels.iter().find(|l| &l.identifier == c))
+        });
+    let Some(current) = current else {
+        return;
+    };
+
+    let to_load = current
+        .neighbours
+        .iter()
+        .filter_map(|n| ldtk_data.levels.iter().find(|l| l.iid == n.level_iid))
+        .filter(|l| level_distance(l, focus) <= streaming.load_distance)
+        .chain(std::iter::once(current))
+        .filter(|l| !manager.loaded_levels.contains_key(&l.identifier))
+        .map(|l| l.identifier.clone())
+        .collect::<Vec<_>>();
+
+    let to_unload = streaming
+        .streamed
+        .iter()
+        .filter(|s| **s != current.identifier && manager.loaded_levels.contains_key(*s))
+        .filter_map(|s| ldtk_data.levels.iter().find(|l| &l.identifier == s))
+        .filter(|l| level_distance(l, focus) > streaming.unload_distance)
+        .map(|l| l.identifier.clone())
+        .collect::<Vec<_>>();
+
+    streaming.current = Some(current.identifier.clone());
+
+    // Forget the levels that are unloaded by the user.
+    let streaming = streaming.as_mut();
+    streaming
+        .streamed
+        .retain(|s| manager.loaded_levels.contains_key(s));
+
+    for level in to_unload {
+        streaming.streamed.remove(&level);
+        manager.unload(&mut commands, level);
+    }
+    for level in to_load {
+        streaming.streamed.insert(level.clone());
+        manager.load(&mut commands, level, None);
+    }
+}
+
+/// The distance in pixels from the point to the bounds of the level.
+fn level_distance(level: &Level, point: Vec2) -> f32 {
+    let min = Vec2::new(level.world_x as f32, -(level.world_y + level.px_hei) as f32);
+    let max = Vec2::new((level.world_x + level.px_wid) as f32, -level.world_y as f32);
+    point.clamp(min, max).distance(point)
 }
 
 fn global_entity_registerer(
@@ -158,33 +532,243 @@ fn global_entity_registerer(
     });
 }
 
+fn ldtk_tile_data_applier(
+    commands: ParallelCommands,
+    tiles_query: Query<(Entity, &Tile), Added<Tile>>,
+    tilesets_query: Query<&TilesetUid>,
+    tile_metadata: Res<LdtkTileMetadata>,
+    config: Res<LdtkLoadConfig>,
+) {
+    if !config.tile_data_components {
+        return;
+    }
+
+    tiles_query.par_iter().for_each(|(entity, tile)| {
+        let Ok(tileset) = tilesets_query.get(tile.tilemap_id) else {
+            return;
+        };
+        let TileTexture::Static(layers) = &tile.texture else {
+            return;
+        };
+
+        // The upper layers take precedence.
+        if let Some(data) = layers
+            .iter()
+            .rev()
+            .find_map(|layer| tile_metadata.get(tileset.0, layer.texture_index))
+        {
+            commands.command_scope(|mut c| {
+                c.entity(entity).insert(data.clone());
+            });
+        }
+    });
+}
+
+fn ldtk_tile_tags_collector(
+    tiles_query: Query<&Tile, Added<Tile>>,
+    mut tilemaps_query: Query<(&TilesetUid, &mut LdtkTileTags)>,
+    tile_metadata: Res<LdtkTileMetadata>,
+) {
+    tiles_query.iter().for_each(|tile| {
+        let Ok((tileset, mut tile_tags)) = tilemaps_query.get_mut(tile.tilemap_id) else {
+            return;
+        };
+
+        let mut tags = Vec::new();
+        if let TileTexture::Static(layers) = &tile.texture {
+            layers
+                .iter()
+                .filter_map(|layer| tile_metadata.get(tileset.0, layer.texture_index))
+                .flat_map(|data| data.enum_tags.iter())
+                .for_each(|tag| {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                });
+        }
+        tile_tags.set(tile.index, tags);
+    });
+}
+
 fn ldtk_temp_tranform_applier(
     commands: ParallelCommands,
-    mut entities_query: Query<(Entity, &mut Transform, &LdtkTempTransform)>,
+    mut entities_query: Query<(
+        Entity,
+        Option<&mut Transform>,
+        &LdtkTempTransform,
+        Option<&LdtkLevelRoot>,
+        Has<GlobalEntity>,
+    )>,
 ) {
-    entities_query
-        .par_iter_mut()
-        .for_each(|(entity, mut transform, ldtk_temp)| {
-            transform.translation += ldtk_temp.level_translation.extend(ldtk_temp.z_index);
+    entities_query.par_iter_mut().for_each(
+        |(entity, transform, ldtk_temp, level_root, is_global)| {
+            // Global entities outlive their levels, so they stay at the root.
+            let parent = level_root.filter(|_| !is_global).map(|root| root.0);
+            let translation = match parent {
+                Some(_) => ldtk_temp.local_translation,
+                None => ldtk_temp.level_translation + ldtk_temp.local_translation,
+            }
+            .extend(ldtk_temp.z_index);
             commands.command_scope(|mut c| {
-                c.entity(entity).remove::<LdtkTempTransform>();
+                let mut e = c.entity(entity);
+                e.remove::<LdtkTempTransform>();
+                if transform.is_none() {
+                    e.insert(SpatialBundle::from_transform(Transform::from_translation(
+                        translation,
+                    )));
+                }
+                if let Some(parent) = parent {
+                    e.set_parent(parent);
+                }
             });
+            if let Some(mut transform) = transform {
+                transform.translation += translation;
+            }
+        },
+    );
+}
+
+/// Apply the atmosphere of the newly loaded level to the cameras with `LdtkAtmosphereReceiver`.
+///
+/// The ambient color becomes the clear color, and the bloom is only applied to hdr cameras.
+pub fn ldtk_atmosphere_applier(
+    mut commands: Commands,
+    atmosphere_query: Query<&LdtkLevelAtmosphere, Added<LdtkLevelAtmosphere>>,
+    mut cameras_query: Query<
+        (Entity, &mut Camera, Option<&mut BloomSettings>),
+        With<LdtkAtmosphereReceiver>,
+    >,
+) {
+    let Some(atmosphere) = atmosphere_query.iter().last() else {
+        return;
+    };
+
+    cameras_query
+        .iter_mut()
+        .for_each(|(entity, mut camera, bloom_settings)| {
+            if let Some(color) = atmosphere.ambient_color {
+                camera.clear_color = ClearColorConfig::Custom(color);
+            }
+
+            let Some(intensity) = atmosphere.bloom else {
+                return;
+            };
+            if let Some(mut bloom_settings) = bloom_settings {
+                bloom_settings.intensity = intensity;
+            } else if camera.hdr {
+                commands.entity(entity).insert(BloomSettings {
+                    intensity,
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+/// Show or hide the levels according to `LdtkVisibleDepths`.
+pub fn ldtk_depth_visibility_applier(
+    visible_depths: Option<Res<LdtkVisibleDepths>>,
+    mut levels_query: Query<(Ref<LdtkLoadedLevel>, &LdtkWorldDepth, &mut Visibility)>,
+    mut was_active: Local<bool>,
+) {
+    let changed = match &visible_depths {
+        Some(depths) => depths.is_changed(),
+        None => *was_active,
+    };
+    *was_active = visible_depths.is_some();
+
+    levels_query
+        .iter_mut()
+        .for_each(|(level, depth, mut visibility)| {
+            if !changed && !level.is_added() {
+                return;
+            }
+
+            let visible = visible_depths
+                .as_ref()
+                .map(|depths| depths.contains(depth.0))
+                .unwrap_or(true);
+            let new_visibility = if visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            visibility.set_if_neq(new_visibility);
+        });
+}
+
+type LdtkLayerVisibilityQuery<'a> = (
+    Entity,
+    &'a Parent,
+    &'a InheritedVisibility,
+    &'a mut TilemapLayerOpacities,
+    Option<&'a LdtkHiddenLayerOpacities>,
+);
+type LdtkLayerVisibilityFilter = (With<LayerIid>, Changed<InheritedVisibility>);
+
+/// Make the layers of the hidden levels fully transparent, as tilemaps are
+/// rendered regardless of their `Visibility`.
+///
+/// The opacities of the layers are restored once the level is shown again,
+/// so the ones changed at runtime are kept.
+pub fn ldtk_layer_visibility_applier(
+    mut commands: Commands,
+    levels_query: Query<(), With<LdtkLoadedLevel>>,
+    mut layers_query: Query<LdtkLayerVisibilityQuery, LdtkLayerVisibilityFilter>,
+) {
+    layers_query
+        .iter_mut()
+        .for_each(|(entity, parent, visibility, mut opacities, hidden)| {
+            if levels_query.get(parent.get()).is_err() {
+                return;
+            }
+
+            match (visibility.get(), hidden) {
+                (true, Some(hidden)) => {
+                    opacities.0 = hidden.0;
+                    commands.entity(entity).remove::<LdtkHiddenLayerOpacities>();
+                }
+                (false, None) => {
+                    commands
+                        .entity(entity)
+                        .insert(LdtkHiddenLayerOpacities(opacities.0));
+                    opacities.0 = Vec4::ZERO;
+                }
+                _ => {}
+            }
         });
 }
 
+type LdtkPendingUnloadFilter = (
+    With<LdtkUnloader>,
+    Or<(With<LdtkLoader>, With<LdtkLevelTask>)>,
+);
+
 pub fn unload_ldtk_level(
     mut commands: Commands,
     mut query: Query<(Entity, &LdtkLoadedLevel, &LevelIid), With<LdtkUnloader>>,
+    spawners_query: Query<(Entity, &LdtkLevelSpawner), With<LdtkUnloader>>,
+    pending_query: Query<Entity, LdtkPendingUnloadFilter>,
     mut ldtk_events: EventWriter<LdtkEvent>,
     global_entities: Res<LdtkGlobalEntityRegistry>,
 ) {
     query.iter_mut().for_each(|(entity, level, iid)| {
-        ldtk_events.send(LdtkEvent::LevelUnloaded(LevelEvent {
-            identifier: level.identifier.clone(),
-            iid: iid.0.clone(),
-        }));
+        send_level_unloaded(&mut ldtk_events, entity, level, iid, &global_entities);
         level.unload(&mut commands, &global_entities);
-        commands.entity(entity).despawn();
+        commands.entity(entity).remove_parent().despawn();
+    });
+
+    // Levels that are unloaded before being fully spawned.
+    spawners_query.iter().for_each(|(entity, spawner)| {
+        spawner
+            .layers
+            .unload_spawned(&mut commands, &global_entities);
+        commands.entity(entity).remove_parent().despawn();
+    });
+
+    // Levels that are unloaded while waiting for their files or being built.
+    // Nothing is spawned for them yet, and dropping the task cancels it.
+    pending_query.iter().for_each(|entity| {
+        commands.entity(entity).remove_parent().despawn();
     });
 }
 
@@ -217,79 +801,348 @@ pub fn unload_ldtk_layer(
     });
 }
 
+/// The resources used to build and spawn LDtk levels.
+#[derive(SystemParam)]
+pub struct LdtkLevelLoadParams<'w> {
+    pub asset_server: Res<'w, AssetServer>,
+    pub config: Res<'w, LdtkLoadConfig>,
+    pub addi_layers: Res<'w, LdtkAdditionalLayers>,
+    pub global_entities: Res<'w, LdtkGlobalEntityRegistry>,
+    pub manager: ResMut<'w, LdtkLevelManager>,
+    pub ldtk_assets: ResMut<'w, LdtkAssets>,
+    pub patterns: ResMut<'w, LdtkPatterns>,
+    pub entity_registry: Option<NonSend<'w, LdtkEntityRegistry>>,
+    pub entity_tag_registry: Option<NonSend<'w, LdtkEntityTagRegistry>>,
+    pub ldtk_events: EventWriter<'w, LdtkEvent>,
+}
+
 pub fn load_ldtk_json(
     mut commands: Commands,
-    loader_query: Query<(Entity, &LdtkLoader)>,
-    asset_server: Res<AssetServer>,
-    entity_registry: Option<NonSend<LdtkEntityRegistry>>,
-    entity_tag_registry: Option<NonSend<LdtkEntityTagRegistry>>,
+    loader_query: Query<(Entity, &LdtkLoader), Without<LdtkUnloader>>,
+    mut params: LdtkLevelLoadParams,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    mut ldtk_events: EventWriter<LdtkEvent>,
-    config: Res<LdtkLoadConfig>,
-    mut manager: ResMut<LdtkLevelManager>,
-    addi_layers: Res<LdtkAdditionalLayers>,
-    mut ldtk_assets: ResMut<LdtkAssets>,
     mut entity_material_assets: ResMut<Assets<LdtkEntityMaterial>>,
     mut mesh_assets: ResMut<Assets<Mesh>>,
-    mut patterns: ResMut<LdtkPatterns>,
-    global_entities: Res<LdtkGlobalEntityRegistry>,
+    budget: Option<ResMut<StreamingBudget>>,
 ) {
-    for (entity, loader) in loader_query.iter() {
-        let entity_registry = entity_registry.as_ref().map(|r| &**r);
-        let entity_tag_registry = entity_tag_registry.as_ref().map(|r| &**r);
+    // Wait until the LDtk file and the external level files are loaded.
+    let mut loaders = loader_query
+        .iter()
+        .filter(|(_, loader)| {
+            params.manager.is_initialized()
+                && params.manager.json_handle.as_ref() == Some(&loader.json)
+                && params
+                    .manager
+                    .request_level(&loader.level, &params.config, &params.asset_server)
+        })
+        .collect::<Vec<_>>();
+    // Async levels are limited when they are applied in `ldtk_level_task_applier`.
+    let mut budget = budget.filter(|_| !params.config.async_loading);
+    if let Some(budget) = budget.as_ref().filter(|b| b.focus().is_some()) {
+        loaders.sort_by_cached_key(|(_, loader)| {
+            level_stats(&params.manager, loader)
+                .map(|(center, _)| FloatOrd(budget.priority(center)))
+                .unwrap_or(FloatOrd(f32::MAX))
+        });
+    }
 
-        ldtk_assets.initialize(
-            &config,
-            &manager,
-            &asset_server,
+    for (entity, loader) in loaders {
+        // Incrementally spawned levels are limited in `ldtk_level_spawner`.
+        if let Some(budget) = budget
+            .as_mut()
+            .filter(|_| !params.config.is_incremental(loader.mode))
+        {
+            let tile_count = level_stats(&params.manager, loader)
+                .map(|(_, count)| count)
+                .unwrap_or_default();
+            if !budget.try_spend_tiles(tile_count) {
+                break;
+            }
+        }
+
+        params.ldtk_assets.initialize(
+            &params.config,
+            &params.manager,
+            &params.asset_server,
             &mut atlas_layouts,
             &mut entity_material_assets,
             &mut mesh_assets,
         );
 
-        load_levels(
+        if params.config.async_loading {
+            spawn_level_task(&mut commands, &params, loader, entity);
+        } else {
+            load_levels(&mut commands, &mut params, loader, entity);
+        }
+
+        commands.entity(entity).remove::<LdtkLoader>();
+    }
+}
+
+/// A level that is being built on the async compute pool.
+#[derive(Component)]
+pub struct LdtkLevelTask {
+    task: Task<(LdtkLayers, Level)>,
+    center: Vec2,
+    tile_count: usize,
+    mode: LdtkLoaderMode,
+}
+
+pub fn ldtk_level_task_applier(
+    mut commands: Commands,
+    mut tasks_query: Query<(Entity, &mut LdtkLevelTask), Without<LdtkUnloader>>,
+    params: LdtkLevelLoadParams,
+    mut budget: Option<ResMut<StreamingBudget>>,
+) {
+    let LdtkLevelLoadParams {
+        asset_server,
+        config,
+        ldtk_assets,
+        mut patterns,
+        entity_registry,
+        entity_tag_registry,
+        mut ldtk_events,
+        ..
+    } = params;
+    let entity_registry = entity_registry.as_deref();
+    let entity_tag_registry = entity_tag_registry.as_deref();
+
+    let mut finished = tasks_query
+        .iter_mut()
+        .filter(|(_, task)| task.task.is_finished())
+        .collect::<Vec<_>>();
+    if let Some(budget) = budget.as_ref().filter(|b| b.focus().is_some()) {
+        finished.sort_by_cached_key(|(_, task)| FloatOrd(budget.priority(task.center)));
+    }
+
+    for (entity, mut task) in finished {
+        if config.is_incremental(task.mode) {
+            let (mut ldtk_layers, level) = bevy::tasks::block_on(&mut task.task);
+
+            commands.entity(entity).remove::<LdtkLevelTask>();
+            ldtk_layers.apply_entities(
+                &mut commands,
+                entity_registry.unwrap_or(&LdtkEntityRegistry::default()),
+                entity_tag_registry.unwrap_or(&LdtkEntityTagRegistry::default()),
+                &config,
+                &ldtk_assets,
+                &asset_server,
+            );
+            commands.entity(entity).insert((
+                LdtkLevelLoadProgress {
+                    built: true,
+                    ..LdtkLevelLoadProgress::new(ldtk_layers.remaining_layers())
+                },
+                LdtkLevelSpawner {
+                    layers: ldtk_layers,
+                    level,
+                },
+            ));
+            continue;
+        }
+
+        if let Some(budget) = budget.as_mut() {
+            if !budget.try_spend_tiles(task.tile_count) {
+                break;
+            }
+        }
+
+        let (mut ldtk_layers, level) = bevy::tasks::block_on(&mut task.task);
+
+        commands
+            .entity(entity)
+            .remove::<(LdtkLevelTask, LdtkLevelLoadProgress)>();
+        ldtk_layers.apply_all(
             &mut commands,
+            &mut patterns,
+            &level,
+            entity_registry.unwrap_or(&LdtkEntityRegistry::default()),
+            entity_tag_registry.unwrap_or(&LdtkEntityTagRegistry::default()),
             &config,
-            &mut manager,
-            &addi_layers,
-            loader,
+            &ldtk_assets,
             &asset_server,
-            &entity_registry.unwrap_or(&LdtkEntityRegistry::default()),
-            &entity_tag_registry.unwrap_or(&LdtkEntityTagRegistry::default()),
-            entity,
-            &mut ldtk_events,
-            &mut ldtk_assets,
-            &mut patterns,
-            &global_entities,
         );
 
-        commands.entity(entity).remove::<LdtkLoader>();
+        send_level_loaded(&mut ldtk_events, &level, &ldtk_layers);
     }
 }
 
+/// A level whose layers are being spawned over multiple frames.
+/// See `LdtkLoadConfig::incremental_spawning`.
+#[derive(Component)]
+pub struct LdtkLevelSpawner {
+    layers: LdtkLayers,
+    level: Level,
+}
+
+pub fn ldtk_level_spawner(
+    mut commands: Commands,
+    mut spawners_query: Query<
+        (Entity, &mut LdtkLevelSpawner, &mut LdtkLevelLoadProgress),
+        Without<LdtkUnloader>,
+    >,
+    mut ldtk_events: EventWriter<LdtkEvent>,
+    config: Res<LdtkLoadConfig>,
+    mut budget: Option<ResMut<StreamingBudget>>,
+) {
+    let mut spawners = spawners_query.iter_mut().collect::<Vec<_>>();
+    if let Some(budget) = budget.as_ref().filter(|b| b.focus().is_some()) {
+        spawners.sort_by_cached_key(|(_, spawner, _)| {
+            FloatOrd(budget.priority(spawner.layers.translation))
+        });
+    }
+
+    for (entity, mut spawner, mut progress) in spawners {
+        let spawner = &mut *spawner;
+
+        while let Some(tile_count) = spawner.layers.next_layer_tile_count() {
+            if let Some(budget) = budget.as_mut() {
+                if !budget.try_spend_tiles(tile_count) {
+                    break;
+                }
+            }
+
+            spawner
+                .layers
+                .apply_next_layer(&mut commands, &spawner.level);
+            progress.spawned_layers += 1;
+
+            // Without a budget, spawn one layer per frame.
+            if budget.is_none() {
+                break;
+            }
+        }
+
+        if spawner.layers.remaining_layers() > 0 {
+            continue;
+        }
+
+        spawner
+            .layers
+            .finish(&mut commands, &spawner.level, &config);
+        commands
+            .entity(entity)
+            .remove::<(LdtkLevelSpawner, LdtkLevelLoadProgress)>();
+
+        send_level_loaded(&mut ldtk_events, &spawner.level, &spawner.layers);
+    }
+}
+
+fn spawn_level_task(
+    commands: &mut Commands,
+    params: &LdtkLevelLoadParams,
+    loader: &LdtkLoader,
+    level_entity: Entity,
+) {
+    let LdtkLevelLoadParams {
+        asset_server,
+        config,
+        addi_layers,
+        global_entities,
+        manager,
+        ldtk_assets,
+        patterns,
+        ..
+    } = params;
+    let ldtk_data = manager.get_cached_data();
+
+    let Some((level_index, level)) = manager.get_level(&loader.level) else {
+        return;
+    };
+
+    let translation = loader
+        .trans_ovrd
+        .unwrap_or_else(|| get_level_translation(ldtk_data, level_index));
+
+    let level_px = UVec2 {
+        x: level.px_wid as u32,
+        y: level.px_hei as u32,
+    };
+
+    let background = load_background(level, level_px, asset_server, config);
+    commands
+        .entity(level_entity)
+        .insert(SpatialBundle::from_transform(Transform::from_translation(
+            translation.extend(0.),
+        )));
+    if let Some(parent) = loader.parent {
+        commands.entity(level_entity).set_parent(parent);
+    }
+
+    let ldtk_layers = LdtkLayers::new(
+        level_entity,
+        level.layer_instances.len(),
+        ldtk_assets,
+        translation,
+        config.level_z_index(level),
+        loader.mode,
+        background,
+    );
+
+    let (center, tile_count) = level_stats(manager, loader).unwrap_or_default();
+    let mode = loader.mode;
+    let total_layers = level.layer_instances.len();
+    let level = level.clone();
+    let config = LdtkLoadConfig::clone(config);
+    let addi_layers = LdtkAdditionalLayers::clone(addi_layers);
+    let loader = loader.clone();
+    let global_entities = LdtkGlobalEntityRegistry::clone(global_entities);
+    let pattern_size = patterns.pattern_size;
+
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let mut ldtk_layers = ldtk_layers;
+        // Only the pattern size is used when building the layers.
+        let patterns = LdtkPatterns {
+            pattern_size,
+            ..Default::default()
+        };
+
+        build_layers(
+            &level,
+            &mut ldtk_layers,
+            translation,
+            &config,
+            &addi_layers,
+            &global_entities,
+            &patterns,
+            &loader,
+        );
+
+        (ldtk_layers, level)
+    });
+
+    commands.entity(level_entity).insert((
+        LdtkLevelTask {
+            task,
+            center,
+            tile_count,
+            mode,
+        },
+        LdtkLevelLoadProgress::new(total_layers),
+    ));
+}
+
 fn load_levels(
     commands: &mut Commands,
-    config: &LdtkLoadConfig,
-    manager: &mut LdtkLevelManager,
-    addi_layers: &LdtkAdditionalLayers,
+    params: &mut LdtkLevelLoadParams,
     loader: &LdtkLoader,
-    asset_server: &AssetServer,
-    entity_registry: &LdtkEntityRegistry,
-    entity_tag_registry: &LdtkEntityTagRegistry,
     level_entity: Entity,
-    ldtk_events: &mut EventWriter<LdtkEvent>,
-    ldtk_assets: &mut LdtkAssets,
-    patterns: &mut LdtkPatterns,
-    global_entities: &LdtkGlobalEntityRegistry,
 ) {
+    let LdtkLevelLoadParams {
+        asset_server,
+        config,
+        addi_layers,
+        global_entities,
+        manager,
+        ldtk_assets,
+        patterns,
+        entity_registry,
+        entity_tag_registry,
+        ldtk_events,
+    } = params;
     let ldtk_data = manager.get_cached_data();
 
-    let Some((level_index, level)) = ldtk_data
-        .levels
-        .iter()
-        .enumerate()
-        .find(|(_, level)| level.identifier == loader.level)
-    else {
+    let Some((level_index, level)) = manager.get_level(&loader.level) else {
         return;
     };
 
@@ -302,19 +1155,144 @@ fn load_levels(
         y: level.px_hei as u32,
     };
 
-    let background = load_background(level, translation, level_px, asset_server, config);
+    let background = load_background(level, level_px, asset_server, config);
+    commands
+        .entity(level_entity)
+        .insert(SpatialBundle::from_transform(Transform::from_translation(
+            translation.extend(0.),
+        )));
+    if let Some(parent) = loader.parent {
+        commands.entity(level_entity).set_parent(parent);
+    }
 
     let mut ldtk_layers = LdtkLayers::new(
         level_entity,
         level.layer_instances.len(),
         &ldtk_assets,
         translation,
-        config.z_index,
+        config.level_z_index(level),
         loader.mode,
         background,
     );
 
+    build_layers(
+        level,
+        &mut ldtk_layers,
+        translation,
+        config,
+        addi_layers,
+        global_entities,
+        patterns,
+        loader,
+    );
+
+    if config.is_incremental(loader.mode) {
+        ldtk_layers.apply_entities(
+            commands,
+            entity_registry
+                .as_deref()
+                .unwrap_or(&LdtkEntityRegistry::default()),
+            entity_tag_registry
+                .as_deref()
+                .unwrap_or(&LdtkEntityTagRegistry::default()),
+            config,
+            ldtk_assets,
+            asset_server,
+        );
+        commands.entity(level_entity).insert((
+            LdtkLevelLoadProgress {
+                built: true,
+                ..LdtkLevelLoadProgress::new(ldtk_layers.remaining_layers())
+            },
+            LdtkLevelSpawner {
+                layers: ldtk_layers,
+                level: level.clone(),
+            },
+        ));
+        return;
+    }
+
+    ldtk_layers.apply_all(
+        commands,
+        patterns,
+        level,
+        entity_registry
+            .as_deref()
+            .unwrap_or(&LdtkEntityRegistry::default()),
+        entity_tag_registry
+            .as_deref()
+            .unwrap_or(&LdtkEntityTagRegistry::default()),
+        config,
+        ldtk_assets,
+        asset_server,
+    );
+
+    send_level_loaded(ldtk_events, level, &ldtk_layers);
+}
+
+fn send_level_unloaded(
+    ldtk_events: &mut EventWriter<LdtkEvent>,
+    entity: Entity,
+    level: &LdtkLoadedLevel,
+    iid: &LevelIid,
+    global_entities: &LdtkGlobalEntityRegistry,
+) {
+    let event = LevelEvent {
+        identifier: level.identifier.clone(),
+        iid: iid.0.clone(),
+        entity,
+    };
+    ldtk_events.send(LdtkEvent::LevelUnloaded(event.clone()));
+
+    level
+        .entities
+        .iter()
+        .filter(|(iid, _)| global_entities.contains(iid))
+        .for_each(|(iid, entity)| {
+            ldtk_events.send(LdtkEvent::GlobalEntityPreserved(GlobalEntityEvent {
+                level: event.clone(),
+                iid: iid.clone(),
+                entity: *entity,
+            }));
+        });
+}
+
+fn send_level_loaded(
+    ldtk_events: &mut EventWriter<LdtkEvent>,
+    level: &Level,
+    ldtk_layers: &LdtkLayers,
+) {
+    let event = LevelEvent {
+        identifier: level.identifier.clone(),
+        iid: level.iid.clone(),
+        entity: ldtk_layers.level_entity,
+    };
+    ldtk_events.send(LdtkEvent::LevelLoaded(event.clone()));
+
+    if ldtk_layers.ty == LdtkLoaderMode::Tilemap {
+        ldtk_events.send(LdtkEvent::LevelSpawned(LevelSpawnedEvent {
+            level: event,
+            layers: ldtk_layers.spawned_layers.clone(),
+            entities: ldtk_layers.spawned_entities.clone(),
+        }));
+    }
+}
+
+pub(crate) fn build_layers(
+    level: &Level,
+    ldtk_layers: &mut LdtkLayers,
+    translation: Vec2,
+    config: &LdtkLoadConfig,
+    #[allow(unused_variables)] addi_layers: &LdtkAdditionalLayers,
+    global_entities: &LdtkGlobalEntityRegistry,
+    patterns: &LdtkPatterns,
+    loader: &LdtkLoader,
+) {
     for (layer_index, layer) in level.layer_instances.iter().enumerate() {
+        if !config.should_load_layer(&layer.identifier) {
+            continue;
+        }
+
         #[cfg(feature = "algorithm")]
         if let Some(path) = addi_layers.path_layer.as_ref() {
             if layer.identifier == path.identifier {
@@ -342,58 +1320,89 @@ fn load_levels(
         load_layer(
             layer_index,
             layer,
-            &mut ldtk_layers,
+            ldtk_layers,
             translation,
+            config.level_z_index(level),
             config,
-            &global_entities,
+            global_entities,
             patterns,
             loader,
         );
     }
-
-    ldtk_layers.apply_all(
-        commands,
-        patterns,
-        level,
-        entity_registry,
-        entity_tag_registry,
-        config,
-        ldtk_assets,
-        asset_server,
-    );
-
-    ldtk_events.send(LdtkEvent::LevelLoaded(LevelEvent {
-        identifier: level.identifier.clone(),
-        iid: level.iid.clone(),
-    }));
 }
 
 fn load_background(
     level: &Level,
-    translation: Vec2,
     level_px: UVec2,
     asset_server: &AssetServer,
     config: &LdtkLoadConfig,
-) -> SpriteBundle {
-    let texture = level
-        .bg_rel_path
-        .as_ref()
-        .map(|path| asset_server.load(Path::new(&config.asset_path_prefix).join(path)));
+) -> LdtkBackground {
+    let level_size = level_px.as_vec2();
 
-    SpriteBundle {
+    let color = SpriteBundle {
         sprite: Sprite {
             color: level.bg_color.into(),
-            custom_size: Some(level_px.as_vec2()),
+            custom_size: Some(level_size),
             ..Default::default()
         },
-        texture: texture.unwrap_or_default(),
         transform: Transform::from_xyz(
-            level_px.x as f32 / 2. + translation.x,
-            -(level_px.y as f32) / 2. + translation.y,
-            config.z_index as f32 - level.layer_instances.len() as f32 - 1.,
+            level_size.x / 2.,
+            -level_size.y / 2.,
+            config.level_z_index(level) as f32 - level.layer_instances.len() as f32 - 1.,
         ),
         ..Default::default()
-    }
+    };
+
+    let image = level
+        .bg_rel_path
+        .as_ref()
+        .zip(level.bg_pos.as_ref())
+        .map(|(path, pos)| {
+            let texture = asset_server.load(Path::new(&config.asset_path_prefix).join(path));
+
+            // The image is a child of the color sprite, which is at the center of the level.
+            if level.bg_pos_mode == Some(BgPos::Repeat) {
+                return (
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(level_size),
+                            ..Default::default()
+                        },
+                        texture,
+                        transform: Transform::from_xyz(0., 0., 0.5),
+                        ..Default::default()
+                    },
+                    Some(ImageScaleMode::Tiled {
+                        tile_x: true,
+                        tile_y: true,
+                        stretch_value: 1.,
+                    }),
+                );
+            }
+
+            let [crop_x, crop_y, crop_w, crop_h] = pos.crop_rect;
+            let top_left = Vec2::new(pos.top_left_px[0] as f32, pos.top_left_px[1] as f32);
+            (
+                SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::new(crop_w, crop_h) * Vec2::from(pos.scale)),
+                        rect: Some(Rect::new(crop_x, crop_y, crop_x + crop_w, crop_y + crop_h)),
+                        anchor: Anchor::TopLeft,
+                        ..Default::default()
+                    },
+                    texture,
+                    transform: Transform::from_xyz(
+                        top_left.x - level_size.x / 2.,
+                        level_size.y / 2. - top_left.y,
+                        0.5,
+                    ),
+                    ..Default::default()
+                },
+                None,
+            )
+        });
+
+    LdtkBackground { color, image }
 }
 
 fn load_layer(
@@ -401,42 +1410,105 @@ fn load_layer(
     layer: &LayerInstance,
     ldtk_layers: &mut LdtkLayers,
     translation: Vec2,
+    z_index: i32,
     config: &LdtkLoadConfig,
     global_entities: &LdtkGlobalEntityRegistry,
     patterns: &LdtkPatterns,
     loader: &LdtkLoader,
 ) {
     match layer.ty {
+        LayerType::IntGrid if layer.auto_layer_tiles.is_empty() => {
+            layer
+                .int_grid_csv
+                .iter()
+                .enumerate()
+                .filter_map(|(cell, value)| {
+                    config
+                        .int_grid_palette
+                        .get(value)
+                        .map(|color| (cell, Vec4::from(color.as_rgba_f32())))
+                })
+                .for_each(|(cell, color)| {
+                    ldtk_layers.set_int_grid_cell(
+                        layer_index,
+                        layer,
+                        cell,
+                        color,
+                        patterns,
+                        &loader.mode,
+                    );
+                });
+        }
         LayerType::IntGrid | LayerType::AutoLayer => {
             layer.auto_layer_tiles.iter().for_each(|tile| {
                 ldtk_layers.set_tile(layer_index, layer, tile, config, patterns, &loader.mode);
             });
         }
         LayerType::Entities => {
-            for (order, entity_instance) in layer.entity_instances.iter().enumerate() {
-                let iid = EntityIid(entity_instance.iid.clone());
-                if global_entities.contains(&iid) {
-                    continue;
-                }
+            let instances = layer
+                .entity_instances
+                .iter()
+                .filter(|entity_instance| config.should_load_entity(&entity_instance.identifier))
+                .enumerate()
+                .collect::<Vec<_>>();
+            let count = instances.len();
+            let layer_offset = layer.total_offset();
+            let layer_z = z_index as f32 - layer_index as f32;
+            let z_strategy = config.entity_z_strategies.get(&layer.identifier);
 
-                let fields = entity_instance
-                    .field_instances
-                    .iter()
-                    .map(|field| (field.identifier.clone(), field.clone()))
-                    .collect();
-                let packed_entity = PackedLdtkEntity {
-                    instance: entity_instance.clone(),
-                    fields,
-                    iid,
-                    transform: LdtkTempTransform {
-                        level_translation: translation,
-                        z_index: config.z_index as f32
-                            - layer_index as f32
-                            - (1. - (order as f32 / layer.entity_instances.len() as f32)),
-                    },
-                };
-                ldtk_layers.set_entity(packed_entity);
-            }
+            // Cloning the instances and fields is the heavy part, so do it in parallel.
+            instances
+                .par_chunk_map(ComputeTaskPool::get(), ENTITY_BATCH_SIZE, |batch| {
+                    batch
+                        .iter()
+                        .filter_map(|(order, entity_instance)| {
+                            let iid = EntityIid(entity_instance.iid.clone());
+                            let global_entity = global_entities.get(&iid);
+                            if global_entity.is_some() && !config.refresh_global_entities {
+                                return None;
+                            }
+
+                            let fields = entity_instance
+                                .field_instances
+                                .iter()
+                                .map(|field| (field.identifier.clone(), field.clone()))
+                                .collect::<HashMap<_, _>>();
+                            if global_entity.is_some()
+                                && !global_entities.is_outdated(&iid, &fields)
+                            {
+                                return None;
+                            }
+
+                            let packed_entity = PackedLdtkEntity {
+                                instance: (*entity_instance).clone(),
+                                fields,
+                                iid,
+                                transform: LdtkTempTransform {
+                                    level_translation: translation,
+                                    local_translation: entity_instance.pivot_translation()
+                                        + layer_offset,
+                                    z_index: z_strategy.z_index(
+                                        entity_instance,
+                                        layer,
+                                        layer_z,
+                                        *order,
+                                        count,
+                                    ),
+                                },
+                            };
+                            Some((global_entity, packed_entity))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .into_iter()
+                .flatten()
+                .for_each(|(global_entity, packed_entity)| {
+                    if let Some(global_entity) = global_entity {
+                        ldtk_layers.refresh_entity(global_entity, packed_entity);
+                    } else {
+                        ldtk_layers.set_entity(packed_entity);
+                    }
+                });
         }
         LayerType::Tiles => {
             layer.grid_tiles.iter().for_each(|tile| {
@@ -446,6 +1518,23 @@ fn load_layer(
     }
 }
 
+/// The world space center and the tile count of the level the loader is going to load.
+fn level_stats(manager: &LdtkLevelManager, loader: &LdtkLoader) -> Option<(Vec2, usize)> {
+    let (level_index, level) = manager.get_level(&loader.level)?;
+
+    let translation = loader
+        .trans_ovrd
+        .unwrap_or_else(|| get_level_translation(manager.get_cached_data(), level_index));
+    let center = translation + Vec2::new(level.px_wid as f32, -level.px_hei as f32) / 2.;
+    let tile_count = level
+        .layer_instances
+        .iter()
+        .map(|layer| layer.grid_tiles.len() + layer.auto_layer_tiles.len())
+        .sum();
+
+    Some((center, tile_count))
+}
+
 fn get_level_translation(ldtk_data: &LdtkJson, index: usize) -> Vec2 {
     let level = &ldtk_data.levels[index];
     match ldtk_data.world_layout.unwrap() {
@@ -456,3 +1545,60 @@ fn get_level_translation(ldtk_data: &LdtkJson, index: usize) -> Vec2 {
         WorldLayout::LinearHorizontal | WorldLayout::LinearVertical => Vec2::ZERO,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::{event::Events, system::RunSystemOnce, world::World},
+        math::Vec2,
+        tasks::{AsyncComputeTaskPool, TaskPool},
+    };
+
+    use crate::ldtk::{
+        components::{LdtkLoader, LdtkLoaderMode, LdtkUnloader},
+        events::LdtkEvent,
+        resources::LdtkGlobalEntityRegistry,
+    };
+
+    use super::{unload_ldtk_level, LdtkLevelTask};
+
+    #[test]
+    fn test_unload_pending_level() {
+        let mut world = World::new();
+        world.init_resource::<Events<LdtkEvent>>();
+        world.init_resource::<LdtkGlobalEntityRegistry>();
+
+        let waiting = world
+            .spawn((
+                LdtkLoader {
+                    json: Default::default(),
+                    level: "Level_0".to_string(),
+                    mode: LdtkLoaderMode::Tilemap,
+                    trans_ovrd: None,
+                    parent: None,
+                },
+                LdtkUnloader,
+            ))
+            .id();
+
+        // A task that never finishes, like a level still being built.
+        let task = AsyncComputeTaskPool::get_or_init(TaskPool::default)
+            .spawn(async { std::future::pending().await });
+        let building = world
+            .spawn((
+                LdtkLevelTask {
+                    task,
+                    center: Vec2::ZERO,
+                    tile_count: 0,
+                    mode: LdtkLoaderMode::Tilemap,
+                },
+                LdtkUnloader,
+            ))
+            .id();
+
+        world.run_system_once(unload_ldtk_level);
+
+        assert!(world.get_entity(waiting).is_none());
+        assert!(world.get_entity(building).is_none());
+    }
+}
@@ -1,32 +1,52 @@
-use std::{fs::read_to_string, path::Path};
+use std::{path::Path, sync::Arc};
 
 use bevy::{
     asset::{AssetServer, Assets, Handle},
     ecs::{
         entity::Entity,
-        system::{Commands, Resource},
+        system::{Commands, EntityCommands, Resource},
+        world::World,
     },
-    log::error,
+    log::{error, warn},
     math::{IVec2, UVec2, Vec2},
     reflect::Reflect,
     render::{
+        color::Color,
         mesh::{Indices, Mesh},
         render_asset::RenderAssetUsages,
         render_resource::{FilterMode, PrimitiveTopology},
     },
-    sprite::{Mesh2dHandle, SpriteBundle, TextureAtlasLayout},
-    utils::HashMap,
+    sprite::{Mesh2dHandle, TextureAtlasLayout},
+    transform::components::Transform,
+    utils::{HashMap, HashSet},
 };
 
-use crate::{serializing::pattern::PatternsLayer, tilemap::tile::RawTileAnimation};
+use crate::{
+    render::material::{
+        StandardTilemapMaterial, TilemapMaterial, WaitForStandardMaterialReplacement,
+    },
+    serializing::pattern::PatternsLayer,
+    tilemap::{
+        buffers::TileBuilderBuffer,
+        tile::{RawTileAnimation, TileTexture},
+    },
+};
 use crate::{
     serializing::pattern::{PackedPatternLayers, TilemapPattern},
     tilemap::map::{TilemapRotation, TilemapTexture, TilemapTextureDescriptor},
 };
 
 use super::{
-    components::{EntityIid, LayerIid},
-    json::{definitions::EntityDef, EntityRef, LdtkJson, TocInstance},
+    auto_rules::LdtkAutoRules,
+    components::{
+        EntityIid, GlobalEntity, LayerIid, LdtkBackground, LdtkLevelRoot, LdtkTileData, LevelIid,
+    },
+    json::{
+        definitions::{Definitions, EntityDef, LayerType},
+        field::FieldInstance,
+        level::{EntityInstance, LayerInstance, Level},
+        EntityRef, LdtkColor, LdtkJson, TocInstance, WorldLayout,
+    },
     sprite::{AtlasRect, LdtkEntityMaterial},
     LdtkLoader, LdtkLoaderMode, LdtkUnloader,
 };
@@ -42,9 +62,15 @@ pub struct LdtkPatterns {
         Option<LayerIid>,
     )>,
     #[reflect(ignore)]
-    pub backgrounds: Vec<Option<SpriteBundle>>,
+    pub backgrounds: Vec<Option<LdtkBackground>>,
     pub idents: Vec<String>,
     pub idents_to_index: HashMap<String, usize>,
+    /// The seams of each pattern in order: up, right, left, down.
+    ///
+    /// Two patterns can only be placed next to each other if the touching seams are the same.
+    pub seams: Vec<Option<[String; 4]>>,
+    /// The user tags of each pattern, by identifier.
+    pub tags: HashMap<String, Vec<String>>,
 }
 
 impl LdtkPatterns {
@@ -89,7 +115,109 @@ impl LdtkPatterns {
         layer[pattern_index] = Some(pattern);
     }
 
-    pub fn add_background(&mut self, identifier: &str, background: SpriteBundle) {
+    /// Get the pattern of the level `identifier` on the layer `layer_index`.
+    ///
+    /// Use `TilemapPattern::stamp` to place it into a live tilemap.
+    pub fn get_pattern(&self, layer_index: usize, identifier: &str) -> Option<&TilemapPattern> {
+        let pattern_index = *self.idents_to_index.get(identifier)?;
+        self.patterns
+            .get(layer_index)?
+            .0
+            .get(pattern_index)?
+            .as_ref()
+    }
+
+    /// Get the texture of the patterns on the layer `layer_index`.
+    #[inline]
+    pub fn get_layer_texture(&self, layer_index: usize) -> Option<&TilemapTexture> {
+        self.patterns.get(layer_index)?.1.as_ref()
+    }
+
+    /// The identifiers of all the patterns, which are the identifiers of the levels.
+    #[inline]
+    pub fn identifiers(&self) -> impl Iterator<Item = &str> {
+        self.idents.iter().map(|ident| ident.as_str())
+    }
+
+    #[inline]
+    pub fn layer_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Find the index of the layer by its identifier in LDtk, to be used in `get_pattern`.
+    pub fn layer_index(&self, layer_identifier: &str) -> Option<usize> {
+        self.patterns.iter().position(|(layer, _, _)| {
+            layer
+                .iter()
+                .flatten()
+                .any(|pattern| pattern.label.as_deref() == Some(layer_identifier))
+        })
+    }
+
+    /// Get the label of the pattern, which is the identifier of its layer.
+    #[inline]
+    pub fn get_label(&self, layer_index: usize, identifier: &str) -> Option<&str> {
+        self.get_pattern(layer_index, identifier)?.label.as_deref()
+    }
+
+    /// Get the size of the pattern in tiles.
+    ///
+    /// This is the size of the layer in LDtk, so it can be different from
+    /// `pattern_size` if the layers have different grid sizes.
+    #[inline]
+    pub fn get_pattern_size(&self, layer_index: usize, identifier: &str) -> Option<UVec2> {
+        self.get_pattern(layer_index, identifier)
+            .map(|pattern| pattern.tiles.aabb().size().as_uvec2())
+    }
+
+    /// Clone the tiles of the pattern, for example to place them with `TilemapStorage::fill_with_buffer`.
+    #[inline]
+    pub fn clone_tiles(&self, layer_index: usize, identifier: &str) -> Option<TileBuilderBuffer> {
+        self.get_pattern(layer_index, identifier)
+            .map(|pattern| pattern.tiles.clone())
+    }
+
+    #[inline]
+    pub fn get_background(&self, identifier: &str) -> Option<&LdtkBackground> {
+        let pattern_index = *self.idents_to_index.get(identifier)?;
+        self.backgrounds.get(pattern_index)?.as_ref()
+    }
+
+    /// Tag the pattern, like `"boss_room"` or `"dead_end"`.
+    pub fn add_tag(&mut self, identifier: &str, tag: impl Into<String>) {
+        let tag = tag.into();
+        let tags = self.tags.entry(identifier.to_string()).or_default();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    pub fn remove_tag(&mut self, identifier: &str, tag: &str) {
+        if let Some(tags) = self.tags.get_mut(identifier) {
+            tags.retain(|t| t != tag);
+        }
+    }
+
+    #[inline]
+    pub fn get_tags(&self, identifier: &str) -> &[String] {
+        self.tags
+            .get(identifier)
+            .map(|tags| tags.as_slice())
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn has_tag(&self, identifier: &str, tag: &str) -> bool {
+        self.get_tags(identifier).iter().any(|t| t == tag)
+    }
+
+    /// The identifiers of the patterns with the tag, in the order of the levels.
+    pub fn with_tag<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a str> {
+        self.identifiers()
+            .filter(move |identifier| self.has_tag(identifier, tag))
+    }
+
+    pub fn add_background(&mut self, identifier: &str, background: LdtkBackground) {
         let pattern_index = self.idents_to_index[identifier];
         if pattern_index >= self.backgrounds.len() {
             self.backgrounds.resize(pattern_index + 1, None);
@@ -98,6 +226,114 @@ impl LdtkPatterns {
         self.backgrounds[pattern_index] = Some(background);
     }
 
+    /// Set the seams of a pattern manually. The order is up, right, left, down.
+    ///
+    /// You can use any tag you like, for example `"corridor"` or `"wall"`.
+    pub fn set_seams(&mut self, identifier: &str, seams: [String; 4]) {
+        let Some(&pattern_index) = self.idents_to_index.get(identifier) else {
+            error!(
+                "Failed to set seams! Pattern {} does not exist.",
+                identifier
+            );
+            return;
+        };
+        if pattern_index >= self.seams.len() {
+            self.seams.resize(pattern_index + 1, None);
+        }
+
+        self.seams[pattern_index] = Some(seams);
+    }
+
+    /// Derive the seams from the border tiles of the patterns on the given layer.
+    /// Patterns that already have seams will be skipped.
+    ///
+    /// **Notice**: Call this after all the patterns are loaded.
+    pub fn derive_seams_from_layer(&mut self, layer_index: usize) {
+        let Some((layer, _, _)) = self.patterns.get(layer_index) else {
+            error!(
+                "Failed to derive seams! Layer {} does not exist.",
+                layer_index
+            );
+            return;
+        };
+
+        self.seams.resize(self.idents.len(), None);
+        let size = self.pattern_size.as_ivec2();
+        // Tiles in LDtk patterns are placed from (0, -1) to (size.x - 1, -size.y).
+        let edges = [
+            (IVec2::new(0, -1), IVec2::X, size.x),
+            (IVec2::new(size.x - 1, -1), IVec2::NEG_Y, size.y),
+            (IVec2::new(0, -1), IVec2::NEG_Y, size.y),
+            (IVec2::new(0, -size.y), IVec2::X, size.x),
+        ];
+
+        layer.iter().enumerate().for_each(|(index, pattern)| {
+            let Some(pattern) = pattern else {
+                return;
+            };
+            if self.seams[index].is_some() {
+                return;
+            }
+
+            self.seams[index] = Some(edges.map(|(start, step, len)| {
+                (0..len)
+                    .map(|i| match pattern.tiles.get(start + step * i) {
+                        Some(tile) => match &tile.texture {
+                            TileTexture::Static(layers) => layers
+                                .iter()
+                                .map(|l| l.texture_index.to_string())
+                                .collect::<Vec<_>>()
+                                .join("|"),
+                            TileTexture::Animated(anim) => format!("anim{}", anim.start),
+                        },
+                        None => "_".to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }));
+        });
+    }
+
+    /// Generate the wfc rules according to the seams of the patterns.
+    ///
+    /// Use `set_seams` or `derive_seams_from_layer` to set the seams first.
+    /// Returns `None` if any of the patterns has no seams.
+    #[cfg(feature = "algorithm")]
+    pub fn generate_wfc_rules(&self) -> Option<crate::algorithm::wfc::WfcRules> {
+        let mut seams = Vec::with_capacity(self.idents.len());
+        for (i, ident) in self.idents.iter().enumerate() {
+            let Some(s) = self.seams.get(i).cloned().flatten() else {
+                error!(
+                    "Failed to generate wfc rules! Pattern {} has no seams.",
+                    ident
+                );
+                return None;
+            };
+            seams.push(s);
+        }
+
+        let raw = seams
+            .iter()
+            .map(|this| {
+                (0..4)
+                    .map(|dir| {
+                        seams
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, another)| this[dir] == another[3 - dir])
+                            .map(|(i, _)| i as u8)
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        Some(crate::algorithm::wfc::WfcRules::from_raw(
+            &raw,
+            crate::tilemap::map::TilemapType::Square,
+        ))
+    }
+
     /// Pack the patterns into a `PackedPatternLayers` for wfc.
     pub fn pack(&self) -> PackedPatternLayers {
         PackedPatternLayers::new(
@@ -140,6 +376,12 @@ pub struct LdtkAssets {
     pub(crate) meshes: HashMap<String, Mesh2dHandle>,
     /// entity iid to material handle
     pub(crate) materials: HashMap<String, Handle<LdtkEntityMaterial>>,
+    /// layer identifier to the colors of the IntGrid values in the editor.
+    ///
+    /// AutoLayer layers use the colors of their source IntGrid layer.
+    pub(crate) int_grid_colors: HashMap<String, HashMap<i32, Color>>,
+    /// (tileset uid, tile id) to the animation in the custom data of the tile
+    pub(crate) tile_animations: HashMap<(i32, u32), RawTileAnimation>,
 }
 
 impl LdtkAssets {
@@ -163,6 +405,20 @@ impl LdtkAssets {
         self.materials.get(iid).unwrap().clone()
     }
 
+    /// Get the colors of the IntGrid values of the layer, as they are shown in the editor.
+    #[inline]
+    pub fn get_int_grid_colors(&self, layer: &str) -> Option<&HashMap<i32, Color>> {
+        self.int_grid_colors.get(layer)
+    }
+
+    #[inline]
+    pub fn get_int_grid_color(&self, layer: &str, value: i32) -> Option<Color> {
+        self.int_grid_colors
+            .get(layer)
+            .and_then(|c| c.get(&value))
+            .copied()
+    }
+
     /// Initialize the assets.
     ///
     /// You need to call this after you changed something like the size of an entity,
@@ -179,6 +435,64 @@ impl LdtkAssets {
         self.associated_file = config.file_path.clone();
         self.load_texture(config, manager, asset_server, atlas_layouts);
         self.load_entities(config, manager, material_assets, mesh_assets);
+        self.load_int_grid_colors(manager);
+        self.load_tile_animations(config, manager);
+    }
+
+    fn load_tile_animations(&mut self, config: &LdtkLoadConfig, manager: &LdtkLevelManager) {
+        self.tile_animations.clear();
+        if !config.custom_data_animations {
+            return;
+        }
+
+        manager
+            .get_cached_data()
+            .defs
+            .tilesets
+            .iter()
+            .for_each(|tileset| {
+                tileset.custom_data.iter().for_each(|data| {
+                    match LdtkTileData::parse_animation(&data.data) {
+                        Ok(Some(animation)) => {
+                            self.tile_animations
+                                .insert((tileset.uid, data.tile_id as u32), animation);
+                        }
+                        Ok(None) => {}
+                        Err(e) => warn!(
+                            "Failed to parse the animation of tile {} in tileset {}: {}",
+                            data.tile_id, tileset.identifier, e
+                        ),
+                    }
+                });
+            });
+    }
+
+    /// Get the animation imported from the custom data of the tile.
+    #[inline]
+    pub fn get_tile_animation(&self, tileset_uid: i32, tile_id: u32) -> Option<&RawTileAnimation> {
+        self.tile_animations.get(&(tileset_uid, tile_id))
+    }
+
+    fn load_int_grid_colors(&mut self, manager: &LdtkLevelManager) {
+        let layers = &manager.get_cached_data().defs.layers;
+        self.int_grid_colors = layers
+            .iter()
+            .filter_map(|layer| {
+                let source = match layer.ty {
+                    LayerType::IntGrid => layer,
+                    LayerType::AutoLayer => layers
+                        .iter()
+                        .find(|l| Some(l.uid) == layer.auto_source_layer_def_uid)?,
+                    _ => return None,
+                };
+                let colors = source
+                    .int_grid_values
+                    .iter()
+                    .map(|v| (v.value, LdtkColor::from(v.color.clone()).into()))
+                    .collect();
+                Some((layer.identifier.clone(), colors))
+            })
+            .collect();
     }
 
     fn load_texture(
@@ -206,11 +520,7 @@ impl LdtkAssets {
                 },
                 filter_mode: config.filter_mode,
             };
-            let texture = TilemapTexture {
-                texture,
-                desc,
-                rotation: TilemapRotation::None,
-            };
+            let texture = TilemapTexture::new(texture, desc, TilemapRotation::None);
 
             self.tilesets.insert(tileset.uid, texture.clone());
             self.atlas_handles
@@ -231,9 +541,8 @@ impl LdtkAssets {
                 .insert(entity.identifier.clone(), entity.clone());
         });
 
-        ldtk_data
-            .levels
-            .iter()
+        manager
+            .iter_levels()
             .map(|level| level.layer_instances.iter())
             .flatten()
             .map(|layer| layer.entity_instances.iter())
@@ -320,10 +629,59 @@ impl LdtkWfcManager {
     }
 }
 
+/// The entities that are exported to the table of content, grouped by identifier.
+///
+/// This is refreshed when the LDtk file is loaded. Use this to find entities in the levels
+/// that are not loaded, like the markers on a minimap or the fast travel points.
 #[derive(Resource, Debug, Clone, Default)]
 pub struct LdtkTocs(pub(crate) HashMap<String, HashMap<EntityRef, TocInstance>>);
 
 impl LdtkTocs {
+    pub fn new(ldtk_json: &LdtkJson) -> Self {
+        Self(
+            ldtk_json
+                .toc
+                .iter()
+                .map(|toc| {
+                    (
+                        toc.identifier.clone(),
+                        toc.instances_data
+                            .iter()
+                            .map(|inst| (inst.iids.clone(), inst.clone()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Iterate over the instances of the entity with their world positions.
+    /// See `TocInstance::world_pos`.
+    pub fn iter_world_pos(&self, identifier: &str) -> impl Iterator<Item = (&TocInstance, Vec2)> {
+        self.0
+            .get(identifier)
+            .into_iter()
+            .flat_map(|instances| instances.values())
+            .map(|inst| (inst, inst.world_pos()))
+    }
+
+    /// Iterate over the instances of all the entities with their identifiers and world positions.
+    pub fn iter_all_world_pos(&self) -> impl Iterator<Item = (&str, &TocInstance, Vec2)> {
+        self.0.iter().flat_map(|(identifier, instances)| {
+            instances
+                .values()
+                .map(move |inst| (identifier.as_str(), inst, inst.world_pos()))
+        })
+    }
+
+    /// Get the instance nearest to `world_pos`, like the closest fast travel point.
+    pub fn nearest(&self, identifier: &str, world_pos: Vec2) -> Option<(&TocInstance, Vec2)> {
+        self.iter_world_pos(identifier).min_by(|(_, a), (_, b)| {
+            a.distance_squared(world_pos)
+                .total_cmp(&b.distance_squared(world_pos))
+        })
+    }
+
     pub fn get(&self, identifier: String, entity: EntityRef) -> Option<&TocInstance> {
         self.0.get(&identifier)?.get(&entity)
     }
@@ -337,7 +695,7 @@ impl LdtkTocs {
 ///
 /// This includes path layer and physics layer. Entitiles will generate these layers
 /// acoording to the LDtk file.
-#[derive(Resource, Default, Reflect)]
+#[derive(Resource, Default, Reflect, Clone)]
 pub struct LdtkAdditionalLayers {
     #[cfg(feature = "algorithm")]
     pub path_layer: Option<super::layer::path::LdtkPathLayer>,
@@ -346,8 +704,14 @@ pub struct LdtkAdditionalLayers {
 }
 
 /// Configuration for loading the LDtk file.
-#[derive(Resource, Default, Reflect)]
+#[derive(Resource, Default, Reflect, Clone)]
 pub struct LdtkLoadConfig {
+    /// The path of the LDtk file, relative to the assets folder,
+    /// like `ldtk/grid_vania.ldtk`.
+    ///
+    /// **Notice**: This used to be relative to the working directory, like
+    /// `assets/ldtk/grid_vania.ldtk`. Remove the `assets/` prefix from old paths,
+    /// as the file is now loaded through the `AssetServer`.
     pub file_path: String,
     pub asset_path_prefix: String,
     #[reflect(ignore)]
@@ -355,56 +719,643 @@ pub struct LdtkLoadConfig {
     pub z_index: i32,
     /// Map a certain texture index to a animation.
     pub animation_mapper: HashMap<u32, RawTileAnimation>,
+    /// Import the animations written in the custom data of the tiles in the tilesets,
+    /// so animated tiles can be authored in LDtk. The tiles placed in the levels are
+    /// animated if they have a line like these in their custom data:
+    ///
+    /// ```text
+    /// animation: 12 13 14 15 @ 8
+    /// animation: 12:100 13:100 14:250 ping_pong
+    /// ```
+    ///
+    /// The frames are tile ids in the same tileset, played either at the fps after `@`,
+    /// or with per-frame durations in milliseconds like in Aseprite. The mode is
+    /// `loop` by default, or `ping_pong` or `once`. `animation_mapper` takes precedence.
+    pub custom_data_animations: bool,
+    /// The colors of the IntGrid values.
+    ///
+    /// IntGrid layers that don't have auto-layer tiles are rendered as pure color tilemaps
+    /// with these colors, like the LDtk editor does. The values not in here are left empty.
+    /// Leave it empty to skip these layers.
+    pub int_grid_palette: HashMap<i32, Color>,
+    /// Map a certain texture index to a `TileAudioEmitter` tag.
+    ///
+    /// Tilemaps that contain these tiles get a `TilemapAudioEmitters`.
+    pub audio_emitter_mapper: HashMap<u32, String>,
     pub ignore_unregistered_entities: bool,
     pub ignore_unregistered_entity_tags: bool,
+    /// Build the levels on the async compute pool instead of the main thread.
+    pub async_loading: bool,
+    /// Spawn the layers of the levels over multiple frames instead of all at once,
+    /// limited by the `StreamingBudget` if it exists, or one layer per frame otherwise.
+    ///
+    /// Only applies to `LdtkLoaderMode::Tilemap`. Poll `LdtkLevelLoadProgress`
+    /// on the level entity to track the progress.
+    pub incremental_spawning: bool,
+    /// The level fields that are read into `LdtkLevelAtmosphere`.
+    pub atmosphere_fields: LdtkAtmosphereFields,
+    /// The z index offset between levels of adjacent `worldDepth`s.
+    ///
+    /// Should be larger than the layer count of the levels so the floors won't overlap.
+    /// Leave it 0 to put all the levels at `z_index`.
+    pub world_depth_z_offset: i32,
+    /// Respawn the loaded levels in place when the LDtk file or their level files
+    /// are modified on disk. `GlobalEntity` entities are kept as they are.
+    ///
+    /// This needs the `file_watcher` feature of bevy.
+    pub hot_reload: bool,
+    /// Initialize the `GlobalEntity` entities again when their level is loaded
+    /// and their fields are different from the last time.
+    ///
+    /// The components in `LdtkGlobalEntitySnapshots` are kept through this.
+    pub refresh_global_entities: bool,
+    /// Insert `LdtkTileData` on the tiles that have custom data or enum tags in their tileset.
+    pub tile_data_components: bool,
+    /// Only load the layers with these identifiers, like only the collision layer
+    /// for a server build. Leave it empty to load all the layers.
+    ///
+    /// This also applies to the layers in `LdtkAdditionalLayers`.
+    pub load_only_layers: Vec<String>,
+    /// Don't load the layers with these identifiers.
+    pub skip_layers: Vec<String>,
+    /// Only spawn the entities with these identifiers. Leave it empty to spawn all the entities.
+    pub load_only_entities: Vec<String>,
+    /// Don't spawn the entities with these identifiers.
+    pub skip_entities: Vec<String>,
+    /// The entities with any of these tags get `LdtkPersistent`, so they survive
+    /// when their level is unloaded.
+    pub persistent_entity_tags: Vec<String>,
+    /// Panic if there are any issues in the `LdtkValidationReport` when the LDtk file
+    /// is loaded, or when unregistered entities and tags are spawned.
+    /// Otherwise the issues are only logged, and the unregistered entities and tags are skipped.
+    pub strict_validation: bool,
+    /// Custom materials for the tilemaps of certain layers.
+    #[reflect(ignore)]
+    pub layer_materials: LdtkLayerMaterials,
+    /// How the z of the entities in each entity layer is decided.
+    #[reflect(ignore)]
+    pub entity_z_strategies: LdtkEntityZStrategies,
+}
+
+impl LdtkLoadConfig {
+    /// The z index of the level, taking `world_depth_z_offset` into account.
+    #[inline]
+    pub fn level_z_index(&self, level: &Level) -> i32 {
+        self.z_index + level.world_depth * self.world_depth_z_offset
+    }
+
+    /// Whether the layer passes `load_only_layers` and `skip_layers`.
+    pub fn should_load_layer(&self, identifier: &str) -> bool {
+        (self.load_only_layers.is_empty() || self.load_only_layers.iter().any(|l| l == identifier))
+            && !self.skip_layers.iter().any(|l| l == identifier)
+    }
+
+    /// Whether the entity passes `load_only_entities` and `skip_entities`.
+    pub fn should_load_entity(&self, identifier: &str) -> bool {
+        (self.load_only_entities.is_empty()
+            || self.load_only_entities.iter().any(|e| e == identifier))
+            && !self.skip_entities.iter().any(|e| e == identifier)
+    }
+
+    /// Whether the levels loaded in `mode` are spawned over multiple frames.
+    #[inline]
+    pub fn is_incremental(&self, mode: LdtkLoaderMode) -> bool {
+        self.incremental_spawning && mode == LdtkLoaderMode::Tilemap
+    }
+}
+
+pub type LdtkLayerMaterialFactory = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// Layer identifier to the factory of the material that replaces the standard one,
+/// like a distortion shader for a water layer.
+///
+/// The `EntiTilesMaterialPlugin` of the material needs to be added.
+#[derive(Default, Clone)]
+pub struct LdtkLayerMaterials(pub(crate) HashMap<String, LdtkLayerMaterialFactory>);
+
+impl LdtkLayerMaterials {
+    /// Use the material from `factory` for the tilemaps of the layer.
+    /// The factory runs once for each spawned tilemap, so clone a handle
+    /// in it to share a material between levels.
+    pub fn insert<M: TilemapMaterial>(
+        &mut self,
+        identifier: &str,
+        factory: impl Fn(&mut Assets<M>) -> Handle<M> + Send + Sync + 'static,
+    ) {
+        self.0.insert(
+            identifier.to_string(),
+            Arc::new(move |world: &mut World, entity: Entity| {
+                let material = factory(&mut world.resource_mut::<Assets<M>>());
+                if let Some(mut tilemap) = world.get_entity_mut(entity) {
+                    tilemap
+                        .remove::<(
+                            Handle<StandardTilemapMaterial>,
+                            WaitForStandardMaterialReplacement,
+                        )>()
+                        .insert(material);
+                }
+            }),
+        );
+    }
+
+    pub fn with<M: TilemapMaterial>(
+        mut self,
+        identifier: &str,
+        factory: impl Fn(&mut Assets<M>) -> Handle<M> + Send + Sync + 'static,
+    ) -> Self {
+        self.insert(identifier, factory);
+        self
+    }
+
+    #[inline]
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.0.contains_key(identifier)
+    }
+
+    /// Replace the material of the tilemap if the layer has a custom one. This is deferred
+    /// using commands as the material assets are not accessible while loading.
+    pub(crate) fn apply_deferred(commands: &mut EntityCommands, identifier: String) {
+        commands.add(move |entity: Entity, world: &mut World| {
+            let Some(factory) = world
+                .get_resource::<LdtkLoadConfig>()
+                .and_then(|config| config.layer_materials.0.get(&identifier).cloned())
+            else {
+                return;
+            };
+            factory(world, entity);
+        });
+    }
+}
+
+pub type LdtkEntityZFn = Arc<dyn Fn(&EntityInstance, &LayerInstance, f32) -> f32 + Send + Sync>;
+
+/// How the z of the entities in an entity layer is decided.
+///
+/// The z is only assigned when the entities are spawned, so entities that move
+/// need to keep sorting themselves.
+#[derive(Default, Clone)]
+pub enum LdtkEntityZStrategy {
+    /// Stack the entities below the layer in the order they are defined in LDtk.
+    #[default]
+    Fixed,
+    /// Draw the entities lower in the level in front, by the grid row of their pivot.
+    /// The rows are as tall as the slot size of the layer, and entities in the same row
+    /// fall back to the LDtk order.
+    YSort,
+    /// Decide the z from the entity, its layer and the z `Fixed` would give.
+    Custom(LdtkEntityZFn),
+}
+
+impl LdtkEntityZStrategy {
+    /// `order` is the index of the entity in the loaded entities of the layer, out of `count`.
+    pub fn z_index(
+        &self,
+        entity: &EntityInstance,
+        layer: &LayerInstance,
+        layer_z: f32,
+        order: usize,
+        count: usize,
+    ) -> f32 {
+        let order = order as f32 / count as f32;
+        let fixed = layer_z - (1. - order);
+        match self {
+            LdtkEntityZStrategy::Fixed => fixed,
+            LdtkEntityZStrategy::YSort => {
+                let rows = layer.c_hei.max(1) as f32;
+                let row = (entity.local_pos[1] as f32 / layer.grid_size as f32)
+                    .floor()
+                    .clamp(0., rows);
+                layer_z - 1. + (row + order) / (rows + 1.)
+            }
+            LdtkEntityZStrategy::Custom(f) => f(entity, layer, fixed),
+        }
+    }
 }
 
+/// Entity layer identifier to the `LdtkEntityZStrategy` of the layer.
+/// The layers not in here use `LdtkEntityZStrategy::Fixed`.
+#[derive(Default, Clone)]
+pub struct LdtkEntityZStrategies(pub(crate) HashMap<String, LdtkEntityZStrategy>);
+
+impl LdtkEntityZStrategies {
+    pub fn insert(&mut self, identifier: &str, strategy: LdtkEntityZStrategy) {
+        self.0.insert(identifier.to_string(), strategy);
+    }
+
+    pub fn with(mut self, identifier: &str, strategy: LdtkEntityZStrategy) -> Self {
+        self.insert(identifier, strategy);
+        self
+    }
+
+    /// Use a closure to decide the z of the entities in the layer.
+    pub fn with_custom(
+        self,
+        identifier: &str,
+        f: impl Fn(&EntityInstance, &LayerInstance, f32) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        self.with(identifier, LdtkEntityZStrategy::Custom(Arc::new(f)))
+    }
+
+    #[inline]
+    pub fn get(&self, identifier: &str) -> &LdtkEntityZStrategy {
+        const FIXED: &LdtkEntityZStrategy = &LdtkEntityZStrategy::Fixed;
+        self.0.get(identifier).unwrap_or(FIXED)
+    }
+}
+
+/// The identifiers of the level fields that describe the atmosphere of a level.
+#[derive(Reflect, Clone)]
+pub struct LdtkAtmosphereFields {
+    /// A `Color` field.
+    pub ambient_color: String,
+    /// A `Float` field, the intensity of the bloom.
+    pub bloom: String,
+}
+
+impl Default for LdtkAtmosphereFields {
+    fn default() -> Self {
+        Self {
+            ambient_color: "ambient_color".to_string(),
+            bloom: "bloom".to_string(),
+        }
+    }
+}
+
+/// The manager of the LDtk levels.
+///
+/// The LDtk file is loaded as an `LdtkJson` asset through the `AssetServer`,
+/// so the manager won't be initialized until the asset is loaded.
+/// Levels requested before that will be loaded as soon as the asset is ready.
+///
+/// If the project saves levels separately, each level file is only loaded when the
+/// level is requested, and released after all the instances of the level are unloaded.
 #[derive(Resource, Default, Reflect)]
 pub struct LdtkLevelManager {
     pub(crate) ldtk_json: Option<LdtkJson>,
+    pub(crate) json_handle: Option<Handle<LdtkJson>>,
     pub(crate) loaded_levels: HashMap<String, Entity>,
+    pub(crate) instances: HashMap<Entity, String>,
+    /// level identifier to the handle of the external level file
+    pub(crate) external_handles: HashMap<String, Handle<Level>>,
+    /// level identifier to the loaded external level
+    pub(crate) external_levels: HashMap<String, Level>,
+    /// level root to the translation it's loaded at, if overridden
+    pub(crate) trans_ovrds: HashMap<Entity, Vec2>,
+    /// level root to the entity it's attached to
+    pub(crate) parents: HashMap<Entity, Entity>,
 }
 
 impl LdtkLevelManager {
     /// Reloads the LDtk file and refresh the level cache.
-    pub fn reload_json(&mut self, config: &LdtkLoadConfig) {
+    ///
+    /// The cached data will be replaced once the asset is loaded.
+    /// If the asset is modified on disk and hot reloading is enabled,
+    /// the cache is refreshed automatically.
+    pub fn reload_json(&mut self, config: &LdtkLoadConfig, asset_server: &AssetServer) {
         if config.file_path.is_empty() {
             error!("No specified LDtk level file path!");
             return;
         }
 
-        let path = std::env::current_dir().unwrap().join(&config.file_path);
-        let str_raw = match read_to_string(&path) {
-            Ok(data) => data,
-            Err(e) => panic!("Could not read file at path: {:?}!\n{}", path, e),
-        };
+        let handle = asset_server.load(config.file_path.clone());
+        if self.json_handle.as_ref() == Some(&handle) {
+            asset_server.reload(config.file_path.clone());
+        }
+        self.json_handle = Some(handle);
+    }
 
-        self.ldtk_json = match serde_json::from_str::<LdtkJson>(&str_raw) {
-            Ok(data) => Some(data),
-            Err(e) => panic!("Could not parse file at path: {}!\n{}", config.file_path, e),
-        };
+    /// Returns true if the json file is requested but not loaded yet.
+    #[inline]
+    pub fn is_parsing(&self) -> bool {
+        self.json_handle.is_some() && self.ldtk_json.is_none()
+    }
+
+    /// Get the handle of the LDtk file.
+    #[inline]
+    pub fn get_handle(&self) -> Option<&Handle<LdtkJson>> {
+        self.json_handle.as_ref()
     }
 
+    /// Get the parsed LDtk file.
+    ///
+    /// **Notice**: This panics if the LDtk file is not loaded yet. See `is_initialized`.
     pub fn get_cached_data(&self) -> &LdtkJson {
         self.check_initialized();
         self.ldtk_json.as_ref().unwrap()
     }
 
+    /// Get the level and its index in the LDtk file.
+    ///
+    /// If the level is saved separately, this returns the content of the level file,
+    /// or `None` if the LDtk file or the level file is not loaded yet.
+    pub fn get_level(&self, identifier: &str) -> Option<(usize, &Level)> {
+        let (index, level) = self
+            .ldtk_json
+            .as_ref()?
+            .levels
+            .iter()
+            .enumerate()
+            .find(|(_, level)| level.identifier == identifier)?;
+
+        if level.external_rel_path.is_some() {
+            self.external_levels.get(identifier).map(|l| (index, l))
+        } else {
+            Some((index, level))
+        }
+    }
+
+    /// Get the auto-layer rules of the layer `layer` in the level `level`.
+    ///
+    /// Returns `None` if the level is not available or the layer doesn't have any active rule.
+    pub fn get_auto_rules(&self, level: &str, layer: &str) -> Option<LdtkAutoRules> {
+        let (_, level) = self.get_level(level)?;
+        let layer = level
+            .layer_instances
+            .iter()
+            .find(|l| l.identifier == layer)?;
+        LdtkAutoRules::from_layer(self.ldtk_json.as_ref()?, layer)
+    }
+
+    /// Get the level at the world position, using the level rects in the LDtk file,
+    /// so the level doesn't need to be loaded.
+    ///
+    /// If the levels overlap, the one with the largest `worldDepth` is returned.
+    /// This always returns `None` for the `LinearHorizontal` and `LinearVertical` layouts,
+    /// as all the levels are loaded at the origin, or if the LDtk file is not loaded yet.
+    pub fn level_at(&self, world_pos: Vec2) -> Option<LevelIid> {
+        let ldtk_data = self.ldtk_json.as_ref()?;
+        if !matches!(
+            ldtk_data.world_layout,
+            Some(WorldLayout::GridVania | WorldLayout::Free)
+        ) {
+            return None;
+        }
+
+        ldtk_data
+            .levels
+            .iter()
+            .filter(|level| super::level_distance(level, world_pos) == 0.)
+            .max_by_key(|level| level.world_depth)
+            .map(|level| LevelIid(level.iid.clone()))
+    }
+
+    /// Iterate over all the levels whose data is available.
+    /// This is empty if the LDtk file is not loaded yet.
+    pub fn iter_levels(&self) -> impl Iterator<Item = &Level> {
+        self.ldtk_json
+            .iter()
+            .flat_map(|ldtk_data| ldtk_data.levels.iter())
+            .filter_map(|level| {
+                if level.external_rel_path.is_some() {
+                    self.external_levels.get(&level.identifier)
+                } else {
+                    Some(level)
+                }
+            })
+    }
+
+    /// Returns true if the level is ready to be spawned.
+    /// Otherwise, start loading the level file if the level is saved separately.
+    pub(crate) fn request_level(
+        &mut self,
+        identifier: &str,
+        config: &LdtkLoadConfig,
+        asset_server: &AssetServer,
+    ) -> bool {
+        let Some(level) = self
+            .get_cached_data()
+            .levels
+            .iter()
+            .find(|level| level.identifier == identifier)
+        else {
+            // Let the loader handle the missing level.
+            return true;
+        };
+        let Some(rel_path) = level.external_rel_path.as_ref() else {
+            return true;
+        };
+        if self.external_levels.contains_key(identifier) {
+            return true;
+        }
+
+        if !self.external_handles.contains_key(identifier) {
+            let path = Path::new(&config.file_path)
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(rel_path);
+            self.external_handles
+                .insert(identifier.to_string(), asset_server.load(path));
+        }
+        false
+    }
+
+    /// Drop the level file if the level is saved separately and no longer used.
+    fn release_level(&mut self, identifier: &str) {
+        if self.loaded_levels.contains_key(identifier)
+            || self.instances.values().any(|l| l == identifier)
+        {
+            return;
+        }
+
+        self.external_handles.remove(identifier);
+        self.external_levels.remove(identifier);
+    }
+
     pub fn load(&mut self, commands: &mut Commands, level: String, trans_ovrd: Option<Vec2>) {
-        self.check_initialized();
+        self.load_impl(commands, level, trans_ovrd, None);
+    }
+
+    /// Load the level as a child of `parent`, so the whole level follows
+    /// the transform of the parent, like the interior of a moving ship.
+    ///
+    /// The level is placed at `trans_ovrd` relative to the parent,
+    /// or at its world coordinates if `None`.
+    /// `GlobalEntity` entities are still spawned at the root, as if the level had no parent.
+    pub fn load_with_parent(
+        &mut self,
+        commands: &mut Commands,
+        level: String,
+        parent: Entity,
+        trans_ovrd: Option<Vec2>,
+    ) {
+        self.load_impl(commands, level, trans_ovrd, Some(parent));
+    }
+
+    fn load_impl(
+        &mut self,
+        commands: &mut Commands,
+        level: String,
+        trans_ovrd: Option<Vec2>,
+        parent: Option<Entity>,
+    ) {
+        self.check_requested();
 
         if self.loaded_levels.contains_key(&level) {
             error!("Trying to load {:?} that is already loaded!", level);
         } else {
             let entity = commands.spawn(LdtkLoader {
+                json: self.json_handle.clone().unwrap(),
                 level: level.clone(),
                 mode: LdtkLoaderMode::Tilemap,
                 trans_ovrd,
+                parent,
             });
+            if let Some(translation) = trans_ovrd {
+                self.trans_ovrds.insert(entity.id(), translation);
+            }
+            if let Some(parent) = parent {
+                self.parents.insert(entity.id(), parent);
+            }
             self.loaded_levels.insert(level.clone(), entity.id());
         }
     }
 
+    /// Load all the levels in the world at their positions in the world layout.
+    /// `LdtkEvent::LevelLoaded` is sent for each of them as usual.
+    ///
+    /// The levels in `GridVania` and `Free` layouts are placed at their world coordinates,
+    /// and the ones in `LinearHorizontal` and `LinearVertical` layouts are placed next to
+    /// each other in order, without gaps. The levels that are already loaded are skipped.
+    ///
+    /// Unlike `load`, this needs the LDtk file to be loaded. See `is_initialized`.
+    pub fn load_world(&mut self, commands: &mut Commands) {
+        if !self.is_initialized() {
+            error!("Trying to load the world before the LDtk file is loaded!");
+            return;
+        }
+
+        for (level, translation) in self.world_translations() {
+            if !self.loaded_levels.contains_key(&level) {
+                self.load(commands, level, translation);
+            }
+        }
+    }
+
+    /// The levels in the world and their translation overrides in `load_world`.
+    /// The translation is `None` if the level is placed at its world coordinates.
+    /// This is empty if the LDtk file is not loaded yet.
+    pub fn world_translations(&self) -> Vec<(String, Option<Vec2>)> {
+        let Some(ldtk_data) = self.ldtk_json.as_ref() else {
+            return Vec::new();
+        };
+        let mut offset = Vec2::ZERO;
+
+        ldtk_data
+            .levels
+            .iter()
+            .map(|level| {
+                let translation = match ldtk_data.world_layout {
+                    Some(WorldLayout::LinearHorizontal) => {
+                        let translation = offset;
+                        offset.x += level.px_wid as f32;
+                        Some(translation)
+                    }
+                    Some(WorldLayout::LinearVertical) => {
+                        let translation = offset;
+                        offset.y -= level.px_hei as f32;
+                        Some(translation)
+                    }
+                    _ => None,
+                };
+                (level.identifier.clone(), translation)
+            })
+            .collect()
+    }
+
+    /// Spawn a new instance of the level. Unlike `load`, the same level can be
+    /// instanced multiple times, every instance has its own root entity.
+    ///
+    /// Use the returned entity to unload the instance. The entities of the instance get
+    /// `LdtkLevelRoot` with it, and their `EntityRef` fields refer to the same instance.
+    pub fn load_instance(
+        &mut self,
+        commands: &mut Commands,
+        level: String,
+        translation: Vec2,
+    ) -> Entity {
+        self.load_instance_impl(commands, level, translation, None)
+    }
+
+    /// Spawn a new instance of the level as a child of `parent`.
+    /// The instance is placed at `translation` relative to the parent.
+    ///
+    /// See `load_instance` and `load_with_parent`.
+    pub fn load_instance_with_parent(
+        &mut self,
+        commands: &mut Commands,
+        level: String,
+        parent: Entity,
+        translation: Vec2,
+    ) -> Entity {
+        self.load_instance_impl(commands, level, translation, Some(parent))
+    }
+
+    fn load_instance_impl(
+        &mut self,
+        commands: &mut Commands,
+        level: String,
+        translation: Vec2,
+        parent: Option<Entity>,
+    ) -> Entity {
+        self.check_requested();
+
+        let entity = commands
+            .spawn(LdtkLoader {
+                json: self.json_handle.clone().unwrap(),
+                level: level.clone(),
+                mode: LdtkLoaderMode::Tilemap,
+                trans_ovrd: Some(translation),
+                parent,
+            })
+            .id();
+        self.instances.insert(entity, level);
+        self.trans_ovrds.insert(entity, translation);
+        if let Some(parent) = parent {
+            self.parents.insert(entity, parent);
+        }
+        entity
+    }
+
+    /// Get the entity the level root is attached to, if loaded with a parent.
+    #[inline]
+    pub fn get_parent(&self, level_entity: Entity) -> Option<Entity> {
+        self.parents.get(&level_entity).copied()
+    }
+
+    pub fn unload_instance(&mut self, commands: &mut Commands, instance: Entity) {
+        if let Some(level) = self.instances.remove(&instance) {
+            commands.entity(instance).insert(LdtkUnloader);
+            self.trans_ovrds.remove(&instance);
+            self.parents.remove(&instance);
+            self.release_level(&level);
+        } else {
+            error!(
+                "Trying to unload instance {:?} that is not loaded!",
+                instance
+            );
+        }
+    }
+
+    /// Get all the instances of the level.
+    pub fn get_instances(&self, level: &str) -> Vec<Entity> {
+        self.instances
+            .iter()
+            .filter_map(|(e, l)| if l == level { Some(*e) } else { None })
+            .collect()
+    }
+
+    /// Get the identifier of the level that the instance is spawned from.
+    #[inline]
+    pub fn get_instance_level(&self, instance: Entity) -> Option<&str> {
+        self.instances.get(&instance).map(|l| l.as_str())
+    }
+
+    /// Iterate over all the instances and the identifiers of their levels.
+    #[inline]
+    pub fn iter_instances(&self) -> impl Iterator<Item = (Entity, &str)> {
+        self.instances.iter().map(|(e, l)| (*e, l.as_str()))
+    }
+
     pub fn load_all_patterns(&mut self, commands: &mut Commands) {
         self.check_initialized();
 
@@ -418,16 +1369,18 @@ impl LdtkLevelManager {
                     error!("Trying to load {:?} that is already loaded!", level);
                 } else {
                     commands.spawn(LdtkLoader {
+                        json: self.json_handle.clone().unwrap(),
                         level: level.identifier.clone(),
                         mode: LdtkLoaderMode::MapPattern,
                         trans_ovrd: None,
+                        parent: None,
                     });
                 }
             });
     }
 
     pub fn switch_to(&mut self, commands: &mut Commands, level: String, trans_ovrd: Option<Vec2>) {
-        self.check_initialized();
+        self.check_requested();
         if self.loaded_levels.contains_key(&level) {
             error!("Trying to load {:?} that is already loaded!", level);
         } else {
@@ -439,17 +1392,29 @@ impl LdtkLevelManager {
     pub fn unload(&mut self, commands: &mut Commands, level: String) {
         if let Some(l) = self.loaded_levels.get(&level) {
             commands.entity(*l).insert(LdtkUnloader);
+            self.trans_ovrds.remove(l);
+            self.parents.remove(l);
             self.loaded_levels.remove(&level);
+            self.release_level(&level);
         } else {
             error!("Trying to unload {:?} that is not loaded!", level);
         }
     }
 
+    /// Unload all the levels, including the instances.
     pub fn unload_all(&mut self, commands: &mut Commands) {
         for (_, l) in self.loaded_levels.iter() {
             commands.entity(*l).insert(LdtkUnloader);
         }
+        for (e, _) in self.instances.iter() {
+            commands.entity(*e).insert(LdtkUnloader);
+        }
         self.loaded_levels.clear();
+        self.instances.clear();
+        self.trans_ovrds.clear();
+        self.parents.clear();
+        self.external_handles.clear();
+        self.external_levels.clear();
     }
 
     pub fn is_loaded(&self, level: String) -> bool {
@@ -466,35 +1431,236 @@ impl LdtkLevelManager {
             "LdtkLevelManager is not initialized!"
         );
     }
+
+    fn check_requested(&self) {
+        assert!(
+            self.json_handle.is_some(),
+            "LdtkLevelManager doesn't have a LDtk file! Call `reload_json` first."
+        );
+    }
 }
 
-#[derive(Resource, Default, Reflect)]
-pub struct LdtkGlobalEntityRegistry(pub(crate) HashMap<EntityIid, Entity>);
+/// Insert this resource to only show the levels whose `worldDepth` is within `min..=max`,
+/// like the current floor of a building.
+///
+/// The level entities of hidden levels are set to `Visibility::Hidden`, which hides
+/// their children, and their tilemap layers become fully transparent.
+/// Removing this resource shows all the levels again.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct LdtkVisibleDepths {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl LdtkVisibleDepths {
+    /// Only show the levels of one depth.
+    pub fn single(depth: i32) -> Self {
+        Self {
+            min: depth,
+            max: depth,
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, depth: i32) -> bool {
+        depth >= self.min && depth <= self.max
+    }
+}
+
+/// Insert this resource to automatically load the levels around the camera,
+/// and unload the ones that are far away.
+///
+/// The level the camera is in and its neighbours are loaded when they are closer than
+/// `load_distance` to the camera, and unloaded when they are farther than `unload_distance`.
+/// Keep `unload_distance` larger than `load_distance` so the levels near the border
+/// won't be loaded and unloaded over and over.
+///
+/// **Notice**: This only works with the `GridVania` and `Free` world layouts.
+/// Levels loaded through `LdtkLevelManager` by yourself are never unloaded by this.
+#[derive(Resource, Debug, Clone, Reflect)]
+pub struct LdtkLevelStreaming {
+    /// In pixels.
+    pub load_distance: f32,
+    /// In pixels.
+    pub unload_distance: f32,
+    pub(crate) current: Option<String>,
+    pub(crate) streamed: HashSet<String>,
+}
+
+impl Default for LdtkLevelStreaming {
+    fn default() -> Self {
+        Self::new(256., 512.)
+    }
+}
+
+impl LdtkLevelStreaming {
+    pub fn new(load_distance: f32, unload_distance: f32) -> Self {
+        assert!(
+            unload_distance >= load_distance,
+            "unload_distance should not be smaller than load_distance!"
+        );
+
+        Self {
+            load_distance,
+            unload_distance,
+            current: None,
+            streamed: HashSet::new(),
+        }
+    }
+
+    /// The identifier of the level the camera is in.
+    #[inline]
+    pub fn current_level(&self) -> Option<&String> {
+        self.current.as_ref()
+    }
+
+    /// Returns true if the level is loaded by the streaming.
+    #[inline]
+    pub fn is_streamed(&self, level: &str) -> bool {
+        self.streamed.contains(level)
+    }
+}
+
+/// The custom data and enum tags of the tiles in all the tilesets.
+#[derive(Resource, Default, Reflect, Clone)]
+pub struct LdtkTileMetadata {
+    /// (tileset uid, tile id) to the data
+    pub(crate) tiles: HashMap<(i32, i32), LdtkTileData>,
+}
+
+impl LdtkTileMetadata {
+    pub fn new(defs: &Definitions) -> Self {
+        let mut tiles = HashMap::<(i32, i32), LdtkTileData>::default();
+
+        defs.tilesets.iter().for_each(|tileset| {
+            tileset.custom_data.iter().for_each(|data| {
+                tiles
+                    .entry((tileset.uid, data.tile_id))
+                    .or_default()
+                    .custom_data = Some(data.data.clone());
+            });
+            tileset.enum_tags.iter().for_each(|tag| {
+                tag.tile_ids.iter().for_each(|tile_id| {
+                    tiles
+                        .entry((tileset.uid, *tile_id))
+                        .or_default()
+                        .enum_tags
+                        .push(tag.enum_value_id.clone());
+                });
+            });
+        });
+
+        Self { tiles }
+    }
+
+    /// Get the data of the tile. Returns `None` if the tile has neither custom data nor tags.
+    #[inline]
+    pub fn get(&self, tileset_uid: i32, tile_id: i32) -> Option<&LdtkTileData> {
+        self.tiles.get(&(tileset_uid, tile_id))
+    }
+
+    #[inline]
+    pub fn has_tag(&self, tileset_uid: i32, tile_id: i32, tag: &str) -> bool {
+        self.get(tileset_uid, tile_id)
+            .is_some_and(|data| data.has_tag(tag))
+    }
+}
+
+#[derive(Resource, Default, Reflect, Clone)]
+pub struct LdtkGlobalEntityRegistry {
+    pub(crate) entities: HashMap<EntityIid, Entity>,
+    /// The fields the entities were initialized with.
+    ///
+    /// Only recorded when `LdtkLoadConfig::refresh_global_entities` is enabled.
+    pub(crate) fields: HashMap<EntityIid, HashMap<String, FieldInstance>>,
+}
 
 impl LdtkGlobalEntityRegistry {
     #[inline]
     pub fn register(&mut self, iid: EntityIid, entity: Entity) {
-        self.0.insert(iid, entity);
+        self.entities.insert(iid, entity);
     }
 
     #[inline]
     pub fn contains(&self, iid: &EntityIid) -> bool {
-        self.0.contains_key(iid)
+        self.entities.contains_key(iid)
     }
 
     #[inline]
     pub fn get(&self, iid: &EntityIid) -> Option<Entity> {
-        self.0.get(iid).cloned()
+        self.entities.get(iid).cloned()
+    }
+
+    /// Get the iid of the global entity.
+    pub fn get_iid(&self, entity: Entity) -> Option<&EntityIid> {
+        self.entities
+            .iter()
+            .find_map(|(iid, e)| if *e == entity { Some(iid) } else { None })
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityIid, Entity)> {
+        self.entities.iter().map(|(iid, e)| (iid, *e))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Move the entity into the coordinate space of another level, like when the player
+    /// walks through a door into a level loaded somewhere else.
+    ///
+    /// The entity is translated by `offset`, usually the difference between the translations
+    /// of the two levels, and gets `LdtkLevelRoot` of `level` so its `EntityRef` fields
+    /// prefer the entities of that level. Returns false if the entity is not registered.
+    pub fn reparent(
+        &self,
+        commands: &mut Commands,
+        iid: &EntityIid,
+        level: Entity,
+        offset: Vec2,
+    ) -> bool {
+        let Some(entity) = self.get(iid) else {
+            return false;
+        };
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(LdtkLevelRoot(level));
+        entity_commands.add(move |entity: Entity, world: &mut World| {
+            if let Some(mut transform) = world.get_mut::<Transform>(entity) {
+                transform.translation += offset.extend(0.);
+            }
+        });
+        true
+    }
+
+    /// Get the fields the entity was last initialized with.
+    #[inline]
+    pub fn get_fields(&self, iid: &EntityIid) -> Option<&HashMap<String, FieldInstance>> {
+        self.fields.get(iid)
+    }
+
+    /// Returns true if the entity was initialized with fields different from `fields`.
+    pub fn is_outdated(&self, iid: &EntityIid, fields: &HashMap<String, FieldInstance>) -> bool {
+        self.fields.get(iid).is_some_and(|f| f != fields)
     }
 
     #[inline]
     pub fn remove(&mut self, iid: &EntityIid) -> Option<Entity> {
-        self.0.remove(iid)
+        self.fields.remove(iid);
+        self.entities.remove(iid)
     }
 
     #[inline]
     pub fn remove_all(&mut self) {
-        self.0.clear();
+        self.entities.clear();
+        self.fields.clear();
     }
 
     #[inline]
@@ -504,11 +1670,42 @@ impl LdtkGlobalEntityRegistry {
         }
     }
 
+    /// Despawn the entities that `f` returns true for, like the ones that
+    /// don't belong to the next world.
+    pub fn despawn_if(
+        &mut self,
+        commands: &mut Commands,
+        mut f: impl FnMut(&EntityIid, Entity) -> bool,
+    ) {
+        let despawned = self
+            .iter()
+            .filter(|(iid, entity)| f(iid, *entity))
+            .map(|(iid, _)| iid.clone())
+            .collect::<Vec<_>>();
+        despawned.iter().for_each(|iid| self.despawn(commands, iid));
+    }
+
     #[inline]
     pub fn despawn_all(&mut self, commands: &mut Commands) {
-        self.0.iter().for_each(|(_, entity)| {
+        self.entities.iter().for_each(|(_, entity)| {
             commands.entity(*entity).despawn();
         });
         self.remove_all();
     }
+
+    /// Record the fields of the entity if it turns out to be a `GlobalEntity`.
+    pub(crate) fn record_fields_deferred(
+        commands: &mut EntityCommands,
+        iid: EntityIid,
+        fields: HashMap<String, FieldInstance>,
+    ) {
+        commands.add(move |entity: Entity, world: &mut World| {
+            if world.get::<GlobalEntity>(entity).is_none() {
+                return;
+            }
+            let mut registry = world.resource_mut::<LdtkGlobalEntityRegistry>();
+            registry.register(iid.clone(), entity);
+            registry.fields.insert(iid, fields);
+        });
+    }
 }
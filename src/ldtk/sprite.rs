@@ -11,8 +11,6 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{math::extension::DivToCeil, utils::mesh::clip_quad_mesh};
-
 use super::{
     json::{
         definitions::{EntityDef, TilesetRect},
@@ -86,6 +84,8 @@ impl TileRenderMode {
         let tile_px = IVec2::new(tile_rect.width, tile_rect.height);
         let entity_px = IVec2::new(entity.width, entity.height);
         let pivot = Vec2::new(entity.pivot[0], -entity.pivot[1]);
+        // The cropped modes keep the pivot of the texture at the pivot of the entity.
+        let uv_pivot = Vec2::from(entity.pivot);
 
         /*
          * 0 - 1
@@ -111,7 +111,7 @@ impl TileRenderMode {
                     corner_uv
                         .into_iter()
                         .map(|p| {
-                            p * render_size / tile_size + pivot * (1. - render_size / tile_size)
+                            p * render_size / tile_size + uv_pivot * (1. - render_size / tile_size)
                         })
                         .collect(),
                     vec![0, 3, 1, 1, 3, 2],
@@ -126,31 +126,11 @@ impl TileRenderMode {
                 )
             }
             TileRenderMode::Repeat => {
-                let mut vertices = Vec::new();
-                let mut uvs = Vec::new();
-                let mut vertex_indices = Vec::new();
-                let mut i = 0;
-                let tiled_count = entity_px.div_to_ceil(tile_px);
-                for dy in 0..tiled_count.y {
-                    for dx in 0..tiled_count.x {
-                        let offset = Vec2::new(dx as f32, dy as f32) * tile_size;
-                        let mut v = corner_pos
-                            .into_iter()
-                            .map(|p| (Vec2::new(p.x, -p.y) + pivot) * tile_size + offset)
-                            .collect();
-                        let mut u = corner_uv.to_vec();
-                        clip_quad_mesh(&mut v, &mut u, [Vec2::ZERO, render_size].into());
-                        vertices.extend(
-                            v.into_iter()
-                                .map(|v| Vec2::new(v.x, -v.y) - pivot * render_size),
-                        );
-                        uvs.extend(u);
-                        vertex_indices
-                            .extend(vec![0, 3, 1, 1, 3, 2].into_iter().map(|v| v + i * 4));
-                        i += 1;
-                    }
-                }
-                (vertices, uvs, vertex_indices)
+                let quads = SpriteMesh::from_slices(
+                    &repeat_segments(0., render_size.x, 0., 1., tile_size.x),
+                    &repeat_segments(0., render_size.y, 0., 1., tile_size.y),
+                );
+                return quads.with_pivot(pivot, render_size);
             }
             TileRenderMode::Stretch => (
                 corner_pos.into_iter().map(|p| p * render_size).collect(),
@@ -161,12 +141,11 @@ impl TileRenderMode {
                 let d = render_size / tile_size;
                 let size = Vec2::new(d.x.min(1.), d.y.min(1.)) * tile_size;
                 let uv_scale = size / tile_size;
-                let pivot = Vec2::new(pivot.x, -pivot.y);
                 (
                     corner_pos.into_iter().map(|p| p * size).collect(),
                     corner_uv
                         .into_iter()
-                        .map(|p| p * uv_scale + pivot * (1. - uv_scale))
+                        .map(|p| p * uv_scale + uv_pivot * (1. - uv_scale))
                         .collect(),
                     vec![0, 3, 1, 1, 3, 2],
                 )
@@ -177,16 +156,11 @@ impl TileRenderMode {
                 vec![0, 3, 1, 1, 3, 2],
             ),
             TileRenderMode::NineSlice => {
-                let nine_slice_mesh = defs[&entity.identifier].nine_slice_borders.generate_mesh(
-                    IVec2::new(entity.width, entity.height),
-                    IVec2::new(tile_rect.width, tile_rect.height),
-                    pivot,
-                );
-                (
-                    nine_slice_mesh.vertices,
-                    nine_slice_mesh.uvs,
-                    nine_slice_mesh.indices,
-                )
+                let borders = defs[&entity.identifier].nine_slice_borders;
+                if !borders.is_valid {
+                    return TileRenderMode::Stretch.get_mesh(entity, tile_rect, defs);
+                }
+                return borders.generate_mesh(entity_px, tile_px, pivot);
             }
         };
 
@@ -214,231 +188,161 @@ pub struct SpriteMesh {
     pub indices: Vec<u16>,
 }
 
+impl SpriteMesh {
+    /// Build the quads of a grid of slices. The slices are `(min, max, uv_min, uv_max)`,
+    /// in pixels with y pointing down.
+    fn from_slices(columns: &[(f32, f32, f32, f32)], rows: &[(f32, f32, f32, f32)]) -> Self {
+        let mut vertices = Vec::with_capacity(columns.len() * rows.len() * 4);
+        let mut uvs = Vec::with_capacity(columns.len() * rows.len() * 4);
+        let mut indices = Vec::with_capacity(columns.len() * rows.len() * 6);
+
+        for (y_min, y_max, v_min, v_max) in rows {
+            for (x_min, x_max, u_min, u_max) in columns {
+                let i = vertices.len() as u16;
+                vertices.extend_from_slice(&[
+                    Vec2::new(*x_min, *y_min),
+                    Vec2::new(*x_max, *y_min),
+                    Vec2::new(*x_max, *y_max),
+                    Vec2::new(*x_min, *y_max),
+                ]);
+                uvs.extend_from_slice(&[
+                    Vec2::new(*u_min, *v_min),
+                    Vec2::new(*u_max, *v_min),
+                    Vec2::new(*u_max, *v_max),
+                    Vec2::new(*u_min, *v_max),
+                ]);
+                indices.extend([0, 3, 1, 1, 3, 2].into_iter().map(|v| v + i));
+            }
+        }
+
+        Self {
+            vertices,
+            uvs,
+            indices,
+        }
+    }
+
+    /// Flip the y axis and move the pivot of the entity to the origin.
+    fn with_pivot(mut self, pivot: Vec2, render_size: Vec2) -> Self {
+        self.vertices
+            .iter_mut()
+            .for_each(|v| *v = Vec2::new(v.x, -v.y) - pivot * render_size);
+        self
+    }
+}
+
+/// Cover `min..max` with the uv range `uv_min..uv_max` repeated every `step` pixels.
+/// The last segment is cropped. Returns `(min, max, uv_min, uv_max)` for each segment.
+fn repeat_segments(
+    min: f32,
+    max: f32,
+    uv_min: f32,
+    uv_max: f32,
+    step: f32,
+) -> Vec<(f32, f32, f32, f32)> {
+    if max <= min {
+        return Vec::new();
+    }
+    if step <= 0. {
+        return vec![(min, max, uv_min, uv_max)];
+    }
+
+    let mut segments = Vec::new();
+    let mut start = min;
+    while start < max {
+        let end = (start + step).min(max);
+        segments.push((
+            start,
+            end,
+            uv_min,
+            uv_min + (uv_max - uv_min) * (end - start) / step,
+        ));
+        start = end;
+    }
+    segments
+}
+
 impl NineSliceBorders {
+    /// Generate the mesh of an entity with the size `render_size`, using a tile of `tile_size`.
+    ///
+    /// The corners keep their sizes, the edges and the center are repeated just like
+    /// in the editor. If the entity is smaller than the borders, the borders are shrunk.
     pub fn generate_mesh(&self, render_size: IVec2, tile_size: IVec2, pivot: Vec2) -> SpriteMesh {
-        let inner_pxs = IVec2::new(
-            render_size.x - self.left - self.right,
-            render_size.y - self.up - self.down,
-        );
-        let sliced_tile_inner_size = IVec2::new(
-            tile_size.x - self.left - self.right,
-            tile_size.y - self.up - self.down,
-        );
-        let border_pxs = IVec4::new(self.up, self.down, self.left, self.right).as_vec4();
-
         let tile_size = tile_size.as_vec2();
         let render_size = render_size.as_vec2();
+
+        // up, down, left, right in the tile
+        let tile_borders = IVec4::new(self.up, self.down, self.left, self.right)
+            .max(IVec4::ZERO)
+            .as_vec4();
+        let tile_borders = Vec4::new(
+            tile_borders.x.min(tile_size.y),
+            tile_borders
+                .y
+                .min(tile_size.y - tile_borders.x.min(tile_size.y)),
+            tile_borders.z.min(tile_size.x),
+            tile_borders
+                .w
+                .min(tile_size.x - tile_borders.z.min(tile_size.x)),
+        );
         let border_uvs = Vec4::new(
-            border_pxs.x / tile_size.y,
-            border_pxs.y / tile_size.y,
-            border_pxs.z / tile_size.x,
-            border_pxs.w / tile_size.x,
+            tile_borders.x / tile_size.y,
+            tile_borders.y / tile_size.y,
+            tile_borders.z / tile_size.x,
+            tile_borders.w / tile_size.x,
         );
 
-        let mut vertices = Vec::new();
-        let mut uvs = Vec::new();
-        let mut vertex_indices = Vec::new();
-        let base_indices = [0, 3, 1, 1, 3, 2];
-        let mut quad_count = 0;
-        // corners
-        // u_l
-        vertices.extend_from_slice(&[
-            Vec2::new(0., 0.),
-            Vec2::new(border_pxs.z, 0.),
-            Vec2::new(border_pxs.z, border_pxs.x),
-            Vec2::new(0., border_pxs.x),
-        ]);
-        uvs.extend_from_slice(&[
-            Vec2::new(0., 0.),
-            Vec2::new(border_uvs.z, 0.),
-            Vec2::new(border_uvs.z, border_uvs.x),
-            Vec2::new(0., border_uvs.x),
-        ]);
-        vertex_indices.extend(base_indices);
-        quad_count += 1;
-        // u_r
-        vertices.extend_from_slice(&[
-            Vec2::new(render_size.x - border_pxs.w, 0.),
-            Vec2::new(render_size.x, 0.),
-            Vec2::new(render_size.x, border_pxs.x),
-            Vec2::new(render_size.x - border_pxs.w, border_pxs.x),
-        ]);
-        uvs.extend_from_slice(&[
-            Vec2::new(1. - border_uvs.w, 0.),
-            Vec2::new(1., 0.),
-            Vec2::new(1., border_uvs.x),
-            Vec2::new(1. - border_uvs.w, border_uvs.x),
-        ]);
-        vertex_indices.extend(base_indices.iter().map(|v| v + quad_count * 4));
-        quad_count += 1;
-        // d_l
-        vertices.extend_from_slice(&[
-            Vec2::new(0., render_size.y - border_pxs.y),
-            Vec2::new(border_pxs.z, render_size.y - border_pxs.y),
-            Vec2::new(border_pxs.z, render_size.y),
-            Vec2::new(0., render_size.y),
-        ]);
-        uvs.extend_from_slice(&[
-            Vec2::new(0., 1. - border_uvs.y),
-            Vec2::new(border_uvs.z, 1. - border_uvs.y),
-            Vec2::new(border_uvs.z, 1.),
-            Vec2::new(0., 1.),
-        ]);
-        vertex_indices.extend(base_indices.iter().map(|v| v + quad_count * 4));
-        quad_count += 1;
-        // d_r
-        vertices.extend_from_slice(&[
-            Vec2::new(render_size.x - border_pxs.w, render_size.y - border_pxs.y),
-            Vec2::new(render_size.x, render_size.y - border_pxs.y),
-            Vec2::new(render_size.x, render_size.y),
-            Vec2::new(render_size.x - border_pxs.w, render_size.y),
-        ]);
-        uvs.extend_from_slice(&[
-            Vec2::new(1. - border_uvs.w, 1. - border_uvs.y),
-            Vec2::new(1., 1. - border_uvs.y),
-            Vec2::new(1., 1.),
-            Vec2::new(1. - border_uvs.w, 1.),
-        ]);
-        vertex_indices.extend(base_indices.iter().map(|v| v + quad_count * 4));
-        quad_count += 1;
-
-        // up and down
-        let tiled_count = inner_pxs.div_to_ceil(sliced_tile_inner_size);
-        let tiled_size = Vec2 {
-            x: (tile_size.x - border_pxs.z - border_pxs.w) as f32,
-            y: (tile_size.y - border_pxs.x - border_pxs.y) as f32,
-        };
-        let origins = [
-            Vec2::new(border_pxs.z, 0.),
-            Vec2::new(border_pxs.z, render_size.y - border_pxs.y),
-            Vec2::new(0., border_pxs.x),
-            Vec2::new(render_size.x - border_pxs.w, border_pxs.x),
-        ];
-        let extents = [
-            Vec2::new(tiled_size.x, border_pxs.x),
-            Vec2::new(tiled_size.x, border_pxs.y),
-            Vec2::new(border_pxs.z, tiled_size.y),
-            Vec2::new(border_pxs.w, tiled_size.y),
-        ];
-        let repeat = [
-            IVec2::new(tiled_count.x, 1),
-            IVec2::new(tiled_count.x, 1),
-            IVec2::new(1, tiled_count.y),
-            IVec2::new(1, tiled_count.y),
-        ];
-        let border_slice_uvs = [
-            [
-                Vec2::new(border_uvs.z, 0.),
-                Vec2::new(1. - border_uvs.w, 0.),
-                Vec2::new(1. - border_uvs.w, border_uvs.x),
-                Vec2::new(border_uvs.z, border_uvs.x),
-            ],
-            [
-                Vec2::new(border_uvs.z, 1. - border_uvs.y),
-                Vec2::new(1. - border_uvs.w, 1. - border_uvs.y),
-                Vec2::new(1. - border_uvs.w, 1.),
-                Vec2::new(border_uvs.z, 1.),
-            ],
-            [
-                Vec2::new(0., border_uvs.x),
-                Vec2::new(border_uvs.z, border_uvs.x),
-                Vec2::new(border_uvs.z, 1. - border_uvs.y),
-                Vec2::new(0., 1. - border_uvs.y),
-            ],
-            [
-                Vec2::new(1. - border_uvs.w, border_uvs.x),
-                Vec2::new(1., border_uvs.x),
-                Vec2::new(1., 1. - border_uvs.y),
-                Vec2::new(1. - border_uvs.w, 1. - border_uvs.y),
-            ],
-        ];
-        let valid_rects = [
-            [
-                Vec2::new(border_pxs.z, 0.),
-                Vec2::new(render_size.x - border_pxs.z, border_pxs.x),
-            ],
-            [
-                Vec2::new(border_pxs.z, render_size.y - border_pxs.y),
-                Vec2::new(render_size.x - border_pxs.w, render_size.y),
-            ],
-            [
-                Vec2::new(0., border_pxs.x),
-                Vec2::new(border_pxs.z, render_size.y - border_pxs.y),
-            ],
-            [
-                Vec2::new(render_size.x - border_pxs.w, border_pxs.x),
-                Vec2::new(render_size.x, render_size.y - border_pxs.y),
-            ],
-        ];
-        for i in 0..4 {
-            for dx in 0..repeat[i].x {
-                for dy in 0..repeat[i].y {
-                    let (dx, dy) = (dx as f32, dy as f32);
-                    let mut v = vec![
-                        Vec2 {
-                            x: origins[i].x + dx * extents[i].x,
-                            y: origins[i].y + dy * extents[i].y,
-                        },
-                        Vec2 {
-                            x: origins[i].x + (dx + 1.) * extents[i].x,
-                            y: origins[i].y + dy * extents[i].y,
-                        },
-                        Vec2 {
-                            x: origins[i].x + (dx + 1.) * extents[i].x,
-                            y: origins[i].y + (dy + 1.) * extents[i].y,
-                        },
-                        Vec2 {
-                            x: origins[i].x + dx * extents[i].x,
-                            y: origins[i].y + (dy + 1.) * extents[i].y,
-                        },
-                    ];
-                    let mut u = border_slice_uvs[i].to_vec();
-                    clip_quad_mesh(&mut v, &mut u, valid_rects[i].into());
-                    vertices.extend(v);
-                    uvs.extend(u);
-                    vertex_indices.extend(base_indices.iter().map(|v| v + quad_count * 4));
-                    quad_count += 1;
-                }
+        // The borders in the entity.
+        let shrink = |size: f32, a: f32, b: f32| {
+            if a + b > size && a + b > 0. {
+                size / (a + b)
+            } else {
+                1.
             }
-        }
+        };
+        let shrink_x = shrink(render_size.x, tile_borders.z, tile_borders.w);
+        let shrink_y = shrink(render_size.y, tile_borders.x, tile_borders.y);
+        let borders = Vec4::new(
+            tile_borders.x * shrink_y,
+            tile_borders.y * shrink_y,
+            tile_borders.z * shrink_x,
+            tile_borders.w * shrink_x,
+        );
 
-        // inner
-        let origin = Vec2::new(border_pxs.z, border_pxs.x);
-        let inner_slice_uvs = [
-            Vec2::new(border_uvs.x, border_uvs.z),
-            Vec2::new(1. - border_uvs.y, border_uvs.z),
-            Vec2::new(1. - border_uvs.y, 1. - border_uvs.w),
-            Vec2::new(border_uvs.x, 1. - border_uvs.w),
-        ];
-        let valid_inner_range = [
-            Vec2::new(border_pxs.z, border_pxs.x),
-            Vec2::new(render_size.x - border_pxs.w, render_size.y - border_pxs.y),
-        ];
-        for dx in 0..tiled_count.x {
-            for dy in 0..tiled_count.y {
-                let (dx, dy) = (dx as f32, dy as f32);
-                let mut v = vec![
-                    origin + tiled_size * Vec2::new(dx, dy),
-                    origin + tiled_size * Vec2::new(dx + 1., dy),
-                    origin + tiled_size * Vec2::new(dx + 1., dy + 1.),
-                    origin + tiled_size * Vec2::new(dx, dy + 1.),
-                ];
-                let mut u = inner_slice_uvs.to_vec();
-                clip_quad_mesh(&mut v, &mut u, valid_inner_range.into());
-                vertices.extend(v);
-                uvs.extend(u);
-                vertex_indices.extend(base_indices.iter().map(|v| v + quad_count * 4));
-                quad_count += 1;
-            }
-        }
+        let mut columns = Vec::new();
+        columns.extend(repeat_segments(0., borders.z, 0., border_uvs.z, 0.));
+        columns.extend(repeat_segments(
+            borders.z,
+            render_size.x - borders.w,
+            border_uvs.z,
+            1. - border_uvs.w,
+            tile_size.x - tile_borders.z - tile_borders.w,
+        ));
+        columns.extend(repeat_segments(
+            render_size.x - borders.w,
+            render_size.x,
+            1. - border_uvs.w,
+            1.,
+            0.,
+        ));
 
-        SpriteMesh {
-            vertices: vertices
-                .into_iter()
-                .map(|v| Vec2::new(v.x, -v.y) - pivot * render_size)
-                .collect(),
-            uvs,
-            indices: vertex_indices,
-        }
+        let mut rows = Vec::new();
+        rows.extend(repeat_segments(0., borders.x, 0., border_uvs.x, 0.));
+        rows.extend(repeat_segments(
+            borders.x,
+            render_size.y - borders.y,
+            border_uvs.x,
+            1. - border_uvs.y,
+            tile_size.y - tile_borders.x - tile_borders.y,
+        ));
+        rows.extend(repeat_segments(
+            render_size.y - borders.y,
+            render_size.y,
+            1. - border_uvs.y,
+            1.,
+            0.,
+        ));
+
+        SpriteMesh::from_slices(&columns, &rows).with_pivot(pivot, render_size)
     }
 }
@@ -2,12 +2,19 @@ use std::marker::PhantomData;
 
 use bevy::{
     asset::AssetServer,
-    ecs::{bundle::Bundle, component::Component, system::EntityCommands},
+    ecs::{
+        bundle::Bundle,
+        component::Component,
+        entity::Entity,
+        system::{CommandQueue, Commands, EntityCommands, Resource},
+        world::World,
+    },
+    transform::components::Transform,
     utils::HashMap,
 };
 
 use super::{
-    json::{field::FieldInstance, level::EntityInstance},
+    json::{definitions::Definitions, field::FieldInstance, level::EntityInstance},
     resources::LdtkAssets,
 };
 
@@ -59,8 +66,211 @@ impl<T: LdtkEntity + Bundle> PhantomLdtkEntityTrait for PhantomLdtkEntity<T> {
     }
 }
 
+/// A hook that runs after an LDtk entity is spawned.
+pub type LdtkSpawnHook = Box<dyn Fn(&mut LdtkSpawnContext) + Send + Sync>;
+
+/// All the spawn hooks, grouped by the entity identifier.
+///
+/// Use `App::add_ldtk_spawn_hook` to add hooks.
+#[derive(Resource, Default)]
+pub struct LdtkSpawnHooks(pub(crate) HashMap<String, Vec<LdtkSpawnHook>>);
+
+impl LdtkSpawnHooks {
+    pub fn add(&mut self, identifier: &str, hook: LdtkSpawnHook) {
+        self.0.entry(identifier.to_string()).or_default().push(hook);
+    }
+
+    #[inline]
+    pub fn get(&self, identifier: &str) -> Option<&Vec<LdtkSpawnHook>> {
+        self.0.get(identifier)
+    }
+
+    /// Run the hooks that belong to the entity. This is deferred using commands
+    /// so the hooks can read all the resources in the world.
+    pub(crate) fn run_deferred(
+        commands: &mut EntityCommands,
+        entity_instance: EntityInstance,
+        fields: HashMap<String, FieldInstance>,
+    ) {
+        commands.add(move |entity: Entity, world: &mut World| {
+            let mut queue = CommandQueue::default();
+            {
+                let world = &*world;
+                let Some(hooks) = world
+                    .get_resource::<LdtkSpawnHooks>()
+                    .and_then(|h| h.get(&entity_instance.identifier))
+                else {
+                    return;
+                };
+
+                let mut ctx = LdtkSpawnContext {
+                    entity,
+                    entity_instance: &entity_instance,
+                    fields: &fields,
+                    commands: Commands::new(&mut queue, world),
+                    world,
+                };
+                hooks.iter().for_each(|hook| hook(&mut ctx));
+            }
+            queue.apply(world);
+        });
+    }
+}
+
+/// The context of a spawn hook.
+///
+/// You can read any resources from here, and use the commands to modify the entity
+/// or spawn other entities.
+pub struct LdtkSpawnContext<'w, 's> {
+    pub entity: Entity,
+    pub entity_instance: &'w EntityInstance,
+    pub fields: &'w HashMap<String, FieldInstance>,
+    pub commands: Commands<'w, 's>,
+    world: &'w World,
+}
+
+impl<'w, 's> LdtkSpawnContext<'w, 's> {
+    /// Get the commands of the spawned entity.
+    #[inline]
+    pub fn entity_commands(&mut self) -> EntityCommands<'_> {
+        self.commands.entity(self.entity)
+    }
+
+    /// Get a resource. Panics if the resource doesn't exist.
+    #[inline]
+    pub fn resource<R: Resource>(&self) -> &'w R {
+        self.world.resource::<R>()
+    }
+
+    #[inline]
+    pub fn get_resource<R: Resource>(&self) -> Option<&'w R> {
+        self.world.get_resource::<R>()
+    }
+}
+
+/// Takes a snapshot of a component of the entity. The returned function puts it back.
+pub type LdtkComponentSnapshot = fn(&World, Entity) -> Option<LdtkComponentRestorer>;
+pub type LdtkComponentRestorer = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// The components of the `GlobalEntity` entities that are kept when the entities are
+/// initialized again. See `LdtkLoadConfig::refresh_global_entities`.
+///
+/// `Transform` is kept by default. Use `App::preserve_ldtk_global_component` to add more.
+#[derive(Resource)]
+pub struct LdtkGlobalEntitySnapshots {
+    pub(crate) snapshots: Vec<LdtkComponentSnapshot>,
+    pub(crate) pending: HashMap<Entity, Vec<LdtkComponentRestorer>>,
+}
+
+impl Default for LdtkGlobalEntitySnapshots {
+    fn default() -> Self {
+        let mut snapshots = Self {
+            snapshots: Vec::new(),
+            pending: HashMap::new(),
+        };
+        snapshots.register::<Transform>();
+        snapshots
+    }
+}
+
+impl LdtkGlobalEntitySnapshots {
+    pub fn register<C: Component + Clone>(&mut self) {
+        self.snapshots.push(|world, entity| {
+            let component = world.get::<C>(entity)?.clone();
+            Some(Box::new(move |world: &mut World| {
+                if let Some(mut entity) = world.get_entity_mut(entity) {
+                    entity.insert(component);
+                }
+            }))
+        });
+    }
+
+    /// Take a snapshot of the registered components of the entity.
+    /// Use `restore` to put them back.
+    pub fn snapshot(&self, world: &World, entity: Entity) -> Vec<LdtkComponentRestorer> {
+        self.snapshots
+            .iter()
+            .filter_map(|snapshot| snapshot(world, entity))
+            .collect()
+    }
+
+    pub fn restore(world: &mut World, snapshot: Vec<LdtkComponentRestorer>) {
+        snapshot.into_iter().for_each(|restorer| restorer(world));
+    }
+
+    /// Take a snapshot of the entity when the commands are applied.
+    pub(crate) fn snapshot_deferred(commands: &mut EntityCommands) {
+        commands.add(|entity: Entity, world: &mut World| {
+            let Some(snapshots) = world.get_resource::<Self>() else {
+                return;
+            };
+            let snapshot = snapshots.snapshot(world, entity);
+            world
+                .resource_mut::<Self>()
+                .pending
+                .insert(entity, snapshot);
+        });
+    }
+
+    /// Restore the snapshot taken by `snapshot_deferred` when the commands are applied.
+    pub(crate) fn restore_deferred(commands: &mut EntityCommands) {
+        commands.add(|entity: Entity, world: &mut World| {
+            let Some(snapshot) = world
+                .get_resource_mut::<Self>()
+                .and_then(|mut s| s.pending.remove(&entity))
+            else {
+                return;
+            };
+            Self::restore(world, snapshot);
+        });
+    }
+}
+
 pub trait LdtkEnum {
     fn get_identifier(ident: &str) -> Self;
+    /// Same as `get_identifier`, but returns `None` if the identifier is unknown.
+    fn try_get_identifier(ident: &str) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+/// Checks if an LDtk enum value exists in the Rust enum.
+pub type LdtkEnumValidator = fn(&str) -> bool;
+
+/// The Rust enums that the LDtk enums are mapped to, by the identifier of the LDtk enum.
+///
+/// Use `App::register_ldtk_enum` to register enums. The values of the registered enums
+/// are validated when the LDtk file is loaded.
+#[derive(Resource, Default)]
+pub struct LdtkEnumRegistry(pub(crate) HashMap<String, LdtkEnumValidator>);
+
+impl LdtkEnumRegistry {
+    pub fn register<T: LdtkEnum>(&mut self, identifier: &str) {
+        self.0.insert(identifier.to_string(), |ident| {
+            T::try_get_identifier(ident).is_some()
+        });
+    }
+
+    #[inline]
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.0.contains_key(identifier)
+    }
+
+    /// Find the values of the registered enums that don't exist in the Rust enums,
+    /// as `(enum identifier, value)` pairs.
+    pub fn validate<'a>(&self, defs: &'a Definitions) -> Vec<(&'a str, &'a str)> {
+        defs.enums
+            .iter()
+            .chain(defs.external_enums.iter())
+            .filter_map(|def| self.0.get(&def.identifier).map(|v| (def, v)))
+            .flat_map(|(def, validator)| {
+                def.values
+                    .iter()
+                    .filter(|value| !validator(&value.id))
+                    .map(|value| (def.identifier.as_str(), value.id.as_str()))
+            })
+            .collect()
+    }
 }
 
 pub type LdtkEntityTagRegistry = HashMap<String, Box<dyn PhantomLdtkEntityTagTrait>>;
@@ -0,0 +1,224 @@
+use std::fmt::Display;
+
+use bevy::{
+    ecs::system::Resource,
+    log::warn,
+    reflect::Reflect,
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    json::LdtkJson,
+    resources::LdtkLoadConfig,
+    traits::{LdtkEntityRegistry, LdtkEntityTagRegistry, LdtkEnumRegistry},
+};
+
+/// A problem in the LDtk file that may break the loading.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+pub enum LdtkIssue {
+    /// The tileset has no image, or uses an internal atlas of LDtk,
+    /// so the tiles using it are not spawned.
+    MissingTilesetImage {
+        tileset: String,
+    },
+    /// The layer uses a tileset that is not defined.
+    MissingTileset {
+        layer: String,
+        uid: i32,
+    },
+    /// The entity is not registered, and `LdtkLoadConfig::ignore_unregistered_entities`
+    /// is disabled. It's skipped when the level spawns.
+    UnregisteredEntity {
+        identifier: String,
+    },
+    /// The entity tag is not registered, and `LdtkLoadConfig::ignore_unregistered_entity_tags`
+    /// is disabled. It's skipped when the level spawns.
+    UnregisteredEntityTag {
+        tag: String,
+    },
+    /// The enum is registered but does not exist in the file.
+    MissingEnum {
+        identifier: String,
+    },
+    /// The value of a registered enum does not exist in the Rust enum.
+    UnknownEnumValue {
+        identifier: String,
+        value: String,
+    },
+    Unsupported {
+        feature: String,
+    },
+}
+
+impl Display for LdtkIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LdtkIssue::MissingTilesetImage { tileset } => {
+                write!(f, "Tileset {} has no image", tileset)
+            }
+            LdtkIssue::MissingTileset { layer, uid } => write!(
+                f,
+                "Layer {} uses tileset {} which does not exist",
+                layer, uid
+            ),
+            LdtkIssue::UnregisteredEntity { identifier } => {
+                write!(f, "Entity {} is not registered", identifier)
+            }
+            LdtkIssue::UnregisteredEntityTag { tag } => {
+                write!(f, "Entity tag {} is not registered", tag)
+            }
+            LdtkIssue::MissingEnum { identifier } => {
+                write!(f, "Registered LDtk enum {} does not exist", identifier)
+            }
+            LdtkIssue::UnknownEnumValue { identifier, value } => write!(
+                f,
+                "Value {} of LDtk enum {} does not exist in the registered enum",
+                value, identifier
+            ),
+            LdtkIssue::Unsupported { feature } => write!(f, "{} is not supported", feature),
+        }
+    }
+}
+
+/// The issues found in the LDtk file, checked every time the file is loaded.
+///
+/// The issues are also logged as warnings. Enable `LdtkLoadConfig::strict_validation`
+/// to panic instead.
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+pub struct LdtkValidationReport {
+    pub issues: Vec<LdtkIssue>,
+}
+
+impl LdtkValidationReport {
+    pub fn new(
+        ldtk_json: &LdtkJson,
+        config: &LdtkLoadConfig,
+        entity_registry: Option<&LdtkEntityRegistry>,
+        entity_tag_registry: Option<&LdtkEntityTagRegistry>,
+        enum_registry: Option<&LdtkEnumRegistry>,
+    ) -> Self {
+        let defs = &ldtk_json.defs;
+        let mut issues = Vec::new();
+
+        if ldtk_json.world_layout.is_none() {
+            issues.push(LdtkIssue::Unsupported {
+                feature: "Multi-worlds".to_string(),
+            });
+        }
+
+        defs.tilesets
+            .iter()
+            .filter(|tileset| tileset.rel_path.is_none())
+            .for_each(|tileset| {
+                issues.push(LdtkIssue::MissingTilesetImage {
+                    tileset: tileset.identifier.clone(),
+                });
+            });
+
+        let tilesets = defs.tilesets.iter().map(|t| t.uid).collect::<HashSet<_>>();
+        let mut missing_tilesets = defs
+            .layers
+            .iter()
+            .filter_map(|layer| layer.tileset_def_uid.map(|uid| (&layer.identifier, uid)))
+            .chain(
+                ldtk_json
+                    .levels
+                    .iter()
+                    .flat_map(|level| level.layer_instances.iter())
+                    .filter_map(|layer| layer.tileset_def_uid.map(|uid| (&layer.identifier, uid))),
+            )
+            .filter(|(_, uid)| !tilesets.contains(uid))
+            .collect::<Vec<_>>();
+        missing_tilesets.sort();
+        missing_tilesets.dedup();
+        missing_tilesets.into_iter().for_each(|(layer, uid)| {
+            issues.push(LdtkIssue::MissingTileset {
+                layer: layer.clone(),
+                uid,
+            });
+        });
+
+        if !config.ignore_unregistered_entities {
+            defs.entities
+                .iter()
+                .filter(|entity| config.should_load_entity(&entity.identifier))
+                .filter(|entity| {
+                    !entity_registry.is_some_and(|r| r.contains_key(&entity.identifier))
+                })
+                .for_each(|entity| {
+                    issues.push(LdtkIssue::UnregisteredEntity {
+                        identifier: entity.identifier.clone(),
+                    });
+                });
+        }
+        if !config.ignore_unregistered_entity_tags {
+            // The definitions don't keep the tags, so they are collected from the instances.
+            ldtk_json
+                .levels
+                .iter()
+                .flat_map(|level| level.layer_instances.iter())
+                .flat_map(|layer| layer.entity_instances.iter())
+                .filter(|entity| config.should_load_entity(&entity.identifier))
+                .flat_map(|entity| entity.tags.iter())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter(|tag| !entity_tag_registry.is_some_and(|r| r.contains_key(*tag)))
+                .for_each(|tag| {
+                    issues.push(LdtkIssue::UnregisteredEntityTag { tag: tag.clone() });
+                });
+        }
+
+        if let Some(registry) = enum_registry {
+            let enums = defs
+                .enums
+                .iter()
+                .chain(defs.external_enums.iter())
+                .map(|def| (def.identifier.as_str(), def))
+                .collect::<HashMap<_, _>>();
+            registry
+                .0
+                .keys()
+                .filter(|identifier| !enums.contains_key(identifier.as_str()))
+                .for_each(|identifier| {
+                    issues.push(LdtkIssue::MissingEnum {
+                        identifier: identifier.clone(),
+                    });
+                });
+            registry
+                .validate(defs)
+                .into_iter()
+                .for_each(|(identifier, value)| {
+                    issues.push(LdtkIssue::UnknownEnumValue {
+                        identifier: identifier.to_string(),
+                        value: value.to_string(),
+                    });
+                });
+        }
+
+        Self { issues }
+    }
+
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Log the issues, or panic if `strict` and there are any.
+    pub(crate) fn report(&self, strict: bool) {
+        if strict && !self.is_ok() {
+            panic!(
+                "The LDtk file has {} issue(s):\n{}",
+                self.issues.len(),
+                self.issues
+                    .iter()
+                    .map(|issue| issue.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
+
+        self.issues
+            .iter()
+            .for_each(|issue| warn!("LDtk validation: {}!", issue));
+    }
+}
@@ -24,38 +24,64 @@ pub mod tilemap;
 pub mod utils;
 
 pub const MAX_LAYER_COUNT: usize = 4;
+/// Use `tilemap::chunking::suggest_chunk_size` to pick one that fits your map and view.
 pub const DEFAULT_CHUNK_SIZE: u32 = 16;
 
 pub mod prelude {
     #[cfg(feature = "algorithm")]
     pub use crate::algorithm::{
-        pathfinding::{Path, PathFinder},
+        pathfinding::{Path, PathFinder, PathFollower},
         wfc::WfcRunner,
     };
     #[cfg(feature = "ldtk")]
-    pub use crate::ldtk::resources::{LdtkAssets, LdtkLevelManager};
+    pub use crate::ldtk::resources::{
+        LdtkAssets, LdtkLevelManager, LdtkLevelStreaming, LdtkVisibleDepths,
+    };
     pub use crate::math::{aabb::Aabb2d, TileArea};
+    pub use crate::render::culling::{CullingVolume, TilemapCullingVolumes};
     #[cfg(feature = "serializing")]
     pub use crate::serializing::{
         chunk::{
             load::{ChunkLoadCache, ChunkLoadConfig},
             save::{ChunkSaveCache, ChunkSaveConfig},
         },
-        map::{load::TilemapLoader, save::TilemapSaver},
+        map::{
+            load::TilemapLoader,
+            save::TilemapSaver,
+            savegame::{Persistent, SaveBundle},
+        },
     };
     #[cfg(feature = "tiled")]
-    pub use crate::tiled::resources::{TiledLoadConfig, TiledTilemapManger};
+    pub use crate::tiled::{
+        parallax::{ParallaxCamera, ParallaxLayersBuilder},
+        resources::{TiledLoadConfig, TiledTilemapManger},
+    };
     #[cfg(feature = "physics")]
-    pub use crate::tilemap::physics::{DataPhysicsTilemap, PhysicsTile, PhysicsTilemap};
+    pub use crate::tilemap::physics::{
+        nav::TilemapNavObstacles, DataPhysicsTilemap, PhysicsTile, PhysicsTilemap,
+    };
     pub use crate::tilemap::{
-        bundles::{StandardPureColorTilemapBundle, StandardTilemapBundle},
-        chunking::camera::{CameraChunkUpdater, CameraChunkUpdation},
+        audio::{TileAudioEmitter, TilemapAudioEmitters},
+        bundles::{HexTilemapBundle, StandardPureColorTilemapBundle, StandardTilemapBundle},
+        chunking::{
+            budget::StreamingBudget,
+            camera::{CameraChunkUpdater, CameraChunkUpdation},
+        },
+        command::{
+            DespawnTilemapRegion, RemoveTileCommand, SetTileCommand, TileCommand,
+            TilemapCommandQueue,
+        },
+        decal::{Decal, TilemapDecals},
+        kind::{TileKindDescriptor, TileKindRegistry},
         map::{
             TilePivot, TileRenderSize, TilemapAnimations, TilemapLayerOpacities, TilemapName,
             TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTextureDescriptor,
-            TilemapTransform, TilemapType,
+            TilemapTransform, TilemapTransformSync, TilemapType,
         },
-        tile::{RawTileAnimation, TileBuilder, TileLayer, TileUpdater},
+        occluder::TilemapOccluders,
+        occupancy::TilemapOccupancy,
+        tick::{TileRandomTick, TilemapRandomTick},
+        tile::{RawTileAnimation, TileAnimationMode, TileBuilder, TileLayer, TileUpdater},
     };
 }
 
@@ -13,9 +13,13 @@ use bevy::{
     },
 };
 
-use crate::tilemap::map::TilemapType;
+use crate::tilemap::map::{TilemapAlphaMode, TilemapType};
 
-use super::{extract::ExtractedTilemap, material::TilemapMaterial};
+use super::{
+    culling::{CullingVolume, MAX_CULLING_VOLUMES},
+    extract::ExtractedTilemap,
+    material::TilemapMaterial,
+};
 
 pub trait UniformBuffer<E, U: ShaderType + WriteInto + 'static> {
     fn insert(&mut self, extracted: &E) -> DynamicOffsetComponent<U>;
@@ -102,6 +106,20 @@ pub struct TilemapUniform {
     pub axis_dir: Vec2,
     pub hex_legs: f32,
     pub time: f32,
+    pub fade_in_duration: f32,
+    pub tint_jitter_strength: f32,
+    pub tint_jitter_seed: u32,
+    pub culling_volumes: [Vec4; MAX_CULLING_VOLUMES],
+    /// The count of culling volumes, 0 if the tilemap has none.
+    pub culling_volume_count: u32,
+    /// The i-th bit is set if the i-th culling volume is a rect.
+    pub culling_volume_rects: u32,
+    /// How many tiles are in a row of the texture. Used by tiles with footprints.
+    pub texture_columns: u32,
+    /// The alpha cutoff of `TilemapAlphaMode::Mask`, 0 for the other modes.
+    pub alpha_cutoff: f32,
+    /// The z of the tiles when drawn for 3d cameras.
+    pub depth: f32,
     #[cfg(feature = "atlas")]
     pub texture_tiled_size: bevy::math::IVec2,
     #[cfg(feature = "atlas")]
@@ -142,6 +160,12 @@ impl<M: TilemapMaterial> UniformBuffer<(&ExtractedTilemap<M>, f32), TilemapUnifo
             }
         };
 
+        let texture_columns = extracted
+            .texture
+            .as_ref()
+            .map(|tex| tex.desc.size.x / tex.desc.tile_size.x)
+            .unwrap_or_default();
+
         #[cfg(feature = "atlas")]
         let (texture_tiled_size, tile_uv_size) = {
             if let Some(tex) = extracted.texture.as_ref() {
@@ -154,25 +178,57 @@ impl<M: TilemapMaterial> UniformBuffer<(&ExtractedTilemap<M>, f32), TilemapUnifo
             }
         };
 
-        DynamicOffsetComponent::new(self.buffer().push(&TilemapUniform {
-            translation: extracted.transform.translation,
-            rotation: extracted.transform.get_rotation_matrix(),
-            uv_rotation,
-            tile_render_size: extracted.tile_render_size,
-            slot_size: extracted.slot_size,
-            pivot: extracted.tile_pivot,
-            layer_opacities: extracted.layer_opacities,
-            axis_dir: extracted.axis_flip.as_vec2(),
-            hex_legs: match extracted.ty {
-                TilemapType::Hexagonal(legs) => legs as f32,
-                _ => 0.,
-            },
-            time,
-            #[cfg(feature = "atlas")]
-            texture_tiled_size,
-            #[cfg(feature = "atlas")]
-            tile_uv_size,
-        }))
+        let mut culling_volumes = [Vec4::ZERO; MAX_CULLING_VOLUMES];
+        let mut culling_volume_count = 0;
+        let mut culling_volume_rects = 0;
+        if let Some(volumes) = extracted.culling_volumes.as_ref() {
+            for (i, volume) in volumes.0.iter().take(MAX_CULLING_VOLUMES).enumerate() {
+                culling_volumes[i] = volume.as_vec4();
+                if let CullingVolume::Rect(_) = volume {
+                    culling_volume_rects |= 1 << i;
+                }
+                culling_volume_count += 1;
+            }
+        }
+
+        let alpha_cutoff = match extracted.alpha_mode {
+            TilemapAlphaMode::Mask(cutoff) => cutoff,
+            _ => 0.,
+        };
+
+        DynamicOffsetComponent::new(
+            self.buffer().push(&TilemapUniform {
+                translation: extracted.transform.translation,
+                rotation: extracted.transform.get_rotation_matrix(),
+                uv_rotation,
+                tile_render_size: extracted.tile_render_size,
+                slot_size: extracted.slot_size,
+                pivot: extracted.tile_pivot,
+                layer_opacities: extracted.layer_opacities,
+                axis_dir: extracted.axis_flip.as_vec2(),
+                hex_legs: match extracted.ty {
+                    TilemapType::Hexagonal(legs) => legs as f32,
+                    _ => 0.,
+                },
+                time,
+                fade_in_duration: extracted.fade_in.unwrap_or_default(),
+                tint_jitter_strength: extracted
+                    .tint_jitter
+                    .map(|j| j.strength)
+                    .unwrap_or_default(),
+                tint_jitter_seed: extracted.tint_jitter.map(|j| j.seed).unwrap_or_default(),
+                culling_volumes,
+                culling_volume_count,
+                culling_volume_rects,
+                texture_columns,
+                alpha_cutoff,
+                depth: extracted.depth_write.map(|d| d.z).unwrap_or_default(),
+                #[cfg(feature = "atlas")]
+                texture_tiled_size,
+                #[cfg(feature = "atlas")]
+                tile_uv_size,
+            }),
+        )
     }
 
     #[inline]
@@ -2,8 +2,8 @@ use std::marker::PhantomData;
 
 use bevy::{
     ecs::{component::Component, entity::EntityHashMap, event::Event},
-    math::{IVec2, IVec4, UVec4},
-    prelude::{Entity, Mesh, Resource, Vec3, Vec4},
+    math::{IVec2, IVec4, UVec2, UVec4},
+    prelude::{Entity, Mesh, Resource, Vec2, Vec3, Vec4},
     reflect::Reflect,
     render::{
         mesh::{GpuBufferInfo, GpuMesh, Indices},
@@ -17,8 +17,8 @@ use bevy::{
 use crate::{
     math::{aabb::Aabb2d, extension::DivToFloor},
     tilemap::{
-        map::{TilemapTexture, TilemapType},
-        tile::TileTexture,
+        map::{TilemapRenderFlags, TilemapTexture, TilemapType},
+        tile::{TileAnimationStart, TileTexture},
     },
     MAX_LAYER_COUNT,
 };
@@ -26,10 +26,30 @@ use crate::{
 use super::{
     extract::{ExtractedTile, ExtractedTilemap},
     material::TilemapMaterial,
-    TILEMAP_MESH_ATTR_COLOR, TILEMAP_MESH_ATTR_FLIP, TILEMAP_MESH_ATTR_INDEX,
+    TILEMAP_MESH_ATTR_ANIM_START, TILEMAP_MESH_ATTR_COLOR, TILEMAP_MESH_ATTR_FLIP,
+    TILEMAP_MESH_ATTR_FOOTPRINT, TILEMAP_MESH_ATTR_INDEX, TILEMAP_MESH_ATTR_LOAD_TIME,
     TILEMAP_MESH_ATTR_TEX_INDICES,
 };
 
+/// Limits the GPU memory used by the chunk meshes of all the tilemaps.
+///
+/// When the meshes take more than `max_bytes`, the meshes of the chunks that
+/// have been invisible for the longest time are dropped. The tiles are kept, so the meshes
+/// are rebuilt as soon as the chunks become visible again.
+///
+/// **Notice**: Visible chunks are never evicted, so the budget can still be exceeded
+/// if everything on screen takes more than `max_bytes`.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct RenderChunkMemoryBudget {
+    pub max_bytes: u64,
+}
+
+impl RenderChunkMemoryBudget {
+    pub fn new(max_bytes: u64) -> Self {
+        Self { max_bytes }
+    }
+}
+
 #[derive(Component, Default, Debug, Clone, Reflect)]
 pub struct UnloadRenderChunk(pub Vec<IVec2>);
 
@@ -49,6 +69,12 @@ pub struct MeshTileData {
     pub texture_indices: IVec4,
     pub color: Vec4,
     pub flip: UVec4,
+    // Overrides the pivot of the tilemap
+    pub pivot: Option<Vec2>,
+    // The cells this tile covers
+    pub footprint: UVec2,
+    // The time when the animation of this tile started
+    pub anim_start: f32,
 }
 
 #[derive(Clone)]
@@ -63,11 +89,21 @@ pub struct TilemapRenderChunk<M: TilemapMaterial> {
     pub mesh: Mesh,
     pub gpu_mesh: Option<GpuMesh>,
     pub aabb: Aabb2d,
+    /// How many cells the tiles with footprints reach out of the chunk.
+    pub overflow: u32,
+    /// The time when this chunk is created. Only used when the tilemap fades in chunks.
+    pub load_time: Option<f32>,
+    /// Whether the tile colors are left out of the mesh. See `TilemapRenderFlags::NO_COLOR`.
+    pub no_color: bool,
+    /// The last time this chunk was visible.
+    pub last_visible: f32,
+    /// Whether the mesh is dropped because of the `RenderChunkMemoryBudget`.
+    pub evicted: bool,
     pub marker: PhantomData<M>,
 }
 
 impl<M: TilemapMaterial> TilemapRenderChunk<M> {
-    pub fn from_index(index: IVec2, tilemap: &ExtractedTilemap<M>) -> Self {
+    pub fn from_index(index: IVec2, tilemap: &ExtractedTilemap<M>, time: f32) -> Self {
         TilemapRenderChunk {
             visible: true,
             index: index.div_to_floor(IVec2::splat(tilemap.chunk_size as i32)),
@@ -90,6 +126,11 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
                 tilemap.slot_size,
                 tilemap.transform,
             ),
+            overflow: 0,
+            load_time: tilemap.fade_in.map(|_| time),
+            no_color: tilemap.render_flags.contains(TilemapRenderFlags::NO_COLOR),
+            last_visible: time,
+            evicted: false,
             marker: PhantomData,
         }
     }
@@ -110,6 +151,9 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
         let mut vertex_indices = Vec::with_capacity(len * 6);
         let mut color = Vec::with_capacity(len * 4);
         let mut flip = Vec::with_capacity(len * 4);
+        let mut footprint = Vec::with_capacity(len * 4);
+        let mut load_time = Vec::with_capacity(len * 4);
+        let mut anim_start = Vec::with_capacity(len * 4);
 
         for tile_data in self.tiles.iter() {
             if let Some(tile) = tile_data {
@@ -120,9 +164,17 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
                         tile.texture_indices,
                         tile.texture_indices,
                     ]);
+                    anim_start.extend_from_slice(&[
+                        tile.anim_start,
+                        tile.anim_start,
+                        tile.anim_start,
+                        tile.anim_start,
+                    ]);
                 }
 
-                let pos = Vec3::ZERO;
+                // The position is not used for positioning (which is done in the shader),
+                // so xy stores the pivot of the tile and z indicates whether it's overridden.
+                let pos = tile.pivot.map(|p| p.extend(1.)).unwrap_or(Vec3::ZERO);
                 positions.extend_from_slice(&[pos, pos, pos, pos]);
 
                 vertex_indices.extend_from_slice(&[
@@ -137,8 +189,19 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
                 v_index += 4;
 
                 grid_indices.extend_from_slice(&[tile.index, tile.index, tile.index, tile.index]);
-                color.extend_from_slice(&[tile.color, tile.color, tile.color, tile.color]);
+                if !self.no_color {
+                    color.extend_from_slice(&[tile.color, tile.color, tile.color, tile.color]);
+                }
                 flip.extend_from_slice(&[tile.flip, tile.flip, tile.flip, tile.flip]);
+                footprint.extend_from_slice(&[
+                    tile.footprint,
+                    tile.footprint,
+                    tile.footprint,
+                    tile.footprint,
+                ]);
+                if let Some(time) = self.load_time {
+                    load_time.extend_from_slice(&[time, time, time, time]);
+                }
             }
         }
 
@@ -146,11 +209,23 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
             .insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
         self.mesh
             .insert_attribute(TILEMAP_MESH_ATTR_INDEX, grid_indices);
-        self.mesh.insert_attribute(TILEMAP_MESH_ATTR_COLOR, color);
+        if self.no_color {
+            self.mesh.remove_attribute(TILEMAP_MESH_ATTR_COLOR);
+        } else {
+            self.mesh.insert_attribute(TILEMAP_MESH_ATTR_COLOR, color);
+        }
+        self.mesh
+            .insert_attribute(TILEMAP_MESH_ATTR_FOOTPRINT, footprint);
         if !is_pure_color {
             self.mesh
                 .insert_attribute(TILEMAP_MESH_ATTR_TEX_INDICES, texture_indices);
-            self.mesh.insert_attribute(TILEMAP_MESH_ATTR_FLIP, flip)
+            self.mesh.insert_attribute(TILEMAP_MESH_ATTR_FLIP, flip);
+            self.mesh
+                .insert_attribute(TILEMAP_MESH_ATTR_ANIM_START, anim_start);
+        }
+        if self.load_time.is_some() {
+            self.mesh
+                .insert_attribute(TILEMAP_MESH_ATTR_LOAD_TIME, load_time);
         }
         self.mesh.insert_indices(Indices::U32(vertex_indices));
 
@@ -186,14 +261,37 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
         });
 
         self.dirty_mesh = false;
+        self.evicted = false;
+    }
+
+    /// The size of the vertex and index buffers of this chunk in bytes.
+    pub fn gpu_memory(&self) -> u64 {
+        self.gpu_mesh.as_ref().map_or(0, |mesh| {
+            mesh.vertex_buffer.size()
+                + match &mesh.buffer_info {
+                    GpuBufferInfo::Indexed { buffer, .. } => buffer.size(),
+                    GpuBufferInfo::NonIndexed => 0,
+                }
+        })
+    }
+
+    /// Drop the mesh of this chunk. It will be rebuilt once the chunk is visible again.
+    pub fn evict_mesh(&mut self) {
+        self.gpu_mesh = None;
+        self.mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        self.dirty_mesh = true;
+        self.evicted = true;
     }
 
     /// Set a tile in the chunk. Overwrites the previous tile.
-    pub fn set_tile(&mut self, index: usize, tile: Option<&ExtractedTile>) {
+    pub fn set_tile(&mut self, index: usize, tile: Option<(&ExtractedTile, &TileAnimationStart)>) {
         // TODO fix this. This allows the tile sort by y axis. But this approach looks weird.
         let index = self.tiles.len() - index - 1;
 
-        let Some(tile) = tile else {
+        let Some((tile, anim_start)) = tile else {
             self.tiles[index] = None;
             self.dirty_mesh = true;
             return;
@@ -223,11 +321,27 @@ impl<M: TilemapMaterial> TilemapRenderChunk<M> {
             }
         };
 
+        let footprint = tile.footprint.unwrap_or(UVec2::ONE);
+        let overflow = footprint.max_element() - 1;
+        if overflow > self.overflow {
+            // The tile may reach out of this chunk, so the chunk needs to be visible
+            // as long as the tile is visible.
+            let cell_size = (self.aabb.size() / self.size as f32)
+                / (1. + 2. * self.overflow as f32 / self.size as f32);
+            let expand = (overflow - self.overflow) as f32 * cell_size;
+            self.aabb.min -= expand;
+            self.aabb.max += expand;
+            self.overflow = overflow;
+        }
+
         self.tiles[index] = Some(MeshTileData {
             index: tile_index,
             texture_indices,
             color: tile.color,
             flip,
+            pivot: tile.pivot,
+            footprint,
+            anim_start: anim_start.0,
         });
         self.dirty_mesh = true;
     }
@@ -250,9 +364,52 @@ impl<M: TilemapMaterial> RenderChunkStorage<M> {
     /// Update the mesh for all chunks of a tilemap.
     pub fn prepare_chunks(&mut self, tilemap: &ExtractedTilemap<M>, render_device: &RenderDevice) {
         if let Some(chunks) = self.value.get_mut(&tilemap.id) {
-            chunks
-                .values_mut()
-                .for_each(|c| c.try_update_mesh(render_device));
+            chunks.values_mut().for_each(|c| {
+                // The vertex layout must match the pipeline.
+                if tilemap.fade_in.is_some() != c.load_time.is_some() {
+                    c.load_time = tilemap.fade_in.map(|_| 0.);
+                    c.dirty_mesh = true;
+                }
+                let no_color = tilemap.render_flags.contains(TilemapRenderFlags::NO_COLOR);
+                if no_color != c.no_color {
+                    c.no_color = no_color;
+                    c.dirty_mesh = true;
+                }
+                // Evicted chunks are rebuilt on demand.
+                if c.evicted && !c.visible {
+                    return;
+                }
+                c.try_update_mesh(render_device)
+            });
+        }
+    }
+
+    /// Drop the meshes of the least recently visible chunks until they fit in `budget`.
+    pub fn evict_chunks(&mut self, budget: &RenderChunkMemoryBudget) {
+        let mut used = self
+            .value
+            .values()
+            .flat_map(|chunks| chunks.values())
+            .map(|c| c.gpu_memory())
+            .sum::<u64>();
+        if used <= budget.max_bytes {
+            return;
+        }
+
+        let mut candidates = self
+            .value
+            .values_mut()
+            .flat_map(|chunks| chunks.values_mut())
+            .filter(|c| !c.visible && c.gpu_mesh.is_some())
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by(|a, b| a.last_visible.total_cmp(&b.last_visible));
+
+        for chunk in candidates {
+            if used <= budget.max_bytes {
+                break;
+            }
+            used -= chunk.gpu_memory();
+            chunk.evict_mesh();
         }
     }
 
@@ -1,13 +1,19 @@
 use bevy::{
     ecs::{
         component::Component,
-        entity::Entity,
-        system::{ParallelCommands, Res, Resource},
+        entity::{Entity, EntityHashMap},
+        system::{ParallelCommands, Res, Resource, SystemParam},
     },
+    math::{Vec2, Vec4},
     prelude::{Query, ResMut},
+    reflect::Reflect,
+    time::Time,
 };
 
-use crate::{math::CameraAabb2d, tilemap::map::TilemapAabbs};
+use crate::{
+    math::{aabb::Aabb2d, CameraAabb2d},
+    tilemap::map::TilemapAabbs,
+};
 
 use super::{
     chunk::RenderChunkStorage,
@@ -27,6 +33,136 @@ impl Default for FrustumCulling {
     }
 }
 
+/// The max count of culling volumes of a tilemap that are sent to the shader.
+pub const MAX_CULLING_VOLUMES: usize = 8;
+
+/// A shape in world space that tiles need to be inside to be drawn.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub enum CullingVolume {
+    Circle { center: Vec2, radius: f32 },
+    Rect(Aabb2d),
+}
+
+impl CullingVolume {
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            CullingVolume::Circle { center, radius } => {
+                center.distance_squared(point) <= radius * radius
+            }
+            CullingVolume::Rect(rect) => rect.contains(point),
+        }
+    }
+
+    pub fn is_intersected(&self, aabb: Aabb2d) -> bool {
+        match self {
+            CullingVolume::Circle { center, radius } => {
+                center.clamp(aabb.min, aabb.max).distance_squared(*center) <= radius * radius
+            }
+            CullingVolume::Rect(rect) => rect.is_intersected(aabb),
+        }
+    }
+
+    /// Circles are `(center, radius, 0)` and rects are `(min, max)`.
+    pub(crate) fn as_vec4(&self) -> Vec4 {
+        match self {
+            CullingVolume::Circle { center, radius } => center.extend(*radius).extend(0.),
+            CullingVolume::Rect(rect) => Vec4::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y),
+        }
+    }
+}
+
+/// Extra culling volumes of a tilemap, like the light radius of the player or the bounds of a room.
+///
+/// A tile is drawn only if its center is inside at least one of the volumes.
+/// This works even if `FrustumCulling` is disabled.
+///
+/// **Notice**: Only the first `MAX_CULLING_VOLUMES` volumes are taken into account.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+pub struct TilemapCullingVolumes(pub Vec<CullingVolume>);
+
+impl TilemapCullingVolumes {
+    pub fn is_intersected(&self, aabb: Aabb2d) -> bool {
+        self.0
+            .iter()
+            .take(MAX_CULLING_VOLUMES)
+            .any(|v| v.is_intersected(aabb))
+    }
+}
+
+/// Insert this resource to pause the animations of the tilemaps that have been
+/// completely out of view for `frames` frames. The animation data of them is not uploaded,
+/// which saves some work every frame in worlds with many animated tilemaps.
+///
+/// The animations jump to the current time as soon as any chunk is visible again.
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct TilemapAnimationLod {
+    pub frames: u32,
+}
+
+impl TilemapAnimationLod {
+    pub fn new(frames: u32) -> Self {
+        Self { frames }
+    }
+}
+
+/// How many frames the tilemaps have been out of view, and the time they are paused at.
+#[derive(Resource, Default)]
+pub struct TilemapAnimationLodState(pub(crate) EntityHashMap<(u32, f32)>);
+
+impl TilemapAnimationLodState {
+    /// Returns the time to render the tilemap with, and whether its animations are paused.
+    pub(crate) fn update(
+        &mut self,
+        tilemap: Entity,
+        visible: bool,
+        lod: &TilemapAnimationLod,
+        time: f32,
+    ) -> (f32, bool) {
+        if visible {
+            self.0.remove(&tilemap);
+            return (time, false);
+        }
+
+        let (frames, paused_at) = self.0.entry(tilemap).or_insert((0, time));
+        *frames = frames.saturating_add(1);
+        if *frames > lod.frames {
+            (*paused_at, true)
+        } else {
+            *paused_at = time;
+            (time, false)
+        }
+    }
+}
+
+/// The time to render the tilemaps with, which stops for the tilemaps
+/// paused by `TilemapAnimationLod`.
+#[derive(SystemParam)]
+pub struct TilemapAnimationTime<'w> {
+    time: Res<'w, Time>,
+    lod: Option<Res<'w, TilemapAnimationLod>>,
+    lod_state: ResMut<'w, TilemapAnimationLodState>,
+}
+
+impl TilemapAnimationTime<'_> {
+    /// Returns the time to render the tilemap with, and whether its animations are paused.
+    pub(crate) fn get<M: TilemapMaterial>(
+        &mut self,
+        tilemap: Entity,
+        render_chunks: &RenderChunkStorage<M>,
+    ) -> (f32, bool) {
+        let time = self.time.elapsed_seconds();
+        let Some(lod) = self.lod.as_deref() else {
+            return (time, false);
+        };
+
+        // Tilemaps without chunks yet count as visible.
+        let visible = !render_chunks
+            .get_chunks(tilemap)
+            .is_some_and(|chunks| chunks.values().all(|c| !c.visible));
+        self.lod_state.update(tilemap, visible, lod, time)
+    }
+}
+
 pub fn cull_tilemaps(
     commands: ParallelCommands,
     tilemaps: Query<(Entity, &TilemapAabbs)>,
@@ -55,24 +191,27 @@ pub fn cull_chunks<M: TilemapMaterial>(
     mut render_chunk_storage: ResMut<RenderChunkStorage<M>>,
     cameras: Query<&ExtractedView>,
     culling: Res<FrustumCulling>,
+    time: Res<Time>,
 ) {
-    if !culling.0 {
-        return;
-    }
+    tilemaps.iter().for_each(|tilemap| {
+        let Some(chunks) = render_chunk_storage.get_chunks_mut(tilemap.id) else {
+            return;
+        };
 
-    cameras.iter().for_each(|cam_aabb| {
-        tilemaps.iter().for_each(|tilemap| {
-            let Some(chunks) = render_chunk_storage.get_chunks_mut(tilemap.id) else {
-                return;
-            };
-
-            chunks.values_mut().for_each(|c| {
-                if c.aabb.is_intersected(cam_aabb.0) {
-                    c.visible = true;
-                } else {
-                    c.visible = false;
-                }
-            });
+        chunks.values_mut().for_each(|c| {
+            let in_view = !culling.0
+                || cameras
+                    .iter()
+                    .any(|cam_aabb| c.aabb.is_intersected(cam_aabb.0));
+            c.visible = in_view
+                && tilemap
+                    .culling_volumes
+                    .as_ref()
+                    .map(|volumes| volumes.is_intersected(c.aabb))
+                    .unwrap_or(true);
+            if c.visible {
+                c.last_visible = time.elapsed_seconds();
+            }
         });
     });
 }
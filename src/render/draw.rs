@@ -1,7 +1,6 @@
 use std::marker::PhantomData;
 
 use bevy::{
-    core_pipeline::core_2d::Transparent2d,
     ecs::{
         query::ROQueryItem,
         system::{
@@ -12,7 +11,10 @@ use bevy::{
     log::error,
     render::{
         mesh::GpuBufferInfo,
-        render_phase::{RenderCommand, RenderCommandResult, TrackedRenderPass},
+        render_phase::{
+            CachedRenderPipelinePhaseItem, PhaseItem, RenderCommand, RenderCommandResult,
+            TrackedRenderPass,
+        },
         render_resource::PipelineCache,
         view::ViewUniformOffset,
     },
@@ -37,7 +39,7 @@ pub type DrawTilemap<M> = (
 );
 
 pub struct SetPipeline;
-impl RenderCommand<Transparent2d> for SetPipeline {
+impl<P: CachedRenderPipelinePhaseItem> RenderCommand<P> for SetPipeline {
     type Param = SRes<PipelineCache>;
 
     type ViewQuery = ();
@@ -46,20 +48,20 @@ impl RenderCommand<Transparent2d> for SetPipeline {
 
     #[inline]
     fn render<'w>(
-        item: &Transparent2d,
+        item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         pipeline_cache: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         let pipeline_cache = pipeline_cache.into_inner();
-        if let Some(pipeline) = pipeline_cache.get_render_pipeline(item.pipeline) {
+        if let Some(pipeline) = pipeline_cache.get_render_pipeline(item.cached_pipeline()) {
             pass.set_render_pipeline(pipeline);
             RenderCommandResult::Success
         } else {
             error!(
                 "Failed to get render pipeline!\n{:?}",
-                pipeline_cache.get_render_pipeline_state(item.pipeline)
+                pipeline_cache.get_render_pipeline_state(item.cached_pipeline())
             );
             RenderCommandResult::Failure
         }
@@ -67,7 +69,7 @@ impl RenderCommand<Transparent2d> for SetPipeline {
 }
 
 pub struct SetTilemapViewBindGroup<const I: usize>;
-impl<const I: usize> RenderCommand<Transparent2d> for SetTilemapViewBindGroup<I> {
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetTilemapViewBindGroup<I> {
     type Param = ();
 
     type ViewQuery = (Read<ViewUniformOffset>, Read<TilemapViewBindGroup>);
@@ -76,7 +78,7 @@ impl<const I: usize> RenderCommand<Transparent2d> for SetTilemapViewBindGroup<I>
 
     #[inline]
     fn render<'w>(
-        _item: &Transparent2d,
+        _item: &P,
         (view_uniform_offset, view_bind_group): ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         _param: SystemParamItem<'w, '_, Self::Param>,
@@ -90,7 +92,7 @@ impl<const I: usize> RenderCommand<Transparent2d> for SetTilemapViewBindGroup<I>
 
 #[derive(Default)]
 pub struct SetTilemapUniformBufferBindGroup<const I: usize, M: TilemapMaterial>(PhantomData<M>);
-impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
+impl<P: PhaseItem, const I: usize, M: TilemapMaterial> RenderCommand<P>
     for SetTilemapUniformBufferBindGroup<I, M>
 {
     type Param = SRes<TilemapBindGroups<M>>;
@@ -101,7 +103,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
     #[inline]
     fn render<'w>(
-        _item: &Transparent2d,
+        _item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         uniform_data: Option<ROQueryItem<'w, Self::ItemQuery>>,
         bind_groups: SystemParamItem<'w, '_, Self::Param>,
@@ -122,7 +124,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
 #[derive(Default)]
 pub struct SetTilemapMaterialBindGroup<const I: usize, M: TilemapMaterial>(PhantomData<M>);
-impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
+impl<P: PhaseItem, const I: usize, M: TilemapMaterial> RenderCommand<P>
     for SetTilemapMaterialBindGroup<I, M>
 {
     type Param = (SRes<TilemapBindGroups<M>>, SRes<TilemapInstances<M>>);
@@ -133,13 +135,13 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
     #[inline]
     fn render<'w>(
-        item: &Transparent2d,
+        item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         (bind_groups, instances): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        let Some(inst) = instances.0.get(&item.entity) else {
+        let Some(inst) = instances.0.get(&item.entity()) else {
             error!("Failed to get tilemap instance!");
             return RenderCommandResult::Failure;
         };
@@ -157,7 +159,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
 #[derive(Default)]
 pub struct SetTilemapStorageBufferBindGroup<const I: usize, M: TilemapMaterial>(PhantomData<M>);
-impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
+impl<P: PhaseItem, const I: usize, M: TilemapMaterial> RenderCommand<P>
     for SetTilemapStorageBufferBindGroup<I, M>
 {
     type Param = SRes<TilemapBindGroups<M>>;
@@ -168,7 +170,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
     #[inline]
     fn render<'w>(
-        item: &Transparent2d,
+        item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         bind_groups: SystemParamItem<'w, '_, Self::Param>,
@@ -177,7 +179,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
         if let Some(bind_group) = bind_groups
             .into_inner()
             .tilemap_storage_buffers
-            .get(&item.entity)
+            .get(&item.entity())
         {
             pass.set_bind_group(I, bind_group, &[]);
         }
@@ -188,7 +190,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
 #[derive(Default)]
 pub struct SetTilemapColorTextureBindGroup<const I: usize, M: TilemapMaterial>(PhantomData<M>);
-impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
+impl<P: PhaseItem, const I: usize, M: TilemapMaterial> RenderCommand<P>
     for SetTilemapColorTextureBindGroup<I, M>
 {
     type Param = (SRes<TilemapBindGroups<M>>, SRes<TilemapInstances<M>>);
@@ -199,13 +201,13 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
     #[inline]
     fn render<'w>(
-        item: &Transparent2d,
+        item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         (bind_groups, instances): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        let Some(texture) = instances.0.get(&item.entity).unwrap().texture.as_ref() else {
+        let Some(texture) = instances.0.get(&item.entity()).unwrap().texture.as_ref() else {
             return RenderCommandResult::Success;
         };
 
@@ -225,7 +227,7 @@ impl<const I: usize, M: TilemapMaterial> RenderCommand<Transparent2d>
 
 #[derive(Default)]
 pub struct DrawTileMesh<M: TilemapMaterial>(PhantomData<M>);
-impl<M: TilemapMaterial> RenderCommand<Transparent2d> for DrawTileMesh<M> {
+impl<P: PhaseItem, M: TilemapMaterial> RenderCommand<P> for DrawTileMesh<M> {
     type Param = SRes<RenderChunkStorage<M>>;
 
     type ViewQuery = ();
@@ -234,13 +236,13 @@ impl<M: TilemapMaterial> RenderCommand<Transparent2d> for DrawTileMesh<M> {
 
     #[inline]
     fn render<'w>(
-        item: &Transparent2d,
+        item: &P,
         _view: ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<ROQueryItem<'w, Self::ItemQuery>>,
         render_chunks: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        if let Some(chunks) = render_chunks.into_inner().get_chunks(item.entity) {
+        if let Some(chunks) = render_chunks.into_inner().get_chunks(item.entity()) {
             for chunk in chunks.values() {
                 if !chunk.visible {
                     continue;
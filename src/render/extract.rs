@@ -4,7 +4,8 @@ use bevy::{
         entity::EntityHashMap,
         event::EventReader,
         query::{Or, With, Without},
-        system::{Res, ResMut},
+        system::{ParallelCommands, Res},
+        world::World,
     },
     prelude::{Changed, Commands, Component, Entity, Query, Vec2, Vec4},
     render::Extract,
@@ -15,17 +16,18 @@ use crate::{
     tilemap::{
         despawn::{DespawnedTile, DespawnedTilemap},
         map::{
-            TilePivot, TileRenderSize, TilemapAnimations, TilemapAxisFlip, TilemapLayerOpacities,
-            TilemapName, TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTransform,
-            TilemapType,
+            TilePivot, TileRenderSize, TilemapAlphaMode, TilemapAnimations, TilemapAxisFlip,
+            TilemapChunkFadeIn, TilemapDepthWrite, TilemapLayerOpacities, TilemapName,
+            TilemapRenderFlags, TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTintJitter,
+            TilemapTransform, TilemapType,
         },
-        tile::Tile,
+        tile::{Tile, TileAnimationStart},
     },
 };
 
 use super::{
-    chunk::{ChunkUnload, UnloadRenderChunk},
-    culling::{FrustumCulling, InvisibleTilemap},
+    chunk::{ChunkUnload, RenderChunkMemoryBudget, UnloadRenderChunk},
+    culling::{FrustumCulling, InvisibleTilemap, TilemapAnimationLod, TilemapCullingVolumes},
     material::TilemapMaterial,
     resources::{ExtractedTilemapMaterials, TilemapInstances},
 };
@@ -48,13 +50,21 @@ pub struct ExtractedTilemap<M: TilemapMaterial> {
     pub texture: Option<TilemapTexture>,
     pub animations: Option<TilemapAnimations>,
     pub chunk_size: u32,
+    pub fade_in: Option<f32>,
+    pub tint_jitter: Option<TilemapTintJitter>,
+    pub culling_volumes: Option<TilemapCullingVolumes>,
+    pub render_flags: TilemapRenderFlags,
+    pub alpha_mode: TilemapAlphaMode,
+    pub depth_write: Option<TilemapDepthWrite>,
 }
 
 pub type ExtractedTile = Tile;
 
 pub type ExtractedView = CameraAabb2d;
 
+/// Extract the changed tilemaps in parallel, each tilemap in its own task.
 pub fn extract_changed_tilemaps<M: TilemapMaterial>(
+    commands: ParallelCommands,
     tilemaps_query: Extract<
         Query<
             (
@@ -71,6 +81,14 @@ pub fn extract_changed_tilemaps<M: TilemapMaterial>(
                 &Handle<M>,
                 Option<&TilemapTexture>,
                 Option<&TilemapAnimations>,
+                (
+                    Option<&TilemapChunkFadeIn>,
+                    Option<&TilemapTintJitter>,
+                    Option<&TilemapCullingVolumes>,
+                    Option<&TilemapRenderFlags>,
+                    Option<&TilemapAlphaMode>,
+                    Option<&TilemapDepthWrite>,
+                ),
             ),
             (
                 Without<InvisibleTilemap>,
@@ -85,13 +103,20 @@ pub fn extract_changed_tilemaps<M: TilemapMaterial>(
                     Changed<Handle<M>>,
                     Changed<TilemapTexture>,
                     Changed<TilemapAnimations>,
+                    Or<(
+                        Changed<TilemapChunkFadeIn>,
+                        Changed<TilemapTintJitter>,
+                        Changed<TilemapCullingVolumes>,
+                        Changed<TilemapRenderFlags>,
+                        Changed<TilemapAlphaMode>,
+                        Changed<TilemapDepthWrite>,
+                    )>,
                 )>,
             ),
         >,
     >,
-    mut instances: ResMut<TilemapInstances<M>>,
 ) {
-    tilemaps_query.iter().for_each(
+    tilemaps_query.par_iter().for_each(
         |(
             entity,
             name,
@@ -106,6 +131,7 @@ pub fn extract_changed_tilemaps<M: TilemapMaterial>(
             material,
             texture,
             animations,
+            (fade_in, tint_jitter, culling_volumes, render_flags, alpha_mode, depth_write),
         )| {
             assert_ne!(
                 storage.tilemap,
@@ -114,24 +140,36 @@ pub fn extract_changed_tilemaps<M: TilemapMaterial>(
                 Did you use the default storage? If so, you have to assign the valid \
                 entity for the storage when creating."
             );
-            instances.0.insert(
-                entity,
-                ExtractedTilemap {
-                    id: entity,
-                    name: name.0.clone(),
-                    tile_render_size: tile_render_size.0,
-                    slot_size: slot_size.0,
-                    ty: *ty,
-                    tile_pivot: tile_pivot.0,
-                    layer_opacities: layer_opacities.0,
-                    transform: *transform,
-                    axis_flip: *axis_flip,
-                    texture: texture.cloned(),
-                    material: material.clone(),
-                    animations: animations.cloned(),
-                    chunk_size: storage.storage.chunk_size,
-                },
-            );
+            let tilemap = ExtractedTilemap::<M> {
+                id: entity,
+                name: name.0.clone(),
+                tile_render_size: tile_render_size.0,
+                slot_size: slot_size.0,
+                ty: *ty,
+                tile_pivot: tile_pivot.0,
+                layer_opacities: layer_opacities.0,
+                transform: *transform,
+                axis_flip: *axis_flip,
+                texture: texture.cloned(),
+                material: material.clone(),
+                animations: animations.cloned(),
+                chunk_size: storage.storage.chunk_size,
+                fade_in: fade_in.map(|f| f.0),
+                tint_jitter: tint_jitter.copied(),
+                culling_volumes: culling_volumes.cloned(),
+                render_flags: render_flags.copied().unwrap_or_default(),
+                alpha_mode: alpha_mode.copied().unwrap_or_default(),
+                depth_write: depth_write.copied(),
+            };
+
+            commands.command_scope(|mut c| {
+                c.add(move |world: &mut World| {
+                    world
+                        .resource_mut::<TilemapInstances<M>>()
+                        .0
+                        .insert(entity, tilemap);
+                });
+            });
         },
     );
 }
@@ -148,28 +186,36 @@ pub fn extract_tilemaps(
     );
 }
 
+/// Extract the changed tiles in parallel.
 pub fn extract_tiles(
-    mut commands: Commands,
-    tiles_query: Extract<Query<(Entity, &Tile), Changed<Tile>>>,
+    commands: ParallelCommands,
+    tiles_query: Extract<
+        Query<
+            (Entity, &Tile, Option<&TileAnimationStart>),
+            Or<(Changed<Tile>, Changed<TileAnimationStart>)>,
+        >,
+    >,
 ) {
-    commands.insert_or_spawn_batch(
-        tiles_query
-            .iter()
-            .map(|(entity, tile)| {
-                (
-                    entity,
-                    ExtractedTile {
-                        tilemap_id: tile.tilemap_id,
-                        chunk_index: tile.chunk_index,
-                        in_chunk_index: tile.in_chunk_index,
-                        index: tile.index,
-                        texture: tile.texture.clone(),
-                        color: tile.color,
-                    },
-                )
-            })
-            .collect::<Vec<_>>(),
-    );
+    tiles_query
+        .par_iter()
+        .for_each(|(entity, tile, anim_start)| {
+            let extracted = (
+                ExtractedTile {
+                    tilemap_id: tile.tilemap_id,
+                    chunk_index: tile.chunk_index,
+                    in_chunk_index: tile.in_chunk_index,
+                    index: tile.index,
+                    texture: tile.texture.clone(),
+                    color: tile.color,
+                    pivot: tile.pivot,
+                    footprint: tile.footprint,
+                },
+                anim_start.copied().unwrap_or_default(),
+            );
+            commands.command_scope(|mut c| {
+                c.get_or_spawn(entity).insert(extracted);
+            });
+        });
 }
 
 pub fn extract_materials<M: TilemapMaterial>(
@@ -222,8 +268,23 @@ pub fn extract_unloaded_chunks(
     ));
 }
 
-pub fn extract_resources(mut commands: Commands, frustum_culling: Extract<Res<FrustumCulling>>) {
+pub fn extract_resources(
+    mut commands: Commands,
+    frustum_culling: Extract<Res<FrustumCulling>>,
+    chunk_memory_budget: Extract<Option<Res<RenderChunkMemoryBudget>>>,
+    animation_lod: Extract<Option<Res<TilemapAnimationLod>>>,
+) {
     commands.insert_resource(FrustumCulling(frustum_culling.0));
+    if let Some(budget) = chunk_memory_budget.as_ref() {
+        commands.insert_resource(**budget);
+    } else {
+        commands.remove_resource::<RenderChunkMemoryBudget>();
+    }
+    if let Some(lod) = animation_lod.as_ref() {
+        commands.insert_resource(**lod);
+    } else {
+        commands.remove_resource::<TilemapAnimationLod>();
+    }
 }
 
 pub fn extract_despawned_tilemaps(
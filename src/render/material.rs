@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use bevy::{
     app::{App, Plugin},
     asset::{Asset, AssetApp, Assets, Handle},
-    core_pipeline::core_2d::Transparent2d,
+    core_pipeline::{core_2d::Transparent2d, core_3d::Opaque3d},
     ecs::{
         component::Component,
         entity::Entity,
@@ -56,7 +56,8 @@ impl<M: TilemapMaterial> Plugin for EntiTilesMaterialPlugin<M> {
                     prepare::prepare_unloaded_chunks::<M>,
                     prepare::prepare_despawned_tilemaps::<M>,
                     prepare::prepare_despawned_tiles::<M>,
-                    culling::cull_chunks::<M>,
+                    prepare::prepare_chunk_eviction::<M>.after(prepare::prepare_tilemaps::<M>),
+                    culling::cull_chunks::<M>.before(prepare::prepare_tilemaps::<M>),
                 )
                     .in_set(RenderSet::Prepare),
             )
@@ -69,7 +70,9 @@ impl<M: TilemapMaterial> Plugin for EntiTilesMaterialPlugin<M> {
             .init_resource::<TilemapInstances<M>>()
             .init_resource::<ExtractedTilemapMaterials<M>>();
 
-        render_app.add_render_command::<Transparent2d, DrawTilemap<M>>();
+        render_app
+            .add_render_command::<Transparent2d, DrawTilemap<M>>()
+            .add_render_command::<Opaque3d, DrawTilemap<M>>();
     }
 
     fn finish(&self, app: &mut bevy::prelude::App) {
@@ -10,8 +10,10 @@ use bevy::{
 use crate::render::{
     binding::TilemapBindGroupLayouts,
     buffer::TilemapStorageBuffers,
-    chunk::{ChunkUnload, RenderChunkStorage, UnloadRenderChunk},
-    culling::FrustumCulling,
+    chunk::{ChunkUnload, RenderChunkMemoryBudget, RenderChunkStorage, UnloadRenderChunk},
+    culling::{
+        FrustumCulling, TilemapAnimationLod, TilemapAnimationLodState, TilemapCullingVolumes,
+    },
     material::StandardTilemapMaterialSingleton,
     texture::TilemapTexturesStorage,
 };
@@ -39,10 +41,19 @@ pub const TILEMAP_MESH_ATTR_INDEX: MeshVertexAttribute =
     MeshVertexAttribute::new("GridIndex", 14513156146, VertexFormat::Sint32x4);
 pub const TILEMAP_MESH_ATTR_COLOR: MeshVertexAttribute =
     MeshVertexAttribute::new("Color", 85415341854, VertexFormat::Float32x4);
+// The id of this attribute must be between color and texture indices.
+pub const TILEMAP_MESH_ATTR_FOOTPRINT: MeshVertexAttribute =
+    MeshVertexAttribute::new("Footprint", 123654789541, VertexFormat::Uint32x2);
 pub const TILEMAP_MESH_ATTR_TEX_INDICES: MeshVertexAttribute =
     MeshVertexAttribute::new("TextureIndex", 186541653135, VertexFormat::Sint32x4);
 pub const TILEMAP_MESH_ATTR_FLIP: MeshVertexAttribute =
     MeshVertexAttribute::new("Flip", 7365156123161, VertexFormat::Uint32x4);
+// The id of this attribute must be between flip and load time.
+pub const TILEMAP_MESH_ATTR_ANIM_START: MeshVertexAttribute =
+    MeshVertexAttribute::new("AnimStart", 8216543213548, VertexFormat::Float32);
+// The id of this attribute must be the largest as it's the last one in the vertex buffer.
+pub const TILEMAP_MESH_ATTR_LOAD_TIME: MeshVertexAttribute =
+    MeshVertexAttribute::new("LoadTime", 9146512368745, VertexFormat::Float32);
 
 #[derive(Default)]
 pub struct EntiTilesRendererPlugin;
@@ -73,7 +84,10 @@ impl Plugin for EntiTilesRendererPlugin {
         app.init_resource::<FrustumCulling>()
             .init_resource::<StandardTilemapMaterialSingleton>();
 
-        app.register_type::<UnloadRenderChunk>();
+        app.register_type::<UnloadRenderChunk>()
+            .register_type::<TilemapCullingVolumes>()
+            .register_type::<RenderChunkMemoryBudget>()
+            .register_type::<TilemapAnimationLod>();
         app.add_event::<ChunkUnload>();
 
         let render_app = app.get_sub_app_mut(RenderApp).unwrap();
@@ -93,7 +107,8 @@ impl Plugin for EntiTilesRendererPlugin {
 
         render_app
             .init_resource::<TilemapTexturesStorage>()
-            .init_resource::<TilemapStorageBuffers>();
+            .init_resource::<TilemapStorageBuffers>()
+            .init_resource::<TilemapAnimationLodState>();
     }
 
     fn finish(&self, app: &mut App) {
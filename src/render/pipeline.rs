@@ -2,21 +2,23 @@ use std::marker::PhantomData;
 
 use bevy::{
     asset::{AssetServer, Handle},
+    core_pipeline::core_3d::CORE_3D_DEPTH_FORMAT,
     ecs::world::World,
     prelude::{FromWorld, Resource},
     render::{
         render_resource::{
-            BindGroupLayout, BlendState, ColorTargetState, ColorWrites, Face, FragmentState,
-            FrontFace, MultisampleState, PolygonMode, PrimitiveState, PrimitiveTopology,
-            RenderPipelineDescriptor, Shader, ShaderDefVal, ShaderRef, SpecializedRenderPipeline,
-            TextureFormat, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+            BindGroupLayout, BlendState, ColorTargetState, ColorWrites, CompareFunction,
+            DepthBiasState, DepthStencilState, Face, FragmentState, FrontFace, MultisampleState,
+            PolygonMode, PrimitiveState, PrimitiveTopology, RenderPipelineDescriptor, Shader,
+            ShaderDefVal, ShaderRef, SpecializedRenderPipeline, StencilState, TextureFormat,
+            VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
         },
         renderer::RenderDevice,
         texture::BevyDefault,
     },
 };
 
-use crate::tilemap::map::TilemapType;
+use crate::tilemap::map::{TilemapAlphaMode, TilemapRenderFlags, TilemapType};
 
 use super::{binding::TilemapBindGroupLayouts, material::TilemapMaterial};
 
@@ -37,6 +39,29 @@ pub struct EntiTilesPipelineKey {
     pub msaa: u32,
     pub map_type: TilemapType,
     pub is_pure_color: bool,
+    pub fade_in: bool,
+    pub flags: TilemapRenderFlags,
+    pub alpha_mode: EntiTilesAlphaModeKey,
+    /// Whether the tilemap is drawn for a 3d camera.
+    pub depth_write: bool,
+}
+
+/// `TilemapAlphaMode` without the cutoff, which is passed through the uniform.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum EntiTilesAlphaModeKey {
+    Blend,
+    Opaque,
+    Mask,
+}
+
+impl From<TilemapAlphaMode> for EntiTilesAlphaModeKey {
+    fn from(value: TilemapAlphaMode) -> Self {
+        match value {
+            TilemapAlphaMode::Blend => Self::Blend,
+            TilemapAlphaMode::Opaque => Self::Opaque,
+            TilemapAlphaMode::Mask(_) => Self::Mask,
+        }
+    }
 }
 
 impl<M: TilemapMaterial> FromWorld for EntiTilesPipeline<M> {
@@ -86,26 +111,71 @@ impl<M: TilemapMaterial> SpecializedRenderPipeline for EntiTilesPipeline<M> {
         #[cfg(feature = "atlas")]
         shader_defs.push("ATLAS".into());
 
+        // The shader locations are fixed, so attributes can be left out
+        // without shifting the others.
         let mut vtx_fmt = vec![
             // position
-            VertexFormat::Float32x3,
+            (VertexFormat::Float32x3, 0),
             // index + anim_start + anim_len
-            VertexFormat::Sint32x4,
-            // color
-            VertexFormat::Float32x4,
+            (VertexFormat::Sint32x4, 1),
         ];
 
+        if key.flags.contains(TilemapRenderFlags::NO_COLOR) {
+            shader_defs.push("NO_COLOR".into());
+        } else {
+            // color
+            vtx_fmt.push((VertexFormat::Float32x4, 2));
+        }
+        // footprint
+        vtx_fmt.push((VertexFormat::Uint32x2, 3));
+
         if key.is_pure_color {
             shader_defs.push("PURE_COLOR".into());
         } else {
             // texture_indices
-            vtx_fmt.push(VertexFormat::Sint32x4);
+            vtx_fmt.push((VertexFormat::Sint32x4, 4));
             // flip
-            vtx_fmt.push(VertexFormat::Uint32x4);
+            vtx_fmt.push((VertexFormat::Uint32x4, 5));
+            // anim_start
+            vtx_fmt.push((VertexFormat::Float32, 7));
+        }
+
+        if key.fade_in {
+            shader_defs.push("FADE_IN".into());
+            // load_time
+            vtx_fmt.push((VertexFormat::Float32, 6));
+        }
+
+        if key.flags.contains(TilemapRenderFlags::NO_ANIMATION) {
+            shader_defs.push("NO_ANIMATION".into());
         }
 
-        let vertex_layout =
-            VertexBufferLayout::from_vertex_formats(VertexStepMode::Vertex, vtx_fmt);
+        if key.alpha_mode == EntiTilesAlphaModeKey::Mask {
+            shader_defs.push("ALPHA_MASK".into());
+        }
+
+        if key.depth_write {
+            shader_defs.push("DEPTH_WRITE".into());
+        }
+
+        let mut offset = 0;
+        let attributes = vtx_fmt
+            .into_iter()
+            .map(|(format, shader_location)| {
+                let attribute = VertexAttribute {
+                    format,
+                    offset,
+                    shader_location,
+                };
+                offset += format.size();
+                attribute
+            })
+            .collect();
+        let vertex_layout = VertexBufferLayout {
+            array_stride: offset,
+            step_mode: VertexStepMode::Vertex,
+            attributes,
+        };
 
         let mut layout = vec![
             // group(0)
@@ -139,7 +209,12 @@ impl<M: TilemapMaterial> SpecializedRenderPipeline for EntiTilesPipeline<M> {
                 entry_point: "tilemap_fragment".into(),
                 targets: vec![Some(ColorTargetState {
                     format: TextureFormat::bevy_default(),
-                    blend: Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    blend: match key.alpha_mode {
+                        EntiTilesAlphaModeKey::Blend => {
+                            Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING)
+                        }
+                        EntiTilesAlphaModeKey::Opaque | EntiTilesAlphaModeKey::Mask => None,
+                    },
                     write_mask: ColorWrites::ALL,
                 })],
             }),
@@ -152,7 +227,14 @@ impl<M: TilemapMaterial> SpecializedRenderPipeline for EntiTilesPipeline<M> {
                 polygon_mode: PolygonMode::Fill,
                 conservative: false,
             },
-            depth_stencil: None,
+            depth_stencil: key.depth_write.then(|| DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: true,
+                // The 3d pipeline uses reversed z.
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
             multisample: MultisampleState {
                 count: key.msaa,
                 mask: !0,
@@ -9,14 +9,18 @@ use bevy::{
     time::Time,
 };
 
-use crate::tilemap::despawn::{DespawnedTile, DespawnedTilemap};
+use crate::tilemap::{
+    despawn::{DespawnedTile, DespawnedTilemap},
+    tile::TileAnimationStart,
+};
 
 use super::{
     binding::TilemapBindGroups,
     buffer::{
         PerTilemapBuffersStorage, TilemapStorageBuffers, TilemapUniformBuffer, UniformBuffer,
     },
-    chunk::{TilemapRenderChunk, UnloadRenderChunk},
+    chunk::{RenderChunkMemoryBudget, TilemapRenderChunk, UnloadRenderChunk},
+    culling::{TilemapAnimationLodState, TilemapAnimationTime},
     extract::{ExtractedTile, TilemapInstance},
     material::TilemapMaterial,
     pipeline::EntiTilesPipeline,
@@ -36,7 +40,7 @@ pub fn prepare_tilemaps<M: TilemapMaterial>(
     mut textures_storage: ResMut<TilemapTexturesStorage>,
     entitiles_pipeline: Res<EntiTilesPipeline<M>>,
     mut bind_groups: ResMut<TilemapBindGroups<M>>,
-    time: Res<Time>,
+    mut animation_time: TilemapAnimationTime,
     tilemap_instances: Res<TilemapInstances<M>>,
     images: Res<RenderAssets<Image>>,
     fallback_image: Res<FallbackImage>,
@@ -49,16 +53,20 @@ pub fn prepare_tilemaps<M: TilemapMaterial>(
         .iter()
         .filter_map(|tilemap| tilemap_instances.0.get(&tilemap))
         .for_each(|tilemap| {
+            let (tilemap_time, paused) = animation_time.get(tilemap.id, &render_chunks);
+
             commands
                 .entity(tilemap.id)
-                .insert(uniform_buffers.insert(&(tilemap, time.elapsed_seconds())));
+                .insert(uniform_buffers.insert(&(tilemap, tilemap_time)));
 
             render_chunks.prepare_chunks(tilemap, &render_device);
 
             if let Some(texture) = tilemap.texture.as_ref() {
-                storage_buffers
-                    .get_or_insert_buffer(tilemap.id)
-                    .extend(&tilemap.animations.as_ref().unwrap().0);
+                // The buffer is left empty while the animations are paused.
+                let buffer = storage_buffers.get_or_insert_buffer(tilemap.id);
+                if !paused {
+                    buffer.extend(&tilemap.animations.as_ref().unwrap().0);
+                }
 
                 if !textures_storage.contains(&texture.texture) {
                     textures_storage.insert(texture.clone_weak(), texture.desc());
@@ -83,22 +91,23 @@ pub fn prepare_tilemaps<M: TilemapMaterial>(
 }
 
 pub fn prepare_tiles<M: TilemapMaterial>(
-    extracted_tiles: Query<&mut ExtractedTile>,
+    extracted_tiles: Query<(&ExtractedTile, &TileAnimationStart)>,
     mut render_chunks: ResMut<RenderChunkStorage<M>>,
     tilemap_instances: Res<TilemapInstances<M>>,
+    time: Res<Time>,
 ) {
-    extracted_tiles.iter().for_each(|tile| {
+    extracted_tiles.iter().for_each(|(tile, anim_start)| {
         let Some(tilemap) = tilemap_instances.0.get(&tile.tilemap_id) else {
             return;
         };
 
         let chunks = render_chunks.value.entry(tile.tilemap_id).or_default();
 
-        let chunk = chunks
-            .entry(tile.chunk_index)
-            .or_insert_with(|| TilemapRenderChunk::from_index(tile.chunk_index, tilemap));
+        let chunk = chunks.entry(tile.chunk_index).or_insert_with(|| {
+            TilemapRenderChunk::from_index(tile.chunk_index, tilemap, time.elapsed_seconds())
+        });
 
-        chunk.set_tile(tile.in_chunk_index, Some(tile));
+        chunk.set_tile(tile.in_chunk_index, Some((tile, anim_start)));
     });
 }
 
@@ -113,16 +122,27 @@ pub fn prepare_unloaded_chunks<M: TilemapMaterial>(
     });
 }
 
+pub fn prepare_chunk_eviction<M: TilemapMaterial>(
+    mut render_chunks: ResMut<RenderChunkStorage<M>>,
+    budget: Option<Res<RenderChunkMemoryBudget>>,
+) {
+    if let Some(budget) = budget {
+        render_chunks.evict_chunks(&budget);
+    }
+}
+
 pub fn prepare_despawned_tilemaps<M: TilemapMaterial>(
     mut render_chunks: ResMut<RenderChunkStorage<M>>,
     mut storage_buffers: ResMut<TilemapStorageBuffers>,
     mut tilemap_instaces: ResMut<TilemapInstances<M>>,
+    mut animation_lod_state: ResMut<TilemapAnimationLodState>,
     tilemaps_query: Query<&DespawnedTilemap>,
 ) {
     tilemaps_query.iter().for_each(|map| {
         render_chunks.remove_tilemap(map.0);
         storage_buffers.remove(map.0);
         tilemap_instaces.0.remove(&map.0);
+        animation_lod_state.0.remove(&map.0);
     });
 }
 
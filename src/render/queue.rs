@@ -1,6 +1,7 @@
 use bevy::{
-    core_pipeline::core_2d::Transparent2d,
-    ecs::query::With,
+    asset::AssetId,
+    core_pipeline::{core_2d::Transparent2d, core_3d::Opaque3d},
+    ecs::{query::With, system::SystemParam},
     prelude::{Commands, Entity, Msaa, Query, Res, ResMut},
     render::{
         render_asset::RenderAssets,
@@ -16,7 +17,7 @@ use bevy::{
 use super::{
     binding::{TilemapBindGroups, TilemapViewBindGroup},
     draw::DrawTilemap,
-    extract::TilemapInstance,
+    extract::{ExtractedTilemap, TilemapInstance},
     material::TilemapMaterial,
     pipeline::{EntiTilesPipeline, EntiTilesPipelineKey},
     resources::TilemapInstances,
@@ -26,12 +27,20 @@ use super::{
 #[cfg(not(feature = "atlas"))]
 use bevy::render::renderer::RenderQueue;
 
+/// The 2d views, and the 3d views that the tilemaps writing depth are drawn to.
+#[derive(SystemParam)]
+pub struct TilemapRenderPhases<'w, 's> {
+    views_query: Query<'w, 's, (Entity, &'static mut RenderPhase<Transparent2d>)>,
+    views_3d_query: Query<'w, 's, (Entity, &'static mut RenderPhase<Opaque3d>)>,
+    draw_functions: Res<'w, DrawFunctions<Transparent2d>>,
+    draw_functions_3d: Res<'w, DrawFunctions<Opaque3d>>,
+}
+
 pub fn queue<M: TilemapMaterial>(
     mut commands: Commands,
-    mut views_query: Query<(Entity, &mut RenderPhase<Transparent2d>)>,
+    phases: TilemapRenderPhases,
     tilemaps_query: Query<Entity, With<TilemapInstance>>,
     pipeline_cache: Res<PipelineCache>,
-    draw_functions: Res<DrawFunctions<Transparent2d>>,
     mut sp_entitiles_pipeline: ResMut<SpecializedRenderPipelines<EntiTilesPipeline<M>>>,
     entitiles_pipeline: Res<EntiTilesPipeline<M>>,
     view_uniforms: Res<ViewUniforms>,
@@ -47,13 +56,52 @@ pub fn queue<M: TilemapMaterial>(
     let Some(view_binding) = view_uniforms.uniforms.binding() else {
         return;
     };
+    let TilemapRenderPhases {
+        mut views_query,
+        mut views_3d_query,
+        draw_functions,
+        draw_functions_3d,
+    } = phases;
 
     #[cfg(not(feature = "atlas"))]
     textures_storage.queue_textures(&render_device, &render_queue, &render_images);
     #[cfg(feature = "atlas")]
     textures_storage.queue_textures(&render_device, &mut render_images);
 
-    for (view_entity, mut transparent_phase) in views_query.iter_mut() {
+    let mut tilemaps = tilemaps_query
+        .iter()
+        .filter_map(|t| tilemap_instances.0.get(&t))
+        .collect::<Vec<_>>();
+    radsort::sort_by_key(&mut tilemaps, |m| m.transform.z_index);
+
+    let mut specialize = |tilemap: &ExtractedTilemap<M>, depth_write: bool| {
+        let is_pure_color = bind_groups.queue_textures(
+            tilemap,
+            &render_device,
+            &textures_storage,
+            &entitiles_pipeline,
+        );
+
+        sp_entitiles_pipeline.specialize(
+            &pipeline_cache,
+            &entitiles_pipeline,
+            EntiTilesPipelineKey {
+                msaa: msaa.samples(),
+                map_type: tilemap.ty,
+                is_pure_color,
+                fade_in: tilemap.fade_in.is_some(),
+                flags: tilemap.render_flags,
+                alpha_mode: tilemap.alpha_mode.into(),
+                depth_write,
+            },
+        )
+    };
+
+    let views = views_query
+        .iter()
+        .map(|(e, _)| e)
+        .chain(views_3d_query.iter().map(|(e, _)| e));
+    for view_entity in views {
         commands.entity(view_entity).insert(TilemapViewBindGroup {
             value: render_device.create_bind_group(
                 "tilemap_view_bind_group",
@@ -64,39 +112,32 @@ pub fn queue<M: TilemapMaterial>(
                 }],
             ),
         });
+    }
 
-        let mut tilemaps = tilemaps_query
-            .iter()
-            .filter_map(|t| tilemap_instances.0.get(&t))
-            .collect::<Vec<_>>();
-        radsort::sort_by_key(&mut tilemaps, |m| m.transform.z_index);
-
+    for (_, mut transparent_phase) in views_query.iter_mut() {
         for tilemap in tilemaps.iter() {
-            let is_pure_color = bind_groups.queue_textures(
-                &tilemap,
-                &render_device,
-                &textures_storage,
-                &entitiles_pipeline,
-            );
-
-            let pipeline = sp_entitiles_pipeline.specialize(
-                &pipeline_cache,
-                &entitiles_pipeline,
-                EntiTilesPipelineKey {
-                    msaa: msaa.samples(),
-                    map_type: tilemap.ty,
-                    is_pure_color,
-                },
-            );
-
             transparent_phase.add(Transparent2d {
                 sort_key: FloatOrd(tilemap.transform.z_index as f32),
                 entity: tilemap.id,
-                pipeline,
+                pipeline: specialize(tilemap, false),
                 draw_function: draw_functions.read().get_id::<DrawTilemap<M>>().unwrap(),
                 batch_range: 0..1,
                 dynamic_offset: None,
             });
         }
     }
+
+    // Only the tilemaps that write depth are drawn for 3d cameras.
+    for (_, mut opaque_phase) in views_3d_query.iter_mut() {
+        for tilemap in tilemaps.iter().filter(|t| t.depth_write.is_some()) {
+            opaque_phase.add(Opaque3d {
+                asset_id: AssetId::default(),
+                pipeline: specialize(tilemap, true),
+                entity: tilemap.id,
+                draw_function: draw_functions_3d.read().get_id::<DrawTilemap<M>>().unwrap(),
+                batch_range: 0..1,
+                dynamic_offset: None,
+            });
+        }
+    }
 }
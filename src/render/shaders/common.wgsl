@@ -7,10 +7,18 @@ struct TilemapVertexInput {
     // it means this tile is a animated tile.
     // So the zw components are the start index and the length of the animation sequence.
     @location(1) index: vec4<i32>,
+#ifndef NO_COLOR
     @location(2) color: vec4<f32>,
+#endif
+    // The cells this tile covers.
+    @location(3) footprint: vec2<u32>,
 #ifndef PURE_COLOR
-    @location(3) texture_indices: vec4<i32>,
-    @location(4) flip: vec4<u32>,
+    @location(4) texture_indices: vec4<i32>,
+    @location(5) flip: vec4<u32>,
+    @location(7) anim_start: f32,
+#endif
+#ifdef FADE_IN
+    @location(6) load_time: f32,
 #endif
 }
 
@@ -23,6 +31,8 @@ struct TilemapVertexOutput {
     @location(3) texture_indices: vec4<i32>,
     // Indicates whether the tile is animated.
     @location(4) anim_flag: i32,
+    // The size of the tile in texture tiles.
+    @location(5) footprint: vec2<f32>,
 #endif
 }
 
@@ -38,6 +48,23 @@ struct Tilemap {
     // this value will only be meaningful when the tilemap is hexagonal!
     hex_legs: f32,
     time: f32,
+    // 0 if the tilemap doesn't fade in chunks
+    fade_in_duration: f32,
+    // 0 if the tilemap doesn't jitter tints
+    tint_jitter_strength: f32,
+    tint_jitter_seed: u32,
+    // circles are (center, radius, 0) and rects are (min, max)
+    culling_volumes: array<vec4<f32>, 8>,
+    // 0 if the tilemap doesn't have extra culling volumes
+    culling_volume_count: u32,
+    // the i-th bit is set if the i-th volume is a rect
+    culling_volume_rects: u32,
+    // how many tiles are in a row of the texture
+    texture_columns: u32,
+    // 0 if the tilemap doesn't use alpha masking
+    alpha_cutoff: f32,
+    // the z of the tiles when drawn for 3d cameras
+    depth: f32,
 #ifdef ATLAS
     // texture size in tiles
     texture_tiled_size: vec2<i32>,
@@ -48,6 +75,28 @@ struct Tilemap {
 @group(1) @binding(0)
 var<uniform> tilemap: Tilemap;
 
+// Returns two pseudo random numbers in [-1, 1] for the tile.
+fn tile_hash(index: vec2<i32>, seed: u32) -> vec2<f32> {
+    var h = (bitcast<u32>(index.x) * 1597334673u)
+            ^ (bitcast<u32>(index.y) * 3812015801u)
+            ^ (seed * 2798796415u);
+    h = (h ^ (h >> 16u)) * 2246822519u;
+    h = (h ^ (h >> 13u)) * 3266489917u;
+    h ^= h >> 16u;
+    return vec2<f32>(f32(h & 0xffffu), f32(h >> 16u)) / 32767.5 - 1.;
+}
+
+// Slightly shifts the brightness and hue of the color.
+fn jitter_tint(color: vec3<f32>, index: vec2<i32>) -> vec3<f32> {
+    let rand = tile_hash(index, tilemap.tint_jitter_seed) * tilemap.tint_jitter_strength;
+    // Rotate the color around the gray axis to shift the hue.
+    let k = vec3<f32>(0.57735);
+    let angle = rand.y * 3.14159265;
+    let rotated = color * cos(angle) + cross(k, color) * sin(angle)
+                  + k * dot(k, color) * (1. - cos(angle));
+    return max(rotated * (1. + rand.x), vec3<f32>(0.));
+}
+
 #ifndef PURE_COLOR
 #ifdef ATLAS
 @group(3) @binding(0)
@@ -63,3 +112,22 @@ var color_texture_sampler: sampler;
 @group(4) @binding(0)
 var<storage> anim_seqs: array<i32>;
 #endif
+
+// Returns true if the point is outside all the culling volumes.
+fn is_culled(point: vec2<f32>) -> bool {
+    if tilemap.culling_volume_count == 0u {
+        return false;
+    }
+
+    for (var i = 0u; i < tilemap.culling_volume_count; i++) {
+        let volume = tilemap.culling_volumes[i];
+        if (tilemap.culling_volume_rects & (1u << i)) != 0u {
+            if all(point >= volume.xy) && all(point <= volume.zw) {
+                return false;
+            }
+        } else if distance(point, volume.xy) <= volume.z {
+            return false;
+        }
+    }
+    return true;
+}
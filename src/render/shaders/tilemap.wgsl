@@ -1,5 +1,6 @@
 #import bevy_entitiles::common::{
-    TilemapVertexInput, TilemapVertexOutput, tilemap, atlas_uvs, anim_seqs
+    TilemapVertexInput, TilemapVertexOutput, tilemap, atlas_uvs, anim_seqs, jitter_tint,
+    is_culled
 }
 #import bevy_sprite::mesh2d_view_bindings::view
 
@@ -21,6 +22,18 @@
 fn tilemap_vertex(input: TilemapVertexInput) -> TilemapVertexOutput {
     var output: TilemapVertexOutput;
     var mesh_origin = get_mesh_origin(input);
+
+    // Tiles with footprints are scaled to cover all their cells.
+    var footprint = vec2<f32>(input.footprint);
+#ifdef ISOMETRIC
+    // The cells form a larger diamond, and the left corner of it comes from the last row.
+    mesh_origin.x -= (footprint.y - 1.) / 2. * tilemap.slot_size.x;
+    footprint = vec2<f32>((footprint.x + footprint.y) / 2.);
+#else
+    // The cells extend towards the other side if the axis is flipped.
+    mesh_origin -= (1. - tilemap.axis_dir) / 2. * (footprint - 1.) * tilemap.slot_size;
+#endif
+    let tile_size = tilemap.tile_render_size * footprint;
     
     var translations = array<vec2<f32>, 4>(
         vec2<f32>(0., 0.),
@@ -29,12 +42,41 @@ fn tilemap_vertex(input: TilemapVertexInput) -> TilemapVertexOutput {
         vec2<f32>(1., 0.),
     );
 
-    var position_model = (translations[input.v_index % 4u] - tilemap.pivot)
-                          * tilemap.tile_render_size + mesh_origin;
+    // The xy of the position is the pivot of the tile if z is not 0.
+    var pivot = tilemap.pivot;
+    if input.position.z != 0. {
+        pivot = input.position.xy;
+    }
+
+    var position_model = (translations[input.v_index % 4u] - pivot) * tile_size + mesh_origin;
     var position_world = vec4<f32>((tilemap.rot_mat * position_model) + tilemap.translation, 0., 1.);
+#ifdef DEPTH_WRITE
+    position_world.z = tilemap.depth;
+#endif
+
+    // Collapse the whole tile into a point so nothing is drawn.
+    let center_model = (vec2<f32>(0.5) - pivot) * tile_size + mesh_origin;
+    if is_culled((tilemap.rot_mat * center_model) + tilemap.translation) {
+        output.position = vec4<f32>(0., 0., 0., 1.);
+        return output;
+    }
 
     output.position = view.view_proj * position_world;
+#ifdef NO_COLOR
+    output.color = vec4<f32>(1.);
+#else
     output.color = vec4<f32>(pow(input.color.rgb, vec3<f32>(2.2)), input.color.a);
+#endif
+
+    if tilemap.tint_jitter_strength > 0. {
+        output.color = vec4<f32>(jitter_tint(output.color.rgb, input.index.xy), output.color.a);
+    }
+
+#ifdef FADE_IN
+    // As we are using premultiplied alpha, all the components need to be multiplied.
+    let fade_in_duration = max(tilemap.fade_in_duration, 0.0001);
+    output.color *= clamp((tilemap.time - input.load_time) / fade_in_duration, 0., 1.);
+#endif
 
 #ifndef PURE_COLOR
 #ifdef ATLAS
@@ -52,22 +94,53 @@ fn tilemap_vertex(input: TilemapVertexInput) -> TilemapVertexOutput {
         vec2<f32>(1., 1.),
     );
 #endif
-    output.uv = uvs[(input.v_index + tilemap.uv_rot) % 4u];
+    output.uv = uvs[(input.v_index + tilemap.uv_rot) % 4u] * footprint;
+    output.footprint = footprint;
     output.flip = input.flip;
+#ifdef NO_ANIMATION
+    // Animated tiles have no static textures, so they are not drawn.
+    output.anim_flag = -1;
+    output.texture_indices = input.texture_indices;
+#else
     output.anim_flag = input.index.z;
 
     if input.index.z != -1 {
         // Means that this tile is a animated tile
         let start = input.index.z;
         let length = input.index.w;
-        // The number before the start index is the fps.
+        // The number before the start index is the fps, with the flags in the high bits.
         // See register function in TilemapAnimations.
-        let fps = f32(anim_seqs[start - 1]);
-        var frame = i32(tilemap.time * fps) % length;
+        let header = anim_seqs[start - 1];
+        let fps = f32(header & 0xFFFF);
+        let flags = header >> 16u;
+        // Animations are played from the time the tile switched to them.
+        let time = max(tilemap.time - input.anim_start, 0.);
+        var frame: i32 = 0;
+        if (flags & 2) != 0 {
+            // Per-frame durations. The end times of the frames follow the sequence.
+            let total = anim_seqs[start + length * 2 - 1];
+            var t = i32(time * 1000.);
+            if (flags & 1) != 0 {
+                t = min(t, total - 1);
+            } else {
+                t = t % max(total, 1);
+            }
+            while frame < length - 1 && t >= anim_seqs[start + length + frame] {
+                frame += 1;
+            }
+        } else {
+            frame = i32(time * fps);
+            if (flags & 1) != 0 {
+                frame = min(frame, length - 1);
+            } else {
+                frame = frame % length;
+            }
+        }
         output.texture_indices[0] = anim_seqs[start + frame];
     } else {
         output.texture_indices = input.texture_indices;
     }
+#endif
 #endif
 
     return output;
@@ -76,7 +149,7 @@ fn tilemap_vertex(input: TilemapVertexInput) -> TilemapVertexOutput {
 @fragment
 fn tilemap_fragment(input: TilemapVertexOutput) -> @location(0) vec4<f32> {
 #ifdef PURE_COLOR
-    return input.color;
+    var color = input.color;
 #else
     var color = vec4<f32>(0., 0., 0., 0.);
 
@@ -90,15 +163,22 @@ fn tilemap_fragment(input: TilemapVertexOutput) -> @location(0) vec4<f32> {
         var uv = input.uv;
         // Flip the uv if needed.
         if (input.flip[i] & 1u) != 0u {
-            uv.x = 1. - uv.x;
+            uv.x = input.footprint.x - uv.x;
         }
         if (input.flip[i] & 2u) != 0u {
-            uv.y = 1. - uv.y;
+            uv.y = input.footprint.y - uv.y;
         }
+
+        // Tiles with footprints cover multiple tiles in the texture,
+        // so find out which one this fragment is in.
+        let cell = min(floor(uv), ceil(input.footprint) - 1.);
+        uv -= cell;
+        let texture_index = input.texture_indices[i] + i32(cell.x)
+                            + i32(cell.y) * i32(tilemap.texture_columns);
 #ifdef ATLAS
         // If `atlas` feature is enabled, we need to calculate the uv.
-        let tile_index = vec2<f32>(f32(input.texture_indices[i] % tilemap.texture_tiled_size.x),
-                                   f32(input.texture_indices[i] / tilemap.texture_tiled_size.x));
+        let tile_index = vec2<f32>(f32(texture_index % tilemap.texture_tiled_size.x),
+                                   f32(texture_index / tilemap.texture_tiled_size.x));
         let atlas_uv = (tile_index + uv) * tilemap.tile_uv_size;
         let tex_color = textureSample(bevy_entitiles::common::color_texture,
                                       bevy_entitiles::common::color_texture_sampler,
@@ -107,7 +187,7 @@ fn tilemap_fragment(input: TilemapVertexOutput) -> @location(0) vec4<f32> {
         // Otherwise, sample the texture at the right layer using the uv directly.
         let tex_color = textureSample(bevy_entitiles::common::color_texture,
                                       bevy_entitiles::common::color_texture_sampler,
-                                      uv, input.texture_indices[i]);
+                                      uv, texture_index);
 #endif
         // Mix the color of each layer.
         color = mix(color, tex_color, tex_color.a * tilemap.layer_opacities[i]);
@@ -119,6 +199,16 @@ fn tilemap_fragment(input: TilemapVertexOutput) -> @location(0) vec4<f32> {
         }
     }
     // Apply the color of the tile.
-    return color * input.color;
+    color *= input.color;
 #endif
+
+#ifdef ALPHA_MASK
+    if color.a <= 0. || color.a < tilemap.alpha_cutoff {
+        discard;
+    }
+    // Undo the premultiplication as the pixel is drawn opaque.
+    color = vec4<f32>(color.rgb / color.a, 1.);
+#endif
+
+    return color;
 }
@@ -0,0 +1,209 @@
+use bevy::{
+    app::{App, Plugin, Update},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Changed, Without},
+        system::{Commands, Query, Res, Resource},
+    },
+    math::{IVec2, UVec2, Vec4},
+    reflect::Reflect,
+};
+
+use crate::{
+    math::aabb::IAabb2d,
+    tilemap::{
+        map::{TilemapAnimations, TilemapStorage},
+        tile::TileBuilder,
+    },
+};
+
+use super::pattern::TilemapPattern;
+
+pub struct EntiTilesBrushPlugin;
+
+impl Plugin for EntiTilesBrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, brush_preview_updater);
+
+        app.init_resource::<TilemapBrushes>();
+
+        app.register_type::<TilemapBrushes>()
+            .register_type::<BrushCollision>()
+            .register_type::<TilemapBrushPreview>();
+    }
+}
+
+/// How a brush treats the tiles that are already in the tilemap.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum BrushCollision {
+    /// Overwrite the existing tiles.
+    #[default]
+    Overwrite,
+    /// Keep the existing tiles and only stamp the tiles that don't overlap them.
+    Skip,
+    /// Stamp nothing if any of the tiles overlaps the existing ones.
+    Reject,
+}
+
+/// Multi-tile brushes made from patterns, for editing tilemaps at runtime.
+#[derive(Resource, Default, Reflect)]
+pub struct TilemapBrushes {
+    pub(crate) brushes: Vec<TilemapPattern>,
+}
+
+impl TilemapBrushes {
+    /// Register a brush. Returns the id of it.
+    pub fn register(&mut self, pattern: TilemapPattern) -> usize {
+        self.brushes.push(pattern);
+        self.brushes.len() - 1
+    }
+
+    #[inline]
+    pub fn get(&self, id: usize) -> Option<&TilemapPattern> {
+        self.brushes.get(id)
+    }
+
+    /// Get the id of the first brush with the label.
+    pub fn find(&self, label: &str) -> Option<usize> {
+        self.brushes
+            .iter()
+            .position(|b| b.label.as_deref() == Some(label))
+    }
+
+    /// Get the cells that the brush would cover at `origin` and already have tiles.
+    pub fn collisions(&self, id: usize, storage: &TilemapStorage, origin: IVec2) -> Vec<IVec2> {
+        let Some(brush) = self.get(id) else {
+            return vec![];
+        };
+
+        brush
+            .tiles
+            .tiles
+            .iter()
+            .flat_map(|(index, tile)| covered_cells(*index + origin, tile).into_iter())
+            .filter(|cell| storage.get(*cell).is_some())
+            .collect()
+    }
+
+    /// Stamp the brush into the tilemap, with `origin` as the bottom left corner of it.
+    /// See `TilemapPattern::stamp`.
+    ///
+    /// Returns false if nothing is stamped, because the brush doesn't exist
+    /// or it's rejected by `collision`.
+    pub fn stamp(
+        &self,
+        commands: &mut Commands,
+        storage: &mut TilemapStorage,
+        animations: &mut TilemapAnimations,
+        id: usize,
+        origin: IVec2,
+        collision: BrushCollision,
+    ) -> bool {
+        let Some(brush) = self.get(id) else {
+            return false;
+        };
+
+        match collision {
+            BrushCollision::Overwrite => brush.stamp(commands, storage, animations, origin),
+            BrushCollision::Skip => {
+                let mut brush = brush.clone();
+                brush.tiles.tiles.retain(|index, tile| {
+                    covered_cells(*index + origin, tile)
+                        .into_iter()
+                        .all(|cell| storage.get(cell).is_none())
+                });
+                if brush.tiles.tiles.is_empty() {
+                    return false;
+                }
+                brush.stamp(commands, storage, animations, origin);
+            }
+            BrushCollision::Reject => {
+                if !self.collisions(id, storage, origin).is_empty() {
+                    return false;
+                }
+                brush.stamp(commands, storage, animations, origin);
+            }
+        }
+
+        true
+    }
+}
+
+/// Shows a brush as ghost tiles, usually above the tilemap that is being edited.
+///
+/// Add this to a separate tilemap with the same texture, type and transform as the
+/// `target`. The tiles are tinted with `tint`, or `blocked_tint` if they overlap
+/// the tiles in the `target`.
+///
+/// **Notice**: The preview is only refreshed when this component changes.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapBrushPreview {
+    pub target: Entity,
+    /// The brush to show. `None` hides the preview.
+    pub brush: Option<usize>,
+    pub origin: IVec2,
+    pub tint: Vec4,
+    pub blocked_tint: Vec4,
+}
+
+impl TilemapBrushPreview {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            brush: None,
+            origin: IVec2::ZERO,
+            tint: Vec4::new(1., 1., 1., 0.5),
+            blocked_tint: Vec4::new(1., 0.3, 0.3, 0.5),
+        }
+    }
+}
+
+pub fn brush_preview_updater(
+    mut commands: Commands,
+    mut previews_query: Query<
+        (
+            &TilemapBrushPreview,
+            &mut TilemapStorage,
+            &mut TilemapAnimations,
+        ),
+        Changed<TilemapBrushPreview>,
+    >,
+    targets_query: Query<&TilemapStorage, Without<TilemapBrushPreview>>,
+    brushes: Res<TilemapBrushes>,
+) {
+    previews_query
+        .iter_mut()
+        .for_each(|(preview, mut storage, mut animations)| {
+            storage.remove_all(&mut commands);
+
+            let Some(brush) = preview.brush.and_then(|id| brushes.get(id)) else {
+                return;
+            };
+            let target = targets_query.get(preview.target).ok();
+
+            let mut tiles = brush.tiles.clone();
+            tiles.tiles.iter_mut().for_each(|(index, tile)| {
+                let blocked = target.is_some_and(|target| {
+                    covered_cells(*index + preview.origin, tile)
+                        .into_iter()
+                        .any(|cell| target.get(cell).is_some())
+                });
+                tile.color *= if blocked {
+                    preview.blocked_tint
+                } else {
+                    preview.tint
+                };
+            });
+
+            *animations = brush.animations.clone();
+            storage.fill_with_buffer(&mut commands, preview.origin, tiles);
+        });
+}
+
+fn covered_cells(index: IVec2, tile: &TileBuilder) -> IAabb2d {
+    IAabb2d {
+        min: index,
+        max: index + tile.footprint.unwrap_or(UVec2::ONE).as_ivec2() - 1,
+    }
+}
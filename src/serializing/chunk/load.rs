@@ -5,7 +5,8 @@ use bevy::{
         component::Component,
         entity::{Entity, EntityHashMap},
         query::With,
-        system::{Commands, ParallelCommands, Query, Res, ResMut, Resource},
+        removal_detection::RemovedComponents,
+        system::{Commands, Local, ParallelCommands, Query, Res, ResMut, Resource},
     },
     math::IVec2,
     reflect::Reflect,
@@ -17,6 +18,7 @@ use crate::{
     serializing::{load_object, map::TilemapLayer},
     tilemap::{
         buffers::TileBuilderBuffer,
+        chunking::budget::StreamingBudget,
         map::{TilemapName, TilemapStorage},
         tile::Tile,
     },
@@ -94,6 +96,40 @@ impl ChunkLoadCache {
                 .flatten()
         })
     }
+
+    /// Pop the chunk with the lowest priority value.
+    pub fn pop_chunk_by(
+        &mut self,
+        tilemap: Entity,
+        layer: TilemapLayer,
+        priority: impl Fn(IVec2) -> f32,
+    ) -> Option<IVec2> {
+        let chunks = self.0.get_mut(&tilemap)?.get_mut(&layer)?;
+        let (index, _) = chunks
+            .iter()
+            .enumerate()
+            .min_by(|(_, lhs), (_, rhs)| priority(**lhs).total_cmp(&priority(**rhs)))?;
+        chunks.remove(index)
+    }
+
+    fn pop_next_chunk(
+        &mut self,
+        tilemap: Entity,
+        layer: TilemapLayer,
+        storage: Option<&TilemapStorage>,
+        budget: Option<&StreamingBudget>,
+    ) -> Option<IVec2> {
+        match (storage, budget.filter(|b| b.focus().is_some())) {
+            (Some(storage), Some(budget)) => self.pop_chunk_by(tilemap, layer, |chunk_index| {
+                storage
+                    .reserved
+                    .get(&chunk_index)
+                    .map(|aabb| budget.priority(aabb.center()))
+                    .unwrap_or(f32::MAX)
+            }),
+            _ => self.pop_chunk(tilemap, layer),
+        }
+    }
 }
 
 pub fn load_color_layer(
@@ -104,30 +140,57 @@ pub fn load_color_layer(
     >,
     config: Res<ChunkLoadConfig>,
     mut cache: ResMut<ChunkLoadCache>,
+    mut budget: Option<ResMut<StreamingBudget>>,
+    mut over_budget: Local<EntityHashMap<(IVec2, TileBuilderBuffer)>>,
+    mut removed: RemovedComponents<TilemapStorage>,
 ) {
+    // Drop the chunks of the despawned tilemaps.
+    removed.read().for_each(|entity| {
+        over_budget.remove(&entity);
+    });
+
     tilemaps_query
         .iter_mut()
         .for_each(|(entity, name, mut storage)| {
             let chunk_size = storage.storage.chunk_size as i32;
-            (0..config.chunks_per_frame).into_iter().for_each(|_| {
-                let Some(chunk_index) = cache.pop_chunk(entity, TilemapLayer::COLOR) else {
-                    cache
-                        .0
-                        .get_mut(&entity)
-                        .unwrap()
-                        .remove(&TilemapLayer::COLOR);
-                    return;
-                };
+            for _ in 0..config.chunks_per_frame {
+                // Chunks that were over the budget are already loaded.
+                let (chunk_index, chunk) = match over_budget.remove(&entity) {
+                    Some(loaded) => loaded,
+                    None => {
+                        let Some(chunk_index) = cache.pop_next_chunk(
+                            entity,
+                            TilemapLayer::COLOR,
+                            Some(&storage),
+                            budget.as_deref(),
+                        ) else {
+                            cache
+                                .0
+                                .get_mut(&entity)
+                                .unwrap()
+                                .remove(&TilemapLayer::COLOR);
+                            break;
+                        };
 
-                let Ok(chunk) = load_object::<TileBuilderBuffer>(
-                    &Path::new(&config.path)
-                        .join(&name.0)
-                        .join(TILE_CHUNKS_FOLDER),
-                    format!("{}.ron", chunk_index.chunk_file_name()).as_str(),
-                ) else {
-                    return;
+                        let Ok(chunk) = load_object::<TileBuilderBuffer>(
+                            &Path::new(&config.path)
+                                .join(&name.0)
+                                .join(TILE_CHUNKS_FOLDER),
+                            format!("{}.ron", chunk_index.chunk_file_name()).as_str(),
+                        ) else {
+                            continue;
+                        };
+                        (chunk_index, chunk)
+                    }
                 };
 
+                if let Some(budget) = budget.as_mut() {
+                    if !budget.try_spend_tiles(chunk.tiles.len()) {
+                        over_budget.insert(entity, (chunk_index, chunk));
+                        break;
+                    }
+                }
+
                 commands.command_scope(|mut c| {
                     let mut tiles = Vec::with_capacity((chunk_size * chunk_size) as usize);
                     let mut entities = vec![None; (chunk_size * chunk_size) as usize];
@@ -146,6 +209,8 @@ pub fn load_color_layer(
                                 index: chunk_origin + in_chunk_index,
                                 texture: tile.texture,
                                 color: tile.color,
+                                pivot: tile.pivot,
+                                footprint: tile.footprint,
                             },
                         ));
                         entities[in_chunk_index_vec] = Some(e);
@@ -154,19 +219,19 @@ pub fn load_color_layer(
                     storage.set_chunk_entity(chunk_index, entities);
                     c.insert_or_spawn_batch(tiles);
                 });
-            });
+            }
         });
 }
 
 #[cfg(feature = "algorithm")]
 pub fn load_path_layer(
-    tilemaps_query: Query<(Entity, &TilemapName, &PathTilemap), With<ScheduledLoadChunks>>,
+    mut tilemaps_query: Query<(Entity, &TilemapName, &mut PathTilemap), With<ScheduledLoadChunks>>,
     config: Res<ChunkLoadConfig>,
     mut cache: ResMut<ChunkLoadCache>,
 ) {
     tilemaps_query
-        .iter()
-        .for_each(|(entity, name, path_tilemap)| {
+        .iter_mut()
+        .for_each(|(entity, name, mut path_tilemap)| {
             let chunk_size = path_tilemap.storage.chunk_size as i32;
             (0..config.chunks_per_frame).into_iter().for_each(|_| {
                 let Some(chunk_index) = cache.pop_chunk(entity, TilemapLayer::PATH) else {
@@ -191,7 +256,7 @@ pub fn load_path_layer(
                 chunk.tiles.into_iter().for_each(|(in_chunk_index, tile)| {
                     c[(in_chunk_index.y * chunk_size + in_chunk_index.x) as usize] = Some(tile);
                 });
-                path_tilemap.storage.get_chunk(chunk_index).replace(&c);
+                path_tilemap.storage.set_chunk(chunk_index, c);
             });
         });
 }
@@ -200,35 +265,67 @@ pub fn load_path_layer(
 pub fn load_physics_layer(
     mut commands: Commands,
     mut tilemaps_query: Query<
-        (Entity, &TilemapName, &mut PhysicsTilemap),
+        (
+            Entity,
+            &TilemapName,
+            &mut PhysicsTilemap,
+            Option<&TilemapStorage>,
+        ),
         With<ScheduledLoadChunks>,
     >,
     config: Res<ChunkLoadConfig>,
     mut cache: ResMut<ChunkLoadCache>,
+    mut budget: Option<ResMut<StreamingBudget>>,
+    mut over_budget: Local<EntityHashMap<(IVec2, PackedPhysicsTileBuffer)>>,
+    mut removed: RemovedComponents<PhysicsTilemap>,
 ) {
+    // Drop the chunks of the despawned tilemaps.
+    removed.read().for_each(|entity| {
+        over_budget.remove(&entity);
+    });
+
     tilemaps_query
         .iter_mut()
-        .for_each(|(entity, name, mut physics_tilemap)| {
+        .for_each(|(entity, name, mut physics_tilemap, storage)| {
             let chunk_size = physics_tilemap.storage.chunk_size as i32;
-            (0..config.chunks_per_frame).into_iter().for_each(|_| {
-                let Some(chunk_index) = cache.pop_chunk(entity, TilemapLayer::PHYSICS) else {
-                    cache
-                        .0
-                        .get_mut(&entity)
-                        .unwrap()
-                        .remove(&TilemapLayer::PHYSICS);
-                    return;
-                };
+            for _ in 0..config.chunks_per_frame {
+                // Chunks that were over the budget are already loaded.
+                let (chunk_index, chunk) = match over_budget.remove(&entity) {
+                    Some(loaded) => loaded,
+                    None => {
+                        let Some(chunk_index) = cache.pop_next_chunk(
+                            entity,
+                            TilemapLayer::PHYSICS,
+                            storage,
+                            budget.as_deref(),
+                        ) else {
+                            cache
+                                .0
+                                .get_mut(&entity)
+                                .unwrap()
+                                .remove(&TilemapLayer::PHYSICS);
+                            break;
+                        };
 
-                let Ok(chunk) = load_object::<PackedPhysicsTileBuffer>(
-                    &Path::new(&config.path)
-                        .join(&name.0)
-                        .join(PHYSICS_TILE_CHUNKS_FOLDER),
-                    format!("{}.ron", chunk_index.chunk_file_name()).as_str(),
-                ) else {
-                    return;
+                        let Ok(chunk) = load_object::<PackedPhysicsTileBuffer>(
+                            &Path::new(&config.path)
+                                .join(&name.0)
+                                .join(PHYSICS_TILE_CHUNKS_FOLDER),
+                            format!("{}.ron", chunk_index.chunk_file_name()).as_str(),
+                        ) else {
+                            continue;
+                        };
+                        (chunk_index, chunk)
+                    }
                 };
 
+                if let Some(budget) = budget.as_mut() {
+                    if !budget.try_spend_colliders(chunk.tiles.len()) {
+                        over_budget.insert(entity, (chunk_index, chunk));
+                        break;
+                    }
+                }
+
                 let mut new_chunk = vec![None; (chunk_size * chunk_size) as usize];
                 chunk.tiles.iter().for_each(|(in_chunk_index, tile)| {
                     new_chunk[(in_chunk_index.y * chunk_size + in_chunk_index.x) as usize] =
@@ -242,6 +339,6 @@ pub fn load_physics_layer(
                         Some(tile.spawn(&mut commands));
                 });
                 physics_tilemap.storage.set_chunk(chunk_index, new_chunk);
-            });
+            }
         });
 }
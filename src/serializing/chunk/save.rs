@@ -142,23 +142,20 @@ pub fn save_color_layer(
                 };
 
                 let tiles = chunk
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(index, t)| {
-                        t.map(|t| {
-                            (
-                                IVec2 {
-                                    x: (index as u32 % storage.storage.chunk_size) as i32,
-                                    y: (index as u32 / storage.storage.chunk_size) as i32,
-                                },
-                                tiles_query
-                                    .get(t)
-                                    .ok()
-                                    .cloned()
-                                    .map(|tile| tile.into())
-                                    .unwrap(),
-                            )
-                        })
+                    .iter_some()
+                    .map(|(index, t)| {
+                        (
+                            IVec2 {
+                                x: (index as u32 % storage.storage.chunk_size) as i32,
+                                y: (index as u32 / storage.storage.chunk_size) as i32,
+                            },
+                            tiles_query
+                                .get(*t)
+                                .ok()
+                                .cloned()
+                                .map(|tile| tile.into())
+                                .unwrap(),
+                        )
                     })
                     .collect();
 
@@ -213,18 +210,15 @@ pub fn save_path_layer(
                 };
 
                 let tiles = chunk
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(index, tile)| {
-                        tile.map(|t| {
-                            (
-                                IVec2 {
-                                    x: (index as u32 % path_tilemap.storage.chunk_size) as i32,
-                                    y: (index as u32 / path_tilemap.storage.chunk_size) as i32,
-                                },
-                                t,
-                            )
-                        })
+                    .iter_some()
+                    .map(|(index, t)| {
+                        (
+                            IVec2 {
+                                x: (index as u32 % path_tilemap.storage.chunk_size) as i32,
+                                y: (index as u32 / path_tilemap.storage.chunk_size) as i32,
+                            },
+                            *t,
+                        )
                     })
                     .collect();
 
@@ -279,18 +273,15 @@ pub fn save_physics_layer(
                 };
 
                 let tiles = chunk
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(index, tile)| {
-                        tile.clone().map(|t| {
-                            (
-                                IVec2 {
-                                    x: (index as u32 % physics_tilemap.storage.chunk_size) as i32,
-                                    y: (index as u32 / physics_tilemap.storage.chunk_size) as i32,
-                                },
-                                t,
-                            )
-                        })
+                    .iter_some()
+                    .map(|(index, t)| {
+                        (
+                            IVec2 {
+                                x: (index as u32 % physics_tilemap.storage.chunk_size) as i32,
+                                y: (index as u32 / physics_tilemap.storage.chunk_size) as i32,
+                            },
+                            t.clone(),
+                        )
                     })
                     .collect();
 
@@ -12,6 +12,7 @@ use bevy::{
 };
 
 use crate::{
+    math::aabb::IAabb2d,
     serializing::load_object,
     tilemap::{
         chunking::storage::{ChunkedStorage, TileBuilderChunkedStorage},
@@ -65,11 +66,11 @@ pub fn load(
         };
 
         let texture = if let Some(tex) = &ser_tilemap.texture {
-            Some(TilemapTexture {
-                texture: asset_server.load(tex.path.clone()),
-                desc: tex.desc.clone().into(),
-                rotation: tex.rotation,
-            })
+            Some(TilemapTexture::new(
+                asset_server.load(tex.path.clone()),
+                tex.desc.clone().into(),
+                tex.rotation,
+            ))
         } else {
             None
         };
@@ -94,29 +95,7 @@ pub fn load(
                 continue;
             };
 
-            let mut bundles = Vec::new();
-            ser_tiles
-                .chunked_iter_some()
-                .for_each(|(chunk_index, in_chunk_index, tile)| {
-                    let tile_entity = commands.spawn_empty().id();
-                    storage
-                        .storage
-                        .set_elem_precise(chunk_index, in_chunk_index, tile_entity);
-                    bundles.push((
-                        tile_entity,
-                        Tile {
-                            tilemap_id: entity,
-                            chunk_index,
-                            in_chunk_index,
-                            index: storage
-                                .storage
-                                .inverse_transform_index(chunk_index, in_chunk_index),
-                            texture: tile.texture.clone(),
-                            color: tile.color,
-                        },
-                    ));
-                });
-            commands.insert_or_spawn_batch(bundles);
+            spawn_tiles(&mut commands, &mut storage, &ser_tiles);
         }
 
         if let Some(tex) = texture {
@@ -153,23 +132,9 @@ pub fn load(
                 continue;
             };
 
-            let mut physics_storage = ChunkedStorage::new(ser_tilemap.chunk_size);
-
-            physics_tiles
-                .chunked_iter_some()
-                .for_each(|(chunk_index, in_chunk_index, tile)| {
-                    physics_storage.set_elem_precise(
-                        chunk_index,
-                        in_chunk_index,
-                        tile.spawn(&mut commands),
-                    );
-                });
-
-            commands.entity(entity).insert(PhysicsTilemap {
-                storage: physics_storage,
-                spawn_queue: Vec::new(),
-                data: physics_tiles,
-            });
+            let physics_tilemap =
+                spawn_physics_tilemap(&mut commands, ser_tilemap.chunk_size, physics_tiles);
+            commands.entity(entity).insert(physics_tilemap);
         }
     }
 }
@@ -182,3 +147,74 @@ fn complete(commands: &mut Commands, entity: Entity, bundle: impl Bundle, is_suc
         commands.entity(entity).despawn_recursive();
     }
 }
+
+/// Spawn the tiles into the storage, whose `tilemap` must be set.
+pub(crate) fn spawn_tiles(
+    commands: &mut Commands,
+    storage: &mut TilemapStorage,
+    ser_tiles: &TileBuilderChunkedStorage,
+) {
+    let mut bundles = Vec::new();
+    ser_tiles
+        .chunked_iter_some()
+        .for_each(|(chunk_index, in_chunk_index, tile)| {
+            let tile_entity = commands.spawn_empty().id();
+            storage
+                .storage
+                .set_elem_precise(chunk_index, in_chunk_index, tile_entity);
+            bundles.push((
+                tile_entity,
+                Tile {
+                    tilemap_id: storage.tilemap,
+                    chunk_index,
+                    in_chunk_index,
+                    index: storage
+                        .storage
+                        .inverse_transform_index(chunk_index, in_chunk_index),
+                    texture: tile.texture.clone(),
+                    color: tile.color,
+                    pivot: tile.pivot,
+                    footprint: tile.footprint,
+                },
+            ));
+        });
+    // Let the tiles with footprints cover all their cells.
+    bundles.iter().for_each(|(entity, tile)| {
+        let Some(footprint) = tile.footprint else {
+            return;
+        };
+        let aabb = IAabb2d {
+            min: tile.index,
+            max: tile.index + footprint.as_ivec2() - 1,
+        };
+        aabb.into_iter().for_each(|cell| {
+            storage.storage.set_elem(cell, *entity);
+        });
+        storage.footprints.insert(*entity, aabb);
+    });
+    commands.insert_or_spawn_batch(bundles);
+}
+
+#[cfg(feature = "physics")]
+pub(crate) fn spawn_physics_tilemap(
+    commands: &mut Commands,
+    chunk_size: u32,
+    physics_tiles: PackedPhysicsTileChunkedStorage,
+) -> PhysicsTilemap {
+    let mut physics_storage = ChunkedStorage::new(chunk_size);
+
+    physics_tiles
+        .chunked_iter_some()
+        .for_each(|(chunk_index, in_chunk_index, tile)| {
+            physics_storage.set_elem_precise(chunk_index, in_chunk_index, tile.spawn(commands));
+        });
+
+    let mut physics_tilemap = PhysicsTilemap::new_with_chunk_size(chunk_size);
+    physics_tiles
+        .iter_some()
+        .filter(|tile| tile.physics_tile.rigid_body)
+        .for_each(|tile| physics_tilemap.occupy_solid(tile.area()));
+    physics_tilemap.storage = physics_storage;
+    physics_tilemap.data = physics_tiles;
+    physics_tilemap
+}
@@ -26,12 +26,15 @@ pub const PHYSICS_TILES: &str = "physics_tiles.ron";
 
 pub mod load;
 pub mod save;
+pub mod savegame;
 
 pub struct EntiTilesTilemapSerializingPlugin;
 
 impl Plugin for EntiTilesTilemapSerializingPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, (save::save, load::load));
+
+        app.register_type::<savegame::Persistent>();
     }
 }
 
@@ -12,7 +12,7 @@ use bevy::{
 use crate::{
     serializing::{pattern::TilemapPattern, save_object},
     tilemap::{
-        chunking::storage::ChunkedStorage,
+        chunking::storage::{ChunkedStorage, TileBuilderChunkedStorage},
         despawn::DespawnMe,
         map::{
             TilePivot, TileRenderSize, TilemapAnimations, TilemapLayerOpacities, TilemapName,
@@ -124,18 +124,7 @@ pub fn save(
 
         // color
         if saver.layers.contains(TilemapLayer::COLOR) {
-            let chunk_size = storage.storage.chunk_size;
-            let ser_tiles = storage.storage.chunked_iter_some().fold(
-                ChunkedStorage::<TileBuilder>::new(chunk_size),
-                |mut acc, (chunk_index, in_chunk_index, tile)| {
-                    acc.set_elem_precise(
-                        chunk_index,
-                        in_chunk_index,
-                        tiles_query.get(*tile).unwrap().clone().into(),
-                    );
-                    acc
-                },
-            );
+            let ser_tiles = serialize_tiles(&storage, |tile| tiles_query.get(tile).unwrap());
 
             match saver.mode {
                 TilemapSaverMode::Tilemap => save_object(&map_path, TILES, &ser_tiles),
@@ -202,3 +191,21 @@ pub fn save(
         commands.entity(entity).remove::<TilemapSaver>();
     }
 }
+
+/// Collect the tiles of the tilemap into builders.
+pub(crate) fn serialize_tiles<'a>(
+    storage: &TilemapStorage,
+    get_tile: impl Fn(Entity) -> &'a Tile,
+) -> TileBuilderChunkedStorage {
+    storage.storage.chunked_iter_some().fold(
+        ChunkedStorage::<TileBuilder>::new(storage.storage.chunk_size),
+        |mut acc, (chunk_index, in_chunk_index, tile)| {
+            let tile = get_tile(*tile);
+            // Tiles with footprints are only saved at their own index.
+            if tile.chunk_index == chunk_index && tile.in_chunk_index == in_chunk_index {
+                acc.set_elem_precise(chunk_index, in_chunk_index, tile.clone().into());
+            }
+            acc
+        },
+    )
+}
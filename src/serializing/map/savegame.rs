@@ -0,0 +1,218 @@
+use bevy::{
+    asset::AssetServer,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::With,
+        system::{CommandQueue, Commands},
+        world::World,
+    },
+    log::warn,
+    reflect::Reflect,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::tilemap::{
+    chunking::storage::{ChunkedStorage, TileBuilderChunkedStorage},
+    despawn::DespawnMe,
+    map::{
+        TilePivot, TileRenderSize, TilemapAnimations, TilemapLayerOpacities, TilemapName,
+        TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTransform, TilemapType,
+    },
+    tile::Tile,
+};
+
+use super::{
+    load::spawn_tiles, save::serialize_tiles, SerializedTilemap, SerializedTilemapTexture,
+    TilemapLayer,
+};
+
+#[cfg(feature = "algorithm")]
+use crate::tilemap::{algorithm::path::PathTilemap, chunking::storage::PathTileChunkedStorage};
+#[cfg(feature = "physics")]
+use crate::tilemap::{chunking::storage::PackedPhysicsTileChunkedStorage, physics::PhysicsTilemap};
+
+/// Marks a tilemap to be captured by `collect_savable_maps`.
+///
+/// The texture is saved by the path of its image, so images created at runtime
+/// are not supported.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct Persistent;
+
+/// A tilemap and its companions in a `SaveBundle`.
+#[derive(Serialize, Deserialize)]
+pub struct SavedTilemap {
+    pub tilemap: SerializedTilemap,
+    pub tiles: TileBuilderChunkedStorage,
+    #[cfg(feature = "algorithm")]
+    pub path_tiles: Option<PathTileChunkedStorage>,
+    #[cfg(feature = "physics")]
+    pub physics_tiles: Option<PackedPhysicsTileChunkedStorage>,
+}
+
+/// All the `Persistent` tilemaps of a world, ready to be written into a savegame.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SaveBundle {
+    pub tilemaps: Vec<SavedTilemap>,
+}
+
+/// Capture all the tilemaps marked with `Persistent`, along with their
+/// `PathTilemap`s and `PhysicsTilemap`s.
+pub fn collect_savable_maps(world: &mut World) -> SaveBundle {
+    let mut tilemaps_query = world.query_filtered::<(
+        &TilemapName,
+        &TileRenderSize,
+        &TilemapSlotSize,
+        &TilemapType,
+        &TilePivot,
+        &TilemapLayerOpacities,
+        &TilemapStorage,
+        &TilemapTransform,
+        Option<&TilemapTexture>,
+        Option<&TilemapAnimations>,
+    ), With<Persistent>>();
+    let world = &*world;
+
+    let tilemaps = tilemaps_query
+        .iter(world)
+        .map(
+            |(
+                name,
+                tile_render_size,
+                slot_size,
+                ty,
+                tile_pivot,
+                layer_opacities,
+                storage,
+                transform,
+                texture,
+                animations,
+            )| {
+                #[allow(unused_mut)]
+                let mut layers = TilemapLayer::COLOR;
+
+                #[cfg(feature = "algorithm")]
+                let path_tiles = world.get::<PathTilemap>(storage.tilemap).map(|path| {
+                    layers |= TilemapLayer::PATH;
+                    path.storage.clone()
+                });
+                #[cfg(feature = "physics")]
+                let physics_tiles = world.get::<PhysicsTilemap>(storage.tilemap).map(|physics| {
+                    layers |= TilemapLayer::PHYSICS;
+                    physics.data.clone()
+                });
+
+                let texture = texture.and_then(|texture| {
+                    let Some(path) = texture.texture.path() else {
+                        warn!(
+                            "The texture of tilemap {} has no path, it won't be saved!",
+                            name.0
+                        );
+                        return None;
+                    };
+                    Some(SerializedTilemapTexture {
+                        path: path.to_string(),
+                        desc: texture.desc.clone().into(),
+                        rotation: texture.rotation,
+                    })
+                });
+
+                SavedTilemap {
+                    tilemap: SerializedTilemap {
+                        name: name.clone(),
+                        tile_render_size: *tile_render_size,
+                        slot_size: *slot_size,
+                        ty: *ty,
+                        tile_pivot: *tile_pivot,
+                        layer_opacities: *layer_opacities,
+                        tilemap_transform: *transform,
+                        texture,
+                        animations: animations.cloned(),
+                        layers,
+                        chunk_size: storage.storage.chunk_size,
+                    },
+                    tiles: serialize_tiles(storage, |tile| world.get::<Tile>(tile).unwrap()),
+                    #[cfg(feature = "algorithm")]
+                    path_tiles,
+                    #[cfg(feature = "physics")]
+                    physics_tiles,
+                }
+            },
+        )
+        .collect();
+
+    SaveBundle { tilemaps }
+}
+
+/// Replace the `Persistent` tilemaps in the world with the ones in the bundle.
+///
+/// Returns the spawned tilemaps in the order of the bundle.
+pub fn apply_save_bundle(world: &mut World, bundle: SaveBundle) -> Vec<Entity> {
+    let mut persistent_query = world.query_filtered::<&TilemapStorage, With<Persistent>>();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+
+    persistent_query.iter(world).for_each(|storage| {
+        let mut storage = storage.clone();
+        storage.despawn(&mut commands);
+        commands.entity(storage.tilemap).insert(DespawnMe);
+    });
+
+    let asset_server = world.resource::<AssetServer>();
+    let tilemaps = bundle
+        .tilemaps
+        .into_iter()
+        .map(|saved| {
+            let entity = commands.spawn(Persistent).id();
+            let mut storage = TilemapStorage {
+                tilemap: entity,
+                storage: ChunkedStorage::new(saved.tilemap.chunk_size),
+                ..Default::default()
+            };
+            spawn_tiles(&mut commands, &mut storage, &saved.tiles);
+
+            let texture = saved.tilemap.texture.as_ref().map(|tex| {
+                TilemapTexture::new(
+                    asset_server.load(tex.path.clone()),
+                    tex.desc.clone().into(),
+                    tex.rotation,
+                )
+            });
+            let mut tilemap = commands.entity(entity);
+            match texture {
+                Some(texture) => {
+                    let mut bundle = saved.tilemap.into_tilemap(entity, texture);
+                    bundle.storage = storage;
+                    tilemap.insert(bundle);
+                }
+                None => {
+                    let mut bundle = saved.tilemap.into_pure_color_tilemap(entity);
+                    bundle.storage = storage;
+                    tilemap.insert(bundle);
+                }
+            }
+
+            #[cfg(feature = "algorithm")]
+            if let Some(path_tiles) = saved.path_tiles {
+                commands.entity(entity).insert(PathTilemap {
+                    storage: path_tiles,
+                });
+            }
+
+            #[cfg(feature = "physics")]
+            if let Some(physics_tiles) = saved.physics_tiles {
+                let physics_tilemap = super::load::spawn_physics_tilemap(
+                    &mut commands,
+                    saved.tilemap.chunk_size,
+                    physics_tiles,
+                );
+                commands.entity(entity).insert(physics_tilemap);
+            }
+
+            entity
+        })
+        .collect();
+
+    queue.apply(world);
+    tilemaps
+}
@@ -4,6 +4,7 @@ use bevy::app::Plugin;
 use ron::error::SpannedError;
 use serde::{Deserialize, Serialize};
 
+pub mod brush;
 pub mod chunk;
 pub mod map;
 pub mod pattern;
@@ -13,6 +14,7 @@ pub struct EntiTilesSerializingPlugin;
 impl Plugin for EntiTilesSerializingPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugins((
+            brush::EntiTilesBrushPlugin,
             chunk::EntiTilesChunkSerializingPlugin,
             map::EntiTilesTilemapSerializingPlugin,
         ));
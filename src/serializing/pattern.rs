@@ -1,17 +1,25 @@
 use crate::{
     prelude::TilemapAnimations,
-    tilemap::{buffers::TileBuffer, map::TilemapTexture},
+    tilemap::{
+        buffers::TileBuffer,
+        map::{TilemapStorage, TilemapTexture},
+        tile::TileTexture,
+    },
+};
+use bevy::{
+    ecs::system::Commands,
+    math::{IVec2, UVec2},
+    reflect::Reflect,
 };
-use bevy::{math::UVec2, reflect::Reflect};
 use serde::{Deserialize, Serialize};
 
 use crate::tilemap::buffers::TileBuilderBuffer;
 
 #[cfg(feature = "algorithm")]
-use crate::tilemap::buffers::PathTileBuffer;
+use crate::tilemap::{algorithm::path::PathTilemap, buffers::PathTileBuffer};
 
 #[cfg(feature = "physics")]
-use crate::tilemap::physics::SerializablePhysicsSource;
+use crate::tilemap::physics::{PhysicsTilemap, SerializablePhysicsSource};
 
 /// A pattern of tiles.
 /// 
@@ -39,6 +47,55 @@ impl TilemapPattern {
             physics_tiles: SerializablePhysicsSource::Buffer(TileBuffer::new()),
         }
     }
+
+    /// Stamp the tiles into a live tilemap, with `origin` as the bottom left corner
+    /// of the pattern. The animations of the pattern are appended to `animations`.
+    ///
+    /// Tiles at the same indices are overwritten, but the cells that are empty in the pattern
+    /// are kept. Use `TilemapStorage::despawn_region` first if you want to clear them.
+    pub fn stamp(
+        &self,
+        commands: &mut Commands,
+        storage: &mut TilemapStorage,
+        animations: &mut TilemapAnimations,
+        origin: IVec2,
+    ) {
+        let mut tiles = self.tiles.clone();
+        if !self.animations.0.is_empty() {
+            let offset = animations.merge(&self.animations);
+            tiles.tiles.values_mut().for_each(|tile| {
+                if let TileTexture::Animated(anim) = &mut tile.texture {
+                    anim.start += offset;
+                }
+            });
+        }
+
+        storage.fill_with_buffer(commands, origin, tiles);
+    }
+
+    /// Stamp the path tiles into a live `PathTilemap`. See `stamp`.
+    #[cfg(feature = "algorithm")]
+    pub fn stamp_path_tiles(&self, path_tilemap: &mut PathTilemap, origin: IVec2) {
+        path_tilemap.fill_with_buffer(origin, self.path_tiles.clone());
+    }
+
+    /// Stamp the physics tiles into a live `PhysicsTilemap`. See `stamp`.
+    ///
+    /// **Notice**: The colliders already in the area are kept.
+    /// Use `PhysicsTilemap::remove_region` first if you want to clear them.
+    #[cfg(feature = "physics")]
+    pub fn stamp_physics_tiles(&self, physics_tilemap: &mut PhysicsTilemap, origin: IVec2) {
+        match &self.physics_tiles {
+            SerializablePhysicsSource::Data(data) => {
+                let mut data = data.clone();
+                data.origin += origin;
+                physics_tilemap.spawn_queue.extend(data.take_aabbs());
+            }
+            SerializablePhysicsSource::Buffer(buffer) => {
+                physics_tilemap.fill_with_buffer_packed(origin, buffer.clone());
+            }
+        }
+    }
 }
 
 /// A layer of patterns. This can be used when performing wfc.
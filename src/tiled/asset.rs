@@ -0,0 +1,153 @@
+use std::fmt::Display;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext, ReadAssetBytesError},
+    log::warn,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+use crate::utils::asset::AssetPath;
+
+use super::{
+    resources::PackedTiledTilemap,
+    xml::{
+        tileset::TiledTileset,
+        version::{self, TiledVersion},
+        TiledTilemap,
+    },
+};
+
+#[derive(Debug)]
+pub enum TiledXmlLoaderError {
+    Io(std::io::Error),
+    /// An external tileset of the map could not be read.
+    Tileset(ReadAssetBytesError),
+    Xml(quick_xml::DeError),
+    /// The `version` of the map is missing or can't be parsed.
+    UnknownVersion(Option<String>),
+    /// The map is saved in a format version that can't be loaded.
+    UnsupportedVersion(TiledVersion),
+}
+
+impl Display for TiledXmlLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TiledXmlLoaderError::Io(e) => write!(f, "Could not read the Tiled file: {}", e),
+            TiledXmlLoaderError::Tileset(e) => write!(f, "Could not read the tileset: {}", e),
+            TiledXmlLoaderError::Xml(e) => write!(f, "Could not parse the Tiled file: {}", e),
+            TiledXmlLoaderError::UnknownVersion(version) => write!(
+                f,
+                "Could not detect the version of the Tiled file, version is {:?}",
+                version
+            ),
+            TiledXmlLoaderError::UnsupportedVersion(version) => write!(
+                f,
+                "TMX format {} is not supported, only {} to {}.x are",
+                version,
+                TiledVersion::MIN_SUPPORTED,
+                TiledVersion::MAX_SUPPORTED.major,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TiledXmlLoaderError {}
+
+fn to_str(bytes: &[u8]) -> Result<&str, TiledXmlLoaderError> {
+    std::str::from_utf8(bytes).map_err(|e| {
+        TiledXmlLoaderError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    })
+}
+
+fn parse_xml<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TiledXmlLoaderError> {
+    quick_xml::de::from_str(to_str(bytes)?).map_err(TiledXmlLoaderError::Xml)
+}
+
+/// Check the format version before parsing the map, as older maps
+/// usually fail to parse with confusing errors.
+fn check_version(bytes: &[u8]) -> Result<(), TiledXmlLoaderError> {
+    let raw_version = version::detect_version(to_str(bytes)?);
+    let Some(version) = raw_version.as_deref().and_then(TiledVersion::parse) else {
+        return Err(TiledXmlLoaderError::UnknownVersion(raw_version));
+    };
+    if version.is_incompatible() {
+        return Err(TiledXmlLoaderError::UnsupportedVersion(version));
+    }
+    if version.is_newer() {
+        warn!(
+            "TMX format {} is newer than {}, some data may be missing!",
+            version,
+            TiledVersion::MAX_SUPPORTED
+        );
+    }
+    Ok(())
+}
+
+async fn read_map(
+    reader: &mut Reader<'_>,
+    load_context: &mut LoadContext<'_>,
+) -> Result<PackedTiledTilemap, TiledXmlLoaderError> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(TiledXmlLoaderError::Io)?;
+    check_version(&bytes)?;
+    let xml = parse_xml::<TiledTilemap>(&bytes)?;
+
+    let path = load_context.path().to_path_buf();
+    let mut tilesets = Vec::with_capacity(xml.tilesets.len());
+    for tileset_def in &xml.tilesets {
+        let tileset_path = path
+            .parent()
+            .unwrap()
+            .join(&tileset_def.source)
+            .to_asset_path();
+        let bytes = load_context
+            .read_asset_bytes(tileset_path)
+            .await
+            .map_err(TiledXmlLoaderError::Tileset)?;
+        tilesets.push(parse_xml::<TiledTileset>(&bytes)?);
+    }
+
+    Ok(PackedTiledTilemap {
+        name: path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string(),
+        path,
+        xml,
+        tilesets,
+    })
+}
+
+/// Loads `.tmx` files along with their external `.tsx` tilesets
+/// as `PackedTiledTilemap` assets.
+///
+/// Maps from TMX format 1.2 onwards are supported, see `TiledVersion`.
+///
+/// Everything is read through the asset sources, so maps can be loaded
+/// wherever the assets are, including on the web.
+#[derive(Default)]
+pub struct TiledXmlLoader;
+
+impl AssetLoader for TiledXmlLoader {
+    type Asset = PackedTiledTilemap;
+    type Settings = ();
+    type Error = TiledXmlLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(read_map(reader, load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
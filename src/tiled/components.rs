@@ -13,6 +13,13 @@ pub struct TiledLoader {
 #[derive(Component, Debug, Clone)]
 pub struct TiledUnloader;
 
+/// The translation of the tilemap that the object belongs to.
+/// This will be applied to the object's transform after it's spawned.
+#[derive(Component, Debug, Clone)]
+pub struct TiledTempTransform {
+    pub translation: Vec2,
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct TiledUnloadLayer;
 
@@ -0,0 +1,181 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    math::{IVec2, Vec2, Vec4},
+    transform::components::Transform,
+    utils::HashMap,
+};
+
+use crate::tilemap::{
+    bundles::StandardTilemapBundle,
+    diff::{self, EntityDiff, LevelDiff},
+};
+
+use super::{
+    components::TiledLoadedTilemap,
+    resources::{PackedTiledTilemap, TiledAssets, TiledTilemapManger},
+    xml::{
+        layer::{ColorTileLayerData, TiledLayer},
+        TiledGroup,
+    },
+};
+
+/// Compare a loaded Tiled map against the map in the tmx file.
+///
+/// Objects are compared by their positions, and only the ones spawned when
+/// loading are compared. Objects spawned at runtime are not reported as added,
+/// as they are not related to the map.
+///
+/// Returns `None` if the map is not loaded yet.
+pub fn diff_tiled_map(world: &World, map_entity: Entity) -> Option<LevelDiff> {
+    let loaded = world.get::<TiledLoadedTilemap>(map_entity)?;
+    let manager = world.resource::<TiledTilemapManger>();
+    let tiled_data = manager.get_cached_data().get(&loaded.map)?;
+    let tiled_assets = world.resource::<TiledAssets>();
+    let translation = manager
+        .translations
+        .get(&map_entity)
+        .copied()
+        .unwrap_or_default();
+
+    let mut level_diff = LevelDiff::default();
+    tiled_data.xml.layers.iter().for_each(|layer| {
+        diff_layer(
+            world,
+            tiled_data,
+            layer,
+            tiled_assets,
+            translation,
+            loaded,
+            &mut level_diff,
+        );
+    });
+    tiled_data.xml.groups.iter().for_each(|group| {
+        diff_group(
+            world,
+            tiled_data,
+            group,
+            tiled_assets,
+            translation,
+            loaded,
+            &mut level_diff,
+        );
+    });
+
+    Some(level_diff)
+}
+
+fn diff_group(
+    world: &World,
+    tiled_data: &PackedTiledTilemap,
+    group: &TiledGroup,
+    tiled_assets: &TiledAssets,
+    translation: Vec2,
+    loaded: &TiledLoadedTilemap,
+    level_diff: &mut LevelDiff,
+) {
+    group.layers.iter().for_each(|layer| {
+        diff_layer(
+            world,
+            tiled_data,
+            layer,
+            tiled_assets,
+            translation,
+            loaded,
+            level_diff,
+        );
+    });
+    group.groups.iter().for_each(|group| {
+        diff_group(
+            world,
+            tiled_data,
+            group,
+            tiled_assets,
+            translation,
+            loaded,
+            level_diff,
+        );
+    });
+}
+
+fn diff_layer(
+    world: &World,
+    tiled_data: &PackedTiledTilemap,
+    layer: &TiledLayer,
+    tiled_assets: &TiledAssets,
+    translation: Vec2,
+    loaded: &TiledLoadedTilemap,
+    level_diff: &mut LevelDiff,
+) {
+    match layer {
+        TiledLayer::Tiles(layer) => {
+            // Only used to receive the texture, which is not compared.
+            let mut tilemap = StandardTilemapBundle::default();
+            let tint = Vec4::new(
+                layer.tint.r,
+                layer.tint.g,
+                layer.tint.b,
+                layer.tint.a * layer.opacity,
+            );
+            let mut source = HashMap::new();
+            match &layer.data {
+                ColorTileLayerData::Tiles(tiles) => {
+                    let size = IVec2::new(layer.width as i32, layer.height as i32);
+                    source.extend(tiles.content.iter_decoded(
+                        size,
+                        tiled_assets,
+                        &mut tilemap,
+                        tiled_data,
+                        tint,
+                    ));
+                }
+                ColorTileLayerData::Chunks(chunks) => {
+                    chunks.content.iter().for_each(|chunk| {
+                        let offset = IVec2::new(chunk.x, chunk.y);
+                        let size = IVec2::new(chunk.width as i32, chunk.height as i32);
+                        source.extend(
+                            chunk
+                                .tiles
+                                .iter_decoded(size, tiled_assets, &mut tilemap, tiled_data, tint)
+                                .map(|(index, builder)| (index + offset, builder)),
+                        );
+                    });
+                }
+            }
+
+            let tilemap = loaded
+                .layers
+                .get(&layer.id)
+                .copied()
+                .unwrap_or(Entity::PLACEHOLDER);
+            level_diff.insert_layer(
+                layer.name.clone(),
+                diff::diff_tiles(world, tilemap, &source),
+            );
+        }
+        TiledLayer::Objects(layer) => {
+            layer.objects.iter().for_each(|obj| {
+                let Some(entity) = loaded.objects.get(&obj.id).copied() else {
+                    return;
+                };
+                let Some(transform) = world.get::<Transform>(entity) else {
+                    level_diff.entities.push(EntityDiff::Removed {
+                        id: obj.id.to_string(),
+                    });
+                    return;
+                };
+
+                let source = Vec2::new(obj.x, -obj.y);
+                let runtime = transform.translation.truncate() - translation;
+                if diff::is_moved(source, runtime) {
+                    level_diff.entities.push(EntityDiff::Moved {
+                        id: obj.id.to_string(),
+                        entity,
+                        source,
+                        runtime,
+                    });
+                }
+            });
+        }
+        TiledLayer::Image(_) | TiledLayer::Other => {}
+    }
+}
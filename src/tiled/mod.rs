@@ -1,16 +1,18 @@
 use bevy::{
     app::{Plugin, PreStartup, Update},
-    asset::{load_internal_asset, AssetServer, Assets, Handle},
+    asset::{load_internal_asset, AssetApp, AssetEvent, AssetServer, Assets, Handle},
     ecs::{
         entity::Entity,
+        event::EventReader,
         query::With,
-        system::{Commands, NonSend, Query, Res, ResMut},
+        system::{Commands, NonSend, ParallelCommands, Query, Res, ResMut, SystemParam},
     },
     math::{IVec2, Vec2, Vec4},
+    prelude::SpatialBundle,
     render::{mesh::Mesh, render_resource::Shader},
     sprite::{Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
     transform::components::Transform,
-    utils::HashMap,
+    utils::{FloatOrd, HashMap},
 };
 
 use crate::{
@@ -18,16 +20,20 @@ use crate::{
     tilemap::{
         buffers::TileBuilderBuffer,
         bundles::StandardTilemapBundle,
+        chunking::{budget::StreamingBudget, suggest_chunk_size, DEFAULT_EXPECTED_VIEW_SIZE},
         map::{
             TilePivot, TileRenderSize, TilemapAxisFlip, TilemapName, TilemapSlotSize,
             TilemapStorage, TilemapTransform, TilemapType,
         },
     },
-    DEFAULT_CHUNK_SIZE,
 };
 
 use self::{
-    components::{TiledLoadedTilemap, TiledLoader, TiledUnloadLayer, TiledUnloader},
+    asset::TiledXmlLoader,
+    components::{
+        TiledLoadedTilemap, TiledLoader, TiledTempTransform, TiledUnloadLayer, TiledUnloader,
+    },
+    parallax::{ParallaxCamera, ParallaxLayer},
     resources::{PackedTiledTilemap, TiledAssets, TiledLoadConfig, TiledTilemapManger},
     sprite::TiledSpriteMaterial,
     xml::{
@@ -37,7 +43,10 @@ use self::{
 };
 
 pub mod app_ext;
+pub mod asset;
 pub mod components;
+pub mod diff;
+pub mod parallax;
 pub mod resources;
 pub mod sprite;
 pub mod traits;
@@ -60,25 +69,74 @@ impl Plugin for EntiTilesTiledPlugin {
 
         app.add_systems(PreStartup, parse_tiled_xml);
 
+        app.init_asset::<PackedTiledTilemap>()
+            .init_asset_loader::<TiledXmlLoader>();
+
         app.init_resource::<TiledLoadConfig>()
             .init_resource::<TiledAssets>()
             .init_resource::<TiledTilemapManger>();
 
         app.register_type::<TiledLoadConfig>()
             .register_type::<TiledAssets>()
-            .register_type::<TiledTilemapManger>();
+            .register_type::<TiledTilemapManger>()
+            .register_type::<ParallaxCamera>()
+            .register_type::<ParallaxLayer>();
 
         app.add_systems(
             Update,
-            (unload_tiled_layer, unload_tiled_tilemap, load_tiled_xml),
+            (
+                tiled_xml_applier,
+                unload_tiled_layer,
+                unload_tiled_tilemap,
+                load_tiled_xml,
+                tiled_temp_transform_applier,
+                parallax::parallax_layer_updater,
+            ),
         );
 
         app.init_non_send_resource::<TiledObjectRegistry>();
     }
 }
 
-fn parse_tiled_xml(mut manager: ResMut<TiledTilemapManger>, config: Res<TiledLoadConfig>) {
-    manager.reload_xml(&config);
+fn parse_tiled_xml(
+    mut manager: ResMut<TiledTilemapManger>,
+    config: Res<TiledLoadConfig>,
+    asset_server: Res<AssetServer>,
+) {
+    manager.reload_xml(&config, &asset_server);
+}
+
+/// Refresh the cached data of the manager when the maps are loaded or modified.
+pub fn tiled_xml_applier(
+    mut manager: ResMut<TiledTilemapManger>,
+    mut asset_events: EventReader<AssetEvent<PackedTiledTilemap>>,
+    tiled_xmls: Res<Assets<PackedTiledTilemap>>,
+) {
+    let changed = asset_events.read().any(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+            manager.contains_handle(*id)
+        }
+        _ => false,
+    });
+    if changed {
+        manager.apply_loaded(&tiled_xmls);
+    }
+}
+
+fn tiled_temp_transform_applier(
+    commands: ParallelCommands,
+    mut objects_query: Query<(Entity, Option<&mut Transform>, &TiledTempTransform)>,
+) {
+    objects_query
+        .par_iter_mut()
+        .for_each(|(entity, transform, temp)| {
+            if let Some(mut transform) = transform {
+                transform.translation += temp.translation.extend(0.);
+            }
+            commands.command_scope(|mut c| {
+                c.entity(entity).remove::<TiledTempTransform>();
+            });
+        });
 }
 
 fn unload_tiled_tilemap(
@@ -104,36 +162,75 @@ fn unload_tiled_layer(
     });
 }
 
+/// The resources used to load Tiled maps.
+#[derive(SystemParam)]
+pub struct TiledLoadParams<'w> {
+    pub asset_server: Res<'w, AssetServer>,
+    pub config: Res<'w, TiledLoadConfig>,
+    pub manager: ResMut<'w, TiledTilemapManger>,
+    pub tiled_assets: ResMut<'w, TiledAssets>,
+    pub material_assets: ResMut<'w, Assets<TiledSpriteMaterial>>,
+    pub mesh_assets: ResMut<'w, Assets<Mesh>>,
+    pub object_registry: NonSend<'w, TiledObjectRegistry>,
+}
+
+/// What the layers of a map are loaded with.
+#[derive(Clone, Copy)]
+struct TiledMapContext<'a> {
+    tiled_data: &'a PackedTiledTilemap,
+    tiled_assets: &'a TiledAssets,
+    asset_server: &'a AssetServer,
+    object_registry: &'a TiledObjectRegistry,
+    config: &'a TiledLoadConfig,
+    translation: Vec2,
+}
+
 fn load_tiled_xml(
     mut commands: Commands,
     loaders_query: Query<(Entity, &TiledLoader)>,
-    mut manager: ResMut<TiledTilemapManger>,
-    config: Res<TiledLoadConfig>,
-    mut tiled_assets: ResMut<TiledAssets>,
-    asset_server: Res<AssetServer>,
-    mut material_assets: ResMut<Assets<TiledSpriteMaterial>>,
-    mut mesh_assets: ResMut<Assets<Mesh>>,
-    object_registry: NonSend<TiledObjectRegistry>,
+    mut params: TiledLoadParams,
+    mut budget: Option<ResMut<StreamingBudget>>,
 ) {
-    for (entity, loader) in &loaders_query {
+    let mut loaders = loaders_query.iter().collect::<Vec<_>>();
+    if let Some(budget) = budget.as_ref().filter(|b| b.focus().is_some()) {
+        // Tiled maps have no size in world space until they are loaded,
+        // so only the translation is taken into account.
+        loaders.sort_by_cached_key(|(_, loader)| {
+            FloatOrd(budget.priority(loader.trans_ovrd.unwrap_or_default()))
+        });
+    }
+
+    for (entity, loader) in loaders {
+        if let Some(budget) = budget.as_mut() {
+            let tile_count = params
+                .manager
+                .get_cached_data()
+                .get(&loader.map)
+                .map(|map| map.tile_count())
+                .unwrap_or_default();
+            if !budget.try_spend_tiles(tile_count) {
+                break;
+            }
+        }
+
+        let TiledLoadParams {
+            asset_server,
+            config,
+            manager,
+            tiled_assets,
+            material_assets,
+            mesh_assets,
+            ..
+        } = &mut params;
         tiled_assets.initialize(
-            &manager,
-            &config,
-            &asset_server,
-            &mut material_assets,
-            &mut mesh_assets,
+            manager,
+            config,
+            asset_server,
+            material_assets,
+            mesh_assets,
         );
 
-        load_tiled_tilemap(
-            &mut commands,
-            &mut manager,
-            &config,
-            &tiled_assets,
-            &asset_server,
-            &loader,
-            &object_registry,
-            entity,
-        );
+        load_tiled_tilemap(&mut commands, &params, loader, entity);
 
         commands.entity(entity).remove::<TiledLoader>();
     }
@@ -141,15 +238,19 @@ fn load_tiled_xml(
 
 fn load_tiled_tilemap(
     commands: &mut Commands,
-    manager: &mut TiledTilemapManger,
-    config: &TiledLoadConfig,
-    tiled_assets: &TiledAssets,
-    asset_server: &AssetServer,
+    params: &TiledLoadParams,
     loader: &TiledLoader,
-    object_registry: &TiledObjectRegistry,
     map_entity: Entity,
 ) {
-    let tiled_data = manager.get_cached_data().get(&loader.map).unwrap();
+    let tiled_data = params.manager.get_cached_data().get(&loader.map).unwrap();
+    let ctx = TiledMapContext {
+        tiled_data,
+        tiled_assets: &params.tiled_assets,
+        asset_server: &params.asset_server,
+        object_registry: &params.object_registry,
+        config: &params.config,
+        translation: loader.trans_ovrd.unwrap_or_default(),
+    };
     let mut loaded_map = TiledLoadedTilemap {
         map: tiled_data.name.clone(),
         layers: HashMap::default(),
@@ -157,29 +258,11 @@ fn load_tiled_tilemap(
     };
 
     tiled_data.xml.layers.iter().for_each(|layer| {
-        load_layer(
-            commands,
-            tiled_data,
-            layer,
-            tiled_assets,
-            asset_server,
-            object_registry,
-            config,
-            &mut loaded_map,
-        )
+        load_layer(commands, ctx, layer, &mut loaded_map);
     });
 
     tiled_data.xml.groups.iter().for_each(|group| {
-        load_group(
-            commands,
-            tiled_data,
-            group,
-            tiled_assets,
-            asset_server,
-            object_registry,
-            config,
-            &mut loaded_map,
-        )
+        load_group(commands, ctx, group, &mut loaded_map);
     });
 
     commands.entity(map_entity).insert(loaded_map);
@@ -187,51 +270,34 @@ fn load_tiled_tilemap(
 
 fn load_group(
     commands: &mut Commands,
-    tiled_data: &PackedTiledTilemap,
+    ctx: TiledMapContext,
     group: &TiledGroup,
-    tiled_assets: &TiledAssets,
-    asset_server: &AssetServer,
-    object_registry: &TiledObjectRegistry,
-    config: &TiledLoadConfig,
     loaded_map: &mut TiledLoadedTilemap,
 ) {
     group.layers.iter().for_each(|content| {
-        load_layer(
-            commands,
-            tiled_data,
-            content,
-            tiled_assets,
-            asset_server,
-            object_registry,
-            config,
-            loaded_map,
-        )
+        load_layer(commands, ctx, content, loaded_map);
     });
 
     group.groups.iter().for_each(|group| {
-        load_group(
-            commands,
-            tiled_data,
-            group,
-            tiled_assets,
-            asset_server,
-            object_registry,
-            config,
-            loaded_map,
-        )
+        load_group(commands, ctx, group, loaded_map);
     });
 }
 
 fn load_layer(
     commands: &mut Commands,
-    tiled_data: &PackedTiledTilemap,
+    ctx: TiledMapContext,
     layer: &TiledLayer,
-    tiled_assets: &TiledAssets,
-    asset_server: &AssetServer,
-    object_registry: &TiledObjectRegistry,
-    config: &TiledLoadConfig,
     loaded_map: &mut TiledLoadedTilemap,
 ) {
+    let TiledMapContext {
+        tiled_data,
+        tiled_assets,
+        asset_server,
+        object_registry,
+        config,
+        translation,
+    } = ctx;
+
     match layer {
         TiledLayer::Tiles(layer) => {
             let tile_size = Vec2::new(
@@ -253,9 +319,17 @@ fn load_layer(
                         TilemapType::Hexagonal(tiled_data.xml.hex_side_length)
                     }
                 },
-                storage: TilemapStorage::new(DEFAULT_CHUNK_SIZE, entity),
+                storage: TilemapStorage::new(
+                    suggest_chunk_size(
+                        layer_size.as_uvec2(),
+                        tile_size,
+                        DEFAULT_EXPECTED_VIEW_SIZE,
+                    ),
+                    entity,
+                ),
                 transform: TilemapTransform::from_translation(
-                    Vec2::new(layer.offset_x as f32, layer.offset_y as f32)
+                    translation
+                        + Vec2::new(layer.offset_x as f32, layer.offset_y as f32)
                         + match tiled_data.xml.orientation {
                             MapOrientation::Orthogonal | MapOrientation::Isometric => Vec2::ZERO,
                             MapOrientation::Staggered | MapOrientation::Hexagonal => {
@@ -314,8 +388,40 @@ fn load_layer(
             loaded_map.layers.insert(layer.id, entity);
         }
         TiledLayer::Objects(layer) => {
+            let tile_size = Vec2::new(
+                tiled_data.xml.tile_width as f32,
+                tiled_data.xml.tile_height as f32,
+            );
             layer.objects.iter().for_each(|obj| {
+                let spatial = SpatialBundle {
+                    transform: obj
+                        .transform(tiled_assets.get_object_z_order(&tiled_data.name, obj.id)),
+                    visibility: obj.visibility(),
+                    ..Default::default()
+                };
+
+                let region = config
+                    .region_classes
+                    .contains(&obj.ty)
+                    .then(|| obj.as_region(tile_size, translation))
+                    .flatten();
+
                 let Some(phantom) = object_registry.get(&obj.ty) else {
+                    // Tile objects without a class are usually decorations.
+                    if obj.gid.is_some() {
+                        let mut entity =
+                            commands.spawn((TiledTempTransform { translation }, spatial));
+                        obj.spawn_sprite(&mut entity, tiled_assets, &tiled_data.name);
+                        loaded_map.objects.insert(obj.id, entity.id());
+                        return;
+                    }
+                    if let Some(region) = region {
+                        let entity = commands
+                            .spawn((TiledTempTransform { translation }, spatial, region))
+                            .id();
+                        loaded_map.objects.insert(obj.id, entity);
+                        return;
+                    }
                     if config.ignore_unregisterd_objects {
                         return;
                     }
@@ -326,7 +432,7 @@ fn load_layer(
                     )
                 };
 
-                let mut entity = commands.spawn_empty();
+                let mut entity = commands.spawn((TiledTempTransform { translation }, spatial));
                 phantom.initialize(
                     &mut entity,
                     obj,
@@ -339,6 +445,9 @@ fn load_layer(
                     tiled_assets,
                     tiled_data.name.clone(),
                 );
+                if let Some(region) = region {
+                    entity.insert(region);
+                }
 
                 loaded_map.objects.insert(obj.id, entity.id());
             });
@@ -349,14 +458,28 @@ fn load_layer(
                 tiled_assets.clone_image_layer_material_handle(&tiled_data.name, layer.id),
             );
 
-            let entity = commands
-                .spawn(MaterialMesh2dBundle {
-                    mesh: Mesh2dHandle(mesh),
-                    material,
-                    transform: Transform::from_xyz(0., 0., z),
-                    ..Default::default()
-                })
-                .id();
+            let mut entity = commands.spawn(MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(mesh),
+                material,
+                transform: Transform::from_translation(translation.extend(z)),
+                ..Default::default()
+            });
+
+            if layer.parallax_x != 1. || layer.parallax_y != 1. {
+                // The parallax origin is relative to the top left corner of the map.
+                entity.insert(ParallaxLayer {
+                    factor: Vec2::new(layer.parallax_x, layer.parallax_y),
+                    origin: translation,
+                    anchor: translation
+                        + Vec2::new(
+                            tiled_data.xml.parallax_origin_x,
+                            -tiled_data.xml.parallax_origin_y,
+                        ),
+                    wrap: Vec2::ZERO,
+                });
+            }
+
+            let entity = entity.id();
 
             loaded_map.layers.insert(layer.id, entity);
         }
@@ -0,0 +1,246 @@
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{With, Without},
+        system::{Commands, Query},
+    },
+    math::{Vec2, Vec4},
+    reflect::Reflect,
+    render::{mesh::Mesh, texture::Image},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::math::aabb::Aabb2d;
+
+use super::sprite::{self, SpriteUniform, TiledSpriteMaterial};
+
+/// Cameras with this component will be followed by parallax layers.
+///
+/// Only the first camera found is used.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct ParallaxCamera;
+
+/// A layer that moves slower or faster than the camera.
+///
+/// The layer is placed at `origin + (camera - anchor) * (1 - factor)`,
+/// so a factor of 1 moves with the world, and 0 sticks to the camera.
+/// Layers should not have a parent, as the translation is set directly.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct ParallaxLayer {
+    pub factor: Vec2,
+    /// The translation of the layer when the camera is at `anchor`.
+    pub origin: Vec2,
+    pub anchor: Vec2,
+    /// The size of the repeating image. Zero on axes that don't wrap.
+    ///
+    /// Wrapping layers jump by whole images to stay around the camera,
+    /// so the mesh only needs to cover the view plus one image on each side.
+    pub wrap: Vec2,
+}
+
+impl Default for ParallaxLayer {
+    fn default() -> Self {
+        Self {
+            factor: Vec2::ONE,
+            origin: Vec2::ZERO,
+            anchor: Vec2::ZERO,
+            wrap: Vec2::ZERO,
+        }
+    }
+}
+
+impl ParallaxLayer {
+    pub fn translation(&self, camera: Vec2) -> Vec2 {
+        let mut translation = self.origin + (camera - self.anchor) * (Vec2::ONE - self.factor);
+        if self.wrap.x > 0. {
+            translation.x += ((camera.x - translation.x) / self.wrap.x).round() * self.wrap.x;
+        }
+        if self.wrap.y > 0. {
+            translation.y += ((camera.y - translation.y) / self.wrap.y).round() * self.wrap.y;
+        }
+        translation
+    }
+}
+
+/// One copy of the image in a `ParallaxLayersBuilder`.
+#[derive(Debug, Clone, Copy, Reflect)]
+pub struct ParallaxLayerDesc {
+    pub factor: Vec2,
+    pub offset: Vec2,
+    pub z: f32,
+    pub tint: Vec4,
+}
+
+impl Default for ParallaxLayerDesc {
+    fn default() -> Self {
+        Self {
+            factor: Vec2::ONE,
+            offset: Vec2::ZERO,
+            z: 0.,
+            tint: Vec4::ONE,
+        }
+    }
+}
+
+/// Spawns copies of a repeating image as parallax layers.
+///
+/// The layers use the same mesh as Tiled image layers, so this also works for
+/// scenes that are not loaded from Tiled.
+#[derive(Debug, Clone)]
+pub struct ParallaxLayersBuilder {
+    image: Handle<Image>,
+    image_size: Vec2,
+    view_size: Vec2,
+    repeat_x: bool,
+    repeat_y: bool,
+    layers: Vec<ParallaxLayerDesc>,
+}
+
+impl ParallaxLayersBuilder {
+    /// `view_size` is the size of the area the camera can see, in world units.
+    pub fn new(image: Handle<Image>, image_size: Vec2, view_size: Vec2) -> Self {
+        Self {
+            image,
+            image_size,
+            view_size,
+            repeat_x: true,
+            repeat_y: false,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Set the axes the image repeats and wraps along. Defaults to only x.
+    pub fn with_repeat(mut self, repeat_x: bool, repeat_y: bool) -> Self {
+        self.repeat_x = repeat_x;
+        self.repeat_y = repeat_y;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: ParallaxLayerDesc) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Add a layer for each factor. The layers are stacked from the back at `z`,
+    /// and each one is `step` further from the previous.
+    pub fn with_layers(mut self, factors: &[Vec2], z: f32, step: Vec2) -> Self {
+        self.layers.extend(
+            factors
+                .iter()
+                .enumerate()
+                .map(|(i, factor)| ParallaxLayerDesc {
+                    factor: *factor,
+                    offset: step * i as f32,
+                    z: z + i as f32,
+                    ..Default::default()
+                }),
+        );
+        self
+    }
+
+    /// Spawn the layers in the order they were added.
+    pub fn build(
+        self,
+        commands: &mut Commands,
+        material_assets: &mut Assets<TiledSpriteMaterial>,
+        mesh_assets: &mut Assets<Mesh>,
+    ) -> Vec<Entity> {
+        let half_extent = self.view_size / 2. + self.image_size;
+        let mesh = mesh_assets.add(sprite::repeated_image_mesh(
+            self.image_size,
+            Vec2::ZERO,
+            Aabb2d {
+                min: -half_extent,
+                max: half_extent,
+            },
+            self.repeat_x,
+            self.repeat_y,
+        ));
+        let wrap = Vec2::new(
+            if self.repeat_x { self.image_size.x } else { 0. },
+            if self.repeat_y { self.image_size.y } else { 0. },
+        );
+
+        self.layers
+            .into_iter()
+            .map(|layer| {
+                let material = material_assets.add(TiledSpriteMaterial {
+                    image: self.image.clone(),
+                    data: SpriteUniform {
+                        atlas: Aabb2d {
+                            min: Vec2::ZERO,
+                            max: Vec2::ONE,
+                        },
+                        tint: layer.tint,
+                    },
+                });
+
+                commands
+                    .spawn((
+                        MaterialMesh2dBundle {
+                            mesh: Mesh2dHandle(mesh.clone()),
+                            material,
+                            transform: Transform::from_translation(layer.offset.extend(layer.z)),
+                            ..Default::default()
+                        },
+                        ParallaxLayer {
+                            factor: layer.factor,
+                            origin: layer.offset,
+                            anchor: Vec2::ZERO,
+                            wrap,
+                        },
+                    ))
+                    .id()
+            })
+            .collect()
+    }
+}
+
+pub fn parallax_layer_updater(
+    cameras_query: Query<&GlobalTransform, With<ParallaxCamera>>,
+    mut layers_query: Query<(&ParallaxLayer, &mut Transform), Without<ParallaxCamera>>,
+) {
+    let Some(camera) = cameras_query.iter().next() else {
+        return;
+    };
+    let camera = camera.translation().truncate();
+
+    layers_query
+        .par_iter_mut()
+        .for_each(|(layer, mut transform)| {
+            let translation = layer.translation(camera);
+            if transform.translation.truncate() != translation {
+                transform.translation = translation.extend(transform.translation.z);
+            }
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::Vec2;
+
+    use super::ParallaxLayer;
+
+    #[test]
+    fn test_parallax_translation() {
+        let layer = ParallaxLayer {
+            factor: Vec2::new(0.5, 1.),
+            origin: Vec2::new(0., 10.),
+            ..Default::default()
+        };
+        assert_eq!(layer.translation(Vec2::new(100., 50.)), Vec2::new(50., 10.));
+
+        let layer = ParallaxLayer {
+            factor: Vec2::new(0.5, 0.),
+            wrap: Vec2::new(64., 0.),
+            ..Default::default()
+        };
+        let translation = layer.translation(Vec2::new(1000., 30.));
+        assert!((translation.x - 1000.).abs() <= 32.);
+        assert_eq!((translation.x - 500.) % 64., 0.);
+        assert_eq!(translation.y, 30.);
+    }
+}
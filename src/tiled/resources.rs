@@ -1,16 +1,13 @@
-use std::{
-    f32::consts::PI,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use bevy::{
-    asset::{AssetServer, Assets, Handle},
+    asset::{Asset, AssetId, AssetServer, Assets, Handle},
     ecs::{
         entity::Entity,
         system::{Commands, Resource},
     },
     log::{error, warn},
-    math::{UVec2, Vec2, Vec4},
+    math::{UVec2, Vec2, Vec3, Vec4},
     reflect::Reflect,
     render::{
         mesh::{Indices, Mesh},
@@ -21,7 +18,7 @@ use bevy::{
 };
 
 use crate::{
-    math::{aabb::Aabb2d, extension::F32Integerize},
+    math::aabb::Aabb2d,
     tilemap::{
         coordinates,
         map::{TilemapRotation, TilemapTexture, TilemapTextureDescriptor},
@@ -31,7 +28,7 @@ use crate::{
 
 use super::{
     components::{TiledLoader, TiledUnloader},
-    sprite::{SpriteUniform, TiledSpriteMaterial},
+    sprite::{self, SpriteUniform, TiledSpriteMaterial},
     xml::{
         layer::TiledLayer,
         tileset::{TiledTile, TiledTileset},
@@ -42,15 +39,47 @@ use super::{
 /// Configuration for loading tiled tilemaps.
 #[derive(Resource, Default, Reflect)]
 pub struct TiledLoadConfig {
+    /// The asset paths of the maps, like `tiled/tilemaps/orthogonal.tmx`.
+    /// Paths starting from the `assets` folder are accepted as well.
     pub map_path: Vec<String>,
+    /// Skip the objects whose classes are not registered instead of panicking.
+    ///
+    /// Tile objects with unregistered classes are always spawned as plain sprites.
     pub ignore_unregisterd_objects: bool,
+    /// The classes of the rectangle objects that get a `Region`, like `Room`.
+    /// These objects are spawned even if their classes are not registered.
+    pub region_classes: Vec<String>,
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Asset, Debug, Clone, Reflect)]
 pub struct PackedTiledTilemap {
     pub name: String,
+    /// The asset path of the map.
     pub path: PathBuf,
     pub xml: TiledTilemap,
+    /// The external tilesets, in the same order as `xml.tilesets`.
+    pub tilesets: Vec<TiledTileset>,
+}
+
+impl PackedTiledTilemap {
+    /// The maximum count of tiles in this map, assuming every tile layer is full.
+    pub fn tile_count(&self) -> usize {
+        fn count(layers: &[TiledLayer], groups: &[TiledGroup]) -> usize {
+            layers
+                .iter()
+                .map(|layer| match layer {
+                    TiledLayer::Tiles(tiles) => (tiles.width * tiles.height) as usize,
+                    _ => 0,
+                })
+                .sum::<usize>()
+                + groups
+                    .iter()
+                    .map(|group| count(&group.layers, &group.groups))
+                    .sum::<usize>()
+        }
+
+        count(&self.xml.layers, &self.xml.groups)
+    }
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -69,32 +98,65 @@ pub struct TiledTilemapManger {
     pub(crate) version: u32,
     pub(crate) cache: HashMap<String, PackedTiledTilemap>,
     pub(crate) loaded_levels: HashMap<String, Entity>,
+    pub(crate) instances: HashMap<Entity, String>,
+    /// The translation of the maps and the instances, by their root entity.
+    pub(crate) translations: HashMap<Entity, Vec2>,
+    #[reflect(ignore)]
+    pub(crate) handles: Vec<Handle<PackedTiledTilemap>>,
+    pub(crate) parsing: bool,
 }
 
 impl TiledTilemapManger {
-    pub fn reload_xml(&mut self, config: &TiledLoadConfig) {
-        self.version += 1;
-        self.cache = config
+    /// Load the maps in `TiledLoadConfig::map_path` through the asset server.
+    ///
+    /// The cache will be replaced once all the maps are loaded.
+    /// Use `TiledTilemapManger::is_initialized` before loading maps.
+    pub fn reload_xml(&mut self, config: &TiledLoadConfig, asset_server: &AssetServer) {
+        let handles = config
             .map_path
             .iter()
+            .map(|path| Path::new(path).to_asset_path())
             .map(|path| {
-                let path = Path::new(path);
-                let name = path.file_stem().unwrap().to_str().unwrap().to_string();
-                (
-                    name.clone(),
-                    PackedTiledTilemap {
-                        name,
-                        path: path.to_path_buf(),
-                        xml: quick_xml::de::from_str(
-                            &std::fs::read_to_string(path).unwrap_or_else(|err| {
-                                panic!("Failed to read {:?}\n{:?}", path, err)
-                            }),
-                        )
-                        .unwrap_or_else(|err| panic!("Failed to parse {:?}\n{:?}", path, err)),
-                    },
-                )
+                let handle = asset_server.load(path.clone());
+                if self.handles.contains(&handle) {
+                    asset_server.reload(path);
+                }
+                handle
             })
             .collect();
+        self.handles = handles;
+        self.parsing = true;
+    }
+
+    /// Returns true if the xml files are requested but not all loaded yet.
+    #[inline]
+    pub fn is_parsing(&self) -> bool {
+        self.parsing
+    }
+
+    /// Whether the asset is one of the maps of this manager.
+    #[inline]
+    pub fn contains_handle(&self, id: AssetId<PackedTiledTilemap>) -> bool {
+        self.handles.iter().any(|handle| handle.id() == id)
+    }
+
+    /// Replace the cache with the loaded maps, if all of them are loaded.
+    pub(crate) fn apply_loaded(&mut self, assets: &Assets<PackedTiledTilemap>) {
+        let Some(maps) = self
+            .handles
+            .iter()
+            .map(|handle| assets.get(handle))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+
+        self.version += 1;
+        self.parsing = false;
+        self.cache = maps
+            .into_iter()
+            .map(|map| (map.name.clone(), map.clone()))
+            .collect();
     }
 
     pub fn load(&mut self, commands: &mut Commands, map_name: String, trans_ovrd: Option<Vec2>) {
@@ -106,10 +168,54 @@ impl TiledTilemapManger {
                 map: map_name.clone(),
                 trans_ovrd,
             });
+            self.translations
+                .insert(entity.id(), trans_ovrd.unwrap_or_default());
             self.loaded_levels.insert(map_name.clone(), entity.id());
         }
     }
 
+    /// Spawn a new instance of the map at the given translation. Unlike `load`,
+    /// the same map can be instanced multiple times, every instance has its own root entity.
+    ///
+    /// Use the returned entity to unload the instance.
+    pub fn load_instance(
+        &mut self,
+        commands: &mut Commands,
+        map_name: String,
+        translation: Vec2,
+    ) -> Entity {
+        self.check_initialized();
+        let entity = commands
+            .spawn(TiledLoader {
+                map: map_name.clone(),
+                trans_ovrd: Some(translation),
+            })
+            .id();
+        self.instances.insert(entity, map_name);
+        self.translations.insert(entity, translation);
+        entity
+    }
+
+    pub fn unload_instance(&mut self, commands: &mut Commands, instance: Entity) {
+        if self.instances.remove(&instance).is_some() {
+            commands.entity(instance).insert(TiledUnloader);
+            self.translations.remove(&instance);
+        } else {
+            error!(
+                "Trying to unload instance {:?} that is not loaded!",
+                instance
+            );
+        }
+    }
+
+    /// Get all the instances of the map.
+    pub fn get_instances(&self, map_name: &str) -> Vec<Entity> {
+        self.instances
+            .iter()
+            .filter_map(|(e, m)| if m == map_name { Some(*e) } else { None })
+            .collect()
+    }
+
     pub fn switch_to(&mut self, commands: &mut Commands, level: String, trans_ovrd: Option<Vec2>) {
         self.check_initialized();
         if self.loaded_levels.contains_key(&level.to_string()) {
@@ -124,17 +230,58 @@ impl TiledTilemapManger {
         let level = level.to_string();
         if let Some(l) = self.loaded_levels.get(&level) {
             commands.entity(*l).insert(TiledUnloader);
+            self.translations.remove(l);
             self.loaded_levels.remove(&level);
         } else {
             error!("Trying to unload {:?} that is not loaded!", level);
         }
     }
 
+    /// Unload all the maps, including the instances.
     pub fn unload_all(&mut self, commands: &mut Commands) {
         for (_, l) in self.loaded_levels.iter() {
             commands.entity(*l).insert(TiledUnloader);
         }
+        for (e, _) in self.instances.iter() {
+            commands.entity(*e).insert(TiledUnloader);
+        }
         self.loaded_levels.clear();
+        self.instances.clear();
+        self.translations.clear();
+    }
+
+    /// Get the name and the root entity of the loaded map or instance at the world position.
+    ///
+    /// Tiled maps don't have a world position until they are loaded,
+    /// so only the loaded ones are taken into account.
+    ///
+    /// **Notice**: Only orthogonal maps are supported for now. Isometric, staggered and
+    /// hexagonal maps are skipped, so this returns `None` for the positions inside them.
+    pub fn map_at(&self, world_pos: Vec2) -> Option<(String, Entity)> {
+        self.loaded_levels
+            .iter()
+            .chain(self.instances.iter().map(|(entity, name)| (name, entity)))
+            .find(|(name, entity)| {
+                let (Some(map), Some(translation)) =
+                    (self.cache.get(*name), self.translations.get(*entity))
+                else {
+                    return false;
+                };
+                if map.xml.orientation != MapOrientation::Orthogonal {
+                    return false;
+                }
+
+                let size = Vec2::new(
+                    (map.xml.width * map.xml.tile_width) as f32,
+                    (map.xml.height * map.xml.tile_height) as f32,
+                );
+                Aabb2d {
+                    min: *translation - Vec2::new(0., size.y),
+                    max: *translation + Vec2::new(size.x, 0.),
+                }
+                .contains(world_pos)
+            })
+            .map(|(name, entity)| (name.clone(), *entity))
     }
 
     #[inline]
@@ -250,6 +397,9 @@ impl TiledAssets {
         }
 
         self.version = manager.version;
+        // The maps may be reloaded, so the old tilesets are not valid anymore.
+        self.tilesets.clear();
+        self.tilemap_tilesets.clear();
         self.load_tilesets(manager, asset_server);
         self.load_map_assets(manager, asset_server, material_assets, mesh_assets);
     }
@@ -259,59 +409,59 @@ impl TiledAssets {
         let mut tileset_records = HashMap::default();
 
         tiled_xml.iter().for_each(|(_, map)| {
-            map.xml.tilesets.iter().for_each(|tileset_def| {
-                let tileset_path = map.path.parent().unwrap().join(&tileset_def.source);
-                let tileset_xml = quick_xml::de::from_str::<TiledTileset>(
-                    &std::fs::read_to_string(&tileset_path).unwrap(),
-                )
-                .unwrap();
-
-                match tileset_records.entry(tileset_xml.name.clone()) {
-                    Entry::Occupied(e) => {
-                        self.tilemap_tilesets
-                            .entry(map.name.clone())
-                            .or_default()
-                            .push((*e.get(), tileset_def.first_gid));
-                    }
-                    Entry::Vacant(_) => {
-                        self.tilemap_tilesets
-                            .entry(map.name.clone())
-                            .or_default()
-                            .push((self.tilesets.len(), tileset_def.first_gid));
+            map.xml
+                .tilesets
+                .iter()
+                .zip(map.tilesets.iter().cloned())
+                .for_each(|(tileset_def, tileset_xml)| {
+                    let tileset_path = map.path.parent().unwrap().join(&tileset_def.source);
+
+                    match tileset_records.entry(tileset_xml.name.clone()) {
+                        Entry::Occupied(e) => {
+                            self.tilemap_tilesets
+                                .entry(map.name.clone())
+                                .or_default()
+                                .push((*e.get(), tileset_def.first_gid));
+                        }
+                        Entry::Vacant(_) => {
+                            self.tilemap_tilesets
+                                .entry(map.name.clone())
+                                .or_default()
+                                .push((self.tilesets.len(), tileset_def.first_gid));
+                        }
                     }
-                }
 
-                let source_path = tileset_path
-                    .parent()
-                    .unwrap()
-                    .join(&tileset_xml.image.source);
-                let texture = TilemapTexture {
-                    texture: asset_server.load(source_path.to_asset_path()),
-                    desc: TilemapTextureDescriptor {
-                        size: UVec2 {
-                            x: tileset_xml.image.width,
-                            y: tileset_xml.image.height,
-                        },
-                        tile_size: UVec2 {
-                            x: tileset_xml.tile_width,
-                            y: tileset_xml.tile_height,
+                    let source_path = tileset_path
+                        .parent()
+                        .unwrap()
+                        .join(&tileset_xml.image.source);
+                    let texture = TilemapTexture::new(
+                        asset_server.load(source_path.to_asset_path()),
+                        TilemapTextureDescriptor {
+                            size: UVec2 {
+                                x: tileset_xml.image.width,
+                                y: tileset_xml.image.height,
+                            },
+                            tile_size: UVec2 {
+                                x: tileset_xml.tile_width,
+                                y: tileset_xml.tile_height,
+                            },
+                            filter_mode: FilterMode::Nearest,
                         },
-                        filter_mode: FilterMode::Nearest,
-                    },
-                    rotation: TilemapRotation::None,
-                };
+                        TilemapRotation::None,
+                    );
 
-                self.tilesets.push(PackedTiledTileset {
-                    name: tileset_xml.name.clone(),
-                    special_tiles: tileset_xml
-                        .special_tiles
-                        .iter()
-                        .map(|tile| (tile.id, tile.clone()))
-                        .collect(),
-                    xml: tileset_xml,
-                    texture,
+                    self.tilesets.push(PackedTiledTileset {
+                        name: tileset_xml.name.clone(),
+                        special_tiles: tileset_xml
+                            .special_tiles
+                            .iter()
+                            .map(|tile| (tile.id, tile.clone()))
+                            .collect(),
+                        xml: tileset_xml,
+                        texture,
+                    });
                 });
-            });
 
             self.tilemap_tilesets.values_mut().for_each(|v| {
                 v.sort_by(|(_, a), (_, b)| a.cmp(b));
@@ -432,13 +582,6 @@ impl TiledAssets {
                     );
 
                 let image_size = Vec2::new(layer.image.width as f32, layer.image.height as f32);
-                let image_verts = vec![
-                    Vec2::ZERO,
-                    Vec2::new(image_size.x, 0.),
-                    Vec2::new(image_size.x, -image_size.y),
-                    Vec2::new(0., -image_size.y),
-                ];
-                let image_uvs = vec![Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
                 let tile_size = Vec2::new(map.xml.tile_width as f32, map.xml.tile_height as f32);
                 let map_size = match map.xml.orientation {
                     MapOrientation::Orthogonal | MapOrientation::Isometric => {
@@ -468,13 +611,6 @@ impl TiledAssets {
                     max: Vec2::new(map_origin.x + map_size.x, map_origin.y - map_origin.y),
                 };
                 let origin = Vec2::new(layer.offset_x, -layer.offset_y) + map_origin;
-                let unit_indices = vec![0, 3, 1, 1, 3, 2];
-
-                let mut vertices =
-                    vec![image_verts.iter().map(|v| *v + origin).collect::<Vec<_>>()];
-                let mut uvs = vec![image_uvs.clone()];
-                let mut indices = vec![unit_indices.clone()];
-                let mut mesh_index = 0;
 
                 if (layer.repeat_x || layer.repeat_y)
                     && (layer.offset_x < 0. || layer.offset_y < 0.)
@@ -491,76 +627,13 @@ impl TiledAssets {
                     );
                 }
 
-                if layer.repeat_x {
-                    vertices.clear();
-                    uvs.clear();
-                    indices.clear();
-
-                    let left = ((origin.x - map_area.min.x) / image_size.x).ceil_to_u32();
-                    let right = ((map_area.max.x - origin.x) / image_size.x).ceil_to_u32();
-                    let repeat_origin_x = origin.x - left as f32 * image_size.x;
-                    for i in 0..(left + right) {
-                        let unclipped_uvs = image_uvs.clone();
-                        let unclipped_verts = image_verts
-                            .iter()
-                            .map(|v| *v + Vec2::new(i as f32 * image_size.x + repeat_origin_x, 0.))
-                            .collect();
-
-                        uvs.push(unclipped_uvs);
-                        vertices.push(unclipped_verts);
-                        indices.push(unit_indices.iter().map(|i| i + mesh_index * 4).collect());
-                        mesh_index += 1;
-                    }
-                }
-
-                if layer.repeat_y {
-                    let origin_images = vertices.clone();
-                    vertices.clear();
-                    uvs.clear();
-
-                    let up = ((map_area.max.y - origin.y) / image_size.y).ceil_to_u32();
-                    let down = ((origin.y - map_area.min.y) / image_size.y).ceil_to_u32();
-                    let repeat_origin_y = origin.y - (down as f32 - 1.) * image_size.y;
-                    for i in 0..(up + down) {
-                        origin_images.iter().for_each(|image| {
-                            let unclipped_uvs = image_uvs.clone();
-                            let unclipped_verts = image
-                                .iter()
-                                .map(|v| {
-                                    *v + Vec2::new(0., i as f32 * image_size.y + repeat_origin_y)
-                                })
-                                .collect();
-
-                            uvs.push(unclipped_uvs);
-                            vertices.push(unclipped_verts);
-                            indices.push(unit_indices.iter().map(|i| i + mesh_index * 4).collect());
-                            mesh_index += 1;
-                        });
-                    }
-                }
-
-                let mesh = mesh_assets.add(
-                    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all())
-                        .with_inserted_attribute(
-                            Mesh::ATTRIBUTE_POSITION,
-                            vertices
-                                .into_iter()
-                                .flat_map(|image| image.into_iter().map(|v| v.extend(0.)))
-                                .collect::<Vec<_>>(),
-                        )
-                        .with_inserted_attribute(
-                            Mesh::ATTRIBUTE_UV_0,
-                            uvs.into_iter()
-                                .flat_map(|image| image.into_iter())
-                                .collect::<Vec<_>>(),
-                        )
-                        .with_inserted_indices(Indices::U16(
-                            indices
-                                .into_iter()
-                                .flat_map(|image| image.into_iter())
-                                .collect::<Vec<_>>(),
-                        )),
-                );
+                let mesh = mesh_assets.add(sprite::repeated_image_mesh(
+                    image_size,
+                    origin,
+                    map_area,
+                    layer.repeat_x,
+                    layer.repeat_y,
+                ));
 
                 self.image_layer_mesh
                     .entry(map.name.clone())
@@ -576,7 +649,7 @@ impl TiledAssets {
         material_assets: &mut Assets<TiledSpriteMaterial>,
         mesh_assets: &mut Assets<Mesh>,
     ) {
-        let mut objects = layers
+        let objects = layers
             .iter()
             .enumerate()
             .filter_map(|(z, layer)| {
@@ -597,9 +670,9 @@ impl TiledAssets {
                 layer
                     .objects
                     .iter()
-                    .enumerate()
-                    .for_each(|(obj_z, object)| {
-                        let obj_z = obj_z as f32 / layer.objects.len() as f32 + z as f32;
+                    .zip(layer.draw_ranks())
+                    .for_each(|(object, rank)| {
+                        let obj_z = rank as f32 / layer.objects.len() as f32 + z as f32;
                         self.object_z_order
                             .entry(map.name.clone())
                             .or_default()
@@ -613,8 +686,6 @@ impl TiledAssets {
             })
             .collect::<Vec<_>>();
 
-        objects.sort_by(|(lhs, _), (rhs, _)| lhs.y.partial_cmp(&rhs.y).unwrap());
-
         let mesh_ext = objects
             .iter()
             .map(|(object, _)| {
@@ -622,19 +693,13 @@ impl TiledAssets {
                 let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all())
                     .with_inserted_attribute(
                         Mesh::ATTRIBUTE_POSITION,
+                        // The rotation is applied by the transform of the object.
                         vec![
-                            Vec2::new(0., object.height),
-                            Vec2::new(object.width, object.height),
-                            Vec2::new(object.width, 0.),
-                            Vec2::ZERO,
-                        ]
-                        .into_iter()
-                        .map(|v| {
-                            Vec2::from_angle(-object.rotation / 180. * PI)
-                                .rotate(v)
-                                .extend(0.)
-                        })
-                        .collect::<Vec<_>>(),
+                            Vec3::new(0., object.height, 0.),
+                            Vec3::new(object.width, object.height, 0.),
+                            Vec3::new(object.width, 0., 0.),
+                            Vec3::ZERO,
+                        ],
                     )
                     .with_inserted_attribute(
                         Mesh::ATTRIBUTE_UV_0,
@@ -665,7 +730,8 @@ impl TiledAssets {
         let mat_ext = objects
             .iter()
             .map(|(object, tint)| {
-                let gid = object.gid.unwrap() & 0x3FFF_FFFF;
+                // Strip the flipping flags.
+                let gid = object.gid.unwrap() & 0x0FFF_FFFF;
                 let (tileset, first_gid) = &self.get_tileset(gid, &map.name);
                 (
                     object.id,
@@ -1,15 +1,17 @@
 use bevy::{
     asset::{Asset, Handle},
-    math::Vec4,
+    math::{Vec2, Vec4},
     reflect::Reflect,
     render::{
-        render_resource::{AsBindGroup, ShaderType},
+        mesh::{Indices, Mesh},
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, PrimitiveTopology, ShaderType},
         texture::Image,
     },
     sprite::Material2d,
 };
 
-use crate::math::aabb::Aabb2d;
+use crate::math::{aabb::Aabb2d, extension::F32Integerize};
 
 use super::TILED_SPRITE_SHADER;
 
@@ -33,3 +35,66 @@ impl Material2d for TiledSpriteMaterial {
         TILED_SPRITE_SHADER.into()
     }
 }
+
+/// Build a mesh of an image whose top left corner is at `origin`.
+///
+/// If the image is repeated along an axis, copies of it are added until `area` is covered.
+/// This is what image layers use, and parallax layers reuse it for hand-built scenes.
+pub fn repeated_image_mesh(
+    image_size: Vec2,
+    origin: Vec2,
+    area: Aabb2d,
+    repeat_x: bool,
+    repeat_y: bool,
+) -> Mesh {
+    let image_verts = [
+        Vec2::ZERO,
+        Vec2::new(image_size.x, 0.),
+        Vec2::new(image_size.x, -image_size.y),
+        Vec2::new(0., -image_size.y),
+    ];
+    let image_uvs = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::Y];
+    let unit_indices = [0, 3, 1, 1, 3, 2];
+
+    let mut images = vec![origin];
+
+    if repeat_x {
+        let left = ((origin.x - area.min.x) / image_size.x).ceil_to_u32();
+        let right = ((area.max.x - origin.x) / image_size.x).ceil_to_u32();
+        let repeat_origin_x = origin.x - left as f32 * image_size.x;
+        images = (0..(left + right))
+            .map(|i| Vec2::new(i as f32 * image_size.x + repeat_origin_x, origin.y))
+            .collect();
+    }
+
+    if repeat_y {
+        let up = ((area.max.y - origin.y) / image_size.y).ceil_to_u32();
+        let down = ((origin.y - area.min.y) / image_size.y).ceil_to_u32();
+        let repeat_origin_y = origin.y - (down as f32 - 1.) * image_size.y;
+        images = (0..(up + down))
+            .flat_map(|i| {
+                images
+                    .iter()
+                    .map(move |image| Vec2::new(image.x, i as f32 * image_size.y + repeat_origin_y))
+            })
+            .collect();
+    }
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::all())
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            images
+                .iter()
+                .flat_map(|image| image_verts.iter().map(|v| (*v + *image).extend(0.)))
+                .collect::<Vec<_>>(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            images.iter().flat_map(|_| image_uvs).collect::<Vec<_>>(),
+        )
+        .with_inserted_indices(Indices::U16(
+            (0..images.len() as u16)
+                .flat_map(|image| unit_indices.iter().map(move |i| i + image * 4))
+                .collect::<Vec<_>>(),
+        ))
+}
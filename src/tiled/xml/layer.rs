@@ -2,8 +2,9 @@ use std::fmt::Formatter;
 
 use bevy::{
     ecs::system::EntityCommands,
-    math::{IVec2, Vec2, Vec4},
+    math::{IVec2, Quat, Vec2, Vec4},
     reflect::Reflect,
+    render::view::Visibility,
     sprite::{MaterialMesh2dBundle, Mesh2dHandle},
     transform::components::Transform,
 };
@@ -13,22 +14,21 @@ use serde::{
 };
 
 use crate::{
+    math::aabb::{Aabb2d, IAabb2d},
     tiled::resources::{PackedTiledTilemap, TiledAssets},
     tilemap::{
         bundles::StandardTilemapBundle,
         coordinates,
+        region::Region,
         tile::{RawTileAnimation, TileBuilder, TileLayer},
     },
 };
 
-use super::{default::*, property::Components, MapOrientation, TiledColor};
+use super::{default::*, property::Components, version, MapOrientation, TiledColor};
 
 #[cfg(feature = "physics")]
 use bevy_xpbd_2d::plugins::collision::Collider;
 
-#[cfg(feature = "physics")]
-use std::f32::consts::PI;
-
 #[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
 pub enum TiledLayer {
     #[serde(rename = "layer")]
@@ -162,7 +162,10 @@ impl<'de> Deserialize<'de> for ColorTileLayerData {
                         "$text" => {
                             tiles = Some(map.next_value::<Tiles>()?);
                         }
-                        _ => panic!("Unknown key for ColorTileLayerData: {}", key),
+                        _ => {
+                            version::warn_unknown_key("ColorTileLayerData", &key);
+                            map.next_value::<IgnoredAny>()?;
+                        }
                     }
                 }
 
@@ -319,10 +322,10 @@ impl Tiles {
                     .and_then(|t| t.animation.as_ref())
                 {
                     builder = builder.with_animation(layer_tilemap.animations.register(
-                        RawTileAnimation {
-                            sequence: anim.frames.iter().map(|f| f.tile_id).collect(),
-                            fps: 1000 / anim.frames[0].duration,
-                        },
+                        RawTileAnimation::with_durations(
+                            anim.frames.iter().map(|f| f.tile_id).collect(),
+                            anim.frames.iter().map(|f| f.duration).collect(),
+                        ),
                     ));
                 } else {
                     builder = builder.with_layer(0, layer.with_texture_index(tile_id));
@@ -446,10 +449,43 @@ pub struct ObjectLayer {
     #[serde(default = "default_onef")]
     pub parallax_y: f32,
 
+    /// Whether the objects are drawn according to the order of
+    /// appearance (“index”) or sorted by their y-coordinate (“topdown”).
+    /// (defaults to “topdown”)
+    #[serde(rename = "@draworder")]
+    #[serde(default)]
+    pub draw_order: ObjectDrawOrder,
+
     #[serde(rename = "object")]
     pub objects: Vec<TiledObjectInstance>,
 }
 
+impl ObjectLayer {
+    /// The draw order of each object, from 0 (the bottom) to `objects.len() - 1` (the top).
+    pub fn draw_ranks(&self) -> Vec<usize> {
+        let mut order = (0..self.objects.len()).collect::<Vec<_>>();
+        if self.draw_order == ObjectDrawOrder::TopDown {
+            // Objects lower on the screen are drawn on top.
+            order.sort_by(|lhs, rhs| self.objects[*lhs].y.total_cmp(&self.objects[*rhs].y));
+        }
+
+        let mut ranks = vec![0; order.len()];
+        order
+            .into_iter()
+            .enumerate()
+            .for_each(|(rank, index)| ranks[index] = rank);
+        ranks
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectDrawOrder {
+    #[default]
+    TopDown,
+    Index,
+}
+
 #[derive(Debug, Clone, Reflect, Serialize)]
 pub struct TiledObjectInstance {
     /// Unique ID of the object (defaults to 0,
@@ -535,7 +571,8 @@ impl<'de> Deserialize<'de> for TiledObjectInstance {
                     match key.as_str() {
                         "@id" => id = Some(map.next_value::<u32>()?),
                         "@name" => name = Some(map.next_value::<String>()?),
-                        "@type" => ty = Some(map.next_value::<String>()?),
+                        // Saved as `class` in 1.9.
+                        "@type" | "@class" => ty = Some(map.next_value::<String>()?),
                         "@x" => x = Some(map.next_value::<f32>()?),
                         "@y" => y = Some(map.next_value::<f32>()?),
                         "@width" => width = Some(map.next_value::<f32>()?),
@@ -549,7 +586,10 @@ impl<'de> Deserialize<'de> for TiledObjectInstance {
                             shape = Some(ObjectShape::Ellipse);
                         }
                         "polygon" => shape = Some(ObjectShape::Polygon(map.next_value()?)),
-                        _ => panic!("Unknown key for TiledObjectInstance: {}", key),
+                        _ => {
+                            version::warn_unknown_key("TiledObjectInstance", &key);
+                            map.next_value::<IgnoredAny>()?;
+                        }
                     }
                 }
 
@@ -575,24 +615,68 @@ impl<'de> Deserialize<'de> for TiledObjectInstance {
 }
 
 impl TiledObjectInstance {
+    /// The transform of the object, with y pointing up.
+    /// Objects are rotated clockwise around their `(x, y)` in Tiled.
+    pub fn transform(&self, z: f32) -> Transform {
+        Transform::from_xyz(self.x, -self.y, z)
+            .with_rotation(Quat::from_rotation_z(-self.rotation.to_radians()))
+    }
+
+    #[inline]
+    pub fn visibility(&self) -> Visibility {
+        if self.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    }
+
+    /// The object as a `Region`, with the tiles in the index space of the tile layers.
+    /// Returns `None` if the object is not a rectangle.
+    ///
+    /// **Notice**: The rotation is ignored, and the tiles are only meaningful for orthogonal maps.
+    pub fn as_region(&self, tile_size: Vec2, translation: Vec2) -> Option<Region> {
+        if self.gid.is_some() || !matches!(self.shape, ObjectShape::Rect) {
+            return None;
+        }
+
+        let min = Vec2::new(self.x, self.y);
+        let max = min + Vec2::new(self.width, self.height);
+        Some(Region {
+            identifier: self.name.clone(),
+            tile_area: IAabb2d {
+                min: (min / tile_size).floor().as_ivec2(),
+                max: (max / tile_size).ceil().as_ivec2() - 1,
+            },
+            world_aabb: Aabb2d {
+                min: translation + Vec2::new(min.x, -max.y),
+                max: translation + Vec2::new(max.x, -min.y),
+            },
+            fields: self
+                .properties
+                .instances
+                .iter()
+                .flat_map(|inst| inst.properties.values())
+                .filter_map(|prop| Some((prop.name.clone(), prop.value.as_region_field()?)))
+                .collect(),
+        })
+    }
+
+    /// The sprite is anchored at its bottom left corner, just like in Tiled.
+    /// Hidden objects still get the sprite, so they can be shown later.
     pub fn spawn_sprite(
         &self,
         commands: &mut EntityCommands,
         tiled_assets: &TiledAssets,
         tiled_map: &str,
     ) {
-        if self.visible {
-            commands.insert(MaterialMesh2dBundle {
-                material: tiled_assets.clone_object_material_handle(&tiled_map, self.id),
-                mesh: Mesh2dHandle(tiled_assets.clone_object_mesh_handle(&tiled_map, self.id)),
-                transform: Transform::from_xyz(
-                    self.x,
-                    -self.y,
-                    tiled_assets.get_object_z_order(&tiled_map, self.id),
-                ),
-                ..Default::default()
-            });
-        }
+        commands.insert(MaterialMesh2dBundle {
+            material: tiled_assets.clone_object_material_handle(&tiled_map, self.id),
+            mesh: Mesh2dHandle(tiled_assets.clone_object_mesh_handle(&tiled_map, self.id)),
+            transform: self.transform(tiled_assets.get_object_z_order(&tiled_map, self.id)),
+            visibility: self.visibility(),
+            ..Default::default()
+        });
     }
 
     #[cfg(feature = "physics")]
@@ -606,13 +690,7 @@ impl TiledObjectInstance {
                     let mut points = polygon.points.clone();
                     points.push(polygon.points[0]);
                     Collider::polyline(
-                        points
-                            .into_iter()
-                            .map(|v| {
-                                Vec2::from_angle(-self.rotation / 180. * PI)
-                                    .rotate(Vec2::new(v.x, -v.y))
-                            })
-                            .collect(),
+                        points.into_iter().map(|v| Vec2::new(v.x, -v.y)).collect(),
                         None,
                     )
                 }
@@ -625,20 +703,18 @@ impl TiledObjectInstance {
                             Vec2::new(0., self.height),
                         ]
                     } else {
-                        [
+                        vec![
                             Vec2::ZERO,
                             Vec2::new(self.width, 0.),
                             Vec2::new(self.width, -self.height),
                             Vec2::new(0., -self.height),
                         ]
-                        .into_iter()
-                        .map(|v| Vec2::from_angle(-self.rotation / 180. * PI).rotate(v))
-                        .collect()
                     }
                 })
                 .unwrap(),
             },
             bevy_xpbd_2d::components::Position::from_xy(self.x, -self.y),
+            bevy_xpbd_2d::components::Rotation::from_degrees(-self.rotation),
         ));
     }
 }
@@ -679,7 +755,10 @@ impl<'de> Deserialize<'de> for Polygon {
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
                         "@points" => points = Some(map.next_value::<String>()?),
-                        _ => panic!("Unknown key for Polygon: {}", key),
+                        _ => {
+                            version::warn_unknown_key("Polygon", &key);
+                            map.next_value::<IgnoredAny>()?;
+                        }
                     }
                 }
 
@@ -825,4 +904,21 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_object_draw_order() {
+        let layer = r#"
+            <objectgroup id="1" name="Objects">
+                <object id="1" gid="1" x="0" y="32" width="16" height="16"/>
+                <object id="2" gid="1" x="0" y="16" width="16" height="16"/>
+                <object id="3" gid="1" x="0" y="48" width="16" height="16"/>
+            </objectgroup>
+        "#;
+        let mut layer: ObjectLayer = quick_xml::de::from_str(layer).unwrap();
+        assert_eq!(layer.draw_order, ObjectDrawOrder::TopDown);
+        assert_eq!(layer.draw_ranks(), vec![1, 0, 2]);
+
+        layer.draw_order = ObjectDrawOrder::Index;
+        assert_eq!(layer.draw_ranks(), vec![0, 1, 2]);
+    }
 }
@@ -15,6 +15,7 @@ pub mod default;
 pub mod layer;
 pub mod property;
 pub mod tileset;
+pub mod version;
 
 #[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -4,7 +4,9 @@ use serde::{
     Deserialize, Serialize,
 };
 
-use super::TiledColor;
+use crate::tilemap::region::RegionField;
+
+use super::{version, TiledColor};
 
 #[derive(Debug, Default, Clone, Reflect, Serialize, Deserialize)]
 pub struct Components {
@@ -65,7 +67,10 @@ impl<'de> Deserialize<'de> for ClassInstance {
                                     .collect(),
                             );
                         }
-                        _ => panic!("Unknown key for ClassInstance: {}", key),
+                        _ => {
+                            version::warn_unknown_key("ClassInstance", &key);
+                            map.next_value::<IgnoredAny>()?;
+                        }
                     }
                 }
 
@@ -152,7 +157,10 @@ impl<'de> Deserialize<'de> for PropertyInstance {
                         "property" => {
                             return map.next_value::<PropertyInstance>();
                         }
-                        _ => panic!("Unknown key for PropertyInstance: {}", key),
+                        _ => {
+                            version::warn_unknown_key("PropertyInstance", &key);
+                            map.next_value::<IgnoredAny>()?;
+                        }
                     }
                 }
 
@@ -215,3 +223,18 @@ pub enum PropertyValue {
     Enum(String, String),
     ObjectRef(u32),
 }
+
+impl PropertyValue {
+    /// Returns `None` for the types that `RegionField` doesn't support.
+    pub fn as_region_field(&self) -> Option<RegionField> {
+        match self {
+            PropertyValue::Int(x) => Some(RegionField::Int(*x)),
+            PropertyValue::Float(x) => Some(RegionField::Float(*x)),
+            PropertyValue::Bool(x) => Some(RegionField::Bool(*x)),
+            PropertyValue::String(x) | PropertyValue::Enum(_, x) => {
+                Some(RegionField::String(x.clone()))
+            }
+            _ => None,
+        }
+    }
+}
@@ -154,7 +154,7 @@ pub struct TiledTile {
 
     /// The class of the tile. Is inherited by tile objects.
     /// (since 1.0, defaults to “”, was saved as class in 1.9)
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", alias = "@class")]
     #[serde(default)]
     pub ty: String,
 
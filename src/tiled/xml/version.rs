@@ -0,0 +1,104 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    sync::{Mutex, OnceLock},
+};
+
+use bevy::log::warn;
+use serde::Deserialize;
+
+/// The version of the TMX format, read from the `version` attribute of the map.
+///
+/// The format version follows the minor releases of Tiled, like `1.10`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TiledVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl TiledVersion {
+    /// The oldest version that can be loaded, since layers have ids from 1.2.
+    /// Older files need to be saved again in Tiled.
+    pub const MIN_SUPPORTED: Self = Self::new(1, 2);
+    /// The newest version the xml module is written against.
+    ///
+    /// Newer minor versions are still loaded, unknown elements and attributes are skipped.
+    pub const MAX_SUPPORTED: Self = Self::new(1, 10);
+
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parse versions like `1.10`. Patch numbers are ignored.
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut numbers = version.trim().split('.').map(|n| n.parse::<u32>());
+        let major = numbers.next()?.ok()?;
+        let minor = numbers.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self::new(major, minor))
+    }
+
+    /// Whether the version is too old, or has a different major version.
+    #[inline]
+    pub fn is_incompatible(&self) -> bool {
+        *self < Self::MIN_SUPPORTED || self.major != Self::MAX_SUPPORTED.major
+    }
+
+    /// Whether the version is newer than `MAX_SUPPORTED`, so some data may be missing.
+    #[inline]
+    pub fn is_newer(&self) -> bool {
+        *self > Self::MAX_SUPPORTED
+    }
+}
+
+impl Display for TiledVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Deserialize)]
+struct MapVersion {
+    #[serde(rename = "@version")]
+    version: String,
+}
+
+/// Read the `version` of a tmx file without parsing the rest of the map.
+pub fn detect_version(xml: &str) -> Option<String> {
+    quick_xml::de::from_str::<MapVersion>(xml)
+        .ok()
+        .map(|map| map.version)
+}
+
+/// Warn about an element or attribute the parser doesn't know, which is usually
+/// added by a newer version of Tiled. Every key is only warned once per element.
+pub(crate) fn warn_unknown_key(element: &str, key: &str) {
+    static WARNED: OnceLock<Mutex<HashSet<(String, String)>>> = OnceLock::new();
+
+    let mut warned = WARNED.get_or_init(Default::default).lock().unwrap();
+    if warned.insert((element.to_string(), key.to_string())) {
+        warn!("Skipping unknown key {} of {}.", key, element);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{detect_version, TiledVersion};
+
+    #[test]
+    fn test_version() {
+        let version = TiledVersion::parse("1.10").unwrap();
+        assert_eq!(version, TiledVersion::new(1, 10));
+        assert!(!version.is_incompatible());
+        assert!(!version.is_newer());
+        assert!(TiledVersion::parse("1.9").unwrap() < version);
+        assert!(TiledVersion::parse("1.0").unwrap().is_incompatible());
+        assert!(TiledVersion::parse("2.0").unwrap().is_incompatible());
+        assert!(TiledVersion::parse("1.11.1").unwrap().is_newer());
+        assert_eq!(TiledVersion::parse("1.x"), None);
+        assert_eq!(
+            detect_version(r#"<map version="1.10" tiledversion="1.10.2"><layer/></map>"#)
+                .as_deref(),
+            Some("1.10")
+        );
+    }
+}
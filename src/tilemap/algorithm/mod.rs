@@ -1,6 +1,6 @@
-use bevy::app::Plugin;
+use bevy::app::{Plugin, Update};
 
-use self::path::{PathTile, PathTilemap};
+use self::path::{PathTile, PathTileRules, PathTilemap};
 
 pub mod path;
 
@@ -8,7 +8,10 @@ pub struct EntiTilesAlgorithmTilemapPlugin;
 
 impl Plugin for EntiTilesAlgorithmTilemapPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_systems(Update, path::path_tilemap_generator);
+
         app.register_type::<PathTilemap>()
-            .register_type::<PathTile>();
+            .register_type::<PathTile>()
+            .register_type::<PathTileRules>();
     }
 }
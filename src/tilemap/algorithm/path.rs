@@ -1,10 +1,23 @@
-use bevy::{ecs::component::Component, math::IVec2, reflect::Reflect};
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::{Entity, EntityHashMap},
+        removal_detection::RemovedComponents,
+        system::{Commands, Local, Query},
+        world::Ref,
+    },
+    math::{IVec2, UVec2},
+    reflect::Reflect,
+    utils::HashMap,
+};
 
 use crate::{
-    math::TileArea,
+    math::{aabb::IAabb2d, TileArea},
     tilemap::{
         buffers::{PathTileBuffer, Tiles},
         chunking::storage::{ChunkedStorage, PathTileChunkedStorage},
+        tile::{Tile, TileTexture},
     },
 };
 
@@ -26,7 +39,7 @@ pub struct PathTilemap {
 
 impl PathTilemap {
     /// Create a new path tilemap with default chunk size.
-    /// 
+    ///
     /// Use `new_with_chunk_size` to create a path tilemap with custom chunk size.
     pub fn new() -> Self {
         Self {
@@ -57,6 +70,11 @@ impl PathTilemap {
         self.storage.remove_elem(index)
     }
 
+    /// Remove all the path tiles inside the region.
+    pub fn remove_region(&mut self, region: IAabb2d) {
+        self.storage.remove_region(region.justified());
+    }
+
     /// Set path-finding data using a custom function.
     pub fn fill_path_rect_custom(
         &mut self,
@@ -89,3 +107,129 @@ impl PathTilemap {
         });
     }
 }
+
+/// Derives the `PathTilemap` of a tilemap from the texture indices of its tiles,
+/// for the tilemaps that don't have a dedicated path layer.
+///
+/// The `PathTilemap` is rebuilt when this component changes, and updated
+/// as the tiles are set or removed after that.
+#[derive(Component, Debug, Default, Clone, Reflect)]
+pub struct PathTileRules {
+    /// Texture index to the path tile. `None` means impassable.
+    ///
+    /// For tiles with multiple layers, the topmost layer that has a rule is used.
+    pub rules: HashMap<u32, Option<PathTile>>,
+    /// The path tile of the tiles that don't match any rule, including the animated ones.
+    /// `None` means impassable.
+    pub fallback: Option<PathTile>,
+}
+
+impl PathTileRules {
+    pub fn new(fallback: Option<PathTile>) -> Self {
+        Self {
+            rules: HashMap::default(),
+            fallback,
+        }
+    }
+
+    pub fn with_cost(mut self, texture_index: u32, cost: u32) -> Self {
+        self.rules.insert(texture_index, Some(PathTile { cost }));
+        self
+    }
+
+    pub fn with_impassable(mut self, texture_index: u32) -> Self {
+        self.rules.insert(texture_index, None);
+        self
+    }
+
+    /// Get the path tile of the tile. Returns `None` if it's impassable.
+    pub fn get(&self, tile: &Tile) -> Option<PathTile> {
+        let TileTexture::Static(layers) = &tile.texture else {
+            return self.fallback;
+        };
+
+        layers
+            .iter()
+            .rev()
+            .filter(|layer| layer.texture_index >= 0)
+            .find_map(|layer| self.rules.get(&(layer.texture_index as u32)))
+            .copied()
+            .unwrap_or(self.fallback)
+    }
+
+    fn apply(&self, path_tilemap: &mut PathTilemap, tile: &Tile) {
+        let area = tile_area(tile);
+        match self.get(tile) {
+            Some(path_tile) => path_tilemap.fill_path_rect(area, path_tile),
+            None => path_tilemap.remove_region(area.aabb()),
+        }
+    }
+}
+
+#[inline]
+fn tile_area(tile: &Tile) -> TileArea {
+    TileArea::new(tile.index, tile.footprint.unwrap_or(UVec2::ONE))
+}
+
+pub fn path_tilemap_generator(
+    mut commands: Commands,
+    mut tilemaps_query: Query<(Entity, Ref<PathTileRules>, Option<&mut PathTilemap>)>,
+    tiles_query: Query<(Entity, Ref<Tile>)>,
+    mut removed_tiles: RemovedComponents<Tile>,
+    mut tile_areas: Local<EntityHashMap<(Entity, TileArea)>>,
+) {
+    removed_tiles.read().for_each(|tile| {
+        let Some((tilemap, area)) = tile_areas.remove(&tile) else {
+            return;
+        };
+        if let Ok((_, _, Some(mut path_tilemap))) = tilemaps_query.get_mut(tilemap) {
+            path_tilemap.remove_region(area.aabb());
+        }
+    });
+
+    tilemaps_query
+        .iter_mut()
+        .filter(|(_, rules, _)| rules.is_changed())
+        .for_each(|(entity, rules, path_tilemap)| {
+            let mut generated = PathTilemap::new_with_chunk_size(
+                path_tilemap
+                    .as_ref()
+                    .map(|p| p.storage.chunk_size)
+                    .unwrap_or(crate::DEFAULT_CHUNK_SIZE),
+            );
+            tiles_query
+                .iter()
+                .filter(|(_, tile)| tile.tilemap_id == entity)
+                .for_each(|(tile_entity, tile)| {
+                    tile_areas.insert(tile_entity, (entity, tile_area(&tile)));
+                    rules.apply(&mut generated, &tile);
+                });
+
+            match path_tilemap {
+                Some(mut path_tilemap) => *path_tilemap = generated,
+                None => {
+                    commands.entity(entity).insert(generated);
+                }
+            }
+        });
+
+    tiles_query
+        .iter()
+        .filter(|(_, tile)| tile.is_changed())
+        .for_each(|(tile_entity, tile)| {
+            let Ok((_, rules, Some(mut path_tilemap))) = tilemaps_query.get_mut(tile.tilemap_id)
+            else {
+                return;
+            };
+            if rules.is_changed() {
+                return;
+            }
+
+            if let Some((_, area)) =
+                tile_areas.insert(tile_entity, (tile.tilemap_id, tile_area(&tile)))
+            {
+                path_tilemap.remove_region(area.aabb());
+            }
+            rules.apply(&mut path_tilemap, &tile);
+        });
+}
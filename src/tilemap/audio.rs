@@ -0,0 +1,231 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges, component::Component, entity::Entity,
+        removal_detection::RemovedComponents, system::Query, world::Ref,
+    },
+    math::{IVec2, Vec2},
+    reflect::Reflect,
+    utils::HashMap,
+};
+
+use super::{
+    coordinates,
+    map::{TilePivot, TilemapSlotSize, TilemapTransform, TilemapType},
+    tile::Tile,
+};
+
+/// Marks a tile as a sound source, like `"waterfall"` or `"lava"`.
+///
+/// Use `TileBuilder::with_audio_emitter` to add it to tiles.
+/// The tag is collected into `TilemapAudioEmitters` if the tilemap has one.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Reflect)]
+pub struct TileAudioEmitter(pub String);
+
+#[derive(Debug, Clone, Reflect)]
+pub struct TileAudioEmitterInstance {
+    pub index: IVec2,
+    /// The world position of the pivot of the tile.
+    pub position: Vec2,
+    pub tag: String,
+}
+
+/// A spatial index of the tiles that have a `TileAudioEmitter`. Add this to a tilemap entity.
+///
+/// This crate doesn't play anything. Query the emitters around the listener
+/// and drive the sounds with whatever audio backend you use, for example `bevy_audio`:
+///
+/// ```ignore
+/// #[derive(Component)]
+/// struct AmbientSound(Entity);
+///
+/// fn ambient_sounds(
+///     mut commands: Commands,
+///     listener: Query<&Transform, With<Camera>>,
+///     emitters: Query<&TilemapAudioEmitters>,
+///     sounds: Query<(Entity, &AmbientSound)>,
+///     asset_server: Res<AssetServer>,
+/// ) {
+///     let listener = listener.single().translation.truncate();
+///     let nearest = emitters.single().nearest(listener, 300., None, 4);
+///
+///     // Stop the sounds that are no longer among the nearest ones.
+///     sounds.iter().for_each(|(sound, AmbientSound(tile))| {
+///         if !nearest.iter().any(|(t, _)| t == tile) {
+///             commands.entity(sound).despawn();
+///         }
+///     });
+///
+///     for (tile, emitter) in nearest {
+///         if sounds.iter().any(|(_, AmbientSound(t))| *t == tile) {
+///             continue;
+///         }
+///         commands.spawn((
+///             AudioBundle {
+///                 source: asset_server.load(format!("sounds/{}.ogg", emitter.tag)),
+///                 settings: PlaybackSettings::LOOP.with_spatial(true),
+///             },
+///             TransformBundle::from_transform(Transform::from_translation(
+///                 emitter.position.extend(0.),
+///             )),
+///             AmbientSound(tile),
+///         ));
+///     }
+/// }
+/// ```
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapAudioEmitters {
+    /// The size of a bucket in world units.
+    pub(crate) chunk_size: f32,
+    pub(crate) chunks: HashMap<IVec2, Vec<Entity>>,
+    pub(crate) emitters: HashMap<Entity, TileAudioEmitterInstance>,
+}
+
+impl Default for TilemapAudioEmitters {
+    fn default() -> Self {
+        Self::new_with_chunk_size(256.)
+    }
+}
+
+impl TilemapAudioEmitters {
+    pub fn new_with_chunk_size(chunk_size: f32) -> Self {
+        Self {
+            chunk_size,
+            chunks: Default::default(),
+            emitters: Default::default(),
+        }
+    }
+
+    #[inline]
+    fn chunk_index(&self, position: Vec2) -> IVec2 {
+        (position / self.chunk_size).floor().as_ivec2()
+    }
+
+    #[inline]
+    pub fn get(&self, tile: Entity) -> Option<&TileAudioEmitterInstance> {
+        self.emitters.get(&tile)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.emitters.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.emitters.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &TileAudioEmitterInstance)> {
+        self.emitters.iter().map(|(e, i)| (*e, i))
+    }
+
+    /// Iterate over the emitters within `radius` of `center`, in no particular order.
+    pub fn in_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, &TileAudioEmitterInstance)> {
+        let min = self.chunk_index(center - radius);
+        let max = self.chunk_index(center + radius);
+        let radius_sq = radius * radius;
+
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2 { x, y }))
+            .filter_map(|chunk| self.chunks.get(&chunk))
+            .flatten()
+            .filter_map(|e| self.emitters.get(e).map(|i| (*e, i)))
+            .filter(move |(_, i)| i.position.distance_squared(center) <= radius_sq)
+    }
+
+    /// Get at most `count` emitters within `radius` of `center`, nearest first.
+    ///
+    /// Only the emitters with the same tag are returned if `tag` is `Some`.
+    pub fn nearest(
+        &self,
+        center: Vec2,
+        radius: f32,
+        tag: Option<&str>,
+        count: usize,
+    ) -> Vec<(Entity, &TileAudioEmitterInstance)> {
+        let mut result = self
+            .in_radius(center, radius)
+            .filter(|(_, i)| tag.map(|t| i.tag == t).unwrap_or(true))
+            .collect::<Vec<_>>();
+        result.sort_by(|(_, a), (_, b)| {
+            a.position
+                .distance_squared(center)
+                .total_cmp(&b.position.distance_squared(center))
+        });
+        result.truncate(count);
+        result
+    }
+
+    pub(crate) fn insert(&mut self, tile: Entity, instance: TileAudioEmitterInstance) {
+        self.remove(tile);
+        let chunk = self.chunk_index(instance.position);
+        self.chunks.entry(chunk).or_default().push(tile);
+        self.emitters.insert(tile, instance);
+    }
+
+    pub(crate) fn remove(&mut self, tile: Entity) -> Option<TileAudioEmitterInstance> {
+        let instance = self.emitters.remove(&tile)?;
+        let chunk = self.chunk_index(instance.position);
+        if let Some(tiles) = self.chunks.get_mut(&chunk) {
+            tiles.retain(|e| *e != tile);
+            if tiles.is_empty() {
+                self.chunks.remove(&chunk);
+            }
+        }
+        Some(instance)
+    }
+}
+
+pub fn audio_emitter_indexer(
+    mut tilemaps_query: Query<(
+        &mut TilemapAudioEmitters,
+        Ref<TilemapTransform>,
+        &TilemapType,
+        &TilePivot,
+        &TilemapSlotSize,
+    )>,
+    tiles_query: Query<(Entity, Ref<Tile>, Ref<TileAudioEmitter>)>,
+    mut removed: RemovedComponents<TileAudioEmitter>,
+) {
+    for tile in removed.read() {
+        tilemaps_query.iter_mut().for_each(|(mut emitters, ..)| {
+            emitters.remove(tile);
+        });
+    }
+
+    tiles_query.iter().for_each(|(entity, tile, emitter)| {
+        let Ok((mut emitters, transform, ty, pivot, slot_size)) =
+            tilemaps_query.get_mut(tile.tilemap_id)
+        else {
+            return;
+        };
+
+        // Positions need to be rebuilt when the tilemap moves, or when the index was just added.
+        if !(tile.is_changed()
+            || emitter.is_changed()
+            || transform.is_changed()
+            || emitters.is_added())
+        {
+            return;
+        }
+
+        emitters.insert(
+            entity,
+            TileAudioEmitterInstance {
+                index: tile.index,
+                position: coordinates::index_to_world(
+                    tile.index,
+                    *ty,
+                    &transform,
+                    tile.pivot.unwrap_or(pivot.0),
+                    slot_size.0,
+                ),
+                tag: emitter.0.clone(),
+            },
+        );
+    });
+}
@@ -58,7 +58,7 @@ impl<T: Tiles> TileBuffer<T> {
     }
 
     /// Recalculate the aabb of this tile buffer.
-    /// 
+    ///
     /// This method can be expensive when the tile buffer is large.
     pub fn recalculate_aabb(&mut self) {
         self.aabb = IAabb2d::default();
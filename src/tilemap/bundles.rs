@@ -1,4 +1,4 @@
-use bevy::{asset::Handle, ecs::bundle::Bundle};
+use bevy::{asset::Handle, ecs::bundle::Bundle, math::Vec2};
 
 use crate::render::material::{
     StandardTilemapMaterial, TilemapMaterial, WaitForStandardMaterialReplacement,
@@ -6,8 +6,8 @@ use crate::render::material::{
 
 use super::map::{
     TilePivot, TileRenderSize, TilemapAnimations, TilemapAxisFlip, TilemapLayerOpacities,
-    TilemapName, TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTransform, TilemapType,
-    WaitForTextureUsageChange,
+    TilemapName, TilemapRotation, TilemapSlotSize, TilemapStorage, TilemapTexture,
+    TilemapTransform, TilemapType, WaitForTextureUsageChange,
 };
 
 /// All the possible bundles of the tilemap.
@@ -167,3 +167,97 @@ impl StandardPureColorTilemapBundle {
         }
     }
 }
+
+/// The geometry of a regular hexagonal tilemap.
+///
+/// Use `pointy` or `flat` to create it from the hex radius, then convert it into
+/// `StandardTilemapBundle` or `StandardPureColorTilemapBundle` and fill in the remaining fields.
+#[derive(Bundle, Debug, Clone)]
+pub struct HexTilemapBundle {
+    pub tile_render_size: TileRenderSize,
+    pub slot_size: TilemapSlotSize,
+    pub ty: TilemapType,
+    pub tile_pivot: TilePivot,
+    pub transform: TilemapTransform,
+    pub axis_flip: TilemapAxisFlip,
+}
+
+impl HexTilemapBundle {
+    /// The rotation of the texture that cancels out the rotation of flat hex tilemaps.
+    pub const FLAT_TEXTURE_ROTATION: TilemapRotation = TilemapRotation::Cw270;
+
+    /// Hexagons with a vertex pointing up. `radius` is the distance from the center
+    /// to a vertex in pixels, which is also the length of the legs.
+    ///
+    /// The pivot is relative to the tile size, so the default one, the bottom left corner
+    /// of the bounding box of the hexagon, lines up with the hex coordinates for any radius.
+    pub fn pointy(radius: u32) -> Self {
+        let size = Vec2::new(3f32.sqrt() * radius as f32, 2. * radius as f32);
+        Self {
+            tile_render_size: TileRenderSize(size),
+            slot_size: TilemapSlotSize(size),
+            ty: TilemapType::Hexagonal(radius),
+            tile_pivot: TilePivot::default(),
+            transform: TilemapTransform::default(),
+            axis_flip: TilemapAxisFlip::default(),
+        }
+    }
+
+    /// Hexagons with an edge on the top. `radius` is the distance from the center
+    /// to a vertex in pixels.
+    ///
+    /// This is a pointy tilemap rotated by 90 degrees, so the x axis of the indices points up.
+    /// Create the texture with `HexTilemapBundle::FLAT_TEXTURE_ROTATION` to keep the images upright.
+    pub fn flat(radius: u32) -> Self {
+        let mut bundle = Self::pointy(radius);
+        bundle.transform.rotation = TilemapRotation::Cw90;
+        bundle
+    }
+}
+
+impl From<HexTilemapBundle> for StandardTilemapBundle {
+    fn from(hex: HexTilemapBundle) -> Self {
+        Self {
+            tile_render_size: hex.tile_render_size,
+            slot_size: hex.slot_size,
+            ty: hex.ty,
+            tile_pivot: hex.tile_pivot,
+            transform: hex.transform,
+            axis_flip: hex.axis_flip,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<HexTilemapBundle> for StandardPureColorTilemapBundle {
+    fn from(hex: HexTilemapBundle) -> Self {
+        StandardTilemapBundle::from(hex).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hex_presets() {
+        let pointy = HexTilemapBundle::pointy(16);
+        let flat = HexTilemapBundle::flat(16);
+
+        assert_eq!(pointy.tile_render_size.0, Vec2::new(3f32.sqrt() * 16., 32.));
+        assert_eq!(flat.tile_render_size.0, pointy.tile_render_size.0);
+        assert_eq!(flat.slot_size.0, pointy.slot_size.0);
+        assert_eq!(flat.ty, TilemapType::Hexagonal(16));
+        assert_eq!(pointy.ty, TilemapType::Hexagonal(16));
+        assert_eq!(flat.transform.rotation, TilemapRotation::Cw90);
+        assert_eq!(pointy.transform.rotation, TilemapRotation::None);
+
+        let standard = StandardTilemapBundle::from(flat.clone());
+        let pure_color = StandardPureColorTilemapBundle::from(flat);
+        assert_eq!(standard.ty, TilemapType::Hexagonal(16));
+        assert_eq!(standard.transform.rotation, TilemapRotation::Cw90);
+        assert_eq!(pure_color.ty, TilemapType::Hexagonal(16));
+        assert_eq!(pure_color.transform.rotation, TilemapRotation::Cw90);
+        assert_eq!(pure_color.slot_size.0, standard.slot_size.0);
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::{
+    ecs::system::{Query, ResMut, Resource},
+    math::Vec2,
+    reflect::Reflect,
+};
+
+use crate::math::CameraAabb2d;
+
+/// Limits how much streaming work can be done in a single frame.
+///
+/// Insert this resource to make the chunk loading, collider spawning and
+/// LDtk/Tiled level loading spread their work across frames.
+/// Without it, everything is spawned as soon as possible.
+///
+/// **Notice**: A batch (a chunk or a level) is never split. If nothing is spent yet in
+/// the current frame, the first batch is always allowed even if it's larger than the budget.
+#[derive(Resource, Debug, Clone, Reflect)]
+pub struct StreamingBudget {
+    pub max_tiles_per_frame: usize,
+    pub max_colliders_per_frame: usize,
+    /// Work closer to the camera first.
+    pub prioritize_by_camera: bool,
+    pub(crate) spent_tiles: usize,
+    pub(crate) spent_colliders: usize,
+    pub(crate) focus: Option<Vec2>,
+}
+
+impl Default for StreamingBudget {
+    fn default() -> Self {
+        Self {
+            max_tiles_per_frame: usize::MAX,
+            max_colliders_per_frame: usize::MAX,
+            prioritize_by_camera: true,
+            spent_tiles: 0,
+            spent_colliders: 0,
+            focus: None,
+        }
+    }
+}
+
+impl StreamingBudget {
+    pub fn new(max_tiles_per_frame: usize, max_colliders_per_frame: usize) -> Self {
+        Self {
+            max_tiles_per_frame,
+            max_colliders_per_frame,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn remaining_tiles(&self) -> usize {
+        self.max_tiles_per_frame.saturating_sub(self.spent_tiles)
+    }
+
+    #[inline]
+    pub fn remaining_colliders(&self) -> usize {
+        self.max_colliders_per_frame
+            .saturating_sub(self.spent_colliders)
+    }
+
+    /// Try to spawn `count` tiles in this frame. Returns false if it's over budget.
+    pub fn try_spend_tiles(&mut self, count: usize) -> bool {
+        if self.spent_tiles == 0 || count <= self.remaining_tiles() {
+            self.spent_tiles = self.spent_tiles.saturating_add(count);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Try to spawn `count` colliders in this frame. Returns false if it's over budget.
+    pub fn try_spend_colliders(&mut self, count: usize) -> bool {
+        if self.spent_colliders == 0 || count <= self.remaining_colliders() {
+            self.spent_colliders = self.spent_colliders.saturating_add(count);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Take at most `count` colliders from the budget and return how many are allowed.
+    pub fn take_colliders(&mut self, count: usize) -> usize {
+        let allowed = count.min(self.remaining_colliders());
+        self.spent_colliders += allowed;
+        allowed
+    }
+
+    /// The center of the camera, if `prioritize_by_camera` is enabled.
+    #[inline]
+    pub fn focus(&self) -> Option<Vec2> {
+        if self.prioritize_by_camera {
+            self.focus
+        } else {
+            None
+        }
+    }
+
+    /// The priority of work at `position`. Lower values should be handled first.
+    #[inline]
+    pub fn priority(&self, position: Vec2) -> f32 {
+        self.focus()
+            .map(|focus| focus.distance_squared(position))
+            .unwrap_or_default()
+    }
+}
+
+pub fn streaming_budget_resetter(
+    budget: Option<ResMut<StreamingBudget>>,
+    cameras_query: Query<&CameraAabb2d>,
+) {
+    let Some(mut budget) = budget else {
+        return;
+    };
+
+    budget.spent_tiles = 0;
+    budget.spent_colliders = 0;
+    budget.focus = cameras_query.iter().next().map(|aabb| aabb.0.center());
+}
@@ -1,2 +1,66 @@
+use bevy::math::{UVec2, Vec2};
+
+pub mod budget;
 pub mod camera;
 pub mod storage;
+
+/// The smallest chunk size `suggest_chunk_size` returns.
+pub const MIN_SUGGESTED_CHUNK_SIZE: u32 = 4;
+/// The largest chunk size `suggest_chunk_size` returns. Larger chunks make
+/// every tile change rebuild a huge mesh.
+pub const MAX_SUGGESTED_CHUNK_SIZE: u32 = 64;
+/// The view size in pixels assumed when the crate picks chunk sizes by itself,
+/// like for the tilemaps spawned from LDtk levels and Tiled maps.
+pub const DEFAULT_EXPECTED_VIEW_SIZE: Vec2 = Vec2::new(1280., 720.);
+
+/// Suggest a chunk size for `TilemapStorage::new`, instead of guessing it.
+///
+/// Smaller chunks are culled more precisely but need more draw calls, larger ones
+/// are cheaper to draw but rebuild more tiles when anything changes. This aims for
+/// about 4 chunks across the larger side of the view, and doesn't go far beyond the map size.
+/// The result is a power of two between `MIN_SUGGESTED_CHUNK_SIZE` and `MAX_SUGGESTED_CHUNK_SIZE`.
+///
+/// `map_size` is in tiles, `tile_render_size` and `expected_view_size` are in pixels.
+pub fn suggest_chunk_size(
+    map_size: UVec2,
+    tile_render_size: Vec2,
+    expected_view_size: Vec2,
+) -> u32 {
+    let tiles_in_view = (expected_view_size / tile_render_size.max(Vec2::ONE))
+        .ceil()
+        .max_element() as u32;
+
+    (tiles_in_view / 4)
+        .min(map_size.max_element())
+        .max(1)
+        .next_power_of_two()
+        .clamp(MIN_SUGGESTED_CHUNK_SIZE, MAX_SUGGESTED_CHUNK_SIZE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_suggest_chunk_size() {
+        // 1280 / 16 = 80 tiles in view, 80 / 4 = 20 -> 32
+        assert_eq!(
+            suggest_chunk_size(UVec2::splat(512), Vec2::splat(16.), Vec2::new(1280., 720.)),
+            32
+        );
+        // Small maps don't need chunks larger than themselves.
+        assert_eq!(
+            suggest_chunk_size(UVec2::splat(6), Vec2::splat(16.), Vec2::new(1280., 720.)),
+            8
+        );
+        assert_eq!(
+            suggest_chunk_size(UVec2::splat(2), Vec2::splat(16.), Vec2::new(1280., 720.)),
+            MIN_SUGGESTED_CHUNK_SIZE
+        );
+        // Zoomed out views are capped.
+        assert_eq!(
+            suggest_chunk_size(UVec2::splat(4096), Vec2::splat(1.), Vec2::new(3840., 2160.)),
+            MAX_SUGGESTED_CHUNK_SIZE
+        );
+    }
+}
@@ -1,9 +1,15 @@
 use std::fmt::Debug;
 
-use bevy::{ecs::entity::Entity, math::IVec2, reflect::Reflect, utils::HashMap};
+use bevy::{
+    ecs::entity::Entity,
+    math::IVec2,
+    reflect::Reflect,
+    tasks::ComputeTaskPool,
+    utils::{HashMap, HashSet},
+};
 
 use crate::{
-    math::extension::DivToFloor,
+    math::{aabb::IAabb2d, extension::DivToFloor},
     tilemap::tile::{Tile, TileBuilder},
     DEFAULT_CHUNK_SIZE,
 };
@@ -22,23 +28,331 @@ pub type PhysicsTileChunkedStorage = ChunkedStorage<crate::tilemap::physics::Phy
 pub type PackedPhysicsTileChunkedStorage =
     ChunkedStorage<crate::tilemap::physics::PackedPhysicsTile>;
 
+/// How the chunks of a `ChunkedStorage` keep their elements.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChunkLayout {
+    /// Every chunk allocates a slot for each of its cells. This is the fastest to access.
+    #[default]
+    Dense,
+    /// Every chunk only keeps the existing elements, sorted by their in chunk indices.
+    ///
+    /// This saves a lot of memory for scattered elements, like a decorations layer.
+    Sparse,
+    /// New chunks start sparse and become dense once more than a quarter of the cells are used.
+    ///
+    /// Chunks turn back to sparse when less than an eighth of the cells are used
+    /// after `set_chunk`, `remove_region` or `optimize_layout`.
+    Auto,
+}
+
+/// The elements of a chunk in a `ChunkedStorage`.
+///
+/// Dense chunks are serialized as plain lists of slots, which is also how all the chunks
+/// were saved before sparse chunks existed. Sparse chunks are serialized as
+/// `(sparse: [(in_chunk_index, elem), ...])`.
+#[derive(Debug, Clone, Reflect)]
+pub enum StorageChunk<T: Debug + Clone + Reflect> {
+    Dense(Vec<Option<T>>),
+    /// Sorted by the in chunk indices.
+    Sparse(Vec<(InChunkIndex, T)>),
+}
+
+impl<T: Debug + Clone + Reflect> From<Vec<Option<T>>> for StorageChunk<T> {
+    fn from(value: Vec<Option<T>>) -> Self {
+        Self::Dense(value)
+    }
+}
+
+impl<T: Debug + Clone + Reflect> StorageChunk<T> {
+    /// Create an empty chunk with `area` cells.
+    pub fn new(layout: ChunkLayout, area: usize) -> Self {
+        match layout {
+            ChunkLayout::Dense => Self::Dense(vec![None; area]),
+            ChunkLayout::Sparse | ChunkLayout::Auto => Self::Sparse(Vec::new()),
+        }
+    }
+
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        matches!(self, Self::Sparse(_))
+    }
+
+    pub fn get(&self, index: InChunkIndex) -> Option<&T> {
+        match self {
+            Self::Dense(slots) => slots.get(index).and_then(|t| t.as_ref()),
+            Self::Sparse(elems) => elems
+                .binary_search_by_key(&index, |(i, _)| *i)
+                .ok()
+                .map(|i| &elems[i].1),
+        }
+    }
+
+    pub fn get_mut(&mut self, index: InChunkIndex) -> Option<&mut T> {
+        match self {
+            Self::Dense(slots) => slots.get_mut(index).and_then(|t| t.as_mut()),
+            Self::Sparse(elems) => elems
+                .binary_search_by_key(&index, |(i, _)| *i)
+                .ok()
+                .map(|i| &mut elems[i].1),
+        }
+    }
+
+    /// Insert the element and return the previous one.
+    pub fn set(&mut self, index: InChunkIndex, elem: T) -> Option<T> {
+        match self {
+            Self::Dense(slots) => slots[index].replace(elem),
+            Self::Sparse(elems) => match elems.binary_search_by_key(&index, |(i, _)| *i) {
+                Ok(i) => Some(std::mem::replace(&mut elems[i].1, elem)),
+                Err(i) => {
+                    elems.insert(i, (index, elem));
+                    None
+                }
+            },
+        }
+    }
+
+    /// Remove the element and return it.
+    pub fn take(&mut self, index: InChunkIndex) -> Option<T> {
+        match self {
+            Self::Dense(slots) => slots.get_mut(index).and_then(|t| t.take()),
+            Self::Sparse(elems) => elems
+                .binary_search_by_key(&index, |(i, _)| *i)
+                .ok()
+                .map(|i| elems.remove(i).1),
+        }
+    }
+
+    /// The number of elements in the chunk.
+    ///
+    /// This counts the slots for dense chunks.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Dense(slots) => slots.iter().filter(|t| t.is_some()).count(),
+            Self::Sparse(elems) => elems.len(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the elements with their in chunk indices.
+    pub fn iter_some(&self) -> impl Iterator<Item = (InChunkIndex, &T)> {
+        let (dense, sparse) = match self {
+            Self::Dense(slots) => (Some(slots), None),
+            Self::Sparse(elems) => (None, Some(elems)),
+        };
+        dense
+            .into_iter()
+            .flat_map(|slots| {
+                slots
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, t)| t.as_ref().map(|t| (i, t)))
+            })
+            .chain(
+                sparse
+                    .into_iter()
+                    .flat_map(|elems| elems.iter().map(|(i, t)| (*i, t))),
+            )
+    }
+
+    /// Iterate over the elements mutably with their in chunk indices.
+    pub fn iter_some_mut(&mut self) -> impl Iterator<Item = (InChunkIndex, &mut T)> {
+        let (dense, sparse) = match self {
+            Self::Dense(slots) => (Some(slots), None),
+            Self::Sparse(elems) => (None, Some(elems)),
+        };
+        dense
+            .into_iter()
+            .flat_map(|slots| {
+                slots
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(|(i, t)| t.as_mut().map(|t| (i, t)))
+            })
+            .chain(
+                sparse
+                    .into_iter()
+                    .flat_map(|elems| elems.iter_mut().map(|(i, t)| (*i, t))),
+            )
+    }
+
+    /// Take all the elements with their in chunk indices.
+    pub fn into_iter_some(self) -> impl Iterator<Item = (InChunkIndex, T)> {
+        let (dense, sparse) = match self {
+            Self::Dense(slots) => (Some(slots), None),
+            Self::Sparse(elems) => (None, Some(elems)),
+        };
+        dense
+            .into_iter()
+            .flat_map(|slots| {
+                slots
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, t)| t.map(|t| (i, t)))
+            })
+            .chain(sparse.into_iter().flatten())
+    }
+
+    /// Convert the chunk into slots for each of the `area` cells.
+    pub fn into_dense(self, area: usize) -> Vec<Option<T>> {
+        match self {
+            Self::Dense(slots) => slots,
+            Self::Sparse(elems) => {
+                let mut slots = vec![None; area];
+                elems.into_iter().for_each(|(i, t)| slots[i] = Some(t));
+                slots
+            }
+        }
+    }
+
+    /// Switch the chunk to the representation that `layout` prefers for its element count.
+    pub fn apply_layout(&mut self, layout: ChunkLayout, area: usize) {
+        let sparse = match layout {
+            ChunkLayout::Dense => false,
+            ChunkLayout::Sparse => true,
+            ChunkLayout::Auto => {
+                let len = self.len();
+                if self.is_sparse() {
+                    len * 4 <= area
+                } else {
+                    len * 8 < area
+                }
+            }
+        };
+        if sparse == self.is_sparse() {
+            return;
+        }
+
+        let chunk = std::mem::replace(self, Self::Sparse(Vec::new()));
+        *self = if sparse {
+            Self::Sparse(chunk.into_iter_some().collect())
+        } else {
+            Self::Dense(chunk.into_dense(area))
+        };
+    }
+
+    /// The bytes allocated on the heap by the chunk.
+    pub fn heap_bytes(&self) -> usize {
+        match self {
+            Self::Dense(slots) => slots.capacity() * std::mem::size_of::<Option<T>>(),
+            Self::Sparse(elems) => elems.capacity() * std::mem::size_of::<(InChunkIndex, T)>(),
+        }
+    }
+}
+
+#[cfg(feature = "serializing")]
+mod chunk_serde {
+    use std::{fmt::Debug, marker::PhantomData};
+
+    use bevy::reflect::Reflect;
+    use serde::{
+        de::{value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::{InChunkIndex, StorageChunk};
+
+    #[derive(Serialize)]
+    struct SparseChunkRef<'a, T> {
+        sparse: &'a Vec<(InChunkIndex, T)>,
+    }
+
+    #[derive(Deserialize)]
+    struct SparseChunk<T> {
+        sparse: Vec<(InChunkIndex, T)>,
+    }
+
+    impl<T: Debug + Clone + Reflect + Serialize> Serialize for StorageChunk<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                StorageChunk::Dense(slots) => slots.serialize(serializer),
+                StorageChunk::Sparse(elems) => {
+                    SparseChunkRef { sparse: elems }.serialize(serializer)
+                }
+            }
+        }
+    }
+
+    struct StorageChunkVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Debug + Clone + Reflect + Deserialize<'de>> Visitor<'de> for StorageChunkVisitor<T> {
+        type Value = StorageChunk<T>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a list of slots or a sparse chunk")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut slots = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(slot) = seq.next_element()? {
+                slots.push(slot);
+            }
+            Ok(StorageChunk::Dense(slots))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            SparseChunk::deserialize(MapAccessDeserializer::new(map))
+                .map(|chunk| StorageChunk::Sparse(chunk.sparse))
+        }
+    }
+
+    impl<'de, T: Debug + Clone + Reflect + Deserialize<'de>> Deserialize<'de> for StorageChunk<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(StorageChunkVisitor(PhantomData))
+        }
+    }
+}
+
+/// A sparse 2d grid that groups the elements into square chunks.
+///
+/// This is what the crate uses to store tiles, path tiles and physics tiles,
+/// but it works for any data, like temperature or gameplay state:
+///
+/// ```
+/// # use bevy::math::IVec2;
+/// # use bevy_entitiles::tilemap::chunking::storage::ChunkedStorage;
+/// let mut temperature = ChunkedStorage::<f32>::new(16);
+/// temperature.set_elem(IVec2::new(-3, 20), 36.5);
+/// assert_eq!(temperature.get_elem(IVec2::new(-3, 20)), Some(&36.5));
+/// ```
+///
+/// Chunks that are modified through the methods are marked as dirty.
+/// Use `take_dirty` to handle the changes, like rebuilding a mesh or saving to disk.
+///
+/// Each chunk keeps its elements according to `layout`. Use `ChunkLayout::Sparse`
+/// or `ChunkLayout::Auto` when the elements are scattered, so mostly empty chunks
+/// don't allocate a slot for every cell.
+///
+/// **Notice**: Modifying `chunks` directly won't mark anything as dirty.
 #[derive(Debug, Clone, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChunkedStorage<T: Debug + Clone + Reflect> {
     pub chunk_size: u32,
-    pub chunks: HashMap<IVec2, Vec<Option<T>>>,
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub layout: ChunkLayout,
+    pub chunks: HashMap<IVec2, StorageChunk<T>>,
+    #[reflect(ignore)]
+    #[cfg_attr(feature = "serializing", serde(skip))]
+    pub(crate) dirty: HashSet<IVec2>,
 }
 
 impl<T: Debug + Clone + Reflect> Default for ChunkedStorage<T> {
     fn default() -> Self {
         Self {
             chunk_size: DEFAULT_CHUNK_SIZE,
+            layout: ChunkLayout::default(),
             chunks: HashMap::new(),
+            dirty: HashSet::new(),
         }
     }
 }
 
 impl<T: Debug + Clone + Reflect> ChunkedStorage<T> {
+    /// Create an empty storage whose chunks are `chunk_size` x `chunk_size`.
     pub fn new(chunk_size: u32) -> Self {
         Self {
             chunk_size,
@@ -46,6 +360,30 @@ impl<T: Debug + Clone + Reflect> ChunkedStorage<T> {
         }
     }
 
+    /// Use `layout` for the chunks. The existing chunks are converted.
+    pub fn with_layout(mut self, layout: ChunkLayout) -> Self {
+        self.layout = layout;
+        self.optimize_layout();
+        self
+    }
+
+    /// Convert every chunk to the representation that `layout` prefers.
+    ///
+    /// `ChunkLayout::Auto` only turns dense chunks back to sparse ones in some cases,
+    /// call this after removing lots of elements one by one to shrink the storage.
+    pub fn optimize_layout(&mut self) {
+        let area = self.chunk_area();
+        let layout = self.layout;
+        self.chunks
+            .values_mut()
+            .for_each(|chunk| chunk.apply_layout(layout, area));
+    }
+
+    #[inline]
+    fn chunk_area(&self) -> usize {
+        (self.chunk_size * self.chunk_size) as usize
+    }
+
     pub fn from_mapper(mapper: HashMap<IVec2, T>, chunk_size: Option<u32>) -> Self {
         let mut storage = Self::new(chunk_size.unwrap_or(32));
         mapper.into_iter().for_each(|(index, elem)| {
@@ -54,73 +392,211 @@ impl<T: Debug + Clone + Reflect> ChunkedStorage<T> {
         storage
     }
 
+    /// Get the element at `index`.
     pub fn get_elem(&self, index: IVec2) -> Option<&T> {
         let idx = self.transform_index(index);
-        self.chunks
-            .get(&idx.0)
-            .and_then(|c| c.get(idx.1).as_ref().cloned())
-            .and_then(|t| t.as_ref())
+        self.chunks.get(&idx.0).and_then(|c| c.get(idx.1))
     }
 
+    /// Get the element at `index` mutably. This marks the chunk as dirty.
     pub fn get_elem_mut(&mut self, index: IVec2) -> Option<&mut T> {
         let idx = self.transform_index(index);
         if let Some(chunk) = self.chunks.get_mut(&idx.0) {
-            chunk.get_mut(idx.1).map(|t| t.as_mut()).flatten()
+            self.dirty.insert(idx.0);
+            chunk.get_mut(idx.1)
         } else {
             None
         }
     }
 
+    /// Insert the element at `index`. Overwrites the previous one.
     pub fn set_elem(&mut self, index: IVec2, elem: T) {
         let idx = self.transform_index(index);
-        self.chunks
-            .entry(idx.0)
-            .or_insert_with(|| vec![None; (self.chunk_size * self.chunk_size) as usize])[idx.1] =
-            Some(elem);
+        self.set_elem_precise(idx.0, idx.1, elem);
     }
 
     pub fn set_elem_precise(&mut self, chunk_index: IVec2, in_chunk_index: usize, elem: T) {
-        self.chunks
+        let (area, layout) = (self.chunk_area(), self.layout);
+        self.dirty.insert(chunk_index);
+        let chunk = self
+            .chunks
             .entry(chunk_index)
-            .or_insert_with(|| vec![None; (self.chunk_size * self.chunk_size) as usize])
-            [in_chunk_index] = Some(elem);
+            .or_insert_with(|| StorageChunk::new(layout, area));
+        if chunk.set(in_chunk_index, elem).is_none()
+            && layout == ChunkLayout::Auto
+            && chunk.is_sparse()
+        {
+            chunk.apply_layout(layout, area);
+        }
     }
 
+    /// Remove the element at `index` and return it.
     pub fn remove_elem(&mut self, index: IVec2) -> Option<T> {
         let idx = self.transform_index(index);
-        self.chunks.get_mut(&idx.0).and_then(|c| c[idx.1].take())
+        let elem = self.chunks.get_mut(&idx.0).and_then(|c| c.take(idx.1));
+        if elem.is_some() {
+            self.dirty.insert(idx.0);
+        }
+        elem
     }
 
-    pub fn remove_chunk(&mut self, index: IVec2) -> Option<Vec<Option<T>>> {
-        self.chunks.remove(&index)
+    pub fn remove_chunk(&mut self, index: IVec2) -> Option<StorageChunk<T>> {
+        let chunk = self.chunks.remove(&index);
+        if chunk.is_some() {
+            self.dirty.insert(index);
+        }
+        chunk
     }
 
     pub fn clear(&mut self) {
-        self.chunks.clear();
+        self.dirty
+            .extend(self.chunks.drain().map(|(index, _)| index));
+    }
+
+    /// Remove all the elements inside the region and return them.
+    ///
+    /// Chunks that are fully covered by the region are removed as a whole.
+    pub fn remove_region(&mut self, region: IAabb2d) -> Vec<T> {
+        let isize = IVec2::splat(self.chunk_size as i32);
+        let chunk_min = region.min.div_to_floor(isize);
+        let chunk_max = region.max.div_to_floor(isize);
+        let mut removed = Vec::new();
+
+        for cy in chunk_min.y..=chunk_max.y {
+            for cx in chunk_min.x..=chunk_max.x {
+                let chunk_index = IVec2 { x: cx, y: cy };
+                let chunk_aabb = IAabb2d {
+                    min: chunk_index * isize,
+                    max: chunk_index * isize + isize - IVec2::ONE,
+                };
+
+                if chunk_aabb.is_subset_of(region) {
+                    if let Some(chunk) = self.remove_chunk(chunk_index) {
+                        removed.extend(chunk.into_iter_some().map(|(_, elem)| elem));
+                    }
+                    continue;
+                }
+
+                let Some(chunk) = self.chunks.get_mut(&chunk_index) else {
+                    continue;
+                };
+                let area = chunk_aabb.intersection(region);
+                for y in area.min.y..=area.max.y {
+                    for x in area.min.x..=area.max.x {
+                        let in_chunk = IVec2 { x, y } - chunk_aabb.min;
+                        if let Some(elem) = chunk.take((in_chunk.y * isize.x + in_chunk.x) as usize)
+                        {
+                            removed.push(elem);
+                            self.dirty.insert(chunk_index);
+                        }
+                    }
+                }
+                if self.layout == ChunkLayout::Auto {
+                    chunk.apply_layout(self.layout, (self.chunk_size * self.chunk_size) as usize);
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Get all the elements inside the region, chunk by chunk.
+    pub fn get_region(&self, region: IAabb2d) -> Vec<&T> {
+        let isize = IVec2::splat(self.chunk_size as i32);
+        let chunk_min = region.min.div_to_floor(isize);
+        let chunk_max = region.max.div_to_floor(isize);
+        let mut elems = Vec::new();
+
+        for cy in chunk_min.y..=chunk_max.y {
+            for cx in chunk_min.x..=chunk_max.x {
+                let chunk_index = IVec2 { x: cx, y: cy };
+                let Some(chunk) = self.chunks.get(&chunk_index) else {
+                    continue;
+                };
+                let chunk_aabb = IAabb2d {
+                    min: chunk_index * isize,
+                    max: chunk_index * isize + isize - IVec2::ONE,
+                };
+
+                if chunk_aabb.is_subset_of(region) {
+                    elems.extend(chunk.iter_some().map(|(_, elem)| elem));
+                    continue;
+                }
+
+                let area = chunk_aabb.intersection(region);
+                for y in area.min.y..=area.max.y {
+                    for x in area.min.x..=area.max.x {
+                        let in_chunk = IVec2 { x, y } - chunk_aabb.min;
+                        if let Some(elem) = chunk.get((in_chunk.y * isize.x + in_chunk.x) as usize)
+                        {
+                            elems.push(elem);
+                        }
+                    }
+                }
+            }
+        }
+
+        elems
     }
 
     #[inline]
-    pub fn get_chunk(&self, index: IVec2) -> Option<&Vec<Option<T>>> {
+    pub fn get_chunk(&self, index: IVec2) -> Option<&StorageChunk<T>> {
         self.chunks.get(&index)
     }
 
+    /// Get a chunk mutably. This marks the chunk as dirty.
     #[inline]
-    pub fn get_chunk_mut(&mut self, index: IVec2) -> Option<&mut Vec<Option<T>>> {
-        self.chunks.get_mut(&index)
+    pub fn get_chunk_mut(&mut self, index: IVec2) -> Option<&mut StorageChunk<T>> {
+        let chunk = self.chunks.get_mut(&index);
+        if chunk.is_some() {
+            self.dirty.insert(index);
+        }
+        chunk
     }
 
+    /// Get a chunk mutably, or insert an empty one. This marks the chunk as dirty.
     #[inline]
-    pub fn get_chunk_or_insert(&mut self, index: IVec2) -> &mut Vec<Option<T>> {
+    pub fn get_chunk_or_insert(&mut self, index: IVec2) -> &mut StorageChunk<T> {
+        let (area, layout) = (self.chunk_area(), self.layout);
+        self.dirty.insert(index);
         self.chunks
             .entry(index)
-            .or_insert(vec![None; (self.chunk_size * self.chunk_size) as usize])
+            .or_insert_with(|| StorageChunk::new(layout, area))
     }
 
+    /// Insert the chunk, converted to the representation that `layout` prefers.
     #[inline]
-    pub fn set_chunk(&mut self, index: IVec2, chunk: Vec<Option<T>>) {
+    pub fn set_chunk(&mut self, index: IVec2, chunk: impl Into<StorageChunk<T>>) {
+        let mut chunk = chunk.into();
+        chunk.apply_layout(self.layout, self.chunk_area());
+        self.dirty.insert(index);
         self.chunks.insert(index, chunk);
     }
 
+    /// Whether the chunk is modified since the last `take_dirty` or `clear_dirty`.
+    #[inline]
+    pub fn is_dirty(&self, index: IVec2) -> bool {
+        self.dirty.contains(&index)
+    }
+
+    /// The indices of the modified chunks, including the removed ones.
+    #[inline]
+    pub fn dirty_chunks(&self) -> impl Iterator<Item = ChunkIndex> + '_ {
+        self.dirty.iter().copied()
+    }
+
+    /// Take the indices of the modified chunks and reset them.
+    #[inline]
+    pub fn take_dirty(&mut self) -> HashSet<ChunkIndex> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    #[inline]
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Get the chunk index and the index inside the chunk of `index`.
     pub fn transform_index(&self, index: IVec2) -> (ChunkIndex, InChunkIndex) {
         let isize = IVec2::splat(self.chunk_size as i32);
         let c = index.div_to_floor(isize);
@@ -140,40 +616,55 @@ impl<T: Debug + Clone + Reflect> ChunkedStorage<T> {
     pub fn into_mapper(mut self) -> HashMap<IVec2, T> {
         let mut mapper = HashMap::new();
         self.chunks.drain().for_each(|(chunk_index, chunk)| {
-            chunk.into_iter().enumerate().for_each(|(index, elem)| {
-                if let Some(elem) = elem {
-                    mapper.insert(
-                        chunk_index * IVec2::splat(self.chunk_size as i32)
-                            + IVec2 {
-                                x: index as i32 % self.chunk_size as i32,
-                                y: index as i32 / self.chunk_size as i32,
-                            },
-                        elem,
-                    );
-                }
+            chunk.into_iter_some().for_each(|(index, elem)| {
+                mapper.insert(
+                    chunk_index * IVec2::splat(self.chunk_size as i32)
+                        + IVec2 {
+                            x: index as i32 % self.chunk_size as i32,
+                            y: index as i32 / self.chunk_size as i32,
+                        },
+                    elem,
+                );
             });
         });
         mapper
     }
 
+    /// Iterate over all the slots of the dense chunks.
+    #[deprecated(note = "sparse chunks have no slots and are skipped, use `iter_some` instead")]
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &Option<T>> {
-        self.chunks.values().map(|c| c.iter()).flatten()
+        self.chunks.values().flat_map(|c| match c {
+            StorageChunk::Dense(slots) => slots.as_slice(),
+            StorageChunk::Sparse(_) => &[],
+        })
     }
 
+    /// Iterate over all the slots of the dense chunks mutably. This marks all the chunks as dirty.
+    #[deprecated(note = "sparse chunks have no slots and are skipped, use `iter_some_mut` instead")]
     #[inline]
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Option<T>> {
-        self.chunks.values_mut().map(|c| c.iter_mut()).flatten()
+        self.dirty.extend(self.chunks.keys().copied());
+        self.chunks.values_mut().flat_map(|c| match c {
+            StorageChunk::Dense(slots) => slots.as_mut_slice(),
+            StorageChunk::Sparse(_) => &mut [],
+        })
     }
 
     #[inline]
     pub fn iter_some(&self) -> impl Iterator<Item = &T> {
-        self.iter().map(|o| o.as_ref()).flatten()
+        self.chunks
+            .values()
+            .flat_map(|c| c.iter_some().map(|(_, elem)| elem))
     }
 
+    /// Iterate over all the elements mutably. This marks all the chunks as dirty.
     #[inline]
     pub fn iter_some_mut(&mut self) -> impl Iterator<Item = &mut T> {
-        self.iter_mut().map(|o| o.as_mut()).flatten()
+        self.dirty.extend(self.chunks.keys().copied());
+        self.chunks
+            .values_mut()
+            .flat_map(|c| c.iter_some_mut().map(|(_, elem)| elem))
     }
 
     #[inline]
@@ -182,13 +673,135 @@ impl<T: Debug + Clone + Reflect> ChunkedStorage<T> {
             .iter()
             .map(|(chunk_index, chunk)| {
                 chunk
-                    .iter()
-                    .enumerate()
-                    .filter_map(move |(in_chunk_index, elem)| {
-                        elem.as_ref()
-                            .map(|elem| (*chunk_index, in_chunk_index, elem))
-                    })
+                    .iter_some()
+                    .map(move |(in_chunk_index, elem)| (*chunk_index, in_chunk_index, elem))
             })
             .flatten()
     }
+
+    /// Iterate over all the elements with their indices.
+    #[inline]
+    pub fn iter_with_index(&self) -> impl Iterator<Item = (IVec2, &T)> {
+        self.chunked_iter_some()
+            .map(|(chunk_index, in_chunk_index, elem)| {
+                (
+                    self.inverse_transform_index(chunk_index, in_chunk_index),
+                    elem,
+                )
+            })
+    }
+}
+
+impl<T: Debug + Clone + Reflect + Send + Sync> ChunkedStorage<T> {
+    /// Run `f` on every element with its index. Each chunk is handled in its own task.
+    pub fn par_for_each(&self, f: impl Fn(IVec2, &T) + Send + Sync) {
+        let f = &f;
+        ComputeTaskPool::get().scope(|scope| {
+            self.chunks.iter().for_each(|(chunk_index, chunk)| {
+                scope.spawn(async move {
+                    chunk.iter_some().for_each(|(in_chunk_index, elem)| {
+                        f(
+                            self.inverse_transform_index(*chunk_index, in_chunk_index),
+                            elem,
+                        );
+                    });
+                });
+            });
+        });
+    }
+
+    /// Run `f` on every element with its index mutably. Each chunk is handled in its own task.
+    ///
+    /// This marks all the chunks as dirty.
+    pub fn par_for_each_mut(&mut self, f: impl Fn(IVec2, &mut T) + Send + Sync) {
+        let f = &f;
+        let chunk_size = self.chunk_size as i32;
+        self.dirty.extend(self.chunks.keys().copied());
+        ComputeTaskPool::get().scope(|scope| {
+            self.chunks.iter_mut().for_each(|(chunk_index, chunk)| {
+                let origin = *chunk_index * chunk_size;
+                scope.spawn(async move {
+                    chunk.iter_some_mut().for_each(|(in_chunk_index, elem)| {
+                        let in_chunk = IVec2::new(
+                            in_chunk_index as i32 % chunk_size,
+                            in_chunk_index as i32 / chunk_size,
+                        );
+                        f(origin + in_chunk, elem);
+                    });
+                });
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        math::IVec2,
+        tasks::{ComputeTaskPool, TaskPool},
+    };
+
+    use crate::math::aabb::IAabb2d;
+
+    use super::{ChunkLayout, ChunkedStorage};
+
+    #[test]
+    fn test_chunked_storage() {
+        ComputeTaskPool::get_or_init(TaskPool::default);
+
+        let mut storage = ChunkedStorage::<i32>::new(4);
+        storage.set_elem(IVec2::new(-1, -1), 1);
+        storage.set_elem(IVec2::new(5, 2), 2);
+        assert_eq!(storage.get_elem(IVec2::new(-1, -1)), Some(&1));
+        assert_eq!(storage.take_dirty().len(), 2);
+
+        storage.par_for_each_mut(|index, elem| *elem += index.x);
+        assert_eq!(storage.get_elem(IVec2::new(5, 2)), Some(&7));
+
+        storage.clear_dirty();
+        let removed = storage.remove_region(IAabb2d::new(0, 0, 7, 7));
+        assert_eq!(removed, vec![7]);
+        assert!(storage.is_dirty(IVec2::new(1, 0)));
+        assert!(!storage.is_dirty(IVec2::new(-1, -1)));
+    }
+
+    #[test]
+    fn test_sparse_chunks() {
+        let mut storage = ChunkedStorage::<i32>::new(4).with_layout(ChunkLayout::Auto);
+        storage.set_elem(IVec2::new(3, 1), 1);
+        storage.set_elem(IVec2::new(0, 0), 2);
+        assert!(storage.get_chunk(IVec2::ZERO).unwrap().is_sparse());
+        assert_eq!(storage.get_elem(IVec2::new(3, 1)), Some(&1));
+        assert_eq!(storage.get_elem(IVec2::new(1, 1)), None);
+
+        storage.set_elem(IVec2::new(2, 2), 3);
+        storage.set_elem(IVec2::new(1, 3), 4);
+        storage.set_elem(IVec2::new(1, 2), 5);
+        assert!(!storage.get_chunk(IVec2::ZERO).unwrap().is_sparse());
+        assert_eq!(storage.get_elem(IVec2::new(1, 2)), Some(&5));
+
+        let removed = storage.remove_region(IAabb2d::new(0, 0, 2, 3));
+        assert_eq!(removed.len(), 4);
+        assert!(storage.get_chunk(IVec2::ZERO).unwrap().is_sparse());
+        assert_eq!(storage.iter_with_index().count(), 1);
+    }
+
+    #[cfg(feature = "serializing")]
+    #[test]
+    fn test_legacy_chunks() {
+        // Saved before sparse chunks and `layout` were added.
+        let legacy = "(chunk_size: 2, chunks: {(0, 0): [Some(1), None, None, Some(4)]})";
+        let mut storage = ron::from_str::<ChunkedStorage<i32>>(legacy).unwrap();
+        assert_eq!(storage.layout, ChunkLayout::Dense);
+        assert_eq!(storage.get_elem(IVec2::new(1, 1)), Some(&4));
+
+        storage = storage.with_layout(ChunkLayout::Sparse);
+        storage.set_elem(IVec2::new(-1, 0), 2);
+        let saved = ron::to_string(&storage).unwrap();
+        let loaded = ron::from_str::<ChunkedStorage<i32>>(&saved).unwrap();
+        assert!(loaded.get_chunk(IVec2::ZERO).unwrap().is_sparse());
+        assert_eq!(loaded.get_elem(IVec2::new(0, 0)), Some(&1));
+        assert_eq!(loaded.get_elem(IVec2::new(-1, 0)), Some(&2));
+        assert_eq!(loaded.iter_some().count(), 3);
+    }
 }
@@ -0,0 +1,255 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventReader},
+        reflect::ReflectResource,
+        system::{Command, Commands, Query, ResMut, Resource, SystemState},
+        world::World,
+    },
+    log::warn,
+    math::{IVec2, Vec4},
+    reflect::{std_traits::ReflectDefault, Reflect},
+    utils::HashMap,
+};
+
+use crate::math::aabb::IAabb2d;
+
+use super::{
+    map::{TilemapName, TilemapStorage},
+    tile::{TileBuilder, TileLayer},
+};
+
+/// Set a single layer tile on the tilemap named `tilemap_name`.
+///
+/// Overwrites the tile if it already exists. The commands are only plain data,
+/// so scripting layers can create them through reflection and send them as `TileCommand`
+/// events, or push them into the `TilemapCommandQueue`.
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Default)]
+pub struct SetTileCommand {
+    pub tilemap_name: String,
+    pub index: IVec2,
+    pub texture_index: u32,
+    /// The raw `TileFlip` bits.
+    pub flip: u32,
+    pub color: Vec4,
+}
+
+impl Default for SetTileCommand {
+    fn default() -> Self {
+        Self {
+            tilemap_name: String::default(),
+            index: IVec2::ZERO,
+            texture_index: 0,
+            flip: 0,
+            color: Vec4::ONE,
+        }
+    }
+}
+
+impl SetTileCommand {
+    #[inline]
+    pub fn to_builder(&self) -> TileBuilder {
+        TileBuilder::new()
+            .with_layer(
+                0,
+                TileLayer::new()
+                    .with_texture_index(self.texture_index)
+                    .with_flip_raw(self.flip),
+            )
+            .with_color(self.color)
+    }
+}
+
+/// Remove the tile at `index` on the tilemap named `tilemap_name`.
+#[derive(Debug, Default, Clone, Reflect)]
+#[reflect(Default)]
+pub struct RemoveTileCommand {
+    pub tilemap_name: String,
+    pub index: IVec2,
+}
+
+/// A command that sets or removes a tile.
+/// The commands are applied in the order they are sent.
+#[derive(Event, Debug, Clone, Reflect)]
+pub enum TileCommand {
+    Set(SetTileCommand),
+    Remove(RemoveTileCommand),
+}
+
+/// Despawn all the tiles inside `region` of `tilemap` with `Commands::add`.
+///
+/// The colliders of the `PhysicsTilemap` and the path tiles of the `PathTilemap`
+/// in the same region are removed as well, if the tilemap has them.
+#[derive(Debug, Clone, Copy)]
+pub struct DespawnTilemapRegion {
+    pub tilemap: Entity,
+    pub region: IAabb2d,
+}
+
+impl Command for DespawnTilemapRegion {
+    fn apply(self, world: &mut World) {
+        if !with_component(
+            world,
+            self.tilemap,
+            |storage: &mut TilemapStorage, commands| {
+                storage.despawn_region(commands, self.region);
+            },
+        ) {
+            warn!("Tilemap {:?} does not exist!", self.tilemap);
+            return;
+        }
+
+        #[cfg(feature = "physics")]
+        with_component(
+            world,
+            self.tilemap,
+            |physics_tilemap: &mut super::physics::PhysicsTilemap, commands| {
+                physics_tilemap.remove_region(commands, self.region);
+            },
+        );
+
+        #[cfg(feature = "algorithm")]
+        if let Some(mut path_tilemap) =
+            world.get_mut::<super::algorithm::path::PathTilemap>(self.tilemap)
+        {
+            path_tilemap.remove_region(self.region);
+        }
+    }
+}
+
+/// Run `f` on the component of `entity` and apply the commands it queued.
+/// Returns false if the entity doesn't have the component.
+fn with_component<C: Component>(
+    world: &mut World,
+    entity: Entity,
+    f: impl FnOnce(&mut C, &mut Commands),
+) -> bool {
+    let mut state = SystemState::<(Commands, Query<&mut C>)>::new(world);
+    let (mut commands, mut query) = state.get_mut(world);
+    let Ok(mut component) = query.get_mut(entity) else {
+        return false;
+    };
+    f(&mut component, &mut commands);
+    state.apply(world);
+    true
+}
+
+/// The tile commands to apply in this frame, for scripts that can only access
+/// the world through reflected resources.
+///
+/// Commands in the queue are applied in order, after the `TileCommand` events.
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+#[reflect(Resource, Default)]
+pub struct TilemapCommandQueue {
+    pub commands: Vec<TileCommand>,
+}
+
+impl TilemapCommandQueue {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+pub fn tilemap_command_applier(
+    mut commands: Commands,
+    mut tilemaps_query: Query<(&TilemapName, &mut TilemapStorage)>,
+    mut events: EventReader<TileCommand>,
+    mut queue: ResMut<TilemapCommandQueue>,
+) {
+    if events.is_empty() && queue.is_empty() {
+        return;
+    }
+
+    let queue = std::mem::take(&mut *queue);
+    let mut tilemaps = tilemaps_query
+        .iter_mut()
+        .map(|(name, storage)| (name.0.as_str(), storage))
+        .collect::<HashMap<_, _>>();
+
+    events
+        .read()
+        .chain(queue.commands.iter())
+        .for_each(|command| {
+            let tilemap_name = match command {
+                TileCommand::Set(set) => &set.tilemap_name,
+                TileCommand::Remove(remove) => &remove.tilemap_name,
+            };
+            let Some(storage) = tilemaps.get_mut(tilemap_name.as_str()) else {
+                warn!("Tilemap {} does not exist!", tilemap_name);
+                return;
+            };
+            match command {
+                TileCommand::Set(set) => storage.set(&mut commands, set.index, set.to_builder()),
+                TileCommand::Remove(remove) => storage.remove(&mut commands, remove.index),
+            }
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::{event::Events, system::RunSystemOnce, world::World},
+        math::IVec2,
+    };
+
+    use crate::tilemap::{
+        map::{TilemapName, TilemapStorage},
+        tile::Tile,
+    };
+
+    use super::{
+        tilemap_command_applier, RemoveTileCommand, SetTileCommand, TileCommand,
+        TilemapCommandQueue,
+    };
+
+    #[test]
+    fn test_command_order() {
+        let mut world = World::new();
+        world.init_resource::<Events<TileCommand>>();
+        world.init_resource::<TilemapCommandQueue>();
+        let tilemap = world.spawn_empty().id();
+        world.entity_mut(tilemap).insert((
+            TilemapName("map".to_string()),
+            TilemapStorage::new(4, tilemap),
+        ));
+
+        let set = |texture_index| {
+            TileCommand::Set(SetTileCommand {
+                tilemap_name: "map".to_string(),
+                texture_index,
+                ..Default::default()
+            })
+        };
+        let remove = TileCommand::Remove(RemoveTileCommand {
+            tilemap_name: "map".to_string(),
+            index: IVec2::ZERO,
+        });
+
+        world.resource_mut::<TilemapCommandQueue>().commands = vec![set(0)];
+        world.run_system_once(tilemap_command_applier);
+
+        // Replace the tile by removing it first, then setting a new one.
+        world.resource_mut::<TilemapCommandQueue>().commands = vec![remove.clone(), set(1)];
+        world.run_system_once(tilemap_command_applier);
+
+        let tile = world
+            .get::<TilemapStorage>(tilemap)
+            .unwrap()
+            .get(IVec2::ZERO)
+            .unwrap();
+        assert!(world.get::<Tile>(tile).is_some());
+
+        // And removing a tile right after setting it leaves nothing.
+        world.resource_mut::<TilemapCommandQueue>().commands = vec![set(2), remove];
+        world.run_system_once(tilemap_command_applier);
+
+        assert!(world
+            .get::<TilemapStorage>(tilemap)
+            .unwrap()
+            .get(IVec2::ZERO)
+            .is_none());
+    }
+}
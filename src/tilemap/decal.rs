@@ -0,0 +1,329 @@
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        system::{Commands, Query, Res, ResMut},
+        world::Ref,
+    },
+    math::{IVec2, Vec2, Vec4},
+    reflect::Reflect,
+    render::{
+        color::Color,
+        mesh::{Indices, Mesh},
+        render_asset::RenderAssetUsages,
+        render_resource::PrimitiveTopology,
+        texture::Image,
+    },
+    sprite::{ColorMaterial, MaterialMesh2dBundle, Mesh2dHandle},
+    time::Time,
+    transform::components::Transform,
+    utils::{HashMap, HashSet},
+};
+
+use super::map::{TilemapTextureDescriptor, TilemapTransform};
+
+/// A quad that is placed freely on a tilemap, like blood splats or footprints.
+#[derive(Debug, Clone, Reflect)]
+pub struct Decal {
+    /// The center of the decal, relative to the tilemap's translation.
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Rotation in radians.
+    pub rotation: f32,
+    pub texture_index: u32,
+    pub color: Vec4,
+    /// `None` means the decal never expires.
+    pub lifetime: Option<f32>,
+    /// How many seconds the decal takes to fade out before it expires.
+    pub fade_out: f32,
+    pub(crate) age: f32,
+}
+
+impl Decal {
+    pub fn new(position: Vec2, size: Vec2, texture_index: u32) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.,
+            texture_index,
+            color: Vec4::ONE,
+            lifetime: None,
+            fade_out: 0.,
+            age: 0.,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Make the decal expire after `lifetime` seconds.
+    /// It will fade out during the last `fade_out` seconds.
+    pub fn with_lifetime(mut self, lifetime: f32, fade_out: f32) -> Self {
+        self.lifetime = Some(lifetime);
+        self.fade_out = fade_out.min(lifetime);
+        self
+    }
+
+    #[inline]
+    pub fn is_expired(&self) -> bool {
+        self.lifetime.is_some_and(|l| self.age >= l)
+    }
+
+    #[inline]
+    pub fn is_fading(&self) -> bool {
+        self.lifetime
+            .is_some_and(|l| self.fade_out > 0. && self.age > l - self.fade_out)
+    }
+
+    fn alpha(&self) -> f32 {
+        match self.lifetime {
+            Some(l) if self.fade_out > 0. => ((l - self.age) / self.fade_out).clamp(0., 1.),
+            _ => 1.,
+        }
+    }
+}
+
+/// Decals of a tilemap. Add this to a tilemap entity.
+///
+/// Decals are batched into one mesh per chunk and rendered right above the tilemap,
+/// so they sit on the ground but under things with higher z.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapDecals {
+    pub(crate) texture: Handle<Image>,
+    pub(crate) desc: TilemapTextureDescriptor,
+    /// The size of a decal chunk in world units.
+    pub(crate) chunk_size: f32,
+    /// The z offset relative to the tilemap's z index.
+    pub z_offset: f32,
+    pub(crate) decals: HashMap<IVec2, Vec<Decal>>,
+    pub(crate) dirty: HashSet<IVec2>,
+    pub(crate) chunks: HashMap<IVec2, (Entity, Handle<Mesh>)>,
+    pub(crate) material: Option<Handle<ColorMaterial>>,
+}
+
+impl TilemapDecals {
+    pub fn new(texture: Handle<Image>, desc: TilemapTextureDescriptor) -> Self {
+        Self::new_with_chunk_size(texture, desc, 256.)
+    }
+
+    pub fn new_with_chunk_size(
+        texture: Handle<Image>,
+        desc: TilemapTextureDescriptor,
+        chunk_size: f32,
+    ) -> Self {
+        Self {
+            texture,
+            desc,
+            chunk_size,
+            z_offset: 0.5,
+            decals: Default::default(),
+            dirty: Default::default(),
+            chunks: Default::default(),
+            material: None,
+        }
+    }
+
+    #[inline]
+    fn chunk_index(&self, position: Vec2) -> IVec2 {
+        (position / self.chunk_size).floor().as_ivec2()
+    }
+
+    /// Add a decal.
+    pub fn add(&mut self, decal: Decal) {
+        let chunk = self.chunk_index(decal.position);
+        self.decals.entry(chunk).or_default().push(decal);
+        self.dirty.insert(chunk);
+    }
+
+    /// Remove all the decals whose center is inside the circle.
+    pub fn remove_in_circle(&mut self, center: Vec2, radius: f32) {
+        let min = self.chunk_index(center - radius);
+        let max = self.chunk_index(center + radius);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let chunk = IVec2::new(x, y);
+                let Some(decals) = self.decals.get_mut(&chunk) else {
+                    continue;
+                };
+                let count = decals.len();
+                decals.retain(|d| d.position.distance_squared(center) > radius * radius);
+                if decals.len() != count {
+                    self.dirty.insert(chunk);
+                }
+            }
+        }
+    }
+
+    /// Remove all the decals.
+    pub fn clear(&mut self) {
+        self.dirty.extend(self.decals.drain().map(|(c, _)| c));
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.decals.values().map(|d| d.len()).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Decal> {
+        self.decals.values().flatten()
+    }
+
+    fn build_mesh(&self, decals: &[Decal]) -> Mesh {
+        let mut positions = Vec::with_capacity(decals.len() * 4);
+        let mut uvs = Vec::with_capacity(decals.len() * 4);
+        let mut colors = Vec::with_capacity(decals.len() * 4);
+        let mut indices = Vec::with_capacity(decals.len() * 6);
+
+        let tile_uv_size = self.desc.tile_size.as_vec2() / self.desc.size.as_vec2();
+        let columns = (self.desc.size.x / self.desc.tile_size.x).max(1);
+
+        for (i, decal) in decals.iter().enumerate() {
+            let rot = Vec2::from_angle(decal.rotation);
+            let half = decal.size / 2.;
+            [
+                Vec2::new(-half.x, -half.y),
+                Vec2::new(half.x, -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ]
+            .into_iter()
+            .for_each(|corner| {
+                positions.push((rot.rotate(corner) + decal.position).extend(0.).to_array())
+            });
+
+            let uv_min = IVec2::new(
+                (decal.texture_index % columns) as i32,
+                (decal.texture_index / columns) as i32,
+            )
+            .as_vec2()
+                * tile_uv_size;
+            let uv_max = uv_min + tile_uv_size;
+            uvs.extend_from_slice(&[
+                [uv_min.x, uv_max.y],
+                [uv_max.x, uv_max.y],
+                [uv_max.x, uv_min.y],
+                [uv_min.x, uv_min.y],
+            ]);
+
+            let color = (decal.color * Vec4::new(1., 1., 1., decal.alpha())).to_array();
+            colors.extend_from_slice(&[color; 4]);
+
+            let v = i as u32 * 4;
+            indices.extend_from_slice(&[v, v + 1, v + 2, v, v + 2, v + 3]);
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+fn chunk_transform(tilemap_transform: &TilemapTransform, z_offset: f32) -> Transform {
+    Transform::from_translation(
+        tilemap_transform
+            .translation
+            .extend(tilemap_transform.z_index as f32 + z_offset),
+    )
+    .with_rotation(tilemap_transform.get_rotation_quat())
+}
+
+pub fn decal_updater(time: Res<Time>, mut decals_query: Query<&mut TilemapDecals>) {
+    let delta = time.delta_seconds();
+
+    decals_query.iter_mut().for_each(|mut decals| {
+        let decals = decals.as_mut();
+        for (chunk, chunk_decals) in decals.decals.iter_mut() {
+            let mut changed = false;
+            chunk_decals
+                .iter_mut()
+                .filter(|d| d.lifetime.is_some())
+                .for_each(|d| {
+                    d.age += delta;
+                    changed |= d.is_fading() || d.is_expired();
+                });
+
+            if changed {
+                chunk_decals.retain(|d| !d.is_expired());
+                decals.dirty.insert(*chunk);
+            }
+        }
+    });
+}
+
+pub fn decal_mesh_builder(
+    mut commands: Commands,
+    mut decals_query: Query<(&mut TilemapDecals, Ref<TilemapTransform>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    decals_query
+        .iter_mut()
+        .for_each(|(mut decals, tilemap_transform)| {
+            let decals = decals.as_mut();
+            let material = decals
+                .material
+                .get_or_insert_with(|| {
+                    materials.add(ColorMaterial {
+                        color: Color::WHITE,
+                        texture: Some(decals.texture.clone()),
+                    })
+                })
+                .clone();
+            let transform = chunk_transform(&tilemap_transform, decals.z_offset);
+
+            if tilemap_transform.is_changed() {
+                decals.chunks.values().for_each(|(entity, _)| {
+                    commands.entity(*entity).insert(transform);
+                });
+            }
+
+            let dirty = std::mem::take(&mut decals.dirty);
+            for chunk in dirty {
+                let chunk_decals = decals.decals.get(&chunk).filter(|d| !d.is_empty());
+
+                let Some(chunk_decals) = chunk_decals else {
+                    decals.decals.remove(&chunk);
+                    if let Some((entity, mesh)) = decals.chunks.remove(&chunk) {
+                        commands.entity(entity).despawn();
+                        meshes.remove(mesh);
+                    }
+                    continue;
+                };
+
+                let mesh = decals.build_mesh(chunk_decals);
+                if let Some((_, handle)) = decals.chunks.get(&chunk) {
+                    meshes.insert(handle.id(), mesh);
+                } else {
+                    let handle = meshes.add(mesh);
+                    let entity = commands
+                        .spawn(MaterialMesh2dBundle {
+                            mesh: Mesh2dHandle(handle.clone()),
+                            material: material.clone(),
+                            transform,
+                            ..Default::default()
+                        })
+                        .id();
+                    decals.chunks.insert(chunk, (entity, handle));
+                }
+            }
+        });
+}
@@ -8,7 +8,7 @@ use bevy::{
     math::IVec2,
 };
 
-use super::{map::TilemapStorage, tile::Tile};
+use super::{decal::TilemapDecals, map::TilemapStorage, tile::Tile};
 
 /// Marks an tilemap/tile/physics_tilemap to be despawned.
 #[derive(Component)]
@@ -64,6 +64,16 @@ pub fn despawn_tiles(mut commands: Commands, query: Query<&Tile, With<DespawnMe>
     commands.spawn_batch(despawned_tiles);
 }
 
+pub fn despawn_decals(commands: ParallelCommands, query: Query<&TilemapDecals, With<DespawnMe>>) {
+    query.par_iter().for_each(|decals| {
+        commands.command_scope(|mut c| {
+            decals.chunks.values().for_each(|(entity, _)| {
+                c.entity(*entity).despawn();
+            });
+        });
+    });
+}
+
 #[cfg(feature = "physics")]
 pub fn despawn_physics_tilemaps(
     commands: ParallelCommands,
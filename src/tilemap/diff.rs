@@ -0,0 +1,197 @@
+use bevy::{
+    ecs::{entity::Entity, world::World},
+    math::{IVec2, Vec2},
+    utils::HashMap,
+};
+
+use super::{
+    map::TilemapStorage,
+    tile::{Tile, TileBuilder},
+};
+
+/// How a tile differs from the source file.
+#[derive(Debug, Clone)]
+pub enum TileDiff {
+    /// The tile is not in the source file.
+    Added { index: IVec2, runtime: TileBuilder },
+    /// The tile in the source file is removed.
+    Removed { index: IVec2, source: TileBuilder },
+    /// The texture or the color of the tile is different.
+    Changed {
+        index: IVec2,
+        source: TileBuilder,
+        runtime: TileBuilder,
+    },
+}
+
+impl TileDiff {
+    #[inline]
+    pub fn index(&self) -> IVec2 {
+        match self {
+            TileDiff::Added { index, .. }
+            | TileDiff::Removed { index, .. }
+            | TileDiff::Changed { index, .. } => *index,
+        }
+    }
+}
+
+/// How an entity or object differs from the source file.
+///
+/// `id` is the iid in LDtk, or the object id in Tiled.
+#[derive(Debug, Clone)]
+pub enum EntityDiff {
+    /// The entity is spawned in the level at runtime.
+    Added { entity: Entity },
+    /// The entity in the source file is despawned.
+    Removed { id: String },
+    /// The entity is moved away from where it is in the source file.
+    Moved {
+        id: String,
+        entity: Entity,
+        source: Vec2,
+        runtime: Vec2,
+    },
+}
+
+/// The difference between a loaded level and the file it was loaded from,
+/// for in-game editors to write back only the changes.
+#[derive(Debug, Clone, Default)]
+pub struct LevelDiff {
+    /// The tile changes of each layer, by layer identifier.
+    /// Layers without changes are left out.
+    pub layers: HashMap<String, Vec<TileDiff>>,
+    pub entities: Vec<EntityDiff>,
+}
+
+impl LevelDiff {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty() && self.entities.is_empty()
+    }
+
+    /// Add the tile changes of a layer, if there are any.
+    pub fn insert_layer(&mut self, identifier: String, tiles: Vec<TileDiff>) {
+        if !tiles.is_empty() {
+            self.layers.insert(identifier, tiles);
+        }
+    }
+}
+
+/// Compare the tiles of the tilemap against the tiles in the source file.
+/// Only the textures and the colors are compared.
+///
+/// Tiles with footprints are compared at their own index. If the tilemap
+/// doesn't exist anymore, all the source tiles are removed.
+/// The result is sorted by index, row by row.
+pub fn diff_tiles(
+    world: &World,
+    tilemap: Entity,
+    source: &HashMap<IVec2, TileBuilder>,
+) -> Vec<TileDiff> {
+    let mut runtime = HashMap::<IVec2, TileBuilder>::new();
+    if let Some(storage) = world.get::<TilemapStorage>(tilemap) {
+        storage
+            .storage
+            .chunked_iter_some()
+            .filter_map(|(chunk_index, in_chunk_index, tile)| {
+                world.get::<Tile>(*tile).filter(|tile| {
+                    tile.chunk_index == chunk_index && tile.in_chunk_index == in_chunk_index
+                })
+            })
+            .for_each(|tile| {
+                runtime.insert(tile.index, tile.clone().into());
+            });
+    }
+
+    let mut diffs = source
+        .iter()
+        .filter_map(|(index, source)| match runtime.remove(index) {
+            None => Some(TileDiff::Removed {
+                index: *index,
+                source: source.clone(),
+            }),
+            Some(runtime) if runtime.texture != source.texture || runtime.color != source.color => {
+                Some(TileDiff::Changed {
+                    index: *index,
+                    source: source.clone(),
+                    runtime,
+                })
+            }
+            Some(_) => None,
+        })
+        .collect::<Vec<_>>();
+    diffs.extend(
+        runtime
+            .into_iter()
+            .map(|(index, runtime)| TileDiff::Added { index, runtime }),
+    );
+    diffs.sort_by_key(|diff| (-diff.index().y, diff.index().x));
+    diffs
+}
+
+/// Whether the entity is moved away from `source` by more than half a pixel.
+#[cfg(any(feature = "ldtk", feature = "tiled"))]
+#[inline]
+pub(crate) fn is_moved(source: Vec2, runtime: Vec2) -> bool {
+    source.distance_squared(runtime) > 0.25
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::world::World,
+        math::{IVec2, Vec4},
+        utils::HashMap,
+    };
+
+    use crate::tilemap::{
+        map::TilemapStorage,
+        tile::{Tile, TileBuilder, TileLayer},
+    };
+
+    use super::{diff_tiles, TileDiff};
+
+    #[test]
+    fn test_diff_tiles() {
+        let mut world = World::new();
+        let tilemap = world.spawn_empty().id();
+        let mut storage = TilemapStorage::new(4, tilemap);
+
+        let builder = |texture_index| {
+            TileBuilder::new().with_layer(0, TileLayer::new().with_texture_index(texture_index))
+        };
+        for (index, builder) in [
+            (IVec2::new(0, 0), builder(0)),
+            (IVec2::new(1, 0), builder(1)),
+            (IVec2::new(5, 2), builder(2).with_color(Vec4::ZERO)),
+        ] {
+            let (chunk_index, in_chunk_index) = storage.storage.transform_index(index);
+            let tile = world
+                .spawn(Tile {
+                    tilemap_id: tilemap,
+                    chunk_index,
+                    in_chunk_index,
+                    index,
+                    texture: builder.texture,
+                    color: builder.color,
+                    pivot: None,
+                    footprint: None,
+                })
+                .id();
+            storage.storage.set_elem(index, tile);
+        }
+        world.entity_mut(tilemap).insert(storage);
+
+        let source = HashMap::from_iter([
+            (IVec2::new(0, 0), builder(0)),
+            (IVec2::new(1, 0), builder(3)),
+            (IVec2::new(2, 0), builder(0)),
+        ]);
+        let diffs = diff_tiles(&world, tilemap, &source);
+
+        assert_eq!(diffs.len(), 3);
+        assert!(matches!(diffs[0], TileDiff::Added { index, .. } if index == IVec2::new(5, 2)));
+        assert!(matches!(diffs[1], TileDiff::Changed { index, .. } if index == IVec2::new(1, 0)));
+        assert!(matches!(diffs[2], TileDiff::Removed { index, .. } if index == IVec2::new(2, 0)));
+    }
+}
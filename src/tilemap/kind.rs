@@ -0,0 +1,229 @@
+use std::hash::Hash;
+
+use bevy::{ecs::system::Resource, math::Vec4, utils::HashMap};
+
+#[cfg(feature = "physics")]
+use super::physics::PhysicsTile;
+use super::tile::{Tile, TileAnimation, TileBuilder, TileLayer, TileTexture};
+
+/// Anything that can be used as a tile kind. Usually a fieldless enum defined by the user.
+///
+/// This is implemented automatically for all the types that satisfy the bounds.
+pub trait TileKind: Clone + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Clone + Eq + Hash + Send + Sync + 'static> TileKind for T {}
+
+/// Describes how a tile kind looks like and behaves.
+#[derive(Debug, Clone)]
+pub struct TileKindDescriptor {
+    pub texture: TileTexture,
+    pub color: Vec4,
+    /// The tag of the `TileAudioEmitter` the tiles of this kind get.
+    pub audio_emitter: Option<String>,
+    #[cfg(feature = "physics")]
+    pub physics: Option<PhysicsTile>,
+}
+
+impl TileKindDescriptor {
+    /// Create a descriptor with a single static layer.
+    pub fn from_index(texture_index: u32) -> Self {
+        Self::from_layers(vec![TileLayer::new().with_texture_index(texture_index)])
+    }
+
+    /// Create a descriptor with multiple static layers.
+    pub fn from_layers(layers: Vec<TileLayer>) -> Self {
+        Self {
+            texture: TileTexture::Static(layers),
+            color: Vec4::ONE,
+            audio_emitter: None,
+            #[cfg(feature = "physics")]
+            physics: None,
+        }
+    }
+
+    /// Create a descriptor with an animation.
+    /// Register the animation using `TilemapAnimations::register` first.
+    pub fn from_animation(animation: TileAnimation) -> Self {
+        Self {
+            texture: TileTexture::Animated(animation),
+            color: Vec4::ONE,
+            audio_emitter: None,
+            #[cfg(feature = "physics")]
+            physics: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Vec4) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_audio_emitter(mut self, tag: impl Into<String>) -> Self {
+        self.audio_emitter = Some(tag.into());
+        self
+    }
+
+    #[cfg(feature = "physics")]
+    pub fn with_physics(mut self, physics: PhysicsTile) -> Self {
+        self.physics = Some(physics);
+        self
+    }
+}
+
+/// An optional registry that maps your own tile kinds to their textures, animations
+/// and physical properties, so you don't need to hard code texture indices everywhere.
+///
+/// Insert it as a resource and use `TileBuilder::from_kind` to build tiles.
+///
+/// **Notice**: Reverse lookup only compares the texture of the tile, so two kinds
+/// that share the same texture, like the same art with different physics, can't be
+/// told apart. `kind_of` returns the one registered first, see `kinds_of_texture`.
+#[derive(Resource, Debug, Clone)]
+pub struct TileKindRegistry<K: TileKind> {
+    kinds: HashMap<K, TileKindDescriptor>,
+    /// texture to the kinds using it, in the order they are registered
+    reverse: HashMap<TileTexture, Vec<K>>,
+}
+
+impl<K: TileKind> Default for TileKindRegistry<K> {
+    fn default() -> Self {
+        Self {
+            kinds: Default::default(),
+            reverse: Default::default(),
+        }
+    }
+}
+
+impl<K: TileKind> TileKindRegistry<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a tile kind. Registering the same kind again will overwrite the previous one.
+    pub fn register(&mut self, kind: K, descriptor: TileKindDescriptor) -> &mut Self {
+        self.unregister(&kind);
+        self.reverse
+            .entry(descriptor.texture.clone())
+            .or_default()
+            .push(kind.clone());
+        self.kinds.insert(kind, descriptor);
+        self
+    }
+
+    pub fn with_kind(mut self, kind: K, descriptor: TileKindDescriptor) -> Self {
+        self.register(kind, descriptor);
+        self
+    }
+
+    pub fn unregister(&mut self, kind: &K) -> Option<TileKindDescriptor> {
+        let descriptor = self.kinds.remove(kind)?;
+        if let Some(kinds) = self.reverse.get_mut(&descriptor.texture) {
+            kinds.retain(|k| k != kind);
+            if kinds.is_empty() {
+                self.reverse.remove(&descriptor.texture);
+            }
+        }
+        Some(descriptor)
+    }
+
+    #[inline]
+    pub fn get(&self, kind: &K) -> Option<&TileKindDescriptor> {
+        self.kinds.get(kind)
+    }
+
+    #[inline]
+    pub fn contains(&self, kind: &K) -> bool {
+        self.kinds.contains_key(kind)
+    }
+
+    /// Get the tile builder of the kind.
+    pub fn builder(&self, kind: &K) -> Option<TileBuilder> {
+        self.kinds.get(kind).map(|desc| TileBuilder {
+            texture: desc.texture.clone(),
+            color: desc.color,
+            pivot: None,
+            footprint: None,
+            audio_emitter: desc.audio_emitter.clone(),
+        })
+    }
+
+    #[cfg(feature = "physics")]
+    pub fn physics(&self, kind: &K) -> Option<&PhysicsTile> {
+        self.kinds.get(kind).and_then(|desc| desc.physics.as_ref())
+    }
+
+    /// Find out which kind the texture belongs to.
+    /// Returns the one registered first if multiple kinds share the texture.
+    #[inline]
+    pub fn kind_of_texture(&self, texture: &TileTexture) -> Option<&K> {
+        self.kinds_of_texture(texture).first()
+    }
+
+    /// Get all the kinds that use the texture, in the order they are registered.
+    #[inline]
+    pub fn kinds_of_texture(&self, texture: &TileTexture) -> &[K] {
+        self.reverse
+            .get(texture)
+            .map_or(&[], |kinds| kinds.as_slice())
+    }
+
+    /// Find out which kind the tile belongs to.
+    #[inline]
+    pub fn kind_of(&self, tile: &Tile) -> Option<&K> {
+        self.kind_of_texture(&tile.texture)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &TileKindDescriptor)> {
+        self.kinds.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Kind {
+        Grass,
+        Water,
+        DeepWater,
+    }
+
+    #[test]
+    fn test_kind_registry() {
+        let mut registry = TileKindRegistry::new()
+            .with_kind(Kind::Grass, TileKindDescriptor::from_index(0))
+            .with_kind(Kind::Water, TileKindDescriptor::from_index(3));
+
+        let water = registry.builder(&Kind::Water).unwrap();
+        assert_eq!(registry.kind_of_texture(&water.texture), Some(&Kind::Water));
+
+        registry.register(Kind::Water, TileKindDescriptor::from_index(4));
+        assert_eq!(registry.kind_of_texture(&water.texture), None);
+        assert!(registry.builder(&Kind::Grass).is_some());
+    }
+
+    #[test]
+    fn test_shared_texture() {
+        let mut registry = TileKindRegistry::new()
+            .with_kind(Kind::Water, TileKindDescriptor::from_index(3))
+            .with_kind(Kind::DeepWater, TileKindDescriptor::from_index(3));
+
+        let texture = registry.builder(&Kind::Water).unwrap().texture;
+        assert_eq!(registry.kind_of_texture(&texture), Some(&Kind::Water));
+        assert_eq!(
+            registry.kinds_of_texture(&texture),
+            &[Kind::Water, Kind::DeepWater]
+        );
+
+        registry.unregister(&Kind::DeepWater);
+        assert_eq!(registry.kind_of_texture(&texture), Some(&Kind::Water));
+
+        registry.register(Kind::DeepWater, TileKindDescriptor::from_index(3));
+        registry.register(Kind::DeepWater, TileKindDescriptor::from_index(4));
+        assert_eq!(registry.kinds_of_texture(&texture), &[Kind::Water]);
+
+        registry.unregister(&Kind::Water);
+        assert_eq!(registry.kind_of_texture(&texture), None);
+    }
+}
@@ -1,14 +1,26 @@
-use std::{f32::consts::SQRT_2, fmt::Debug};
+use std::{
+    f32::consts::{FRAC_PI_2, SQRT_2},
+    fmt::Debug,
+};
 
 use bevy::{
     asset::Handle,
-    ecs::{component::Component, query::Changed, system::Query},
-    math::{Mat2, Quat, Vec4},
+    ecs::{
+        change_detection::DetectChangesMut,
+        component::Component,
+        entity::EntityHashMap,
+        event::Event,
+        query::{Changed, With, Without},
+        system::{Query, ResMut, Resource},
+        world::World,
+    },
+    log::warn,
+    math::{EulerRot, Mat2, Quat, Rect, Vec4},
     prelude::{Commands, Entity, IVec2, Image, UVec2, Vec2},
     reflect::Reflect,
     render::render_resource::FilterMode,
-    sprite::TextureAtlasLayout,
-    transform::components::Transform,
+    sprite::{TextureAtlas, TextureAtlasLayout},
+    transform::components::{GlobalTransform, Transform},
     utils::{HashMap, HashSet},
 };
 
@@ -19,10 +31,14 @@ use crate::math::{
 use crate::tilemap::tile::RawTileAnimation;
 
 use super::{
+    audio::TileAudioEmitter,
     buffers::TileBuilderBuffer,
-    chunking::storage::{ChunkedStorage, EntityChunkedStorage},
+    chunking::storage::{ChunkLayout, ChunkedStorage, EntityChunkedStorage, StorageChunk},
     despawn::DespawnMe,
-    tile::{TileAnimation, TileBuilder, TileUpdater},
+    tile::{
+        Tile, TileAnimation, TileAnimationMode, TileAnimationSwitcher, TileBuilder, TileTexture,
+        TileTextureReplacer, TileUpdater,
+    },
 };
 
 /// Defines the shape of tiles in a tilemap.
@@ -37,7 +53,7 @@ pub enum TilemapType {
 }
 
 /// Actually four directions.
-#[derive(Debug, Clone, Copy, Default, Reflect)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub enum TilemapRotation {
     #[default]
@@ -188,6 +204,7 @@ pub struct TilemapTexture {
     pub(crate) texture: Handle<Image>,
     pub(crate) desc: TilemapTextureDescriptor,
     pub(crate) rotation: TilemapRotation,
+    pub(crate) swapped: bool,
 }
 
 impl TilemapTexture {
@@ -200,9 +217,33 @@ impl TilemapTexture {
             texture,
             desc,
             rotation,
+            swapped: false,
         }
     }
 
+    /// Replace the image and the descriptor of this texture at runtime,
+    /// without despawning the tilemap. Returns the old ones.
+    ///
+    /// Tiles and animations are kept, but you will be warned if some of them
+    /// are using texture indices that are out of the range of the new texture.
+    pub fn swap(
+        &mut self,
+        texture: Handle<Image>,
+        desc: TilemapTextureDescriptor,
+    ) -> (Handle<Image>, TilemapTextureDescriptor) {
+        self.swapped = true;
+        (
+            std::mem::replace(&mut self.texture, texture),
+            std::mem::replace(&mut self.desc, desc),
+        )
+    }
+
+    /// Get the count of tiles in this texture.
+    pub fn tile_count(&self) -> u32 {
+        let count = self.desc.size / self.desc.tile_size;
+        count.x * count.y
+    }
+
     pub fn clone_weak(&self) -> Handle<Image> {
         self.texture.clone_weak()
     }
@@ -215,24 +256,68 @@ impl TilemapTexture {
         &self.texture
     }
 
+    /// Get the layout of the tiles, so the texture can be used as a `TextureAtlas`.
     pub fn as_atlas_layout(&self) -> TextureAtlasLayout {
+        let tile_count = self.desc.size / self.desc.tile_size;
         TextureAtlasLayout::from_grid(
             self.desc.tile_size.as_vec2(),
-            self.desc.size.x as usize,
-            self.desc.size.y as usize,
+            tile_count.x as usize,
+            tile_count.y as usize,
             Some(Vec2::ZERO),
             Some(Vec2::ZERO),
         )
     }
 
+    /// Get the `TextureAtlas` to display a tile outside the tilemap, like in the UI.
+    ///
+    /// `layout` should be the handle of `as_atlas_layout`.
+    ///
+    /// ```ignore
+    /// let layout = atlas_layouts.add(texture.as_atlas_layout());
+    /// commands.spawn(AtlasImageBundle {
+    ///     image: UiImage::new(texture.handle().clone()),
+    ///     texture_atlas: texture.texture_atlas(layout, 42),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn texture_atlas(&self, layout: Handle<TextureAtlasLayout>, index: u32) -> TextureAtlas {
+        TextureAtlas {
+            layout,
+            index: index as usize,
+        }
+    }
+
+    /// Get the rect of a tile in pixels.
+    pub fn pixel_rect(&self, index: u32) -> Rect {
+        let tile_count = self.desc.size / self.desc.tile_size;
+        let min = UVec2::new(index % tile_count.x, index / tile_count.x) * self.desc.tile_size;
+        Rect::from_corners(min.as_vec2(), (min + self.desc.tile_size).as_vec2())
+    }
+
+    /// Get the rect of a tile in uv coordinates.
+    ///
+    /// Use this to draw the tile with UI libraries that take uvs, for example `egui`:
+    ///
+    /// ```ignore
+    /// let uv = texture.uv_rect(42);
+    /// ui.add(egui::Image::new(egui::load::SizedTexture::new(texture_id, [32., 32.]))
+    ///     .uv(egui::Rect::from_min_max(uv.min.to_array().into(), uv.max.to_array().into())));
+    /// ```
+    pub fn uv_rect(&self, index: u32) -> Rect {
+        let rect = self.pixel_rect(index);
+        let size = self.desc.size.as_vec2();
+        Rect {
+            min: rect.min / size,
+            max: rect.max / size,
+        }
+    }
+
     /// Get the atlas rect  of a tile in uv coordinates.
     pub fn get_atlas_rect(&self, index: u32) -> Aabb2d {
-        let tile_count = self.desc.size / self.desc.tile_size;
-        let tile_index = Vec2::new((index % tile_count.x) as f32, (index / tile_count.x) as f32);
-        let tile_size = self.desc.tile_size.as_vec2() / self.desc.size.as_vec2();
+        let rect = self.uv_rect(index);
         Aabb2d {
-            min: tile_index * tile_size,
-            max: (tile_index + Vec2::ONE) * tile_size,
+            min: rect.min,
+            max: rect.max,
         }
     }
 }
@@ -328,6 +413,96 @@ impl Default for TilemapLayerOpacities {
     }
 }
 
+/// Add this to a tilemap to make newly loaded render chunks fade in
+/// instead of popping up. The value is the duration in seconds.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub struct TilemapChunkFadeIn(pub f32);
+
+impl Default for TilemapChunkFadeIn {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+/// Add this to a tilemap to apply a deterministic per-tile brightness/hue jitter,
+/// making large areas of the same tile look less repetitive.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub struct TilemapTintJitter {
+    /// How strong the jitter is. Usually between 0 and 1.
+    pub strength: f32,
+    /// Tilemaps with the same seed will have the same jitter pattern.
+    pub seed: u32,
+}
+
+impl Default for TilemapTintJitter {
+    fn default() -> Self {
+        Self {
+            strength: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Turn off rendering features a tilemap doesn't need, so it gets a cheaper
+    /// shader variant. Tilemaps without this component pay for all of them.
+    #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct TilemapRenderFlags: u32 {
+        const NONE         = 0b00;
+        /// Drop the per tile color from the vertices. Tiles are drawn in white.
+        const NO_COLOR     = 0b01;
+        /// Skip the animation lookups. Animated tiles won't be drawn.
+        const NO_ANIMATION = 0b10;
+    }
+}
+
+impl Default for TilemapRenderFlags {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// How the tilemap is blended with what's behind it.
+///
+/// Bevy doesn't have an opaque phase for 2d cameras, so the tilemaps are always
+/// drawn in the transparent phase and sorted by their z index.
+/// The other modes skip the blending, which saves fill rate on large tilemaps.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub enum TilemapAlphaMode {
+    #[default]
+    Blend,
+    /// Transparent pixels will overwrite what's behind them,
+    /// so this is only for tilemaps that are fully opaque.
+    Opaque,
+    /// Pixels with alpha below the cutoff are discarded and the others are drawn opaque.
+    Mask(f32),
+}
+
+/// Add this to a tilemap to also draw it for 3d cameras, writing depth,
+/// so 3d meshes in a 2.5d scene can intersect the tiles correctly.
+///
+/// The tiles lie on the xy plane at `z`. Tilemaps are drawn in the opaque phase of
+/// 3d cameras, so use `TilemapAlphaMode::Mask` if the tiles have transparent pixels.
+/// Chunks are culled using `OrthographicProjection`s, disable `FrustumCulling`
+/// if you are using perspective cameras.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub struct TilemapDepthWrite {
+    pub z: f32,
+}
+
+/// Add this to a tilemap to derive the `TilemapTransform` from the `GlobalTransform`
+/// every frame. So the tilemap can be parented under other spatial entities.
+///
+/// **Notice**: Rotations will be snapped to the nearest multiple of 90 degrees
+/// and the z translation will be rounded to get the z index.
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+#[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
+pub struct TilemapTransformSync;
+
 /// The tilemap's aabb.
 #[derive(Component, Default, Debug, Clone, Copy, Reflect)]
 pub struct TilemapAabbs {
@@ -348,6 +523,9 @@ impl TilemapAabbs {
 }
 
 /// The tilemap's storage. It stores all the tiles in entity form.
+///
+/// This is the only tile storage in the crate, tiles are grouped into chunks.
+/// There is no other tilemap storage to migrate from.
 #[derive(Component, Debug, Clone, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct TilemapStorage {
@@ -355,6 +533,20 @@ pub struct TilemapStorage {
     pub(crate) storage: EntityChunkedStorage,
     pub reserved: HashMap<IVec2, Aabb2d>,
     pub calc_queue: HashSet<IVec2>,
+    /// The cells covered by tiles that have a footprint.
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub(crate) footprints: HashMap<Entity, IAabb2d>,
+}
+
+/// Sent when the tiles of a `fill_rect`, `fill_rect_custom` or `fill_with_buffer`
+/// call are inserted into the world.
+///
+/// The tiles are rendered in the same frame this is sent, so you can wait for this
+/// before taking screenshots, building the colliders or unveiling the camera.
+#[derive(Event, Debug, Clone, Copy, Reflect)]
+pub struct TilemapFilled {
+    pub tilemap: Entity,
+    pub tile_count: usize,
 }
 
 impl TilemapStorage {
@@ -365,6 +557,15 @@ impl TilemapStorage {
             ..Default::default()
         }
     }
+
+    /// Use `layout` for the chunks of the storage.
+    ///
+    /// `ChunkLayout::Sparse` or `ChunkLayout::Auto` saves memory for scattered tiles,
+    /// like a decorations layer.
+    pub fn with_chunk_layout(mut self, layout: ChunkLayout) -> Self {
+        self.storage = self.storage.with_layout(layout);
+        self
+    }
 }
 
 impl Default for TilemapStorage {
@@ -374,20 +575,29 @@ impl Default for TilemapStorage {
             storage: Default::default(),
             reserved: Default::default(),
             calc_queue: Default::default(),
+            footprints: Default::default(),
         }
     }
 }
 
 impl TilemapStorage {
     /// Get a tile.
+    ///
+    /// Returns the tile that covers `index` if the tile has a footprint.
     #[inline]
     pub fn get(&self, index: IVec2) -> Option<Entity> {
         self.storage.get_elem(index).cloned()
     }
 
+    /// Get the cells covered by the tile. Returns `None` if the tile only covers one cell.
+    #[inline]
+    pub fn get_footprint(&self, tile: Entity) -> Option<IAabb2d> {
+        self.footprints.get(&tile).copied()
+    }
+
     /// Get a chunk.
     #[inline]
-    pub fn get_chunk(&self, index: IVec2) -> Option<&Vec<Option<Entity>>> {
+    pub fn get_chunk(&self, index: IVec2) -> Option<&StorageChunk<Entity>> {
         self.storage.chunks.get(&index)
     }
 
@@ -395,7 +605,7 @@ impl TilemapStorage {
     ///
     /// **Notice**: This is not recommended as we may do something extra when you remove/set tiles.
     #[inline]
-    pub fn get_chunk_mut(&mut self, index: IVec2) -> Option<&mut Vec<Option<Entity>>> {
+    pub fn get_chunk_mut(&mut self, index: IVec2) -> Option<&mut StorageChunk<Entity>> {
         self.storage.chunks.get_mut(&index)
     }
 
@@ -403,15 +613,73 @@ impl TilemapStorage {
     ///
     /// Overwrites the tile if it already exists.
     pub fn set(&mut self, commands: &mut Commands, index: IVec2, tile_builder: TileBuilder) {
-        if let Some(previous) = self.storage.get_elem(index) {
-            commands.entity(*previous).despawn();
-        }
+        let footprint = IAabb2d {
+            min: index,
+            max: index + tile_builder.footprint.unwrap_or(UVec2::ONE).as_ivec2() - 1,
+        };
+
+        footprint.into_iter().for_each(|cell| {
+            if let Some(previous) = self.get(cell) {
+                if self.release_footprint(previous) {
+                    // Also remove the rendered tile, as it may be at another index.
+                    commands.entity(previous).insert(DespawnMe);
+                } else {
+                    commands.entity(previous).despawn();
+                }
+            }
+        });
         let new_tile = tile_builder.build_component(index, &self, self.tilemap);
 
         let mut tile_entity = commands.spawn_empty();
-        self.storage.set_elem(index, tile_entity.id());
-        self.reserve(new_tile.chunk_index);
+        let entity = tile_entity.id();
+        footprint.into_iter().for_each(|cell| {
+            let (chunk_index, in_chunk_index) = self.storage.transform_index(cell);
+            self.storage
+                .set_elem_precise(chunk_index, in_chunk_index, entity);
+            self.reserve(chunk_index);
+        });
+        if footprint.size() != IVec2::ONE {
+            self.footprints.insert(entity, footprint);
+        }
         tile_entity.insert(new_tile);
+        if let Some(tag) = tile_builder.audio_emitter {
+            tile_entity.insert(TileAudioEmitter(tag));
+        }
+    }
+
+    /// Clear the cells covered by the tile. Returns false if the tile doesn't have a footprint.
+    fn release_footprint(&mut self, tile: Entity) -> bool {
+        let Some(footprint) = self.footprints.remove(&tile) else {
+            return false;
+        };
+
+        footprint.into_iter().for_each(|cell| {
+            if self.storage.get_elem(cell) == Some(&tile) {
+                self.storage.remove_elem(cell);
+            }
+        });
+        true
+    }
+
+    /// Get the entity at the index or spawn a new one for the tile.
+    ///
+    /// The `TileAudioEmitter` of a reused entity is removed if the new tile doesn't have one.
+    fn get_or_spawn(
+        &mut self,
+        commands: &mut Commands,
+        index: IVec2,
+        tile_builder: &TileBuilder,
+    ) -> Entity {
+        if let Some(e) = self.get(index) {
+            if tile_builder.audio_emitter.is_none() {
+                commands.entity(e).remove::<TileAudioEmitter>();
+            }
+            e
+        } else {
+            let e = commands.spawn_empty().id();
+            self.set_entity(index, Some(e));
+            e
+        }
     }
 
     #[inline]
@@ -429,7 +697,7 @@ impl TilemapStorage {
     #[inline]
     #[allow(dead_code)]
     pub(crate) fn set_chunk_entity(&mut self, index: IVec2, chunk: Vec<Option<Entity>>) {
-        self.storage.chunks.insert(index, chunk);
+        self.storage.set_chunk(index, chunk);
         self.reserve(index);
     }
 
@@ -441,12 +709,32 @@ impl TilemapStorage {
         }
     }
 
+    /// Switch the tile to the animation clip named `clip` in the `TilemapAnimationClips`
+    /// of this tilemap. Like opening a door or a chest.
+    ///
+    /// The clip is played from the frame the tile switches to it.
+    #[inline]
+    pub fn set_tile_animation(
+        &mut self,
+        commands: &mut Commands,
+        index: IVec2,
+        clip: impl Into<String>,
+    ) {
+        if let Some(entity) = self.get(index) {
+            commands
+                .entity(entity)
+                .insert(TileAnimationSwitcher(clip.into()));
+        }
+    }
+
     /// Remove a tile.
     #[inline]
     pub fn remove(&mut self, commands: &mut Commands, index: IVec2) {
         if let Some(entity) = self.get(index) {
             commands.entity(entity).insert(DespawnMe);
-            self.set_entity(index, None);
+            if !self.release_footprint(entity) {
+                self.set_entity(index, None);
+            }
         }
     }
 
@@ -454,7 +742,8 @@ impl TilemapStorage {
     #[inline]
     pub fn remove_chunk(&mut self, commands: &mut Commands, index: IVec2) {
         if let Some(chunk) = self.storage.remove_chunk(index) {
-            chunk.into_iter().filter_map(|e| e).for_each(|e| {
+            chunk.into_iter_some().for_each(|(_, e)| {
+                self.release_footprint(e);
                 commands.entity(e).insert(DespawnMe);
             });
         }
@@ -465,10 +754,28 @@ impl TilemapStorage {
         self.storage
             .chunks
             .drain()
-            .flat_map(|(_, chunk)| chunk.into_iter().filter_map(|e| e))
+            .flat_map(|(_, chunk)| chunk.into_iter_some().map(|(_, e)| e))
             .for_each(|entity| {
                 commands.entity(entity).insert(DespawnMe);
             });
+        self.footprints.clear();
+    }
+
+    /// Remove all the tiles inside the region in one go.
+    ///
+    /// Use the `DespawnTilemapRegion` command to clear the colliders and path tiles
+    /// in the same region as well.
+    pub fn despawn_region(&mut self, commands: &mut Commands, region: IAabb2d) {
+        let removed = self.storage.remove_region(region.justified());
+        removed.iter().for_each(|entity| {
+            self.release_footprint(*entity);
+        });
+        commands.insert_or_spawn_batch(
+            removed
+                .into_iter()
+                .map(|entity| (entity, DespawnMe))
+                .collect::<Vec<_>>(),
+        );
     }
 
     /// Declare that a chunk is existent.
@@ -535,16 +842,21 @@ impl TilemapStorage {
             for x in area.origin.x..=area.dest.x {
                 let index = IVec2 { x, y };
                 let tile = tile_builder.build_component(index, &self, self.tilemap);
-                let entity = self.get(index).unwrap_or_else(|| {
-                    let e = commands.spawn_empty().id();
-                    self.set_entity(index, Some(e));
-                    e
-                });
+                let entity = self.get_or_spawn(commands, index, &tile_builder);
                 tile_batch.push((entity, tile));
             }
         }
 
+        if let Some(tag) = &tile_builder.audio_emitter {
+            let emitters = tile_batch
+                .iter()
+                .map(|(e, _)| (*e, TileAudioEmitter(tag.clone())))
+                .collect::<Vec<_>>();
+            commands.insert_or_spawn_batch(emitters);
+        }
+        let tile_count = tile_batch.len();
         commands.insert_or_spawn_batch(tile_batch);
+        self.notify_filled(commands, tile_count);
     }
 
     /// Fill a rectangle area with tiles returned by `tile_builder`.
@@ -558,6 +870,7 @@ impl TilemapStorage {
         relative_index: bool,
     ) {
         let mut tile_batch = Vec::with_capacity(area.size());
+        let mut emitter_batch = Vec::new();
 
         for y in area.origin.y..=area.dest.y {
             for x in area.origin.x..=area.dest.x {
@@ -573,16 +886,18 @@ impl TilemapStorage {
                 };
 
                 let tile = builder.build_component(index, &self, self.tilemap);
-                let entity = self.get(index).unwrap_or_else(|| {
-                    let e = commands.spawn_empty().id();
-                    self.set_entity(index, Some(e));
-                    e
-                });
+                let entity = self.get_or_spawn(commands, index, &builder);
+                if let Some(tag) = builder.audio_emitter {
+                    emitter_batch.push((entity, TileAudioEmitter(tag)));
+                }
                 tile_batch.push((entity, tile));
             }
         }
 
+        commands.insert_or_spawn_batch(emitter_batch);
+        let tile_count = tile_batch.len();
         commands.insert_or_spawn_batch(tile_batch);
+        self.notify_filled(commands, tile_count);
     }
 
     /// Fill a rectangle area with tiles from a buffer. This can be faster than setting them one by one.
@@ -592,23 +907,35 @@ impl TilemapStorage {
         origin: IVec2,
         buffer: TileBuilderBuffer,
     ) {
+        let mut emitter_batch = Vec::new();
         let batch = buffer
             .tiles
             .into_iter()
             .map(|(i, b)| {
                 let tile = b.build_component(i + origin, &self, self.tilemap);
-
-                if let Some(e) = self.get(tile.index) {
-                    (e, tile)
-                } else {
-                    let e = commands.spawn_empty().id();
-                    self.set_entity(tile.index, Some(e));
-                    (e, tile)
+                let e = self.get_or_spawn(commands, tile.index, &b);
+                if let Some(tag) = b.audio_emitter {
+                    emitter_batch.push((e, TileAudioEmitter(tag)));
                 }
+                (e, tile)
             })
             .collect::<Vec<_>>();
 
+        commands.insert_or_spawn_batch(emitter_batch);
+        let tile_count = batch.len();
         commands.insert_or_spawn_batch(batch);
+        self.notify_filled(commands, tile_count);
+    }
+
+    /// Send `TilemapFilled` after the commands queued so far are applied.
+    fn notify_filled(&self, commands: &mut Commands, tile_count: usize) {
+        let tilemap = self.tilemap;
+        commands.add(move |world: &mut World| {
+            world.send_event(TilemapFilled {
+                tilemap,
+                tile_count,
+            });
+        });
     }
 
     /// Simlar to `TilemapStorage::fill_rect()`.
@@ -653,11 +980,141 @@ impl TilemapStorage {
 
         commands.insert_or_spawn_batch(batch);
     }
+
+    /// Replace the texture index `old_index` with `new_index` in all the tiles inside `region`,
+    /// or in the whole tilemap if `region` is `None`. Useful for world events like
+    /// grass turning into snow, or fixing up maps after reorganizing the atlas.
+    ///
+    /// **Notice**: Animated tiles are not affected, as their frames are shared.
+    /// Use `TilemapAnimations` to change them instead.
+    pub fn replace_texture(
+        &self,
+        commands: &mut Commands,
+        old_index: u32,
+        new_index: u32,
+        region: Option<IAabb2d>,
+    ) {
+        let replacer = TileTextureReplacer {
+            old_index,
+            new_index,
+        };
+        let batch = match region {
+            Some(region) => self
+                .storage
+                .get_region(region.justified())
+                .into_iter()
+                .map(|entity| (*entity, replacer))
+                .collect::<Vec<_>>(),
+            None => self
+                .storage
+                .iter_some()
+                .map(|entity| (*entity, replacer))
+                .collect::<Vec<_>>(),
+        };
+
+        commands.insert_or_spawn_batch(batch);
+    }
+
+    /// Summarize the main world memory usage of this tilemap.
+    ///
+    /// **Notice**: The sizes are estimated and don't include the heap allocations inside tiles,
+    /// or the chunk meshes on the GPU. See `TilemapRenderChunk::gpu_memory` for those.
+    pub fn memory_report(&self, animations: Option<&TilemapAnimations>) -> TilemapMemoryReport {
+        let mut report = TilemapMemoryReport {
+            chunk_size: self.storage.chunk_size,
+            animation_bytes: animations
+                .map(|a| a.0.capacity() * std::mem::size_of::<i32>())
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+
+        self.storage.chunks.iter().for_each(|(index, chunk)| {
+            let count = chunk.len();
+            report.chunks.insert(*index, count);
+            report.tile_count += count;
+            report.storage_bytes +=
+                chunk.heap_bytes() + std::mem::size_of::<(IVec2, StorageChunk<Entity>)>();
+        });
+        report.tile_bytes = report.tile_count * std::mem::size_of::<Tile>();
+
+        report
+    }
+}
+
+/// The main world memory usage of a tilemap. See `TilemapStorage::memory_report`.
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct TilemapMemoryReport {
+    pub chunk_size: u32,
+    /// Tile count of each chunk.
+    pub chunks: HashMap<IVec2, usize>,
+    pub tile_count: usize,
+    /// Bytes of the chunked storage, including the empty slots of dense chunks.
+    pub storage_bytes: usize,
+    /// Bytes of the `Tile` components.
+    pub tile_bytes: usize,
+    /// Bytes of the `TilemapAnimations`.
+    pub animation_bytes: usize,
+}
+
+impl TilemapMemoryReport {
+    /// Bytes of the storage, tiles and animations. The chunk meshes are not included.
+    #[inline]
+    pub fn main_world_bytes(&self) -> usize {
+        self.storage_bytes + self.tile_bytes + self.animation_bytes
+    }
+
+    /// Get the chunk with the most tiles.
+    pub fn largest_chunk(&self) -> Option<(IVec2, usize)> {
+        self.chunks
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(index, count)| (*index, *count))
+    }
+}
+
+/// Insert this resource to get the main world memory reports of all the tilemaps.
+/// It will be updated every frame, so remove it when you don't need it.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct TilemapMemoryReports {
+    pub reports: EntityHashMap<TilemapMemoryReport>,
+}
+
+impl TilemapMemoryReports {
+    #[inline]
+    pub fn get(&self, tilemap: Entity) -> Option<&TilemapMemoryReport> {
+        self.reports.get(&tilemap)
+    }
+
+    #[inline]
+    pub fn main_world_bytes(&self) -> usize {
+        self.reports.values().map(|r| r.main_world_bytes()).sum()
+    }
+
+    #[inline]
+    pub fn tile_count(&self) -> usize {
+        self.reports.values().map(|r| r.tile_count).sum()
+    }
+
+    /// Get the tilemap that uses the most main world memory.
+    pub fn largest_tilemap(&self) -> Option<(Entity, &TilemapMemoryReport)> {
+        self.reports
+            .iter()
+            .max_by_key(|(_, r)| r.main_world_bytes())
+            .map(|(e, r)| (*e, r))
+    }
 }
 
 /// The tilemap's animation buffer.
 ///
-/// Its format is `[fps, seq_elem_1, ..., seq_elem_n, fps, seq_elem_1, ..., seq_elem_n, ...]`.
+/// Its format is `[header, seq_elem_1, ..., seq_elem_n, header, seq_elem_1, ...]`,
+/// where `header` is `fps | flags << 16`.
+///
+/// `flags` has the bit `1` set for animations that play once, and the bit `2` set for
+/// animations with per-frame durations. The cumulative end time of each frame in milliseconds
+/// then follows the sequence, as `[..., seq_elem_n, frame_end_1, ..., frame_end_n, ...]`.
+///
+/// Buffers saved before the flags were added are `[fps, seq_elem_1, ...]`, which is the same
+/// format with no flags set, so they are still loaded as looping animations.
 #[derive(Component, Default, Debug, Clone, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct TilemapAnimations(pub(crate) Vec<i32>);
@@ -665,20 +1122,94 @@ pub struct TilemapAnimations(pub(crate) Vec<i32>);
 impl TilemapAnimations {
     /// Register a tile animation so you can use it in `TileBuilder::with_animation`.
     pub fn register(&mut self, anim: RawTileAnimation) -> TileAnimation {
-        self.0.push(anim.fps as i32);
+        assert!(
+            anim.durations.is_empty() || anim.durations.len() == anim.sequence.len(),
+            "The animation has {} frames but {} durations!",
+            anim.sequence.len(),
+            anim.durations.len()
+        );
+        assert!(
+            anim.fps <= u16::MAX as u32,
+            "The animation has {} fps, which is more than {}!",
+            anim.fps,
+            u16::MAX
+        );
+
+        let mut sequence = anim.sequence;
+        let mut durations = anim.durations;
+        // Unroll ping-pong animations into loops, so the shader only needs to handle loops.
+        if anim.mode == TileAnimationMode::PingPong && sequence.len() > 2 {
+            let back = sequence.len() - 1;
+            sequence.extend(sequence[1..back].iter().rev().copied().collect::<Vec<_>>());
+            if !durations.is_empty() {
+                durations.extend(durations[1..back].iter().rev().copied().collect::<Vec<_>>());
+            }
+        }
+
+        let mut flags = 0;
+        if anim.mode == TileAnimationMode::Once {
+            flags |= 1;
+        }
+        if !durations.is_empty() {
+            flags |= 2;
+        }
+
+        self.0.push(anim.fps as i32 | flags << 16);
         let start = self.0.len() as u32;
-        let length = anim.sequence.len() as u32;
-        self.0.extend(anim.sequence.into_iter().map(|i| i as i32));
+        let length = sequence.len() as u32;
+        self.0.extend(sequence.into_iter().map(|i| i as i32));
+        self.0
+            .extend(durations.into_iter().scan(0, |end, duration| {
+                *end += duration as i32;
+                Some(*end)
+            }));
         TileAnimation {
             start,
             length,
             fps: anim.fps,
         }
     }
+
+    /// Append all the animations in `other` to this buffer.
+    /// Returns the offset to add to the starts of the `TileAnimation`s from `other`.
+    pub fn merge(&mut self, other: &TilemapAnimations) -> u32 {
+        let offset = self.0.len() as u32;
+        self.0.extend_from_slice(&other.0);
+        offset
+    }
+}
+
+/// The named animation clips of a tilemap, so tiles can switch between them at runtime
+/// using `TilemapStorage::set_tile_animation`.
+#[derive(Component, Default, Debug, Clone, Reflect)]
+pub struct TilemapAnimationClips {
+    pub clips: HashMap<String, TileAnimation>,
+}
+
+impl TilemapAnimationClips {
+    /// Register the animation in `animations` and name it `name`.
+    pub fn register(
+        &mut self,
+        animations: &mut TilemapAnimations,
+        name: impl Into<String>,
+        anim: RawTileAnimation,
+    ) -> TileAnimation {
+        let animation = animations.register(anim);
+        self.clips.insert(name.into(), animation);
+        animation
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<TileAnimation> {
+        self.clips.get(name).copied()
+    }
 }
 
 pub fn transform_syncer(
-    mut tilemap_query: Query<(&TilemapTransform, &mut Transform), Changed<TilemapTransform>>,
+    mut tilemap_query: Query<
+        (&TilemapTransform, &mut Transform),
+        (Changed<TilemapTransform>, Without<TilemapTransformSync>),
+    >,
 ) {
     tilemap_query
         .iter_mut()
@@ -690,6 +1221,100 @@ pub fn transform_syncer(
         });
 }
 
+pub fn global_transform_syncer(
+    mut tilemap_query: Query<
+        (&GlobalTransform, &mut TilemapTransform),
+        (With<TilemapTransformSync>, Changed<GlobalTransform>),
+    >,
+) {
+    tilemap_query
+        .iter_mut()
+        .for_each(|(global_transform, mut tilemap_transform)| {
+            let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+            let angle = rotation.to_euler(EulerRot::XYZ).2;
+            let synced = TilemapTransform {
+                translation: translation.truncate(),
+                z_index: translation.z.round() as i32,
+                rotation: match (angle / FRAC_PI_2).round().rem_euclid(4.) as u32 {
+                    1 => TilemapRotation::Cw90,
+                    2 => TilemapRotation::Cw180,
+                    3 => TilemapRotation::Cw270,
+                    _ => TilemapRotation::None,
+                },
+            };
+
+            if tilemap_transform.translation != synced.translation
+                || tilemap_transform.z_index != synced.z_index
+                || tilemap_transform.rotation != synced.rotation
+            {
+                *tilemap_transform = synced;
+            }
+        });
+}
+
+pub fn memory_reporter(
+    tilemaps_query: Query<(Entity, &TilemapStorage, Option<&TilemapAnimations>)>,
+    reports: Option<ResMut<TilemapMemoryReports>>,
+) {
+    let Some(mut reports) = reports else {
+        return;
+    };
+
+    reports.reports = tilemaps_query
+        .iter()
+        .map(|(entity, storage, animations)| (entity, storage.memory_report(animations)))
+        .collect();
+}
+
+pub fn texture_swapper(
+    mut commands: Commands,
+    mut tilemaps_query: Query<
+        (
+            Entity,
+            &mut TilemapTexture,
+            &TilemapStorage,
+            Option<&TilemapAnimations>,
+        ),
+        Changed<TilemapTexture>,
+    >,
+    tiles_query: Query<&Tile>,
+) {
+    tilemaps_query
+        .iter_mut()
+        .filter(|(_, texture, ..)| texture.swapped)
+        .for_each(|(entity, mut texture, storage, animations)| {
+            texture.bypass_change_detection().swapped = false;
+            // The new image also needs the `COPY_SRC` usage.
+            commands.entity(entity).insert(WaitForTextureUsageChange);
+
+            let tile_count = texture.tile_count() as i32;
+            let invalid = storage
+                .storage
+                .iter_some()
+                .filter_map(|tile| tiles_query.get(*tile).ok())
+                .filter(|tile| match &tile.texture {
+                    TileTexture::Static(layers) => {
+                        layers.iter().any(|l| l.texture_index >= tile_count)
+                    }
+                    TileTexture::Animated(anim) => animations
+                        .and_then(|a| {
+                            let start = anim.start as usize;
+                            a.0.get(start..start + anim.length as usize)
+                        })
+                        .is_some_and(|seq| seq.iter().any(|i| *i >= tile_count)),
+                })
+                .count();
+
+            if invalid > 0 {
+                warn!(
+                    "{} tiles in tilemap {:?} are using texture indices that are out of range \
+                    after swapping the texture! The new texture only has {} tiles.",
+                    invalid, entity, tile_count
+                );
+            }
+        });
+}
+
 pub fn queued_chunk_aabb_calculator(
     mut tilemaps_query: Query<(
         &mut TilemapStorage,
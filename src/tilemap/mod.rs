@@ -1,41 +1,94 @@
-use bevy::app::{Plugin, PostUpdate, PreUpdate, Update};
+use bevy::{
+    app::{Plugin, PostUpdate, PreUpdate, Update},
+    ecs::schedule::IntoSystemConfigs,
+    transform::TransformSystem,
+};
 
 use self::{
-    chunking::camera::{CameraChunkUpdater, CameraChunkUpdation},
+    audio::{TileAudioEmitter, TilemapAudioEmitters},
+    chunking::{
+        budget::StreamingBudget,
+        camera::{CameraChunkUpdater, CameraChunkUpdation},
+    },
+    command::{RemoveTileCommand, SetTileCommand, TileCommand, TilemapCommandQueue},
+    decal::TilemapDecals,
     map::{
-        TilePivot, TileRenderSize, TilemapAabbs, TilemapAnimations, TilemapLayerOpacities,
-        TilemapName, TilemapSlotSize, TilemapStorage, TilemapTexture, TilemapTextureDescriptor,
-        TilemapTransform, TilemapType,
+        TilePivot, TileRenderSize, TilemapAabbs, TilemapAlphaMode, TilemapAnimationClips,
+        TilemapAnimations, TilemapChunkFadeIn, TilemapDepthWrite, TilemapFilled,
+        TilemapLayerOpacities, TilemapName, TilemapSlotSize, TilemapStorage, TilemapTexture,
+        TilemapTextureDescriptor, TilemapTintJitter, TilemapTransform, TilemapTransformSync,
+        TilemapType,
+    },
+    occluder::TilemapOccluders,
+    occupancy::TilemapOccupancy,
+    region::{Region, RegionEvent, RegionField, RegionTracker},
+    tick::{TileRandomTick, TilemapRandomTick},
+    tile::{
+        LayerUpdater, Tile, TileAnimationStart, TileAnimationSwitcher, TileLayer, TileTexture,
+        TileTextureReplacer, TileUpdater,
     },
-    tile::{LayerUpdater, Tile, TileLayer, TileTexture, TileUpdater},
 };
 
 #[cfg(feature = "algorithm")]
 pub mod algorithm;
+pub mod audio;
 pub mod buffers;
 pub mod bundles;
 pub mod chunking;
+pub mod command;
 pub mod coordinates;
+pub mod decal;
 pub mod despawn;
+pub mod diff;
+pub mod kind;
 pub mod map;
+pub mod occluder;
+pub mod occupancy;
+pub mod outline;
 #[cfg(feature = "physics")]
 pub mod physics;
+pub mod region;
+pub mod tick;
 pub mod tile;
 
 pub struct EntiTilesTilemapPlugin;
 
 impl Plugin for EntiTilesTilemapPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(PreUpdate, despawn::despawn_applier);
+        app.add_systems(
+            PreUpdate,
+            (
+                despawn::despawn_applier,
+                chunking::budget::streaming_budget_resetter,
+            ),
+        );
 
         app.add_systems(
             Update,
             (
                 map::transform_syncer,
+                map::texture_swapper,
+                map::memory_reporter,
                 map::queued_chunk_aabb_calculator,
                 map::tilemap_aabb_calculator,
                 tile::tile_updater,
+                tile::tile_texture_replacer.after(tile::tile_updater),
+                tile::tile_animation_switcher,
+                (decal::decal_updater, decal::decal_mesh_builder).chain(),
+                audio::audio_emitter_indexer,
                 chunking::camera::camera_chunk_update,
+                region::region_tracker,
+                command::tilemap_command_applier,
+                tick::random_tick_scheduler,
+                occluder::occluder_updater,
+            ),
+        );
+
+        app.add_systems(
+            PostUpdate,
+            (
+                map::global_transform_syncer.after(TransformSystem::TransformPropagate),
+                tile::tile_animation_starter,
             ),
         );
 
@@ -44,6 +97,7 @@ impl Plugin for EntiTilesTilemapPlugin {
             (
                 despawn::despawn_tilemap,
                 despawn::despawn_tiles,
+                despawn::despawn_decals,
                 #[cfg(feature = "physics")]
                 despawn::despawn_physics_tilemaps,
             ),
@@ -52,8 +106,12 @@ impl Plugin for EntiTilesTilemapPlugin {
         app.register_type::<TileLayer>()
             .register_type::<LayerUpdater>()
             .register_type::<TileUpdater>()
+            .register_type::<TileTextureReplacer>()
+            .register_type::<TileAnimationSwitcher>()
+            .register_type::<TileAnimationStart>()
             .register_type::<Tile>()
-            .register_type::<TileTexture>();
+            .register_type::<TileTexture>()
+            .register_type::<TileAudioEmitter>();
 
         app.register_type::<TilemapName>()
             .register_type::<TileRenderSize>()
@@ -66,12 +124,42 @@ impl Plugin for EntiTilesTilemapPlugin {
             .register_type::<TilemapTransform>()
             .register_type::<TilemapTexture>()
             .register_type::<TilemapTextureDescriptor>()
-            .register_type::<TilemapAnimations>();
+            .register_type::<TilemapAnimations>()
+            .register_type::<TilemapAnimationClips>()
+            .register_type::<TilemapChunkFadeIn>()
+            .register_type::<TilemapTransformSync>()
+            .register_type::<TilemapTintJitter>()
+            .register_type::<TilemapAlphaMode>()
+            .register_type::<TilemapDepthWrite>()
+            .register_type::<TilemapDecals>()
+            .register_type::<TilemapOccupancy>()
+            .register_type::<TilemapOccluders>()
+            .register_type::<TilemapAudioEmitters>()
+            .register_type::<TilemapFilled>()
+            .register_type::<TilemapRandomTick>()
+            .register_type::<TileRandomTick>();
 
         app.register_type::<CameraChunkUpdation>()
-            .register_type::<CameraChunkUpdater>();
+            .register_type::<CameraChunkUpdater>()
+            .register_type::<StreamingBudget>();
+
+        app.register_type::<Region>()
+            .register_type::<RegionField>()
+            .register_type::<RegionTracker>()
+            .register_type::<RegionEvent>();
+
+        app.register_type::<SetTileCommand>()
+            .register_type::<RemoveTileCommand>()
+            .register_type::<TileCommand>()
+            .register_type::<TilemapCommandQueue>();
+
+        app.init_resource::<TilemapCommandQueue>();
 
-        app.add_event::<CameraChunkUpdation>();
+        app.add_event::<CameraChunkUpdation>()
+            .add_event::<RegionEvent>()
+            .add_event::<TilemapFilled>()
+            .add_event::<TileCommand>()
+            .add_event::<TileRandomTick>();
 
         #[cfg(feature = "algorithm")]
         app.add_plugins(algorithm::EntiTilesAlgorithmTilemapPlugin);
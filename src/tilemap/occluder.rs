@@ -0,0 +1,183 @@
+use bevy::{
+    ecs::{
+        change_detection::{DetectChanges, DetectChangesMut},
+        component::Component,
+        system::Query,
+        world::Ref,
+    },
+    math::{IVec2, Vec2},
+    reflect::Reflect,
+    utils::HashSet,
+};
+
+use crate::{
+    math::{aabb::IAabb2d, TileArea},
+    DEFAULT_CHUNK_SIZE,
+};
+
+use super::{
+    map::{TilePivot, TilemapSlotSize, TilemapTransform, TilemapType},
+    outline::CellOutlines,
+};
+
+/// Shadow casters for 2d lighting crates, built from the occluder cells of a tilemap.
+/// Add this to a tilemap entity.
+///
+/// Cells are marked with `set` and `fill_rect`, and with `from_physics` the solid tiles
+/// of the `PhysicsTilemap` occlude as well. The occluders are available as outlines
+/// in world space, merged the same way as `TilemapNavObstacles`, or as one bit per cell
+/// for each chunk, which can be uploaded to the GPU as is. See `get_chunk_bits`.
+///
+/// Only the chunks affected by changed cells are rebuilt. Use `Changed<TilemapOccluders>`
+/// and `updated_chunks` to find out what to upload again.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapOccluders {
+    /// Also occlude the tiles with `PhysicsTile::rigid_body`.
+    /// Requires the `physics` feature.
+    pub from_physics: bool,
+    pub(crate) cells: CellOutlines,
+    /// The cells marked with `set` and `fill_rect`.
+    pub(crate) marked: HashSet<IVec2>,
+    /// Whether the solid tiles are added to `cells`.
+    pub(crate) physics_synced: bool,
+    pub(crate) updated: HashSet<IVec2>,
+}
+
+impl Default for TilemapOccluders {
+    fn default() -> Self {
+        Self::new_with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl TilemapOccluders {
+    pub fn new_with_chunk_size(chunk_size: u32) -> Self {
+        Self {
+            from_physics: false,
+            cells: CellOutlines::new(chunk_size),
+            marked: Default::default(),
+            physics_synced: false,
+            updated: Default::default(),
+        }
+    }
+
+    /// Occlude the solid tiles of the `PhysicsTilemap` as well.
+    pub fn with_physics(mut self) -> Self {
+        self.from_physics = true;
+        self
+    }
+
+    /// Returns true if the cell is marked, or covered by a solid tile when `from_physics` is on.
+    #[inline]
+    pub fn is_occluder(&self, index: IVec2) -> bool {
+        self.cells.is_solid(index)
+    }
+
+    /// Mark or unmark the cell as an occluder.
+    pub fn set(&mut self, index: IVec2, occluder: bool) {
+        let changed = if occluder {
+            self.marked.insert(index)
+        } else {
+            self.marked.remove(&index)
+        };
+        if changed {
+            self.cells.apply(IAabb2d::splat(index), occluder);
+        }
+    }
+
+    /// Mark or unmark all the cells in the area.
+    pub fn fill_rect(&mut self, area: TileArea, occluder: bool) {
+        for y in area.origin.y..=area.dest.y {
+            for x in area.origin.x..=area.dest.x {
+                self.set(IVec2 { x, y }, occluder);
+            }
+        }
+    }
+
+    /// Iterate over all the outlines.
+    pub fn iter(&self) -> impl Iterator<Item = &[Vec2]> {
+        self.cells.iter()
+    }
+
+    /// Get the outlines of a chunk.
+    #[inline]
+    pub fn get_chunk(&self, chunk_index: IVec2) -> Option<&Vec<Vec<Vec2>>> {
+        self.cells.outlines.get(&chunk_index)
+    }
+
+    /// Get the occluder cells of a chunk, one bit for each cell.
+    ///
+    /// Bit `i % 32` of word `i / 32` is the cell at `(i % chunk_size, i / chunk_size)`
+    /// relative to the bottom left corner of the chunk.
+    pub fn get_chunk_bits(&self, chunk_index: IVec2) -> Vec<u32> {
+        let area = (self.cells.chunk_size * self.cells.chunk_size) as usize;
+        let mut bits = vec![0; area.div_ceil(32)];
+        self.cells
+            .chunk_cells(chunk_index)
+            .enumerate()
+            .filter(|(_, cell)| self.cells.is_solid(*cell))
+            .for_each(|(i, _)| bits[i / 32] |= 1 << (i % 32));
+        bits
+    }
+
+    /// The chunks that are rebuilt in the last update.
+    #[inline]
+    pub fn updated_chunks(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.updated.iter().copied()
+    }
+}
+
+pub fn occluder_updater(
+    mut tilemaps_query: Query<(
+        &mut TilemapOccluders,
+        &TilemapType,
+        Ref<TilemapTransform>,
+        &TilePivot,
+        &TilemapSlotSize,
+    )>,
+) {
+    tilemaps_query.par_iter_mut().for_each(
+        |(mut occluders, ty, transform, tile_pivot, slot_size)| {
+            let occluders_mut = occluders.bypass_change_detection();
+            if !occluders_mut.updated.is_empty() {
+                occluders_mut.updated.clear();
+            }
+
+            if transform.is_changed() {
+                occluders_mut.cells.mark_all_dirty();
+            }
+
+            if !occluders_mut.cells.dirty.is_empty() {
+                let dirty = occluders_mut.cells.dirty.clone();
+                occluders_mut
+                    .cells
+                    .rebuild(*ty, &transform, tile_pivot.0, slot_size.0);
+                occluders_mut.updated = dirty;
+                occluders.set_changed();
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::{IVec2, UVec2};
+
+    use crate::math::TileArea;
+
+    use super::TilemapOccluders;
+
+    #[test]
+    fn test_chunk_bits() {
+        let mut occluders = TilemapOccluders::new_with_chunk_size(8);
+        occluders.fill_rect(TileArea::new(IVec2::new(1, 0), UVec2::new(2, 1)), true);
+        occluders.set(IVec2::new(7, 7), true);
+        occluders.set(IVec2::new(7, 7), true);
+        occluders.set(IVec2::new(8, 0), true);
+
+        assert_eq!(occluders.get_chunk_bits(IVec2::ZERO), vec![0b110, 1 << 31]);
+        assert_eq!(occluders.get_chunk_bits(IVec2::X), vec![1, 0]);
+
+        occluders.set(IVec2::new(7, 7), false);
+        assert!(!occluders.is_occluder(IVec2::new(7, 7)));
+    }
+}
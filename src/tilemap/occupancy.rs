@@ -0,0 +1,142 @@
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    math::{IVec2, UVec2},
+    reflect::Reflect,
+    utils::HashMap,
+};
+
+use crate::math::{aabb::IAabb2d, TileArea};
+
+use super::chunking::storage::EntityChunkedStorage;
+
+/// Tracks which cells of a tilemap are taken by gameplay objects, like buildings.
+///
+/// This is independent from the tiles, so you can reserve the cells for any entity
+/// and check if a structure fits before placing it. Add this to a tilemap entity.
+///
+/// **Notice**: Reservations are not released automatically when the entity is despawned.
+/// Call `release` when the structure is removed.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+pub struct TilemapOccupancy {
+    pub(crate) cells: EntityChunkedStorage,
+    pub(crate) areas: HashMap<Entity, IAabb2d>,
+}
+
+impl TilemapOccupancy {
+    pub fn new_with_chunk_size(chunk_size: u32) -> Self {
+        Self {
+            cells: EntityChunkedStorage::new(chunk_size),
+            areas: HashMap::new(),
+        }
+    }
+
+    /// Get the entity that takes the cell.
+    #[inline]
+    pub fn get(&self, index: IVec2) -> Option<Entity> {
+        self.cells.get_elem(index).copied()
+    }
+
+    /// Get the area that the entity takes.
+    #[inline]
+    pub fn get_area(&self, entity: Entity) -> Option<TileArea> {
+        self.areas
+            .get(&entity)
+            .map(|aabb| TileArea::new(aabb.min, (aabb.max - aabb.min + 1).as_uvec2()))
+    }
+
+    /// Returns true if none of the cells in the area is taken.
+    pub fn is_free(&self, area: TileArea) -> bool {
+        (area.origin.y..=area.dest.y)
+            .flat_map(|y| (area.origin.x..=area.dest.x).map(move |x| IVec2 { x, y }))
+            .all(|index| self.cells.get_elem(index).is_none())
+    }
+
+    /// Take all the cells in the area for the entity.
+    ///
+    /// Returns false and changes nothing if any of the cells is taken, or the entity
+    /// already has a reservation. Release it first if you want to move the entity.
+    pub fn reserve(&mut self, area: TileArea, entity: Entity) -> bool {
+        if self.areas.contains_key(&entity) || !self.is_free(area) {
+            return false;
+        }
+
+        for y in area.origin.y..=area.dest.y {
+            for x in area.origin.x..=area.dest.x {
+                self.cells.set_elem(IVec2 { x, y }, entity);
+            }
+        }
+        self.areas.insert(entity, area.into());
+        true
+    }
+
+    /// Same as `reserve`, but for a tile at `index` that covers `footprint` cells,
+    /// like the ones built with `TileBuilder::with_footprint`.
+    #[inline]
+    pub fn reserve_footprint(&mut self, index: IVec2, footprint: UVec2, entity: Entity) -> bool {
+        self.reserve(TileArea::new(index, footprint.max(UVec2::ONE)), entity)
+    }
+
+    /// Free the cells taken by the entity and return the area.
+    pub fn release(&mut self, entity: Entity) -> Option<TileArea> {
+        let area = self.get_area(entity)?;
+        self.areas.remove(&entity);
+
+        for y in area.origin.y..=area.dest.y {
+            for x in area.origin.x..=area.dest.x {
+                self.cells.remove_elem(IVec2 { x, y });
+            }
+        }
+        Some(area)
+    }
+
+    /// Free the cell and all the other cells taken by the same entity.
+    #[inline]
+    pub fn release_at(&mut self, index: IVec2) -> Option<(Entity, TileArea)> {
+        let entity = self.get(index)?;
+        self.release(entity).map(|area| (entity, area))
+    }
+
+    /// Iterate over all the entities and the areas they take.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, TileArea)> + '_ {
+        self.areas
+            .keys()
+            .filter_map(|e| self.get_area(*e).map(|a| (*e, a)))
+    }
+
+    /// Free all the cells.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.areas.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::entity::Entity,
+        math::{IVec2, UVec2},
+    };
+
+    use crate::math::TileArea;
+
+    use super::TilemapOccupancy;
+
+    #[test]
+    fn test_occupancy() {
+        let house = Entity::from_raw(1);
+        let tower = Entity::from_raw(2);
+        let mut occupancy = TilemapOccupancy::new_with_chunk_size(4);
+
+        assert!(occupancy.reserve(TileArea::new(IVec2::new(-1, -1), UVec2::new(3, 2)), house));
+        assert_eq!(occupancy.get(IVec2::new(1, 0)), Some(house));
+        assert!(!occupancy.is_free(TileArea::new(IVec2::new(1, 0), UVec2::splat(2))));
+        assert!(!occupancy.reserve_footprint(IVec2::new(1, 0), UVec2::splat(2), tower));
+        assert!(occupancy.reserve_footprint(IVec2::new(2, 0), UVec2::splat(2), tower));
+
+        let (entity, area) = occupancy.release_at(IVec2::new(0, -1)).unwrap();
+        assert_eq!(entity, house);
+        assert_eq!(area.dest, IVec2::new(1, 0));
+        assert!(occupancy.is_free(area));
+        assert_eq!(occupancy.iter().count(), 1);
+    }
+}
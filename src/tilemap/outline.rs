@@ -0,0 +1,235 @@
+use bevy::{
+    math::{IVec2, UVec2, Vec2},
+    reflect::Reflect,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    math::aabb::IAabb2d,
+    tilemap::{
+        coordinates,
+        map::{TilemapTransform, TilemapType},
+    },
+};
+
+/// Solid cells of a tilemap and their outlines, one chunk at a time.
+///
+/// Adjacent solid cells are merged into outlines, so large areas are split at chunk borders.
+/// Outlines go counter-clockwise in tile space, and the ones around holes go clockwise.
+/// Hexagonal tilemaps get one outline per cell.
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct CellOutlines {
+    pub(crate) chunk_size: u32,
+    /// solid cell to the number of things covering it
+    pub(crate) cells: HashMap<IVec2, u32>,
+    /// chunk index to the outlines in world space
+    pub(crate) outlines: HashMap<IVec2, Vec<Vec<Vec2>>>,
+    pub(crate) dirty: HashSet<IVec2>,
+}
+
+impl CellOutlines {
+    pub(crate) fn new(chunk_size: u32) -> Self {
+        Self {
+            chunk_size,
+            cells: Default::default(),
+            outlines: Default::default(),
+            dirty: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_solid(&self, index: IVec2) -> bool {
+        self.cells.contains_key(&index)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &[Vec2]> {
+        self.outlines.values().flatten().map(|o| o.as_slice())
+    }
+
+    #[inline]
+    pub(crate) fn chunk_index(&self, index: IVec2) -> IVec2 {
+        index.div_euclid(IVec2::splat(self.chunk_size as i32))
+    }
+
+    pub(crate) fn apply(&mut self, area: IAabb2d, solid: bool) {
+        for y in area.min.y..=area.max.y {
+            for x in area.min.x..=area.max.x {
+                let index = IVec2 { x, y };
+                if solid {
+                    *self.cells.entry(index).or_default() += 1;
+                } else if let Some(count) = self.cells.get_mut(&index) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.cells.remove(&index);
+                    }
+                }
+                self.dirty.insert(self.chunk_index(index));
+            }
+        }
+    }
+
+    /// Rebuild all the chunks, used when the tilemap is moved.
+    pub(crate) fn mark_all_dirty(&mut self) {
+        let chunks = self
+            .cells
+            .keys()
+            .map(|cell| self.chunk_index(*cell))
+            .collect::<Vec<_>>();
+        self.dirty.extend(chunks);
+    }
+
+    pub(crate) fn rebuild(
+        &mut self,
+        ty: TilemapType,
+        transform: &TilemapTransform,
+        pivot: Vec2,
+        slot_size: Vec2,
+    ) {
+        let corner_to_world = |corner: IVec2| {
+            coordinates::get_tile_collider_world(
+                corner,
+                ty,
+                UVec2::ONE,
+                transform,
+                pivot,
+                slot_size,
+            )[0]
+        };
+
+        for chunk in std::mem::take(&mut self.dirty) {
+            let outlines = match ty {
+                TilemapType::Square | TilemapType::Isometric => self
+                    .trace_chunk(chunk)
+                    .into_iter()
+                    .map(|outline| outline.into_iter().map(corner_to_world).collect())
+                    .collect::<Vec<_>>(),
+                TilemapType::Hexagonal(_) => self
+                    .chunk_cells(chunk)
+                    .filter(|cell| self.is_solid(*cell))
+                    .map(|cell| {
+                        let mut outline = coordinates::get_tile_collider_world(
+                            cell,
+                            ty,
+                            UVec2::ONE,
+                            transform,
+                            pivot,
+                            slot_size,
+                        );
+                        // The collider is closed by repeating the first vertex.
+                        outline.pop();
+                        outline
+                    })
+                    .collect(),
+            };
+
+            if outlines.is_empty() {
+                self.outlines.remove(&chunk);
+            } else {
+                self.outlines.insert(chunk, outlines);
+            }
+        }
+    }
+
+    pub(crate) fn chunk_cells(&self, chunk: IVec2) -> impl Iterator<Item = IVec2> {
+        let size = self.chunk_size as i32;
+        let origin = chunk * size;
+        (0..size).flat_map(move |y| (0..size).map(move |x| origin + IVec2 { x, y }))
+    }
+
+    /// Trace the outlines of the solid cells in the chunk, in tile corners.
+    /// Cell `(x, y)` spans from corner `(x, y)` to `(x + 1, y + 1)`.
+    fn trace_chunk(&self, chunk: IVec2) -> Vec<Vec<IVec2>> {
+        let solid = |index: IVec2| self.chunk_index(index) == chunk && self.is_solid(index);
+
+        // Edges with the solid side on the left.
+        let mut edges = HashMap::<IVec2, Vec<IVec2>>::new();
+        for cell in self.chunk_cells(chunk).filter(|c| self.is_solid(*c)) {
+            let corners = [cell, cell + IVec2::X, cell + IVec2::ONE, cell + IVec2::Y];
+            [-IVec2::Y, IVec2::X, IVec2::Y, -IVec2::X]
+                .into_iter()
+                .enumerate()
+                .filter(|(_, dir)| !solid(cell + *dir))
+                .for_each(|(side, _)| {
+                    edges
+                        .entry(corners[side])
+                        .or_default()
+                        .push(corners[(side + 1) % 4]);
+                });
+        }
+
+        let mut outlines = Vec::new();
+        while let Some(&start) = edges.keys().next() {
+            let mut outline = vec![start];
+            let mut cur = start;
+            let mut dir = IVec2::ZERO;
+
+            loop {
+                let ends = edges.get_mut(&cur).unwrap();
+                // Turn left where two outlines touch at a corner, so they stay apart.
+                let left = IVec2::new(-dir.y, dir.x);
+                let i = ends.iter().position(|e| *e - cur == left).unwrap_or(0);
+                let next = ends.swap_remove(i);
+                if ends.is_empty() {
+                    edges.remove(&cur);
+                }
+
+                dir = next - cur;
+                cur = next;
+                if cur == start {
+                    break;
+                }
+                outline.push(cur);
+            }
+
+            outlines.push(simplify(outline));
+        }
+        outlines
+    }
+}
+
+/// Remove the vertices in the middle of straight lines.
+fn simplify(outline: Vec<IVec2>) -> Vec<IVec2> {
+    let n = outline.len();
+    (0..n)
+        .filter(|i| {
+            let prev = outline[(i + n - 1) % n];
+            let cur = outline[*i];
+            let next = outline[(i + 1) % n];
+            (cur - prev).perp_dot(next - cur) != 0
+        })
+        .map(|i| outline[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::IVec2;
+
+    use crate::math::aabb::IAabb2d;
+
+    use super::CellOutlines;
+
+    #[test]
+    fn test_trace() {
+        let mut cells = CellOutlines::new(8);
+        // An L shape and a cell that only touches it at a corner.
+        cells.apply(
+            IAabb2d {
+                min: IVec2::ZERO,
+                max: IVec2::new(2, 0),
+            },
+            true,
+        );
+        cells.apply(IAabb2d::splat(IVec2::new(0, 1)), true);
+        cells.apply(IAabb2d::splat(IVec2::new(1, 2)), true);
+
+        let mut outlines = cells.trace_chunk(IVec2::ZERO);
+        outlines.sort_by_key(|o| o.len());
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].len(), 4);
+        assert_eq!(outlines[1].len(), 6);
+
+        cells.apply(IAabb2d::splat(IVec2::new(1, 2)), false);
+        assert_eq!(cells.trace_chunk(IVec2::ZERO).len(), 1);
+    }
+}
@@ -1,12 +1,15 @@
 use bevy::{
     app::{App, Plugin, Update},
-    ecs::{component::Component, entity::Entity, system::Commands},
+    ecs::{
+        component::Component, entity::Entity, schedule::IntoSystemConfigs, system::Commands,
+        world::World,
+    },
     math::{IVec2, UVec2, Vec2},
     reflect::Reflect,
     utils::HashMap,
 };
 use bevy_xpbd_2d::{
-    components::{Friction, RigidBody},
+    components::{CollisionLayers, Friction, RigidBody},
     plugins::collision::Collider,
 };
 
@@ -15,8 +18,10 @@ use crate::math::{aabb::IAabb2d, TileArea};
 use super::{
     buffers::{PackedPhysicsTileBuffer, PhysicsTileBuffer, Tiles},
     chunking::storage::{ChunkedStorage, EntityChunkedStorage, PackedPhysicsTileChunkedStorage},
+    occluder,
 };
 
+pub mod nav;
 pub mod systems;
 
 pub struct EntiTilesPhysicsTilemapPlugin;
@@ -28,12 +33,19 @@ impl Plugin for EntiTilesPhysicsTilemapPlugin {
             (
                 systems::spawn_colliders,
                 systems::data_physics_tilemap_analyzer,
+                nav::nav_obstacle_updater.after(systems::spawn_colliders),
+                systems::physics_occluder_syncer
+                    .after(systems::spawn_colliders)
+                    .before(nav::nav_obstacle_updater)
+                    .before(occluder::occluder_updater),
             ),
         );
 
         app.register_type::<PhysicsTilemap>()
             .register_type::<DataPhysicsTilemap>()
-            .register_type::<PhysicsTile>();
+            .register_type::<PhysicsTile>()
+            .register_type::<DisabledCollisionLayers>()
+            .register_type::<nav::TilemapNavObstacles>();
     }
 }
 
@@ -73,6 +85,9 @@ impl PhysicsCollider {
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct PackedPhysicsTile {
     pub parent: IVec2,
+    /// The cells this tile covers, starting from `parent`.
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub size: UVec2,
     pub collider: PhysicsCollider,
     pub physics_tile: PhysicsTile,
 }
@@ -99,6 +114,15 @@ impl PackedPhysicsTile {
         }
         entity.id()
     }
+
+    /// The cells this tile covers.
+    #[inline]
+    pub fn area(&self) -> IAabb2d {
+        IAabb2d {
+            min: self.parent,
+            max: self.parent + self.size.max(UVec2::ONE).as_ivec2() - 1,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -212,6 +236,94 @@ impl DataPhysicsTilemap {
     pub fn set(&mut self, index: UVec2, value: i32) {
         self.data[(index.x + index.y * self.size.x) as usize] = value;
     }
+
+    /// Merge the solid tiles into as few aabbs as possible, and clear them to air.
+    pub(crate) fn take_aabbs(&mut self) -> Vec<(IAabb2d, PhysicsTile)> {
+        let mut aabbs = Vec::new();
+        let size = self.size;
+        let air = self.air;
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let cur = UVec2 { x, y };
+
+                let cur_i = {
+                    let i = self.get_or_air(cur);
+                    if i == air {
+                        continue;
+                    }
+                    i
+                };
+
+                let mut d = UVec2 {
+                    x: if x == size.x - 1 { 0 } else { 1 },
+                    y: if y == size.y - 1 { 0 } else { 1 },
+                };
+                let mut dst = cur;
+                while d.x != 0 || d.y != 0 {
+                    for t_x in cur.x..=dst.x {
+                        if self.get_or_air(UVec2::new(t_x, dst.y + d.y)) != cur_i {
+                            d.y = 0;
+                            break;
+                        }
+                    }
+
+                    for t_y in cur.y..=dst.y {
+                        if self.get_or_air(UVec2::new(dst.x + d.x, t_y)) != cur_i {
+                            d.x = 0;
+                            break;
+                        }
+                    }
+
+                    if d == UVec2::ONE && self.get_or_air(UVec2::new(dst.x + 1, dst.y + 1)) != cur_i
+                    {
+                        d.y = 0;
+                    }
+
+                    dst += d;
+                }
+
+                for y in cur.y..=dst.y {
+                    for x in cur.x..=dst.x {
+                        self.set(UVec2 { x, y }, air);
+                    }
+                }
+
+                aabbs.push((
+                    IAabb2d {
+                        min: cur.as_ivec2() + self.origin,
+                        max: dst.as_ivec2() + self.origin,
+                    },
+                    self.get_tile(cur_i).unwrap_or_default(),
+                ));
+            }
+        }
+
+        aabbs
+    }
+}
+
+/// The collision layers of a collider whose physics tilemap is disabled.
+/// See `PhysicsTilemap::set_enabled`.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+pub struct DisabledCollisionLayers(pub CollisionLayers);
+
+pub(crate) fn set_collider_enabled(world: &mut World, collider: Entity, enabled: bool) {
+    let Some(mut collider) = world.get_entity_mut(collider) else {
+        return;
+    };
+
+    if enabled {
+        if let Some(DisabledCollisionLayers(layers)) = collider.take::<DisabledCollisionLayers>() {
+            collider.insert(layers);
+        }
+    } else if !collider.contains::<DisabledCollisionLayers>() {
+        let layers = collider
+            .get::<CollisionLayers>()
+            .copied()
+            .unwrap_or_default();
+        collider.insert((DisabledCollisionLayers(layers), CollisionLayers::NONE));
+    }
 }
 
 /// A tilemap with physics tiles.
@@ -220,6 +332,12 @@ pub struct PhysicsTilemap {
     pub(crate) storage: EntityChunkedStorage,
     pub(crate) spawn_queue: Vec<(IAabb2d, PhysicsTile)>,
     pub(crate) data: PackedPhysicsTileChunkedStorage,
+    /// parent index to the cells covered by the rigid body tile
+    pub(crate) solid_areas: HashMap<IVec2, IAabb2d>,
+    /// Areas that became solid (true) or not (false), waiting for `TilemapNavObstacles`
+    /// and `TilemapOccluders`.
+    pub(crate) nav_changes: Vec<(IAabb2d, bool)>,
+    pub(crate) disabled: bool,
 }
 
 impl PhysicsTilemap {
@@ -231,6 +349,9 @@ impl PhysicsTilemap {
             storage: ChunkedStorage::default(),
             spawn_queue: Vec::new(),
             data: ChunkedStorage::default(),
+            solid_areas: HashMap::new(),
+            nav_changes: Vec::new(),
+            disabled: false,
         }
     }
 
@@ -240,6 +361,9 @@ impl PhysicsTilemap {
             storage: ChunkedStorage::new(chunk_size),
             spawn_queue: Vec::new(),
             data: ChunkedStorage::new(chunk_size),
+            solid_areas: HashMap::new(),
+            nav_changes: Vec::new(),
+            disabled: false,
         }
     }
 
@@ -255,22 +379,56 @@ impl PhysicsTilemap {
         self.spawn_queue.push((IAabb2d::splat(index), tile));
     }
 
+    /// Set a tile that covers `footprint` cells, like the ones built with `TileBuilder::with_footprint`.
+    ///
+    /// Only one collider is spawned for the whole footprint, and it's stored at `index`.
+    #[inline]
+    pub fn set_with_footprint(&mut self, index: IVec2, footprint: UVec2, tile: PhysicsTile) {
+        self.spawn_queue.push((
+            IAabb2d {
+                min: index,
+                max: index + footprint.max(UVec2::ONE).as_ivec2() - 1,
+            },
+            tile,
+        ));
+    }
+
     /// Remove a tile.
     #[inline]
     pub fn remove(&mut self, commands: &mut Commands, index: IVec2) {
         if let Some(entity) = self.storage.remove_elem(index) {
             commands.entity(entity).despawn();
         }
+        self.release_solid(|parent| parent == index);
     }
 
     /// Remove a chunk.
     #[inline]
     pub fn remove_chunk(&mut self, commands: &mut Commands, index: IVec2) {
         if let Some(chunk) = self.storage.remove_chunk(index) {
-            chunk.into_iter().filter_map(|e| e).for_each(|entity| {
+            chunk.into_iter_some().for_each(|(_, entity)| {
                 commands.entity(entity).despawn();
             });
         }
+        let chunk_size = self.storage.chunk_size;
+        self.release_solid(|parent| parent.div_euclid(IVec2::splat(chunk_size as i32)) == index);
+    }
+
+    /// Remove all the tiles inside the region, including the queued ones.
+    ///
+    /// Concatenated colliders are removed if their origin is inside the region.
+    pub fn remove_region(&mut self, commands: &mut Commands, region: IAabb2d) {
+        let region = region.justified();
+        self.storage
+            .remove_region(region)
+            .into_iter()
+            .for_each(|entity| {
+                commands.entity(entity).despawn();
+            });
+        self.data.remove_region(region);
+        self.spawn_queue
+            .retain(|(aabb, _)| !region.contains(aabb.min));
+        self.release_solid(|parent| region.contains(parent));
     }
 
     /// Remove all tiles.
@@ -280,6 +438,55 @@ impl PhysicsTilemap {
             commands.entity(*entity).despawn();
         }
         self.storage.clear();
+        self.release_solid(|_| true);
+    }
+
+    /// Disable or enable all the colliders of the tilemap, including the ones spawned later.
+    ///
+    /// Disabled colliders get `CollisionLayers::NONE`, and their own collision layers
+    /// are kept in `DisabledCollisionLayers` until they are enabled again.
+    pub fn set_enabled(&mut self, commands: &mut Commands, enabled: bool) {
+        if let Some(colliders) = self.switch_enabled(enabled) {
+            commands.add(move |world: &mut World| {
+                colliders.into_iter().for_each(|collider| {
+                    set_collider_enabled(world, collider, enabled);
+                });
+            });
+        }
+    }
+
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        !self.disabled
+    }
+
+    /// Returns the colliders to update if the state is changed.
+    pub(crate) fn switch_enabled(&mut self, enabled: bool) -> Option<Vec<Entity>> {
+        if self.disabled != enabled {
+            return None;
+        }
+        self.disabled = !enabled;
+        Some(self.storage.iter_some().copied().collect())
+    }
+
+    /// Record the area of a spawned tile for `TilemapNavObstacles`.
+    pub(crate) fn occupy_solid(&mut self, area: IAabb2d) {
+        if let Some(previous) = self.solid_areas.insert(area.min, area) {
+            self.nav_changes.push((previous, false));
+        }
+        self.nav_changes.push((area, true));
+    }
+
+    fn release_solid(&mut self, predicate: impl Fn(IVec2) -> bool) {
+        let nav_changes = &mut self.nav_changes;
+        self.solid_areas.retain(|parent, area| {
+            if predicate(*parent) {
+                nav_changes.push((*area, false));
+                false
+            } else {
+                true
+            }
+        });
     }
 
     /// Fill a rectangle area with the same tile.
@@ -0,0 +1,120 @@
+use bevy::{
+    ecs::{
+        change_detection::{DetectChanges, DetectChangesMut},
+        component::Component,
+        entity::Entity,
+        query::Without,
+        system::Query,
+        world::Ref,
+    },
+    math::{IVec2, Vec2},
+    reflect::Reflect,
+};
+
+use crate::{
+    tilemap::{
+        map::{TilePivot, TilemapSlotSize, TilemapTransform, TilemapType},
+        outline::CellOutlines,
+    },
+    DEFAULT_CHUNK_SIZE,
+};
+
+use super::PhysicsTilemap;
+
+/// Polygon obstacles built from the solid tiles of a `PhysicsTilemap`, for navmesh crates
+/// that take arbitrary polygons instead of grids. Add this to a tilemap entity
+/// that has a `PhysicsTilemap`.
+///
+/// Tiles with `PhysicsTile::rigid_body` are solid. Adjacent solid cells are merged into
+/// outlines, one chunk at a time, so large obstacles are split at chunk borders.
+/// Outlines go counter-clockwise in tile space, and the ones around holes go clockwise.
+/// Hexagonal tilemaps get one outline per cell.
+///
+/// Only the chunks affected by spawned or removed tiles are rebuilt.
+/// Use `Changed<TilemapNavObstacles>` to find out when to rebuild your navmesh.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapNavObstacles {
+    pub(crate) cells: CellOutlines,
+}
+
+impl Default for TilemapNavObstacles {
+    fn default() -> Self {
+        Self::new_with_chunk_size(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl TilemapNavObstacles {
+    pub fn new_with_chunk_size(chunk_size: u32) -> Self {
+        Self {
+            cells: CellOutlines::new(chunk_size),
+        }
+    }
+
+    #[inline]
+    pub fn is_solid(&self, index: IVec2) -> bool {
+        self.cells.is_solid(index)
+    }
+
+    /// Iterate over all the outlines.
+    pub fn iter(&self) -> impl Iterator<Item = &[Vec2]> {
+        self.cells.iter()
+    }
+
+    /// Get the outlines of a chunk.
+    #[inline]
+    pub fn get_chunk(&self, chunk_index: IVec2) -> Option<&Vec<Vec<Vec2>>> {
+        self.cells.outlines.get(&chunk_index)
+    }
+}
+
+pub fn nav_obstacle_updater(
+    mut tilemaps_query: Query<(Entity, &mut PhysicsTilemap, &mut TilemapNavObstacles)>,
+    geometry_query: Query<(
+        &TilemapType,
+        Ref<TilemapTransform>,
+        &TilePivot,
+        &TilemapSlotSize,
+    )>,
+    mut untracked_query: Query<&mut PhysicsTilemap, Without<TilemapNavObstacles>>,
+) {
+    untracked_query.iter_mut().for_each(|mut physics_tilemap| {
+        if !physics_tilemap.nav_changes.is_empty() {
+            physics_tilemap.nav_changes.clear();
+        }
+    });
+
+    tilemaps_query
+        .par_iter_mut()
+        .for_each(|(entity, mut physics_tilemap, mut obstacles)| {
+            let Ok((ty, transform, tile_pivot, slot_size)) = geometry_query.get(entity) else {
+                return;
+            };
+
+            let is_added = obstacles.is_added();
+            let nav_obstacles = &mut obstacles.bypass_change_detection().cells;
+            let physics_tilemap = physics_tilemap.as_mut();
+            if is_added {
+                // Tiles spawned before the component was added.
+                physics_tilemap.nav_changes.clear();
+                nav_obstacles.cells.clear();
+                physics_tilemap
+                    .solid_areas
+                    .values()
+                    .for_each(|area| nav_obstacles.apply(*area, true));
+            } else {
+                physics_tilemap
+                    .nav_changes
+                    .drain(..)
+                    .for_each(|(area, solid)| nav_obstacles.apply(area, solid));
+            }
+
+            if transform.is_changed() {
+                nav_obstacles.mark_all_dirty();
+            }
+
+            if !nav_obstacles.dirty.is_empty() {
+                nav_obstacles.rebuild(*ty, &transform, tile_pivot.0, slot_size.0);
+                obstacles.set_changed();
+            }
+        });
+}
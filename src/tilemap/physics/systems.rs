@@ -1,21 +1,22 @@
-use bevy::{
-    ecs::{
-        entity::Entity,
-        system::{ParallelCommands, Query},
-    },
-    math::UVec2,
+use bevy::ecs::{
+    entity::Entity,
+    system::{ParallelCommands, Query, ResMut},
 };
+use bevy_xpbd_2d::components::CollisionLayers;
 
 use crate::{
     math::aabb::IAabb2d,
     tilemap::{
-        chunking::storage::ChunkedStorage,
+        chunking::budget::StreamingBudget,
         coordinates,
         map::{TilePivot, TilemapSlotSize, TilemapTransform, TilemapType},
+        occluder::TilemapOccluders,
     },
 };
 
-use super::{DataPhysicsTilemap, PackedPhysicsTile, PhysicsCollider, PhysicsTilemap};
+use super::{
+    DataPhysicsTilemap, DisabledCollisionLayers, PackedPhysicsTile, PhysicsCollider, PhysicsTilemap,
+};
 
 pub fn spawn_colliders(
     commands: ParallelCommands,
@@ -26,10 +27,35 @@ pub fn spawn_colliders(
         &TilePivot,
         &TilemapSlotSize,
     )>,
+    mut budget: Option<ResMut<StreamingBudget>>,
 ) {
-    tilemaps_query.par_iter_mut().for_each(
+    // The budget is shared by all the tilemaps, so we can't iterate in parallel.
+    tilemaps_query.iter_mut().for_each(
         |(mut physics_tilemap, ty, transform, tile_pivot, slot_size)| {
-            let physics_tiles = physics_tilemap.spawn_queue.drain(..).collect::<Vec<_>>();
+            let count = match budget.as_mut() {
+                Some(budget) => {
+                    if budget.focus().is_some() {
+                        physics_tilemap.spawn_queue.sort_by(|(lhs, _), (rhs, _)| {
+                            let priority = |aabb: &IAabb2d| {
+                                budget.priority(coordinates::index_to_world(
+                                    aabb.min,
+                                    *ty,
+                                    transform,
+                                    tile_pivot.0,
+                                    slot_size.0,
+                                ))
+                            };
+                            priority(lhs).total_cmp(&priority(rhs))
+                        });
+                    }
+                    budget.take_colliders(physics_tilemap.spawn_queue.len())
+                }
+                None => physics_tilemap.spawn_queue.len(),
+            };
+            let physics_tiles = physics_tilemap
+                .spawn_queue
+                .drain(..count)
+                .collect::<Vec<_>>();
             physics_tiles.into_iter().for_each(|(aabb, physics_tile)| {
                 commands.command_scope(|mut c| {
                     let vertices = coordinates::get_tile_collider_world(
@@ -43,6 +69,7 @@ pub fn spawn_colliders(
 
                     let packed_tile = PackedPhysicsTile {
                         parent: aabb.min,
+                        size: aabb.size().as_uvec2(),
                         collider: match ty {
                             TilemapType::Square | TilemapType::Isometric => {
                                 PhysicsCollider::Convex(vertices.clone())
@@ -54,9 +81,17 @@ pub fn spawn_colliders(
                         physics_tile,
                     };
 
-                    physics_tilemap
-                        .storage
-                        .set_elem(aabb.min, packed_tile.spawn(&mut c));
+                    if packed_tile.physics_tile.rigid_body {
+                        physics_tilemap.occupy_solid(aabb);
+                    }
+                    let collider = packed_tile.spawn(&mut c);
+                    if physics_tilemap.disabled {
+                        c.entity(collider).insert((
+                            DisabledCollisionLayers(CollisionLayers::default()),
+                            CollisionLayers::NONE,
+                        ));
+                    }
+                    physics_tilemap.storage.set_elem(aabb.min, collider);
                     physics_tilemap.data.set_elem(aabb.min, packed_tile);
                 });
             });
@@ -64,6 +99,31 @@ pub fn spawn_colliders(
     );
 }
 
+/// Add the solid tiles to the `TilemapOccluders` with `from_physics`.
+/// Runs before `nav_obstacle_updater` consumes the changes.
+pub fn physics_occluder_syncer(
+    mut tilemaps_query: Query<(&PhysicsTilemap, &mut TilemapOccluders)>,
+) {
+    tilemaps_query
+        .par_iter_mut()
+        .for_each(|(physics_tilemap, mut occluders)| {
+            if occluders.from_physics != occluders.physics_synced {
+                // Turned on or off, so all the solid tiles are added or removed.
+                let solid = occluders.from_physics;
+                physics_tilemap
+                    .solid_areas
+                    .values()
+                    .for_each(|area| occluders.cells.apply(*area, solid));
+                occluders.physics_synced = solid;
+            } else if occluders.physics_synced && !physics_tilemap.nav_changes.is_empty() {
+                physics_tilemap
+                    .nav_changes
+                    .iter()
+                    .for_each(|(area, solid)| occluders.cells.apply(*area, *solid));
+            }
+        });
+}
+
 pub fn data_physics_tilemap_analyzer(
     commands: ParallelCommands,
     mut tilemaps_query: Query<(Entity, &mut DataPhysicsTilemap, Option<&mut PhysicsTilemap>)>,
@@ -71,76 +131,15 @@ pub fn data_physics_tilemap_analyzer(
     tilemaps_query
         .par_iter_mut()
         .for_each(|(entity, mut data_tilemap, mut physics_tilemap)| {
-            let mut aabbs = Vec::new();
-            let size = data_tilemap.size;
-            let air = data_tilemap.air;
-
-            for y in 0..size.y {
-                for x in 0..size.x {
-                    let cur = UVec2 { x, y };
-
-                    let cur_i = {
-                        let i = data_tilemap.get_or_air(cur);
-                        if i == air {
-                            continue;
-                        }
-                        i
-                    };
-
-                    let mut d = UVec2 {
-                        x: if x == size.x - 1 { 0 } else { 1 },
-                        y: if y == size.y - 1 { 0 } else { 1 },
-                    };
-                    let mut dst = cur;
-                    while d.x != 0 || d.y != 0 {
-                        for t_x in cur.x..=dst.x {
-                            if data_tilemap.get_or_air(UVec2::new(t_x, dst.y + d.y)) != cur_i {
-                                d.y = 0;
-                                break;
-                            }
-                        }
-
-                        for t_y in cur.y..=dst.y {
-                            if data_tilemap.get_or_air(UVec2::new(dst.x + d.x, t_y)) != cur_i {
-                                d.x = 0;
-                                break;
-                            }
-                        }
-
-                        if d == UVec2::ONE
-                            && data_tilemap.get_or_air(UVec2::new(dst.x + 1, dst.y + 1)) != cur_i
-                        {
-                            d.y = 0;
-                        }
-
-                        dst += d;
-                    }
-
-                    for y in cur.y..=dst.y {
-                        for x in cur.x..=dst.x {
-                            data_tilemap.set(UVec2 { x, y }, air);
-                        }
-                    }
-
-                    aabbs.push((
-                        IAabb2d {
-                            min: cur.as_ivec2() + data_tilemap.origin,
-                            max: dst.as_ivec2() + data_tilemap.origin,
-                        },
-                        data_tilemap.get_tile(cur_i).unwrap_or_default(),
-                    ));
-                }
-            }
+            let aabbs = data_tilemap.take_aabbs();
 
             commands.command_scope(|mut c| {
                 if let Some(physics_tilemap) = &mut physics_tilemap {
                     physics_tilemap.spawn_queue.extend(aabbs);
                 } else {
-                    c.entity(entity).insert(PhysicsTilemap {
-                        storage: Default::default(),
-                        spawn_queue: aabbs,
-                        data: ChunkedStorage::default(),
-                    });
+                    let mut physics_tilemap = PhysicsTilemap::new();
+                    physics_tilemap.spawn_queue = aabbs;
+                    c.entity(entity).insert(physics_tilemap);
                 }
 
                 c.entity(entity).remove::<DataPhysicsTilemap>();
@@ -0,0 +1,137 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        system::{Query, SystemParam},
+    },
+    math::Vec2,
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+    utils::HashMap,
+};
+
+use crate::math::aabb::{Aabb2d, IAabb2d};
+
+/// A value in the fields of a `Region`.
+///
+/// Enum values are converted into strings. Other types of fields are not kept.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum RegionField {
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+    String(String),
+}
+
+/// A named area of the world, like a room.
+///
+/// This is inserted on the LDtk level entities, and on the Tiled rectangle objects
+/// whose classes are in `TiledLoadConfig::region_classes`.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct Region {
+    pub identifier: String,
+    /// The area in tiles, in the index space of the tilemaps of the level or map.
+    pub tile_area: IAabb2d,
+    /// The area in world space, taken when the region is spawned.
+    pub world_aabb: Aabb2d,
+    pub fields: HashMap<String, RegionField>,
+}
+
+impl Region {
+    #[inline]
+    pub fn contains(&self, world_pos: Vec2) -> bool {
+        self.world_aabb.contains(world_pos)
+    }
+
+    #[inline]
+    pub fn get_field(&self, identifier: &str) -> Option<&RegionField> {
+        self.fields.get(identifier)
+    }
+}
+
+/// Query the regions by position.
+#[derive(SystemParam)]
+pub struct Regions<'w, 's> {
+    regions: Query<'w, 's, (Entity, &'static Region)>,
+}
+
+impl<'w, 's> Regions<'w, 's> {
+    /// Get the smallest region that contains the position, so nested rooms take precedence.
+    pub fn region_at(&self, world_pos: Vec2) -> Option<(Entity, &Region)> {
+        self.regions_at(world_pos)
+            .min_by(|(_, a), (_, b)| a.world_aabb.area().total_cmp(&b.world_aabb.area()))
+    }
+
+    /// Iterate over all the regions that contain the position.
+    pub fn regions_at(&self, world_pos: Vec2) -> impl Iterator<Item = (Entity, &Region)> {
+        self.regions
+            .iter()
+            .filter(move |(_, region)| region.contains(world_pos))
+    }
+}
+
+/// Sent when an entity with `RegionTracker` enters or exits a region.
+///
+/// The exits of a frame are sent before the entries.
+#[derive(Event, Debug, Clone, Copy, Reflect)]
+pub enum RegionEvent {
+    Entered { tracked: Entity, region: Entity },
+    Exited { tracked: Entity, region: Entity },
+}
+
+/// Add this to an entity, like the player, to receive `RegionEvent`s for it.
+///
+/// The regions are tested against the translation of the `GlobalTransform`.
+/// Despawned regions are treated as exited.
+#[derive(Component, Debug, Default, Clone, Reflect)]
+pub struct RegionTracker {
+    pub(crate) regions: Vec<Entity>,
+}
+
+impl RegionTracker {
+    /// The regions the entity is currently in.
+    #[inline]
+    pub fn regions(&self) -> &[Entity] {
+        &self.regions
+    }
+}
+
+pub fn region_tracker(
+    mut trackers_query: Query<(Entity, &GlobalTransform, &mut RegionTracker)>,
+    regions: Regions,
+    mut events: EventWriter<RegionEvent>,
+) {
+    trackers_query
+        .iter_mut()
+        .for_each(|(tracked, transform, mut tracker)| {
+            let current = regions
+                .regions_at(transform.translation().truncate())
+                .map(|(region, _)| region)
+                .collect::<Vec<_>>();
+
+            tracker
+                .regions
+                .iter()
+                .filter(|region| !current.contains(region))
+                .for_each(|region| {
+                    events.send(RegionEvent::Exited {
+                        tracked,
+                        region: *region,
+                    });
+                });
+            current
+                .iter()
+                .filter(|region| !tracker.regions.contains(region))
+                .for_each(|region| {
+                    events.send(RegionEvent::Entered {
+                        tracked,
+                        region: *region,
+                    });
+                });
+
+            if tracker.regions != current {
+                tracker.regions = current;
+            }
+        });
+}
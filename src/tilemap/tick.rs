@@ -0,0 +1,107 @@
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        system::{Query, Res},
+    },
+    math::IVec2,
+    reflect::Reflect,
+    time::{Time, Timer, TimerMode},
+};
+
+use super::map::TilemapStorage;
+
+/// Picks random cells in every chunk of the tilemap at a fixed interval,
+/// and sends a `TileRandomTick` for each of them that has a tile.
+///
+/// Like the random ticks in Minecraft, this is the cheap way to simulate things
+/// like crops growing, fire spreading or tiles decaying over large maps,
+/// as the cost only depends on the number of chunks, not the number of tiles.
+/// A tile is ticked `ticks_per_chunk / chunk_size²` times per interval on average.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TilemapRandomTick {
+    /// How many cells are picked in each chunk every interval.
+    /// Empty cells are picked as well, but don't send ticks.
+    pub ticks_per_chunk: u32,
+    pub timer: Timer,
+    /// The state of the random generator, see `with_seed`.
+    pub(crate) rng: u64,
+}
+
+impl TilemapRandomTick {
+    /// Tick `ticks_per_chunk` cells in every chunk each `interval` seconds.
+    pub fn new(ticks_per_chunk: u32, interval: f32) -> Self {
+        Self {
+            ticks_per_chunk,
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            rng: 0x853C49E6748FEA9B,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // Zero is the only state xorshift can't leave.
+        self.rng = seed.max(1);
+        self
+    }
+
+    /// xorshift64*
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+}
+
+/// Sent when a tile is picked by `TilemapRandomTick`.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct TileRandomTick {
+    pub tilemap: Entity,
+    pub tile: Entity,
+    pub index: IVec2,
+}
+
+pub fn random_tick_scheduler(
+    mut tilemaps_query: Query<(Entity, &TilemapStorage, &mut TilemapRandomTick)>,
+    mut ticks: EventWriter<TileRandomTick>,
+    time: Res<Time>,
+) {
+    tilemaps_query
+        .iter_mut()
+        .for_each(|(tilemap, storage, mut random_tick)| {
+            random_tick.timer.tick(time.delta());
+            // Catch up if the frame took longer than the interval.
+            for _ in 0..random_tick.timer.times_finished_this_tick() {
+                let storage = &storage.storage;
+                let area = (storage.chunk_size * storage.chunk_size) as u64;
+                for (chunk_index, chunk) in storage.chunks.iter() {
+                    for _ in 0..random_tick.ticks_per_chunk {
+                        let in_chunk_index = (random_tick.next() % area) as usize;
+                        if let Some(tile) = chunk.get(in_chunk_index) {
+                            ticks.send(TileRandomTick {
+                                tilemap,
+                                tile: *tile,
+                                index: storage
+                                    .inverse_transform_index(*chunk_index, in_chunk_index),
+                            });
+                        }
+                    }
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod test {
+    use super::TilemapRandomTick;
+
+    #[test]
+    fn test_random_tick_seed() {
+        let mut a = TilemapRandomTick::new(3, 1.).with_seed(42);
+        let mut b = TilemapRandomTick::new(3, 1.).with_seed(42);
+        let picks = (0..16).map(|_| a.next() % 256).collect::<Vec<_>>();
+        assert_eq!(picks, (0..16).map(|_| b.next() % 256).collect::<Vec<_>>());
+        assert!(picks.iter().any(|p| *p != picks[0]));
+    }
+}
@@ -1,17 +1,31 @@
 use bevy::{
-    ecs::system::{ParallelCommands, Query},
-    math::IVec2,
+    ecs::{
+        query::{Added, Without},
+        system::{Commands, ParallelCommands, Query, Res},
+    },
+    hierarchy::BuildChildren,
+    log::warn,
+    math::{IVec2, Rect, UVec2, Vec2},
     prelude::{Component, Entity, Vec4},
     reflect::Reflect,
-    render::render_resource::ShaderType,
+    render::{color::Color, render_resource::ShaderType},
+    sprite::{Sprite, SpriteBundle},
+    time::Time,
+    transform::components::Transform,
 };
 
-use super::{buffers::Tiles, map::TilemapStorage};
+use crate::MAX_LAYER_COUNT;
+
+use super::{
+    buffers::Tiles,
+    kind::{TileKind, TileKindRegistry},
+    map::{TilemapAnimationClips, TilemapAnimations, TilemapStorage, TilemapTexture},
+};
 
 /// A tile layer. This is the logical representation of a tile layer.
 /// Not all the layers you added to a tile will be taken into consideration
 /// when rendering. Only the top 4 layers will be rendered.
-#[derive(Debug, Default, Clone, Copy, Reflect)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileLayer {
     pub(crate) texture_index: i32,
@@ -41,6 +55,18 @@ impl TileLayer {
         self.flip = flip;
         self
     }
+
+    /// The index in the texture, `None` if the layer is empty.
+    #[inline]
+    pub fn texture_index(&self) -> Option<u32> {
+        (self.texture_index >= 0).then_some(self.texture_index as u32)
+    }
+
+    /// The raw `TileFlip` bits of the layer.
+    #[inline]
+    pub fn flip(&self) -> u32 {
+        self.flip
+    }
 }
 
 /// The position of a tile layer.
@@ -65,6 +91,23 @@ pub struct TileUpdater {
     pub color: Option<Vec4>,
 }
 
+/// Replaces a texture index in all the layers of a static tile.
+/// See `TilemapStorage::replace_texture`.
+#[derive(Component, Clone, Copy, Reflect)]
+pub struct TileTextureReplacer {
+    pub old_index: u32,
+    pub new_index: u32,
+}
+
+/// Switches a tile to a named animation clip. See `TilemapStorage::set_tile_animation`.
+#[derive(Component, Clone, Reflect)]
+pub struct TileAnimationSwitcher(pub String);
+
+/// The time in seconds since startup when the animation of a tile started.
+/// Set when an animated tile is spawned or switches to another animation clip.
+#[derive(Component, Default, Debug, Clone, Copy, Reflect)]
+pub struct TileAnimationStart(pub f32);
+
 /// The flip of a tile. This is actually bit flags.
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, Reflect)]
@@ -93,6 +136,12 @@ impl From<u32> for TileFlip {
 pub struct TileBuilder {
     pub(crate) texture: TileTexture,
     pub(crate) color: Vec4,
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub(crate) pivot: Option<Vec2>,
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub(crate) footprint: Option<UVec2>,
+    #[cfg_attr(feature = "serializing", serde(default))]
+    pub(crate) audio_emitter: Option<String>,
 }
 
 impl Tiles for TileBuilder {}
@@ -103,20 +152,71 @@ impl TileBuilder {
         Self {
             texture: TileTexture::Static(Vec::new()),
             color: Vec4::ONE,
+            pivot: None,
+            footprint: None,
+            audio_emitter: None,
         }
     }
 
+    /// Create a tile builder from a registered tile kind.
+    ///
+    /// Returns `None` if the kind is not registered.
+    pub fn from_kind<K: TileKind>(kind: &K, registry: &TileKindRegistry<K>) -> Option<Self> {
+        registry.builder(kind)
+    }
+
+    #[inline]
+    pub fn texture(&self) -> &TileTexture {
+        &self.texture
+    }
+
+    #[inline]
+    pub fn color(&self) -> Vec4 {
+        self.color
+    }
+
     /// Set the color of the entire tile. Default is white.
     pub fn with_color(mut self, color: Vec4) -> Self {
         self.color = color;
         self
     }
 
+    /// Override the `TilePivot` of the tilemap for this tile.
+    ///
+    /// This is useful for tiles that are taller than the grid, like trees or walls,
+    /// which need to be anchored at their base.
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self {
+        self.pivot = Some(pivot);
+        self
+    }
+
+    /// Make the tile cover `footprint` cells, starting from its index towards +x and +y.
+    /// This is useful for oversized tiles like a 2x2 building.
+    ///
+    /// The texture of the tile should cover the same amount of tiles in the tilemap texture,
+    /// and the texture index points to the top left one of them.
+    ///
+    /// All the covered cells point to the same tile entity in `TilemapStorage`,
+    /// so only use `TilemapStorage::set` to spawn these tiles.
+    pub fn with_footprint(mut self, footprint: UVec2) -> Self {
+        self.footprint = Some(footprint.max(UVec2::ONE));
+        self
+    }
+
+    /// Tag the tile as a sound source, like `"waterfall"`.
+    ///
+    /// The tile will get a `TileAudioEmitter` and show up in the `TilemapAudioEmitters`
+    /// of the tilemap.
+    pub fn with_audio_emitter(mut self, tag: impl Into<String>) -> Self {
+        self.audio_emitter = Some(tag.into());
+        self
+    }
+
     /// Set the specific layer of the tile.
-    /// 
+    ///
     /// You don't need to worry about the index of the layer. If the index is greater than the current
     /// layer count, the layer vector will be automatically resized.
-    /// 
+    ///
     /// Notice that you can only add one animation to a tile or multiple static layers.
     pub fn with_layer(mut self, index: usize, layer: TileLayer) -> Self {
         if let TileTexture::Static(ref mut tex) = self.texture {
@@ -129,13 +229,86 @@ impl TileBuilder {
     }
 
     /// Set the animation of the tile.
-    /// 
+    ///
     /// Notice that you can only add one animation to a tile or multiple static layers.
     pub fn with_animation(mut self, animation: TileAnimation) -> Self {
         self.texture = TileTexture::Animated(animation);
         self
     }
 
+    /// Create the sprites that look the same as this tile, one for each rendered layer
+    /// from the bottom to the top. Animated tiles use the first frame of the animation,
+    /// so `animations` should be the `TilemapAnimations` of the tilemap.
+    ///
+    /// The sprites are the size of the texture of the tile in pixels, including the footprint.
+    /// Layer opacities and the rotation of the texture are not applied.
+    pub fn as_sprite_bundles(
+        &self,
+        texture: &TilemapTexture,
+        animations: Option<&TilemapAnimations>,
+    ) -> Vec<SpriteBundle> {
+        let layers = match &self.texture {
+            TileTexture::Static(layers) => layers
+                .iter()
+                .take(MAX_LAYER_COUNT)
+                .filter(|layer| layer.texture_index >= 0)
+                .copied()
+                .collect(),
+            TileTexture::Animated(anim) => animations
+                .and_then(|a| a.0.get(anim.start as usize))
+                .map(|index| vec![TileLayer::new().with_texture_index(*index as u32)])
+                .unwrap_or_default(),
+        };
+
+        let size = (self.footprint.unwrap_or(UVec2::ONE) * texture.desc.tile_size).as_vec2();
+        let color = Color::rgba_linear(self.color.x, self.color.y, self.color.z, self.color.w);
+
+        layers
+            .into_iter()
+            .map(|layer| {
+                let min = texture.pixel_rect(layer.texture_index as u32).min;
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        flip_x: layer.flip & TileFlip::Horizontal as u32 != 0,
+                        flip_y: layer.flip & TileFlip::Vertical as u32 != 0,
+                        custom_size: Some(size),
+                        rect: Some(Rect::from_corners(min, min + size)),
+                        ..Default::default()
+                    },
+                    texture: texture.handle().clone(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Spawn the sprites of `as_sprite_bundles` at `transform`. The upper layers are
+    /// spawned as children of the bottom one.
+    ///
+    /// This is useful for things like picking up a block from the tilemap.
+    /// Returns `None` if the tile has nothing to draw.
+    pub fn spawn_sprite(
+        &self,
+        commands: &mut Commands,
+        texture: &TilemapTexture,
+        animations: Option<&TilemapAnimations>,
+        transform: Transform,
+    ) -> Option<Entity> {
+        let mut sprites = self.as_sprite_bundles(texture, animations).into_iter();
+        let mut bottom = sprites.next()?;
+        bottom.transform = transform;
+
+        let mut entity = commands.spawn(bottom);
+        entity.with_children(|builder| {
+            sprites.enumerate().for_each(|(i, mut sprite)| {
+                sprite.transform.translation.z = (i + 1) as f32 * 0.001;
+                builder.spawn(sprite);
+            });
+        });
+        Some(entity.id())
+    }
+
     pub(crate) fn build_component(
         &self,
         index: IVec2,
@@ -150,13 +323,15 @@ impl TileBuilder {
             index,
             texture: self.texture.clone(),
             color: self.color,
+            pivot: self.pivot,
+            footprint: self.footprint,
         }
     }
 }
 
 /// A tile animation. This is actually information about the position of the animation
 /// in the tilemap animation buffer. So it's cheap to clone.
-#[derive(ShaderType, Debug, Clone, Copy, Reflect)]
+#[derive(ShaderType, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileAnimation {
     pub(crate) start: u32,
@@ -164,15 +339,55 @@ pub struct TileAnimation {
     pub(crate) fps: u32,
 }
 
+/// How a tile animation plays.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum TileAnimationMode {
+    #[default]
+    Loop,
+    /// Play forward then backward, like `0 1 2 1 0 1 2 ...`.
+    PingPong,
+    /// Play once then stay on the last frame.
+    /// The animation starts when the tile is spawned or switches to it.
+    Once,
+}
+
 /// A raw tile animation. This is contains the full information of a tile animation.
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Default, Clone, Reflect)]
 pub struct RawTileAnimation {
     pub sequence: Vec<u32>,
     pub fps: u32,
+    /// The duration of each frame in milliseconds, like the ones authored in Tiled or Aseprite.
+    /// Overrides `fps` if it's not empty, and must be as long as `sequence`.
+    pub durations: Vec<u32>,
+    pub mode: TileAnimationMode,
+}
+
+impl RawTileAnimation {
+    pub fn new(sequence: Vec<u32>, fps: u32) -> Self {
+        Self {
+            sequence,
+            fps,
+            ..Default::default()
+        }
+    }
+
+    /// Create an animation with per-frame durations in milliseconds.
+    pub fn with_durations(sequence: Vec<u32>, durations: Vec<u32>) -> Self {
+        Self {
+            sequence,
+            durations,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_mode(mut self, mode: TileAnimationMode) -> Self {
+        self.mode = mode;
+        self
+    }
 }
 
 /// A tile texture. This is either a static texture or an animation.
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Reflect)]
 #[cfg_attr(feature = "serializing", derive(serde::Serialize, serde::Deserialize))]
 pub enum TileTexture {
     Static(Vec<TileLayer>),
@@ -188,6 +403,10 @@ pub struct Tile {
     pub index: IVec2,
     pub texture: TileTexture,
     pub color: Vec4,
+    /// Overrides the `TilePivot` of the tilemap.
+    pub pivot: Option<Vec2>,
+    /// The cells this tile covers. `None` means a single cell.
+    pub footprint: Option<UVec2>,
 }
 
 impl Tiles for Tile {}
@@ -197,6 +416,9 @@ impl Into<TileBuilder> for Tile {
         TileBuilder {
             texture: self.texture,
             color: self.color,
+            pivot: self.pivot,
+            footprint: self.footprint,
+            audio_emitter: None,
         }
     }
 }
@@ -234,3 +456,79 @@ pub fn tile_updater(
             });
         });
 }
+
+pub fn tile_texture_replacer(
+    commands: ParallelCommands,
+    mut tiles_query: Query<(Entity, &mut Tile, &TileTextureReplacer)>,
+) {
+    tiles_query
+        .par_iter_mut()
+        .for_each(|(entity, mut tile, replacer)| {
+            // Only mutate the tiles that actually change, so the others are not extracted again.
+            if let TileTexture::Static(tex) = &tile.texture {
+                if tex
+                    .iter()
+                    .any(|l| l.texture_index == replacer.old_index as i32)
+                {
+                    if let TileTexture::Static(ref mut tex) = tile.texture {
+                        tex.iter_mut()
+                            .filter(|l| l.texture_index == replacer.old_index as i32)
+                            .for_each(|l| l.texture_index = replacer.new_index as i32);
+                    }
+                }
+            }
+            commands.command_scope(|mut c| {
+                c.entity(entity).remove::<TileTextureReplacer>();
+            });
+        });
+}
+
+pub fn tile_animation_switcher(
+    commands: ParallelCommands,
+    mut tiles_query: Query<(Entity, &mut Tile, &TileAnimationSwitcher)>,
+    clips_query: Query<&TilemapAnimationClips>,
+    time: Res<Time>,
+) {
+    tiles_query
+        .par_iter_mut()
+        .for_each(|(entity, mut tile, switcher)| {
+            match clips_query
+                .get(tile.tilemap_id)
+                .ok()
+                .and_then(|clips| clips.get(&switcher.0))
+            {
+                Some(anim) => {
+                    let texture = TileTexture::Animated(anim);
+                    if tile.texture != texture {
+                        tile.texture = texture;
+                        commands.command_scope(|mut c| {
+                            c.entity(entity)
+                                .insert(TileAnimationStart(time.elapsed_seconds()));
+                        });
+                    }
+                }
+                None => warn!(
+                    "Animation clip {} is not registered in the TilemapAnimationClips of {:?}!",
+                    switcher.0, tile.tilemap_id
+                ),
+            }
+            commands.command_scope(|mut c| {
+                c.entity(entity).remove::<TileAnimationSwitcher>();
+            });
+        });
+}
+
+pub fn tile_animation_starter(
+    commands: ParallelCommands,
+    tiles_query: Query<(Entity, &Tile), (Added<Tile>, Without<TileAnimationStart>)>,
+    time: Res<Time>,
+) {
+    tiles_query.par_iter().for_each(|(entity, tile)| {
+        if let TileTexture::Animated(_) = tile.texture {
+            commands.command_scope(|mut c| {
+                c.entity(entity)
+                    .insert(TileAnimationStart(time.elapsed_seconds()));
+            });
+        }
+    });
+}